@@ -0,0 +1,76 @@
+//! Compares the common substring search reading them off a suffix
+//! automaton, as tree generation does, with the naive search checking
+//! every substring of the first input against the others.
+//!
+//! Run with `cargo bench --bench common_substrings --features testing`.
+
+use regex_generator::testing::automaton_common_substrings;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Returns `count` log lines of about `length` bytes sharing a long
+/// skeleton with pseudo-random fields.
+fn log_lines(count: usize, length: usize) -> Vec<String> {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize
+    };
+    (0..count)
+        .map(|_| {
+            let mut line = String::with_capacity(length + 32);
+            while line.len() < length {
+                line.push_str("request handled user=");
+                line.push_str(&format!("{:06} ", next() % 1_000_000));
+                line.push_str(&format!("latency_ms={} ", next() % 5000));
+            }
+            line
+        })
+        .collect()
+}
+
+/// The naive search: every substring of the first string, checked with
+/// `contains` against every other string.
+fn naive_common_substrings<'a>(strings: &[&'a str]) -> Vec<&'a str> {
+    let first = strings[0];
+    let mut found = vec![];
+    for start in 0..first.len() {
+        for end in start + 1..=first.len() {
+            let Some(substring) = first.get(start..end) else {
+                continue;
+            };
+            if strings[1..].iter().all(|s| s.contains(substring)) {
+                found.push(substring);
+            }
+        }
+    }
+    found
+}
+
+fn time<T>(label: &str, mut run: impl FnMut() -> T) -> Duration {
+    let iterations = 3;
+    let started = Instant::now();
+    for _ in 0..iterations {
+        black_box(run());
+    }
+    let elapsed = started.elapsed() / iterations;
+    println!("{label:<40} {elapsed:>12.2?}");
+    elapsed
+}
+
+fn main() {
+    for length in [256, 1024, 4096] {
+        let lines = log_lines(8, length);
+        let inputs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        time(&format!("suffix automaton, 8 x {length} bytes"), || {
+            automaton_common_substrings(&inputs).len()
+        });
+        if length <= 1024 {
+            time(&format!("naive search, 8 x {length} bytes"), || {
+                naive_common_substrings(&inputs).len()
+            });
+        }
+    }
+}
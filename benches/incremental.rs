@@ -0,0 +1,60 @@
+//! Compares pushing long log lines one at a time into an
+//! [`IncrementalGenerator`] with generating the tree from all of them at
+//! once.
+//!
+//! Run with `cargo bench --bench incremental`.
+
+use regex_generator::{generate_regex_tree, IncrementalGenerator};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Returns `count` log lines of about `length` bytes sharing a long
+/// skeleton with pseudo-random fields.
+fn log_lines(count: usize, length: usize) -> Vec<String> {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize
+    };
+    (0..count)
+        .map(|_| {
+            let mut line = String::with_capacity(length + 32);
+            while line.len() < length {
+                line.push_str("request handled user=");
+                line.push_str(&format!("{:06} ", next() % 1_000_000));
+                line.push_str(&format!("latency_ms={} ", next() % 5000));
+            }
+            line
+        })
+        .collect()
+}
+
+fn time<T>(label: &str, mut run: impl FnMut() -> T) -> Duration {
+    let iterations = 3;
+    let started = Instant::now();
+    for _ in 0..iterations {
+        black_box(run());
+    }
+    let elapsed = started.elapsed() / iterations;
+    println!("{label:<40} {elapsed:>12.2?}");
+    elapsed
+}
+
+fn main() {
+    for length in [800, 1600, 4096] {
+        let lines = log_lines(8, length);
+        let inputs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        time(&format!("incremental, 8 x {length} bytes"), || {
+            let mut generator = IncrementalGenerator::new();
+            for line in &inputs {
+                generator.push(line);
+            }
+            generator.current_tree().iter().count()
+        });
+        time(&format!("batch, 8 x {length} bytes"), || {
+            generate_regex_tree(&inputs)
+        });
+    }
+}
@@ -0,0 +1,54 @@
+//! Compares serial and parallel tree generation on large input sets, where
+//! checking every candidate against every input dominates.
+//!
+//! Run with `cargo bench --features rayon --bench parallel_generation`.
+
+use regex_generator::RegexTreeBuilder;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Returns `count` short log lines sharing a skeleton with pseudo-random
+/// fields.
+fn log_lines(count: usize) -> Vec<String> {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize
+    };
+    (0..count)
+        .map(|_| {
+            format!(
+                "request handled user={:06} latency_ms={} status=ok",
+                next() % 1_000_000,
+                next() % 5000
+            )
+        })
+        .collect()
+}
+
+fn time<T>(label: &str, mut run: impl FnMut() -> T) -> Duration {
+    let iterations = 3;
+    let started = Instant::now();
+    for _ in 0..iterations {
+        black_box(run());
+    }
+    let elapsed = started.elapsed() / iterations;
+    println!("{label:<40} {elapsed:>12.2?}");
+    elapsed
+}
+
+fn main() {
+    for count in [10_000, 100_000] {
+        let lines = log_lines(count);
+        let inputs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        for parallel in [false, true] {
+            let builder = RegexTreeBuilder::new().parallel(parallel);
+            let label = if parallel { "parallel" } else { "serial" };
+            time(&format!("{label}, {count} inputs"), || {
+                builder.build(&inputs).unwrap()
+            });
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::hierarchy::template_tree;
+use crate::{DetectorDictionary, GenerateError, Node, TemplateId};
+use regex::Regex;
+use regex_syntax::hir::{Hir, HirKind};
+
+/// The most example lines reported per side of a template change.
+const MAX_EXAMPLES: usize = 3;
+
+/// How a template differs between the left and right corpora of an
+/// [`AlignmentReport`], with example lines of each side holding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateChange {
+    /// The template only exists in the left corpus.
+    OnlyLeft {
+        /// The pattern of the template.
+        pattern: String,
+        /// Lines of the left corpus it matches.
+        examples: Vec<String>,
+    },
+    /// The template only exists in the right corpus.
+    OnlyRight {
+        /// The pattern of the template.
+        pattern: String,
+        /// Lines of the right corpus it matches.
+        examples: Vec<String>,
+    },
+    /// The template keeps its literal tokens, in order, but some fields
+    /// changed shape or appeared or disappeared.
+    Changed {
+        /// The pattern in the left corpus.
+        left: String,
+        /// The pattern in the right corpus.
+        right: String,
+        /// Lines of the left corpus the left pattern matches.
+        left_examples: Vec<String>,
+        /// Lines of the right corpus the right pattern matches.
+        right_examples: Vec<String>,
+    },
+}
+
+/// The templates of two corpora, such as staging and production logs,
+/// aligned with one another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlignmentReport {
+    changes: Vec<TemplateChange>,
+    unchanged: Vec<String>,
+}
+
+impl AlignmentReport {
+    /// Returns the templates that differ, those of the left corpus first,
+    /// in pre-order of its tree, then those only in the right corpus, in
+    /// pre-order of theirs.
+    pub fn changes(&self) -> &[TemplateChange] {
+        &self.changes
+    }
+
+    /// Returns the patterns of the templates both corpora share.
+    pub fn unchanged(&self) -> &[String] {
+        &self.unchanged
+    }
+
+    /// Returns whether both corpora have the same templates.
+    pub fn is_aligned(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// A template of one side, with the literal tokens it is aligned on.
+struct Aligned {
+    id: TemplateId,
+    pattern: String,
+    skeleton: Vec<String>,
+}
+
+/// Generates the template trees of two corpora and aligns their templates,
+/// reporting which exist only in one and which changed shape, with example
+/// lines, so that drift between them can be acted on.
+///
+/// Both corpora share a vocabulary of named sub-patterns, so a field keeps
+/// its name and pattern on both sides when its shape is unchanged.
+/// Templates with the same [`TemplateId`] are unchanged; otherwise a
+/// template of each side with the same literal tokens, in order, is taken
+/// as one that changed shape.
+///
+/// # Arguments
+///
+/// * `left` - The inputs of the first corpus.
+/// * `right` - The inputs of the second corpus.
+/// * `max_depth` - The maximum number of specialization levels below the
+///   coarse templates, see [`crate::generate_template_tree`].
+///
+/// # Returns
+///
+/// The report, or an error if a corpus is empty or its inputs cannot be
+/// aligned into templates.
+pub fn align_corpora(
+    left: &[&str],
+    right: &[&str],
+    max_depth: usize,
+) -> Result<AlignmentReport, GenerateError> {
+    let mut dictionary = DetectorDictionary::new();
+    let left_tree = template_tree(left, max_depth, Some(&mut dictionary))?;
+    let right_tree = template_tree(right, max_depth, Some(&mut dictionary))?;
+    let left_templates = templates(&left_tree);
+    let mut right_templates = templates(&right_tree);
+
+    let mut changes = vec![];
+    let mut unchanged = vec![];
+    for template in left_templates {
+        if let Some(index) = right_templates.iter().position(|t| t.id == template.id) {
+            right_templates.remove(index);
+            unchanged.push(template.pattern);
+            continue;
+        }
+        let examples = examples(&template.pattern, left);
+        match right_templates
+            .iter()
+            .position(|t| t.skeleton == template.skeleton)
+        {
+            Some(index) => {
+                let counterpart = right_templates.remove(index);
+                changes.push(TemplateChange::Changed {
+                    right_examples: self::examples(&counterpart.pattern, right),
+                    left: template.pattern,
+                    right: counterpart.pattern,
+                    left_examples: examples,
+                });
+            }
+            None => changes.push(TemplateChange::OnlyLeft {
+                pattern: template.pattern,
+                examples,
+            }),
+        }
+    }
+    for template in right_templates {
+        changes.push(TemplateChange::OnlyRight {
+            examples: examples(&template.pattern, right),
+            pattern: template.pattern,
+        });
+    }
+    Ok(AlignmentReport { changes, unchanged })
+}
+
+/// Returns the distinct templates of a tree, in pre-order, but its root.
+fn templates(tree: &Node) -> Vec<Aligned> {
+    let mut templates: Vec<Aligned> = vec![];
+    for node in tree.preorder().into_iter().skip(1) {
+        let id = node.template_id();
+        if templates.iter().all(|t| t.id != id) {
+            templates.push(Aligned {
+                id,
+                skeleton: skeleton(&node.pattern),
+                pattern: node.pattern.to_string(),
+            });
+        }
+    }
+    templates
+}
+
+/// Returns the literal runs of `pattern` outside its fields, in order.
+fn skeleton(pattern: &str) -> Vec<String> {
+    fn collect(hir: &Hir, out: &mut Vec<String>) {
+        match hir.kind() {
+            HirKind::Literal(literal) => out.push(String::from_utf8_lossy(&literal.0).into()),
+            HirKind::Concat(parts) => parts.iter().for_each(|part| collect(part, out)),
+            _ => {}
+        }
+    }
+    let mut skeleton = vec![];
+    if let Ok(hir) = regex_syntax::Parser::new().parse(pattern) {
+        collect(&hir, &mut skeleton);
+    }
+    skeleton
+}
+
+/// Returns the first lines of `inputs` matching `pattern`.
+fn examples(pattern: &str, inputs: &[&str]) -> Vec<String> {
+    let Ok(regex) = Regex::new(pattern) else {
+        return vec![];
+    };
+    inputs
+        .iter()
+        .filter(|s| regex.is_match(s))
+        .take(MAX_EXAMPLES)
+        .map(|s| s.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_corpora() {
+        let staging = [
+            "GET /health 200",
+            "GET /status 404",
+            "job 12 is done",
+            "job 7 is done",
+            "cache warm",
+            "cache cold",
+        ];
+        let production = [
+            "GET /health 200",
+            "GET /status 503",
+            "job a1 is done",
+            "job b2 is done",
+            "shutdown requested by the ops",
+        ];
+        let report = align_corpora(&staging, &production, 0).unwrap();
+
+        assert!(!report.is_aligned());
+        assert_eq!(report.unchanged().len(), 1);
+        assert!(report.unchanged()[0].starts_with(r"^GET\s+"));
+        let changes = report.changes();
+        assert_eq!(changes.len(), 3, "{changes:?}");
+        assert!(
+            matches!(&changes[0], TemplateChange::OnlyLeft { examples, .. }
+            if examples == &["cache warm", "cache cold"])
+        );
+        match &changes[1] {
+            TemplateChange::Changed {
+                left_examples,
+                right_examples,
+                ..
+            } => {
+                assert_eq!(left_examples, &["job 12 is done", "job 7 is done"]);
+                assert_eq!(right_examples, &["job a1 is done", "job b2 is done"]);
+            }
+            change => panic!("{change:?}"),
+        }
+        assert!(
+            matches!(&changes[2], TemplateChange::OnlyRight { examples, .. }
+            if examples == &["shutdown requested by the ops"])
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use crate::privacy::SupportGuard;
+use crate::subsumption::anchored_literal;
+use crate::{Node, Role};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// The order in which the values of an alternation are written.
+///
+/// Every order matches the same strings, but a backtracking engine, such
+/// as PCRE or JavaScript's, tries the branches left to right, so putting
+/// the likeliest first saves it work, and putting longer values before
+/// their prefixes avoids backtracking out of a short match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlternationOrder {
+    /// Values are written in the order they first occur in the inputs.
+    #[default]
+    FirstSeen,
+    /// Values taken by more inputs come first, ties in order of first
+    /// occurrence.
+    Frequency,
+    /// Longer values come first, ties in order of first occurrence.
+    LongestFirst,
+}
+
+impl AlternationOrder {
+    /// Sorts the values `side` of `literal` takes, `0` being before it,
+    /// by this order.
+    fn sort(self, values: &mut [&str], role: Role, literal: &str, inputs: &[&str], side: usize) {
+        match self {
+            AlternationOrder::FirstSeen => {}
+            AlternationOrder::Frequency => {
+                let mut counts: HashMap<&str, usize> = HashMap::new();
+                for input in inputs {
+                    if let Some(at) = literal_position(role, literal, input) {
+                        let value = [&input[..at], &input[at + literal.len()..]][side];
+                        *counts.entry(value).or_default() += 1;
+                    }
+                }
+                values.sort_by_key(|v| Reverse(counts.get(v).copied().unwrap_or(0)));
+            }
+            AlternationOrder::LongestFirst => values.sort_by_key(|v| Reverse(v.chars().count())),
+        }
+    }
+}
+
+/// Replaces the wildcard regions of leaf patterns that take at most
+/// `max_alternatives` distinct values across `inputs` with an explicit
+/// alternation of those values, such as `^(?:GET|POST|PUT) /index$`, and
+/// records the value sets of every node in [`Node::observed_values`].
+///
+/// Interior nodes keep their wildcards, so they still match everything
+/// their children match.
+///
+/// # Arguments
+///
+/// * `node` - The root of the tree to rewrite.
+/// * `inputs` - The strings the tree was mined from.
+/// * `max_alternatives` - The largest number of distinct values spelled
+///   out in an alternation.
+/// * `order` - The order the values are written in, recorded in
+///   [`Node::alternation_order`] of the leaves spelling some out.
+/// * `guard` - Decides which values are common enough to be recorded or
+///   spelled out; a region with a rarer value keeps its wildcard.
+pub(crate) fn infer_alternations(
+    node: &mut Node,
+    inputs: &[&str],
+    max_alternatives: usize,
+    order: AlternationOrder,
+    guard: &SupportGuard,
+) {
+    for child in &mut node.children {
+        infer_alternations(Arc::make_mut(child), inputs, max_alternatives, order, guard);
+    }
+    let Some((role, literal)) = anchored_literal(&node.pattern) else {
+        return;
+    };
+    let regions = region_values(role, &literal, inputs).map(|region| {
+        region.map(|values| {
+            Some(values)
+                .filter(|v| v.len() <= max_alternatives && v.iter().all(|v| guard.allows(v)))
+        })
+    });
+    node.observed_values = regions
+        .iter()
+        .flatten()
+        .map(|values| {
+            values
+                .iter()
+                .flatten()
+                .map(|value| value.to_string())
+                .collect()
+        })
+        .collect();
+
+    let [mut before, mut after] = regions.map(Option::flatten);
+    let exposes = |sides| guard.exposes_secret_around(role, &literal, inputs, sides);
+    if exposes([before.is_some(), after.is_some()]) {
+        if before.is_some() && !exposes([true, false]) {
+            after = None;
+        } else if after.is_some() && !exposes([false, true]) {
+            before = None;
+        } else {
+            (before, after) = (None, None);
+        }
+    }
+    if node.children.is_empty() && (before.is_some() || after.is_some()) {
+        for (side, values) in [&mut before, &mut after].into_iter().enumerate() {
+            if let Some(values) = values {
+                order.sort(values, role, &literal, inputs, side);
+            }
+        }
+        node.pattern =
+            alternation_pattern(role, &literal, before.as_deref(), after.as_deref()).into();
+        if before.iter().chain(&after).any(|values| values.len() > 1) {
+            node.alternation_order = Some(order);
+        }
+    }
+}
+
+/// Returns the pattern of `literal` in `role`, with the regions before and
+/// after it restricted to the given values when some are.
+pub(crate) fn alternation_pattern(
+    role: Role,
+    literal: &str,
+    before: Option<&[&str]>,
+    after: Option<&[&str]>,
+) -> String {
+    let [before, after] = [before, after].map(|values| {
+        values.map(|values| {
+            let mut fragment = String::new();
+            write_alternation(values, &mut fragment);
+            fragment
+        })
+    });
+    region_pattern(role, literal, before.as_deref(), after.as_deref())
+}
+
+/// Returns the pattern of `literal` in `role`, with the regions before and
+/// after it replaced by the given regex fragments when some are.
+pub(crate) fn region_pattern(
+    role: Role,
+    literal: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> String {
+    let mut pattern = String::with_capacity(literal.len() + 4);
+    match (before, role) {
+        (Some(fragment), _) => {
+            pattern.push('^');
+            pattern.push_str(fragment);
+        }
+        (None, Role::Prefix | Role::Full) => pattern.push('^'),
+        (None, _) => pattern.push_str(".*"),
+    }
+    regex_syntax::escape_into(literal, &mut pattern);
+    match (after, role) {
+        (Some(fragment), _) => {
+            pattern.push_str(fragment);
+            pattern.push('$');
+        }
+        (None, Role::Suffix | Role::Full) => pattern.push('$'),
+        (None, _) => pattern.push_str(".*"),
+    }
+    pattern
+}
+
+/// Returns the distinct values, in order of first occurrence, taken by the
+/// text before and after the anchored occurrence of `literal` in the inputs
+/// containing it, for each region the role leaves variable.
+pub(crate) fn region_values<'a>(
+    role: Role,
+    literal: &str,
+    inputs: &[&'a str],
+) -> [Option<Vec<&'a str>>; 2] {
+    let variable = [
+        matches!(role, Role::Suffix | Role::Infix),
+        matches!(role, Role::Prefix | Role::Infix),
+    ];
+    let mut regions: [Option<Vec<&str>>; 2] = variable.map(|v| v.then(Vec::new));
+    let mut seen: [HashSet<&str>; 2] = Default::default();
+    for input in inputs {
+        let Some(at) = literal_position(role, literal, input) else {
+            continue;
+        };
+        let values = [&input[..at], &input[at + literal.len()..]];
+        for side in 0..2 {
+            if let Some(region) = &mut regions[side] {
+                if seen[side].insert(values[side]) {
+                    region.push(values[side]);
+                }
+            }
+        }
+    }
+    regions
+}
+
+/// Returns the byte offset of the anchored occurrence of `literal` in
+/// `input`, if it has one.
+pub(crate) fn literal_position(role: Role, literal: &str, input: &str) -> Option<usize> {
+    match role {
+        Role::Prefix | Role::Full => input.starts_with(literal).then_some(0),
+        Role::Suffix => input
+            .ends_with(literal)
+            .then(|| input.len() - literal.len()),
+        Role::Infix => input.find(literal),
+    }
+}
+
+/// Appends an alternation of the escaped `values` to `out`.
+///
+/// A single value is written as a plain literal. Otherwise the text shared
+/// by all values up to a punctuation character, such as `status=` in
+/// `status=OK` and `status=FAIL`, is factored out of the group on either
+/// side, which then only holds the diverging portions:
+/// `status=(?:OK|FAIL)`. Shared text ending mid-word is left in place, so
+/// `GET` and `PUT` are not split into `(?:GE|PU)T`.
+pub(crate) fn write_alternation(values: &[&str], out: &mut String) {
+    if let [value] = values {
+        regex_syntax::escape_into(value, out);
+        return;
+    }
+    let prefix = common_prefix(values);
+    let rest: Vec<&str> = values.iter().map(|v| &v[prefix.len()..]).collect();
+    let suffix = common_suffix(&rest);
+    regex_syntax::escape_into(prefix, out);
+    out.push_str("(?:");
+    for (index, value) in rest.iter().enumerate() {
+        if index > 0 {
+            out.push('|');
+        }
+        regex_syntax::escape_into(&value[..value.len() - suffix.len()], out);
+    }
+    out.push(')');
+    regex_syntax::escape_into(suffix, out);
+}
+
+/// Returns the longest prefix shared by all `values` that ends with a
+/// punctuation or whitespace character.
+fn common_prefix<'a>(values: &[&'a str]) -> &'a str {
+    let first = values[0];
+    let mut end = 0;
+    for (i, c) in first.char_indices() {
+        let next = i + c.len_utf8();
+        if !values.iter().all(|v| v.get(..next) == Some(&first[..next])) {
+            break;
+        }
+        if !c.is_alphanumeric() && c != '_' {
+            end = next;
+        }
+    }
+    &first[..end]
+}
+
+/// Returns the longest suffix shared by all `values` that starts with a
+/// punctuation or whitespace character.
+fn common_suffix<'a>(values: &[&'a str]) -> &'a str {
+    let first = values[0];
+    let shortest = values.iter().map(|v| v.len()).min().unwrap_or(0);
+    let mut start = first.len();
+    for (i, c) in first.char_indices().rev() {
+        let len = first.len() - i;
+        if len > shortest || !values.iter().all(|v| v.ends_with(&first[i..])) {
+            break;
+        }
+        if !c.is_alphanumeric() && c != '_' {
+            start = i;
+        }
+    }
+    &first[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_regex_tree_with_options, AlternationOrder, GenerateOptions};
+
+    #[test]
+    fn test_infer_alternations() {
+        let inputs = ["GET /index", "POST /index", "PUT /index"];
+        let options = GenerateOptions {
+            max_alternatives: Some(3),
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let leaf = tree
+            .preorder()
+            .into_iter()
+            .find(|n| n.pattern == r"^(?:GET|POST|PUT) /index$")
+            .unwrap();
+        assert_eq!(leaf.observed_values(), [vec!["GET", "POST", "PUT"]]);
+
+        let inputs = ["status=OK", "status=FAIL", "status=TIMEOUT"];
+        let options = GenerateOptions {
+            max_alternatives: Some(4),
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let patterns: Vec<&str> = tree.preorder().iter().map(|n| n.pattern.as_str()).collect();
+        assert!(
+            patterns.contains(&"^status=(?:OK|FAIL|TIMEOUT)$"),
+            "{patterns:?}"
+        );
+        assert!(
+            !patterns.iter().any(|p| p.contains("(?:s)")),
+            "{patterns:?}"
+        );
+
+        let options = GenerateOptions {
+            max_alternatives: Some(2),
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        assert!(tree.preorder().iter().all(|n| !n.pattern.contains('|')));
+    }
+
+    #[test]
+    fn test_alternation_order() {
+        let inputs = ["ls /a", "cd /a", "rm /a", "cd /a", "mkdir /a", "cd /a"];
+        let leaf = |order| {
+            let options = GenerateOptions {
+                max_alternatives: Some(4),
+                alternation_order: order,
+                ..GenerateOptions::default()
+            };
+            let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+            let leaf = tree
+                .preorder()
+                .into_iter()
+                .find(|n| n.pattern.contains('|'))
+                .unwrap()
+                .clone();
+            (leaf.pattern.to_string(), leaf.alternation_order())
+        };
+        assert_eq!(
+            leaf(AlternationOrder::FirstSeen),
+            (
+                "^(?:ls|cd|rm|mkdir) /a$".to_string(),
+                Some(AlternationOrder::FirstSeen)
+            )
+        );
+        assert_eq!(
+            leaf(AlternationOrder::Frequency).0,
+            "^(?:cd|ls|rm|mkdir) /a$"
+        );
+        assert_eq!(
+            leaf(AlternationOrder::LongestFirst).0,
+            "^(?:mkdir|ls|cd|rm) /a$"
+        );
+    }
+}
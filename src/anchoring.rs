@@ -0,0 +1,189 @@
+use crate::builder::fold_case;
+use crate::privacy::SupportGuard;
+use crate::subsumption::anchored_literal;
+use crate::{all_inputs, GraphemeMode, Node, Role};
+use regex::Regex;
+use std::sync::Arc;
+
+/// How the patterns of a tree are anchored to the start and end of inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchoring {
+    /// Every pattern floats, as in `.*ERROR.*`.
+    Floating,
+    /// A literal every input starts or ends with is anchored with `^` or
+    /// `$`, as in `^ERROR: .*`.
+    #[default]
+    Literals,
+    /// Like [`Anchoring::Literals`], and when the inputs share both a
+    /// prefix and a suffix, such as a log level and a file extension, a
+    /// node anchored at both ends, as in `^ERROR: .*\.log$`, is added
+    /// beneath the prefix node.
+    PrefixSuffix,
+}
+
+/// Adds the node anchored at both ends on the longest prefix and suffix
+/// shared by all inputs, if they share both.
+///
+/// The node goes beneath the node of the prefix when the tree has one, and
+/// beneath the root otherwise. It is not added when `guard` rejects the
+/// prefix or the suffix. With a `grapheme_mode`, neither splits a cluster
+/// of any input.
+pub(crate) fn anchor_prefix_suffix(
+    root: &mut Node,
+    inputs: &[&str],
+    grapheme_mode: Option<GraphemeMode>,
+    guard: &SupportGuard,
+) {
+    let Some(&first) = inputs.first() else {
+        return;
+    };
+    let shortest = inputs.iter().map(|s| s.len()).min().unwrap_or(0);
+    let shared_prefix = |n: usize| {
+        inputs
+            .iter()
+            .all(|s| s.as_bytes().starts_with(&first.as_bytes()[..n]))
+    };
+    let prefix_len = (0..=shortest)
+        .rev()
+        .find(|&n| {
+            first.is_char_boundary(n)
+                && shared_prefix(n)
+                && grapheme_mode.is_none_or(|mode| inputs.iter().all(|s| mode.is_boundary(s, n)))
+        })
+        .unwrap_or(0);
+    // The suffix must not overlap the prefix in the shortest input.
+    let shared_suffix = |n: usize| {
+        let suffix = &first.as_bytes()[first.len() - n..];
+        inputs.iter().all(|s| s.as_bytes().ends_with(suffix))
+    };
+    let suffix_len = (0..=shortest - prefix_len)
+        .rev()
+        .find(|&n| {
+            first.is_char_boundary(first.len() - n)
+                && shared_suffix(n)
+                && grapheme_mode
+                    .is_none_or(|mode| inputs.iter().all(|s| mode.is_boundary(s, s.len() - n)))
+        })
+        .unwrap_or(0);
+    if prefix_len == 0 || suffix_len == 0 {
+        return;
+    }
+    let (prefix, suffix) = (&first[..prefix_len], &first[first.len() - suffix_len..]);
+    if !guard.allows(prefix) || !guard.allows(suffix) {
+        return;
+    }
+
+    let mut pattern = String::from("^");
+    regex_syntax::escape_into(prefix, &mut pattern);
+    pattern.push_str(".*");
+    regex_syntax::escape_into(suffix, &mut pattern);
+    pattern.push('$');
+    let prefix_pattern = Role::Prefix.pattern(prefix);
+    let parent = root
+        .preorder()
+        .iter()
+        .position(|n| n.pattern == prefix_pattern)
+        .unwrap_or(0);
+    if let Some(parent) = root.node_mut(parent) {
+        parent.children.push(Arc::new(Node::new(pattern)));
+    }
+}
+
+/// Floats every node whose literal is anchored at a start or end some
+/// input holding it does not have, so anchored patterns are guaranteed to
+/// match all inputs even when the tree was edited or built from other
+/// inputs, while the node of a [`crate::Clustering`] cluster is not
+/// floated by the inputs of the others.
+///
+/// The descendants of a node that is not a literal, such as a stratum of
+/// [`crate::Stratification`], are only checked against the inputs it
+/// matches. When `case_insensitive` is set, the literals are compared with
+/// the inputs as the `(?i)` prefixed patterns will be.
+///
+/// # Returns
+///
+/// The number of nodes floated.
+pub(crate) fn verify_anchors(
+    node: &mut Node,
+    inputs: &[&str],
+    case_insensitive: bool,
+    parallel: bool,
+) -> usize {
+    let literal = anchored_literal(&node.pattern);
+    let matched: Vec<&str>;
+    let regex = if literal.is_none() && !node.children.is_empty() {
+        let flags = if case_insensitive { "(?i)" } else { "" };
+        Regex::new(&format!("{flags}{}", node.pattern)).ok()
+    } else {
+        None
+    };
+    let below = match regex {
+        Some(regex) => {
+            matched = inputs
+                .iter()
+                .copied()
+                .filter(|s| regex.is_match(s))
+                .collect();
+            &matched[..]
+        }
+        None => inputs,
+    };
+    let mut floated = 0;
+    for child in &mut node.children {
+        floated += verify_anchors(Arc::make_mut(child), below, case_insensitive, parallel);
+    }
+    let Some((role, literal)) = literal else {
+        return floated;
+    };
+    let holds = |s: &str| {
+        let folded;
+        let s = if case_insensitive {
+            folded = fold_case(s);
+            folded.as_str()
+        } else {
+            s
+        };
+        !s.contains(literal.as_str())
+            || match role {
+                Role::Prefix => s.starts_with(literal.as_str()),
+                Role::Suffix => s.ends_with(literal.as_str()),
+                Role::Full => s == literal,
+                Role::Infix => true,
+            }
+    };
+    if !all_inputs(inputs, parallel, holds) {
+        node.pattern = Role::Infix.pattern(&literal).into();
+        node.role = Some(Role::Infix);
+        floated += 1;
+    }
+    floated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RegexTreeBuilder;
+
+    #[test]
+    fn test_prefix_suffix_anchoring() {
+        let inputs = ["ERROR: disk a.log", "ERROR: net b.log"];
+        let tree = RegexTreeBuilder::new()
+            .anchoring(Anchoring::PrefixSuffix)
+            .build(&inputs)
+            .unwrap();
+        let prefix = tree.find(|n| n.pattern() == "^ERROR: .*").unwrap();
+        assert!(prefix
+            .children()
+            .any(|n| n.pattern() == r"^ERROR: .*\.log$"));
+
+        let tree = RegexTreeBuilder::new().build(&inputs).unwrap();
+        assert!(tree.find(|n| n.pattern() == r"^ERROR: .*\.log$").is_none());
+
+        let mut node = Node::new("^disk .*".to_string());
+        assert_eq!(
+            verify_anchors(&mut node, &["disk full", "a disk full"], false, false),
+            1
+        );
+        assert_eq!(node.pattern(), ".*disk .*");
+    }
+}
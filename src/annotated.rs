@@ -0,0 +1,135 @@
+use crate::subsumption::subsumes;
+use crate::{Node, PatternScore};
+use petgraph::graph::{DiGraph, NodeIndex};
+
+/// The weight of a node of [`tree_to_annotated_graph`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pattern: String,
+    match_count: Option<usize>,
+    depth: usize,
+    score: Option<PatternScore>,
+    examples: Vec<String>,
+}
+
+impl GraphNode {
+    /// Returns the pattern of the node.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns how many inputs the node matched, when known.
+    pub fn match_count(&self) -> Option<usize> {
+        self.match_count
+    }
+
+    /// Returns the depth of the node, the root being `0`.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the score of the node's pattern, when known.
+    pub fn score(&self) -> Option<PatternScore> {
+        self.score
+    }
+
+    /// Returns the example inputs attached to the node.
+    pub fn examples(&self) -> &[String] {
+        &self.examples
+    }
+}
+
+/// How the pattern of a parent relates to that of its child, the weight of
+/// an edge of [`tree_to_annotated_graph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    /// The parent matches everything the child matches, and more.
+    Strict,
+    /// Both match the same strings.
+    Equivalent,
+    /// The child matches strings the parent does not, as beneath a
+    /// [`crate::Stratification`] stratum, whose children only refine the
+    /// inputs it matched.
+    Partial,
+}
+
+impl Containment {
+    /// Returns the relation of `parent` to `child`.
+    pub(crate) fn between(parent: &str, child: &str) -> Containment {
+        match (subsumes(parent, child), subsumes(child, parent)) {
+            (true, true) => Containment::Equivalent,
+            (true, false) => Containment::Strict,
+            (false, _) => Containment::Partial,
+        }
+    }
+}
+
+/// Converts the regex tree to a graph whose nodes carry their match count,
+/// depth, score and examples, and whose edges carry how the parent's
+/// pattern contains the child's, for visualizations that help choose a
+/// pattern.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// A directed graph of the tree, nodes being added in pre-order from the
+/// root.
+pub fn tree_to_annotated_graph(root: &Node) -> DiGraph<GraphNode, Containment> {
+    let mut graph = DiGraph::new();
+    add_node(&mut graph, root, 0);
+    graph
+}
+
+/// Adds `node` and its descendants to the graph, returning its index.
+fn add_node(graph: &mut DiGraph<GraphNode, Containment>, node: &Node, depth: usize) -> NodeIndex {
+    let index = graph.add_node(GraphNode {
+        pattern: node.pattern.to_string(),
+        match_count: node.match_count,
+        depth,
+        score: node.score,
+        examples: node.examples.clone(),
+    });
+    for child in &node.children {
+        let child_index = add_node(graph, child, depth + 1);
+        let containment = Containment::between(&node.pattern, &child.pattern);
+        graph.add_edge(index, child_index, containment);
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_regex_tree_with_options, GenerateOptions, Stratification};
+
+    #[test]
+    fn test_tree_to_annotated_graph() {
+        let inputs = ["disk sda full", "disk sdb full", "x"];
+        let options = GenerateOptions {
+            stratification: Some(Stratification::Length),
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let graph = tree_to_annotated_graph(&tree);
+
+        assert_eq!(graph.node_count(), tree.size());
+        let root = &graph[NodeIndex::new(0)];
+        assert_eq!((root.pattern(), root.depth()), (".*", 0));
+        assert_eq!(root.score().map(|s| s.coverage()), Some(1.0));
+        for edge in graph.edge_indices() {
+            let (parent, child) = graph.edge_endpoints(edge).unwrap();
+            assert_eq!(graph[child].depth(), graph[parent].depth() + 1);
+        }
+        let disk = graph
+            .node_weights()
+            .find(|n| n.pattern() == "^disk s.*")
+            .unwrap();
+        assert!(disk.score().unwrap().specificity() > 0.0);
+        let edges: Vec<Containment> = graph.edge_weights().copied().collect();
+        assert!(edges.contains(&Containment::Strict));
+        assert!(edges.contains(&Containment::Partial));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{Children, Node, TreeError};
+use regex_syntax::ast::{self, Ast, ClassSet, ClassSetItem, GroupKind};
+use std::ops::Range;
+use std::sync::Arc;
+
+impl Node {
+    /// Returns a copy of the tree safe to share publicly, for instance in a
+    /// bug report, keeping the shape of its patterns but none of the data
+    /// they were mined from.
+    ///
+    /// Literal letters become `a`, or `A` when uppercase, digits `0` and
+    /// other characters `x`, while white space and ASCII punctuation are
+    /// kept as the structure they usually are; classes, ranges,
+    /// quantifiers, anchors and groups are kept, and named groups are
+    /// renamed `group1`, `group2`, and so on, after their index. So
+    /// `^user=alice id=\d{4}$` becomes `^aaaa=aaaaa aa=\d{4}$`, a pattern
+    /// that still compiles. The examples, observed values, bound outliers
+    /// and provenance of every node are dropped, and the chains it
+    /// collapsed are anonymized alike; counts and scores are kept.
+    ///
+    /// # Returns
+    ///
+    /// The anonymized tree, or an error if a pattern does not parse.
+    pub fn anonymized(&self) -> Result<Node, TreeError> {
+        let children = self
+            .children
+            .iter()
+            .map(|child| child.anonymized().map(Arc::new))
+            .collect::<Result<Children, TreeError>>()?;
+        Ok(Node {
+            pattern: anonymize(&self.pattern)?.into(),
+            children,
+            collapsed: self
+                .collapsed
+                .iter()
+                .map(|pattern| anonymize(pattern))
+                .collect::<Result<_, _>>()?,
+            observed_values: vec![],
+            examples: vec![],
+            bound_outliers: vec![],
+            provenance: None,
+            ..self.clone()
+        })
+    }
+}
+
+/// Returns the placeholder standing for a literal character, or `None` if
+/// it is kept.
+fn placeholder(c: char) -> Option<char> {
+    if c.is_whitespace() || c.is_ascii_punctuation() {
+        None
+    } else if c.is_numeric() {
+        Some('0')
+    } else if c.is_uppercase() {
+        Some('A')
+    } else if c.is_alphabetic() {
+        Some('a')
+    } else {
+        Some('x')
+    }
+}
+
+/// Returns `pattern` with its literals replaced by placeholders and its
+/// named groups renamed.
+fn anonymize(pattern: &str) -> Result<String, TreeError> {
+    let ast = ast::parse::Parser::new()
+        .parse(pattern)
+        .map_err(|e| TreeError::InvalidPattern {
+            pattern: pattern.to_string(),
+            message: e.to_string(),
+        })?;
+    let mut edits = vec![];
+    collect_edits(&ast, &mut edits);
+    edits.sort_by_key(|(span, _)| span.start);
+    let mut anonymized = String::with_capacity(pattern.len());
+    let mut at = 0;
+    for (span, replacement) in edits {
+        anonymized.push_str(&pattern[at..span.start]);
+        anonymized.push_str(&replacement);
+        at = span.end;
+    }
+    anonymized.push_str(&pattern[at..]);
+    Ok(anonymized)
+}
+
+/// Collects the byte ranges of the pattern to replace, with their
+/// replacements.
+fn collect_edits(ast: &Ast, edits: &mut Vec<(Range<usize>, String)>) {
+    match ast {
+        Ast::Literal(literal) => literal_edit(literal, edits),
+        Ast::ClassBracketed(class) => class_edits(&class.kind, edits),
+        Ast::Repetition(repetition) => collect_edits(&repetition.ast, edits),
+        Ast::Group(group) => {
+            if let GroupKind::CaptureName { name, .. } = &group.kind {
+                let span = name.span.start.offset..name.span.end.offset;
+                edits.push((span, format!("group{}", name.index)));
+            }
+            collect_edits(&group.ast, edits);
+        }
+        Ast::Alternation(alternation) => {
+            for ast in &alternation.asts {
+                collect_edits(ast, edits);
+            }
+        }
+        Ast::Concat(concat) => {
+            for ast in &concat.asts {
+                collect_edits(ast, edits);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the edits of the literals of a bracketed class, leaving the
+/// bounds of its ranges.
+fn class_edits(set: &ClassSet, edits: &mut Vec<(Range<usize>, String)>) {
+    match set {
+        ClassSet::Item(item) => class_item_edits(item, edits),
+        ClassSet::BinaryOp(op) => {
+            class_edits(&op.lhs, edits);
+            class_edits(&op.rhs, edits);
+        }
+    }
+}
+
+/// Collects the edits of the literals of a class item.
+fn class_item_edits(item: &ClassSetItem, edits: &mut Vec<(Range<usize>, String)>) {
+    match item {
+        ClassSetItem::Literal(literal) => literal_edit(literal, edits),
+        ClassSetItem::Bracketed(class) => class_edits(&class.kind, edits),
+        ClassSetItem::Union(union) => {
+            for item in &union.items {
+                class_item_edits(item, edits);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the edit of a literal, if it is not kept.
+fn literal_edit(literal: &ast::Literal, edits: &mut Vec<(Range<usize>, String)>) {
+    if let Some(c) = placeholder(literal.c) {
+        let span = literal.span.start.offset..literal.span.end.offset;
+        edits.push((span, c.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_regex_tree_with_options, GenerateOptions};
+    use regex::Regex;
+
+    #[test]
+    fn test_anonymized() {
+        assert_eq!(
+            anonymize(r"^user=alice id=\d{4}$").unwrap(),
+            r"^aaaa=aaaaa aa=\d{4}$"
+        );
+        assert_eq!(
+            anonymize(r"(?P<host>Web-0[1-9]|db\x41)\.[a-fé_]+ \w*?").unwrap(),
+            r"(?P<group1>Aaa-0[1-9]|aaA)\.[a-fa_]+ \w*?"
+        );
+
+        let inputs = ["login ok user=alice", "login ok user=bob"];
+        let options = GenerateOptions {
+            examples_per_node: 2,
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let anonymized = tree.anonymized().unwrap();
+        assert_eq!(anonymized.size(), tree.size());
+        for node in anonymized.iter() {
+            assert!(node.examples().is_empty());
+            assert!(!node.pattern().contains("alice") && !node.pattern().contains("login"));
+            assert!(Regex::new(node.pattern()).is_ok());
+        }
+        assert!(anonymized
+            .iter()
+            .any(|n| n.pattern().starts_with("^aaaaa aa aaaa=")));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{ExportError, Node};
+use arrow_array::builder::{BooleanBuilder, MapBuilder, StringBuilder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use parquet::arrow::ArrowWriter;
+use regex::Regex;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Writes the match matrix of a tree over `inputs` as a Parquet table.
+///
+/// The table has one row per input and node, with the columns `input_id`,
+/// `node_id` (the node's pre-order identifier), `matched`, and `captures`,
+/// a map from capture group name, or index for unnamed groups, to the
+/// captured text. `captures` is null when the node does not match and maps
+/// groups that did not participate to null.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `inputs` - The inputs to match, identified by their index.
+/// * `writer` - Where the Parquet file is written.
+///
+/// # Returns
+///
+/// An error if a pattern does not compile or the table cannot be written.
+pub fn write_match_matrix<W: Write + Send>(
+    root: &Node,
+    inputs: &[&str],
+    writer: W,
+) -> Result<(), ExportError> {
+    let regexes: Vec<Regex> = root
+        .preorder()
+        .iter()
+        .map(|node| {
+            Regex::new(&node.pattern).map_err(|e| ExportError::InvalidPattern {
+                pattern: node.pattern.to_string(),
+                message: e.to_string(),
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    let rows = inputs.len() * regexes.len();
+    let mut input_ids = UInt64Builder::with_capacity(rows);
+    let mut node_ids = UInt64Builder::with_capacity(rows);
+    let mut matched = BooleanBuilder::with_capacity(rows);
+    let mut captures = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+    for (input_id, input) in inputs.iter().enumerate() {
+        for (node_id, regex) in regexes.iter().enumerate() {
+            input_ids.append_value(input_id as u64);
+            node_ids.append_value(node_id as u64);
+            let Some(groups) = regex.captures(input) else {
+                matched.append_value(false);
+                captures.append(false)?;
+                continue;
+            };
+            matched.append_value(true);
+            for (index, name) in regex.capture_names().enumerate().skip(1) {
+                match name {
+                    Some(name) => captures.keys().append_value(name),
+                    None => captures.keys().append_value(index.to_string()),
+                }
+                captures
+                    .values()
+                    .append_option(groups.get(index).map(|m| m.as_str()));
+            }
+            captures.append(true)?;
+        }
+    }
+
+    let batch = RecordBatch::try_from_iter([
+        ("input_id", Arc::new(input_ids.finish()) as ArrayRef),
+        ("node_id", Arc::new(node_ids.finish()) as ArrayRef),
+        ("matched", Arc::new(matched.finish()) as ArrayRef),
+        ("captures", Arc::new(captures.finish()) as ArrayRef),
+    ])?;
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::UInt64Type;
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    #[test]
+    fn test_write_match_matrix() {
+        let mut root = Node::new(".*".to_string());
+        root.children.push(Arc::new(Node::new(
+            r"disk (?P<device>sd\w)|(nvme)".to_string(),
+        )));
+        let inputs = ["disk sda", "cpu hot"];
+
+        let path = std::env::temp_dir().join("regex_generator_match_matrix_test.parquet");
+        write_match_matrix(&root, &inputs, File::create(&path).unwrap()).unwrap();
+        let batch = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(batch.num_rows(), 4);
+        let node_ids = batch.column(1).as_primitive::<UInt64Type>();
+        assert_eq!(node_ids.values(), &[0, 1, 0, 1]);
+        let matched = batch.column(2).as_boolean();
+        assert_eq!(
+            matched.iter().collect::<Vec<_>>(),
+            [Some(true), Some(true), Some(true), Some(false)]
+        );
+
+        let captures = batch.column(3).as_map();
+        assert!(captures.is_null(3));
+        let entries = captures.value(1);
+        let keys = entries.column(0).as_string::<i32>();
+        let values = entries.column(1).as_string::<i32>();
+        assert_eq!(keys.value(0), "device");
+        assert_eq!(values.value(0), "sda");
+        assert_eq!(keys.value(1), "2");
+        assert!(values.is_null(1));
+    }
+}
@@ -0,0 +1,202 @@
+/// A state of a suffix automaton: the class of substrings sharing the same
+/// set of end positions.
+#[derive(Debug, Clone)]
+struct State {
+    /// The length of the longest substring of the class.
+    len: usize,
+    /// The state of the longest suffix belonging to another class.
+    link: usize,
+    /// The byte transitions, few per state, so kept as a list.
+    next: Vec<(u8, usize)>,
+    /// The index of the last byte of the first occurrence of the class.
+    first_end: usize,
+}
+
+impl State {
+    fn get(&self, byte: u8) -> Option<usize> {
+        self.next
+            .iter()
+            .find(|(b, _)| *b == byte)
+            .map(|&(_, to)| to)
+    }
+
+    fn set(&mut self, byte: u8, to: usize) {
+        match self.next.iter_mut().find(|(b, _)| *b == byte) {
+            Some(transition) => transition.1 = to,
+            None => self.next.push((byte, to)),
+        }
+    }
+}
+
+/// The suffix automaton of a string, recognising exactly its substrings
+/// with at most twice as many states as it has bytes.
+#[derive(Debug, Clone)]
+pub(crate) struct SuffixAutomaton<'a> {
+    text: &'a str,
+    states: Vec<State>,
+}
+
+impl<'a> SuffixAutomaton<'a> {
+    /// Builds the automaton of `text` in time linear in its length.
+    pub(crate) fn new(text: &'a str) -> Self {
+        let mut states = Vec::with_capacity(2 * text.len() + 1);
+        states.push(State {
+            len: 0,
+            link: 0,
+            next: vec![],
+            first_end: 0,
+        });
+        let mut last = 0;
+        for (end, byte) in text.bytes().enumerate() {
+            let current = states.len();
+            states.push(State {
+                len: states[last].len + 1,
+                link: 0,
+                next: vec![],
+                first_end: end,
+            });
+            let mut p = Some(last);
+            while let Some(state) = p.filter(|&s| states[s].get(byte).is_none()) {
+                states[state].set(byte, current);
+                p = (state != 0).then_some(states[state].link);
+            }
+            if let Some(state) = p {
+                let q = states[state].get(byte).unwrap_or_default();
+                if states[state].len + 1 == states[q].len {
+                    states[current].link = q;
+                } else {
+                    let clone = states.len();
+                    states.push(State {
+                        len: states[state].len + 1,
+                        ..states[q].clone()
+                    });
+                    let mut p = Some(state);
+                    while let Some(s) = p.filter(|&s| states[s].get(byte) == Some(q)) {
+                        states[s].set(byte, clone);
+                        p = (s != 0).then_some(states[s].link);
+                    }
+                    states[q].link = clone;
+                    states[current].link = clone;
+                }
+            }
+            last = current;
+        }
+        SuffixAutomaton { text, states }
+    }
+
+    /// Returns the states ordered by decreasing length, so every state
+    /// comes before its suffix link.
+    fn by_decreasing_len(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.text.len() + 2];
+        for state in &self.states {
+            counts[state.len] += 1;
+        }
+        let mut offset = 0;
+        for count in counts.iter_mut().rev() {
+            let n = *count;
+            *count = offset;
+            offset += n;
+        }
+        let mut order = vec![0; self.states.len()];
+        for (index, state) in self.states.iter().enumerate() {
+            order[counts[state.len]] = index;
+            counts[state.len] += 1;
+        }
+        order
+    }
+
+    /// Returns, for each state, the length of the longest substring of its
+    /// class occurring in `other`, found in one pass over `other`.
+    fn longest_in(&self, other: &str, order: &[usize]) -> Vec<usize> {
+        let mut best = vec![0; self.states.len()];
+        let (mut state, mut len) = (0, 0);
+        for byte in other.bytes() {
+            while state != 0 && self.states[state].get(byte).is_none() {
+                state = self.states[state].link;
+                len = self.states[state].len;
+            }
+            match self.states[state].get(byte) {
+                Some(next) => {
+                    state = next;
+                    len += 1;
+                }
+                None => len = 0,
+            }
+            best[state] = best[state].max(len);
+        }
+        // A match also covers every suffix of it, which lie in the suffix
+        // link classes in full.
+        for &state in order {
+            let link = self.states[state].link;
+            if state != 0 && best[state] > 0 {
+                best[link] = self.states[link].len;
+            }
+        }
+        best
+    }
+}
+
+/// Finds every distinct substring of `strings[0]` of at most `max_width`
+/// bytes that also occurs in every other string, in time linear in the
+/// total length of the inputs plus the size of the result.
+///
+/// # Arguments
+///
+/// * `strings` - A non-empty slice of strings.
+/// * `max_width` - The longest substring returned, in bytes.
+///
+/// # Returns
+///
+/// The common substrings on char boundaries, borrowed from the first
+/// string, longer ones first and then in order of first occurrence.
+pub(crate) fn common_substrings<'a>(strings: &[&'a str], max_width: usize) -> Vec<&'a str> {
+    let first = strings[0];
+    let automaton = SuffixAutomaton::new(first);
+    let order = automaton.by_decreasing_len();
+    let mut common: Vec<usize> = automaton.states.iter().map(|s| s.len).collect();
+    for other in &strings[1..] {
+        let best = automaton.longest_in(other, &order);
+        for (common, best) in common.iter_mut().zip(best) {
+            *common = (*common).min(best);
+        }
+    }
+
+    let mut found: Vec<(usize, usize)> = vec![];
+    for (index, state) in automaton.states.iter().enumerate().skip(1) {
+        let shortest = automaton.states[state.link].len + 1;
+        let end = state.first_end + 1;
+        for width in shortest..=common[index].min(max_width) {
+            let start = end - width;
+            if first.is_char_boundary(start) && first.is_char_boundary(end) {
+                found.push((width, start));
+            }
+        }
+    }
+    found.sort_unstable_by_key(|&(width, start)| (std::cmp::Reverse(width), start));
+    found
+        .into_iter()
+        .map(|(width, start)| &first[start..start + width])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_substrings() {
+        assert_eq!(
+            common_substrings(&["xabcx", "abc!", "zzabc"], usize::MAX),
+            ["abc", "ab", "bc", "a", "b", "c"]
+        );
+        assert_eq!(
+            common_substrings(&["abab", "bab"], 2),
+            ["ab", "ba", "a", "b"]
+        );
+        assert_eq!(common_substrings(&["café", "thé"], usize::MAX), ["é"]);
+        assert!(common_substrings(&["abc", "xyz"], usize::MAX).is_empty());
+
+        let automaton = SuffixAutomaton::new("abcbc");
+        assert!(automaton.states.len() < 2 * 5);
+    }
+}
@@ -0,0 +1,295 @@
+//! Generates a regex from strings read from stdin or files, one per line.
+//!
+//! ```text
+//! grep ERROR app.log | regex_generator --output tree
+//! ```
+
+use regex::Regex;
+use regex_generator::codegen::Flavor;
+use regex_generator::{
+    export_dot, generate_regex, generate_regex_tree_with_negatives, Anchoring, Node, Pattern,
+    RegexTreeBuilder,
+};
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: regex_generator [OPTIONS]
+
+Reads strings, one per line, from the given files or from stdin, and prints
+a regex matching all of them.
+
+Options:
+  --file FILE               Read strings from FILE; may be repeated
+  --output FORMAT           regex (default), tree, dot, json, or grep or rg
+                            for a shell command line running the pattern
+  --negatives FILE          Strings, one per line, the patterns must reject;
+                            the builder options below are then ignored
+  --anchoring MODE          literals (default), floating or prefix-suffix
+  --min-substring-len N     Ignore common substrings shorter than N chars
+  --max-children N          Keep at most N children per node
+  --lazy                    Use lazy wildcards
+  --case-insensitive        Ignore case
+  -h, --help                Print this help";
+
+/// The output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Regex,
+    Tree,
+    Dot,
+    Json,
+    Grep,
+    Rg,
+}
+
+/// The parsed command line.
+#[derive(Debug)]
+struct Args {
+    files: Vec<String>,
+    output: Output,
+    negatives: Option<String>,
+    builder: RegexTreeBuilder,
+    case_insensitive: bool,
+    help: bool,
+}
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Args, String> {
+    let mut parsed = Args {
+        files: vec![],
+        output: Output::Regex,
+        negatives: None,
+        builder: RegexTreeBuilder::new(),
+        case_insensitive: false,
+        help: false,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--file" => parsed.files.push(value()?),
+            "--output" => {
+                parsed.output = match value()?.as_str() {
+                    "regex" => Output::Regex,
+                    "tree" => Output::Tree,
+                    "dot" => Output::Dot,
+                    "json" => Output::Json,
+                    "grep" => Output::Grep,
+                    "rg" => Output::Rg,
+                    other => return Err(format!("unknown output format {other:?}")),
+                }
+            }
+            "--negatives" => parsed.negatives = Some(value()?),
+            "--anchoring" => {
+                let anchoring = match value()?.as_str() {
+                    "literals" => Anchoring::Literals,
+                    "floating" => Anchoring::Floating,
+                    "prefix-suffix" => Anchoring::PrefixSuffix,
+                    other => return Err(format!("unknown anchoring {other:?}")),
+                };
+                parsed.builder = parsed.builder.anchoring(anchoring);
+            }
+            "--min-substring-len" => {
+                let len = parse_count(&arg, &value()?)?;
+                parsed.builder = parsed.builder.min_substring_len(len);
+            }
+            "--max-children" => {
+                let max = parse_count(&arg, &value()?)?;
+                parsed.builder = parsed.builder.max_children(max);
+            }
+            "--lazy" => parsed.builder = parsed.builder.lazy_wildcards(true),
+            "--case-insensitive" => {
+                parsed.case_insensitive = true;
+                parsed.builder = parsed.builder.case_insensitive(true);
+            }
+            "-h" | "--help" => parsed.help = true,
+            other => return Err(format!("unknown argument {other:?}")),
+        }
+    }
+    Ok(parsed)
+}
+
+fn parse_count(arg: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{arg} needs a non-negative integer, got {value:?}"))
+}
+
+/// Reads the non-empty lines of `reader`, without line terminators.
+fn read_lines<R: Read>(reader: R, lines: &mut Vec<String>) -> io::Result<()> {
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Quotes `arg` for POSIX shells, in single quotes, so it is passed as is.
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Formats the shell command line running `program` with `pattern`, put
+/// behind `-e` in case it starts with a dash.
+fn command_line(program: &str, pattern: &str) -> String {
+    let flag = if pattern.starts_with('-') { "-e " } else { "" };
+    format!("{program} {flag}{}\n", shell_quote(pattern))
+}
+
+/// Formats the tree as indented patterns, one per line.
+fn format_tree(node: &Node, depth: usize, out: &mut String) {
+    out.push_str(&"  ".repeat(depth));
+    out.push_str(node.pattern());
+    out.push('\n');
+    for child in node.children() {
+        format_tree(child, depth + 1, out);
+    }
+}
+
+fn run(args: Args) -> Result<String, String> {
+    let mut lines = vec![];
+    if args.files.is_empty() {
+        read_lines(io::stdin().lock(), &mut lines).map_err(|e| format!("stdin: {e}"))?;
+    }
+    for file in &args.files {
+        let reader = std::fs::File::open(file).map_err(|e| format!("{file}: {e}"))?;
+        read_lines(reader, &mut lines).map_err(|e| format!("{file}: {e}"))?;
+    }
+    let inputs: Vec<&str> = lines.iter().map(String::as_str).collect();
+
+    let mut negatives = vec![];
+    if let Some(file) = &args.negatives {
+        let reader = std::fs::File::open(file).map_err(|e| format!("{file}: {e}"))?;
+        read_lines(reader, &mut negatives).map_err(|e| format!("{file}: {e}"))?;
+    }
+    let negatives: Vec<&str> = negatives.iter().map(String::as_str).collect();
+
+    let tree = match &args.negatives {
+        Some(_) => generate_regex_tree_with_negatives(&inputs, &negatives),
+        None => args.builder.build(&inputs),
+    }
+    .map_err(|e| e.to_string())?;
+    let regex = || single_regex(&tree, &inputs, &negatives, args.case_insensitive);
+
+    Ok(match args.output {
+        Output::Regex => format!("{}\n", regex()),
+        Output::Tree => {
+            let mut out = String::new();
+            format_tree(&tree, 0, &mut out);
+            out
+        }
+        Output::Dot => export_dot(&tree),
+        Output::Json => format!("{}\n", tree.to_json().map_err(|e| e.to_string())?),
+        Output::Grep => {
+            let pattern = Pattern::new(regex())
+                .render(Flavor::PosixExtended)
+                .map_err(|e| e.to_string())?;
+            command_line("grep -E", &pattern)
+        }
+        Output::Rg => {
+            let pattern = Pattern::new(regex())
+                .render(Flavor::Ripgrep)
+                .map_err(|e| e.to_string())?;
+            command_line("rg", &pattern)
+        }
+    })
+}
+
+/// Returns the single regex printed for `inputs`: the one
+/// [`generate_regex`] synthesizes from them, or the tree's own, see
+/// [`Node::to_regex`], if that one matches a negative.
+fn single_regex(
+    tree: &Node,
+    inputs: &[&str],
+    negatives: &[&str],
+    case_insensitive: bool,
+) -> String {
+    let synthesized = if case_insensitive {
+        let lowered: Vec<String> = inputs.iter().map(|s| s.to_lowercase()).collect();
+        let lowered: Vec<&str> = lowered.iter().map(String::as_str).collect();
+        format!("(?i){}", generate_regex(&lowered))
+    } else {
+        generate_regex(inputs)
+    };
+    match Regex::new(&synthesized) {
+        Ok(regex) if !negatives.iter().any(|n| regex.is_match(n)) => synthesized,
+        _ => tree.to_regex(),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) if args.help => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("regex_generator: {message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("regex_generator: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Result<Args, String> {
+        parse_args(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed =
+            args("--file a.txt --file b.txt --output dot --lazy --max-children 2").unwrap();
+        assert_eq!(parsed.files, ["a.txt", "b.txt"]);
+        assert_eq!(parsed.output, Output::Dot);
+        assert!(!parsed.help);
+
+        assert!(args("--output yaml").is_err());
+        assert!(args("--max-children many").is_err());
+        assert!(args("--file").is_err());
+        assert!(args("--help").unwrap().help);
+
+        let mut lines = vec![];
+        read_lines("disk full\r\n\nnet down\n".as_bytes(), &mut lines).unwrap();
+        assert_eq!(lines, ["disk full", "net down"]);
+
+        assert_eq!(args("--output rg").unwrap().output, Output::Rg);
+        assert_eq!(
+            command_line("grep -E", "^it's (a|b)$"),
+            "grep -E '^it'\\''s (a|b)$'\n"
+        );
+        assert_eq!(command_line("rg", "-v"), "rg -e '-v'\n");
+    }
+
+    #[test]
+    fn test_single_regex() {
+        let inputs = ["user alice logged in", "user bob logged in"];
+        let tree = RegexTreeBuilder::new().build(&inputs).unwrap();
+        assert_eq!(
+            single_regex(&tree, &inputs, &[], false),
+            "^user (?:alice|bob) logged in$"
+        );
+        assert_eq!(
+            single_regex(&tree, &inputs, &[], true),
+            "(?i)^user (?:alice|bob) logged in$"
+        );
+        let rejected = single_regex(&tree, &inputs, &["user bob logged in"], false);
+        assert_eq!(rejected, tree.to_regex());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::discriminative::distinct_substrings;
+use std::collections::HashMap;
+
+/// Which inputs contain which candidate substrings, one packed bitset per
+/// candidate.
+///
+/// Bit `i` of a candidate's row is set when input `i` contains it, so the
+/// support of a candidate is a popcount and the inputs shared by several
+/// candidates are a bitwise and of their rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainmentMatrix {
+    candidates: Vec<String>,
+    inputs: usize,
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl ContainmentMatrix {
+    /// Computes which of `inputs` contain each of `candidates`.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidates` - The candidate substrings, one row each.
+    /// * `inputs` - The inputs, one column each.
+    ///
+    /// # Returns
+    ///
+    /// The containment matrix.
+    pub fn new(candidates: &[&str], inputs: &[&str]) -> Self {
+        let words = inputs.len().div_ceil(64);
+        let mut bits = vec![0; candidates.len() * words];
+        let mut rows: HashMap<&str, Vec<usize>> = HashMap::with_capacity(candidates.len());
+        for (row, &candidate) in candidates.iter().enumerate() {
+            rows.entry(candidate).or_default().push(row);
+        }
+        for (column, input) in inputs.iter().enumerate() {
+            for substring in distinct_substrings(input) {
+                for &row in rows.get(substring).into_iter().flatten() {
+                    bits[row * words + column / 64] |= 1 << (column % 64);
+                }
+            }
+        }
+        let empty = rows.get("").into_iter().flatten();
+        for &row in empty {
+            for column in 0..inputs.len() {
+                bits[row * words + column / 64] |= 1 << (column % 64);
+            }
+        }
+        ContainmentMatrix {
+            candidates: candidates.iter().map(|c| c.to_string()).collect(),
+            inputs: inputs.len(),
+            words,
+            bits,
+        }
+    }
+
+    /// Returns the candidate substrings, in row order.
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    /// Returns the number of inputs.
+    pub fn input_count(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns whether input `input` contains candidate `candidate`.
+    pub fn contains(&self, candidate: usize, input: usize) -> bool {
+        input < self.inputs && self.row(candidate)[input / 64] & (1 << (input % 64)) != 0
+    }
+
+    /// Returns the number of inputs containing candidate `candidate`.
+    pub fn support(&self, candidate: usize) -> usize {
+        count(self.row(candidate))
+    }
+
+    /// Returns the indices of the inputs containing candidate `candidate`.
+    pub fn inputs_containing(&self, candidate: usize) -> Vec<usize> {
+        indices(self.row(candidate))
+    }
+
+    /// Returns the packed row of candidate `candidate`.
+    pub(crate) fn row(&self, candidate: usize) -> &[u64] {
+        &self.bits[candidate * self.words..(candidate + 1) * self.words]
+    }
+}
+
+/// Returns the bitwise and of two rows.
+pub(crate) fn intersect(a: &[u64], b: &[u64]) -> Vec<u64> {
+    a.iter().zip(b).map(|(a, b)| a & b).collect()
+}
+
+/// Returns whether every bit set in `a` is set in `b`.
+pub(crate) fn is_subset(a: &[u64], b: &[u64]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a & !b == 0)
+}
+
+/// Returns the number of bits set in a row.
+pub(crate) fn count(row: &[u64]) -> usize {
+    row.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+/// Returns the positions of the bits set in a row, in increasing order.
+pub(crate) fn indices(row: &[u64]) -> Vec<usize> {
+    let mut indices = Vec::with_capacity(count(row));
+    for (n, &word) in row.iter().enumerate() {
+        let mut word = word;
+        while word != 0 {
+            indices.push(n * 64 + word.trailing_zeros() as usize);
+            word &= word - 1;
+        }
+    }
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_containment_matrix() {
+        let inputs: Vec<String> = (0..70).map(|i| format!("item {i}")).collect();
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let matrix = ContainmentMatrix::new(&["item", "69", "6"], &inputs);
+        assert_eq!(matrix.support(0), 70);
+        assert_eq!(matrix.inputs_containing(1), [69]);
+        assert!(matrix.contains(2, 6) && matrix.contains(2, 66) && !matrix.contains(2, 5));
+        assert_eq!(matrix.support(2), 16);
+        assert!(is_subset(matrix.row(1), matrix.row(2)));
+        assert_eq!(count(&intersect(matrix.row(1), matrix.row(2))), 1);
+    }
+}
@@ -0,0 +1,117 @@
+/// The length, in bytes, of the n-grams recorded by a filter.
+const NGRAM: usize = 3;
+
+/// A Bloom filter of the byte n-grams of a text.
+///
+/// A substring whose n-grams are not all in the filter cannot occur in the
+/// text, so most impossible containment checks are answered without
+/// scanning it. Substrings shorter than an n-gram are never ruled out.
+#[derive(Debug, Clone)]
+pub(crate) struct NgramFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl NgramFilter {
+    /// Builds the filter of `text`, sized so that a single n-gram lookup is
+    /// a false positive with probability `false_positive_rate`, clamped to
+    /// a sensible range.
+    pub(crate) fn new(text: &str, false_positive_rate: f64) -> Self {
+        let rate = false_positive_rate.clamp(1e-9, 0.5);
+        let items = text.len().saturating_sub(NGRAM - 1).max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-items * rate.ln() / (ln2 * ln2)).ceil().max(64.0) as usize;
+        let hashes = ((bits as f64 / items) * ln2).round().clamp(1.0, 16.0) as u32;
+
+        let mut filter = NgramFilter {
+            bits: vec![0; bits.div_ceil(64)],
+            hashes,
+        };
+        let size = filter.bits.len() * 64;
+        for gram in text.as_bytes().windows(NGRAM) {
+            for bit in positions(gram, size, hashes) {
+                filter.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        filter
+    }
+
+    /// Returns whether `substring` may occur in the text; `false` is certain.
+    pub(crate) fn may_contain(&self, substring: &str) -> bool {
+        let size = self.bits.len() * 64;
+        substring.as_bytes().windows(NGRAM).all(|gram| {
+            positions(gram, size, self.hashes)
+                .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+        })
+    }
+}
+
+/// A Bloom filter of 64-bit hashes, for sets too large to hold exactly.
+#[derive(Debug, Clone)]
+pub(crate) struct HashFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl HashFilter {
+    /// Creates a filter sized for `items` hashes, so that a lookup is a
+    /// false positive with probability `false_positive_rate`, clamped to a
+    /// sensible range.
+    pub(crate) fn new(items: usize, false_positive_rate: f64) -> Self {
+        let rate = false_positive_rate.clamp(1e-9, 0.5);
+        let items = items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-items * rate.ln() / (ln2 * ln2)).ceil().max(64.0) as usize;
+        let hashes = ((bits as f64 / items) * ln2).round().clamp(1.0, 16.0) as u32;
+        HashFilter {
+            bits: vec![0; bits.div_ceil(64)],
+            hashes,
+        }
+    }
+
+    /// Adds `hash`, returning whether it may have been added before;
+    /// `false` is certain.
+    pub(crate) fn insert(&mut self, hash: u64) -> bool {
+        let size = self.bits.len() * 64;
+        let mut present = true;
+        for bit in probes(hash, size, self.hashes) {
+            present &= self.bits[bit / 64] & (1 << (bit % 64)) != 0;
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        present
+    }
+}
+
+/// Returns the `hashes` bit positions of an n-gram in a filter of `size`
+/// bits, by double hashing.
+fn positions(gram: &[u8], size: usize, hashes: u32) -> impl Iterator<Item = usize> {
+    let h1 = gram.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x100_0000_01b3)
+    });
+    probes(h1, size, hashes)
+}
+
+/// Returns the `hashes` bit positions of a hash in a filter of `size`
+/// bits, by double hashing.
+fn probes(h1: u64, size: usize, hashes: u32) -> impl Iterator<Item = usize> {
+    let h2 = (h1.rotate_left(32) ^ 0x9e37_79b9_7f4a_7c15) | 1;
+    (0..hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % size as u64) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ngram_filter() {
+        let filter = NgramFilter::new("the disk quota exceeded for alice", 0.01);
+        assert!(filter.may_contain("quota exceeded"));
+        assert!(filter.may_contain("al"));
+        assert!(!filter.may_contain("network unreachable"));
+
+        let mut filter = HashFilter::new(100, 0.01);
+        assert!(!filter.insert(42));
+        assert!(filter.insert(42));
+        assert!(!filter.insert(7));
+    }
+}
@@ -0,0 +1,469 @@
+use crate::anchoring::verify_anchors;
+use crate::codegen::{keep_translatable, Flavor};
+use crate::examples::attach_examples;
+use crate::{
+    generate_tolerant, Algorithm, Anchoring, CancellationToken, Diagnostic, GenerateError,
+    GenerateOptions, Node, Provenance, TieBreak,
+};
+use regex_syntax::hir::{ClassUnicode, ClassUnicodeRange};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// Settings shaping the tree itself rather than candidate selection.
+#[derive(Debug, Clone)]
+pub(crate) struct TreeShape {
+    /// How the tree is mined.
+    pub(crate) algorithm: Algorithm,
+    /// How patterns are anchored.
+    pub(crate) anchoring: Anchoring,
+    /// The shortest candidate, in characters, turned into a node.
+    pub(crate) min_substring_len: usize,
+    /// The most children kept per node.
+    pub(crate) max_children: Option<usize>,
+    /// Whether the checks of every candidate against every input run on
+    /// rayon's thread pool.
+    pub(crate) parallel: bool,
+    /// The token aborting generation, if any.
+    pub(crate) cancellation: Option<CancellationToken>,
+}
+
+impl TreeShape {
+    /// Returns whether generation was cancelled.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+impl Default for TreeShape {
+    fn default() -> Self {
+        TreeShape {
+            algorithm: Algorithm::CommonSubstring,
+            anchoring: Anchoring::Literals,
+            min_substring_len: 1,
+            max_children: None,
+            parallel: false,
+            cancellation: None,
+        }
+    }
+}
+
+/// A builder configuring how a regex pattern tree is generated.
+///
+/// [`crate::generate_regex_tree`] is the same as building with the
+/// defaults, which anchor patterns, keep every candidate and child, and use
+/// greedy, case-sensitive wildcards.
+#[derive(Debug, Clone)]
+pub struct RegexTreeBuilder {
+    options: GenerateOptions,
+    shape: TreeShape,
+    lazy_wildcards: bool,
+    case_insensitive: bool,
+    flavor: Option<Flavor>,
+    dataset_id: Option<String>,
+}
+
+impl Default for RegexTreeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegexTreeBuilder {
+    /// Creates a builder with the default settings.
+    pub fn new() -> Self {
+        RegexTreeBuilder {
+            options: GenerateOptions::default(),
+            shape: TreeShape::default(),
+            lazy_wildcards: false,
+            case_insensitive: false,
+            flavor: None,
+            dataset_id: None,
+        }
+    }
+
+    /// Sets the options controlling candidate selection and ranking.
+    pub fn options(mut self, options: GenerateOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets how the tree is mined from the inputs. Candidate selection
+    /// options only apply to [`Algorithm::CommonSubstring`].
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.shape.algorithm = algorithm;
+        self
+    }
+
+    /// Sets whether a literal every input starts or ends with is anchored
+    /// with `^` or `$`. When unset, every pattern floats, as in `.*GET.*`.
+    ///
+    /// This is a shorthand for [`Anchoring::Literals`] and
+    /// [`Anchoring::Floating`].
+    pub fn anchored(self, anchored: bool) -> Self {
+        self.anchoring(match anchored {
+            true => Anchoring::Literals,
+            false => Anchoring::Floating,
+        })
+    }
+
+    /// Sets how patterns are anchored to the start and end of inputs.
+    /// Anchored patterns are verified to match every input, and floated
+    /// otherwise.
+    pub fn anchoring(mut self, anchoring: Anchoring) -> Self {
+        self.shape.anchoring = anchoring;
+        self
+    }
+
+    /// Sets the shortest candidate, in characters, or in grapheme clusters
+    /// with [`GenerateOptions::grapheme_mode`], turned into a node, so
+    /// single characters such as `/` do not clutter the tree. Required
+    /// literals are kept whatever their length.
+    pub fn min_substring_len(mut self, min_substring_len: usize) -> Self {
+        self.shape.min_substring_len = min_substring_len;
+        self
+    }
+
+    /// Sets the most children kept per node. The best-ranked children are
+    /// kept and the others are dropped with their subtrees.
+    pub fn max_children(mut self, max_children: usize) -> Self {
+        self.shape.max_children = Some(max_children);
+        self
+    }
+
+    /// Sets how candidates of equal score are ordered, which decides the
+    /// ones the tree is built around, see [`TieBreak`].
+    pub fn tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.options.tie_break = tie_break;
+        self
+    }
+
+    /// Sets whether the wildcards around literals are lazy, as in
+    /// `^GET.*?`, which matters when the patterns are later extended with
+    /// capture groups. The root stays `.*`.
+    pub fn lazy_wildcards(mut self, lazy_wildcards: bool) -> Self {
+        self.lazy_wildcards = lazy_wildcards;
+        self
+    }
+
+    /// Sets whether inputs differing only in case share literals. The
+    /// inputs, required literals and stop substrings are mined with the
+    /// simple case folding `(?i)` uses, and every pattern but the root is
+    /// prefixed with `(?i)`.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Sets the syntax the patterns are meant to be written in with
+    /// [`Node::to_regex_with_flavor`] or [`Node::to_regex`], such as
+    /// [`Flavor::Vim`] or [`Flavor::PosixExtended`]. Nodes whose pattern
+    /// uses a construct the flavor cannot express, such as the inline flags
+    /// of a stratum, are left out, their children taking their place.
+    pub fn flavor(mut self, flavor: Flavor) -> Self {
+        self.flavor = Some(flavor);
+        self
+    }
+
+    /// Sets whether the checks of every candidate against every input,
+    /// which dominate on large input sets, and the verification of the
+    /// anchors run on rayon's thread pool. The tree is the same either way.
+    #[cfg(feature = "rayon")]
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.shape.parallel = parallel;
+        self
+    }
+
+    /// Sets the token a service can abort generation with, for instance
+    /// when its client disconnects. It is checked between phases and while
+    /// placing candidates, and [`RegexTreeBuilder::build`] then fails with
+    /// [`GenerateError::Cancelled`].
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.shape.cancellation = Some(token);
+        self
+    }
+
+    /// Sets the identifier of the dataset the inputs come from, recorded in
+    /// the tree's [`Provenance`] next to the hash of the inputs themselves.
+    pub fn dataset_id(mut self, dataset_id: &str) -> Self {
+        self.dataset_id = Some(dataset_id.to_string());
+        self
+    }
+
+    /// Generates the regex pattern tree of `strings`.
+    ///
+    /// # Arguments
+    ///
+    /// * `strings` - A slice of strings to generate the regex pattern tree
+    ///   from.
+    ///
+    /// # Returns
+    ///
+    /// The root node of the regex pattern tree, holding its
+    /// [`Provenance`], or an error if a required literal is missing from
+    /// one of the inputs.
+    pub fn build(&self, strings: &[&str]) -> Result<Node, GenerateError> {
+        self.build_with_diagnostics(strings).map(|(root, _)| root)
+    }
+
+    /// Generates the regex pattern tree of `strings` like
+    /// [`RegexTreeBuilder::build`], also reporting the issues that degraded
+    /// it, such as candidates excluded by a filter or inputs left
+    /// unmatched.
+    ///
+    /// # Arguments
+    ///
+    /// * `strings` - A slice of strings to generate the regex pattern tree
+    ///   from.
+    ///
+    /// # Returns
+    ///
+    /// The root node of the regex pattern tree with the diagnostics, in the
+    /// order met, or an error as [`RegexTreeBuilder::build`] fails.
+    pub fn build_with_diagnostics(
+        &self,
+        strings: &[&str],
+    ) -> Result<(Node, Vec<Diagnostic>), GenerateError> {
+        let mut diagnostics = vec![];
+        let mut options = self.options.clone();
+        let examples = std::mem::take(&mut options.examples_per_node);
+        let lowered: Vec<String>;
+        let mined: Vec<&str> = if self.case_insensitive {
+            for literal in options
+                .required_literals
+                .iter_mut()
+                .chain(&mut options.stop_substrings)
+            {
+                *literal = fold_case(literal);
+            }
+            lowered = strings.iter().map(|s| fold_case(s)).collect();
+            lowered.iter().map(String::as_str).collect()
+        } else {
+            strings.to_vec()
+        };
+
+        let (mut root, dropped) =
+            generate_tolerant(&mined, &options, &self.shape, &mut diagnostics)?;
+        let cancelled = |root: &Node| GenerateError::Cancelled {
+            partial: root.to_regex(),
+        };
+        if self.shape.is_cancelled() {
+            return Err(cancelled(&root));
+        }
+        diagnostics.extend(
+            dropped
+                .iter()
+                .map(|&input| Diagnostic::InputDropped { input }),
+        );
+        let kept: Vec<usize> = (0..strings.len())
+            .filter(|index| dropped.binary_search(index).is_err())
+            .collect();
+        let guarded = options.min_literal_support.is_some_and(|k| k > 1)
+            || diagnostics
+                .iter()
+                .any(|d| matches!(d, Diagnostic::SecretsGeneralized { .. }));
+        // A trie spells out exactly the inputs beneath each node, so its
+        // full patterns need no verifying against all of them.
+        let trie = self.shape.algorithm == Algorithm::Trie && !guarded;
+        if self.shape.anchoring != Anchoring::Floating && !trie {
+            // Anchors must hold on the inputs themselves, not their folds.
+            let remaining: Vec<&str> = kept.iter().map(|&index| strings[index]).collect();
+            verify_anchors(
+                &mut root,
+                &remaining,
+                self.case_insensitive,
+                self.shape.parallel,
+            );
+            if self.shape.is_cancelled() {
+                return Err(cancelled(&root));
+            }
+        }
+        self.rewrite(&mut root);
+        if let Some(flavor) = self.flavor {
+            let size = root.size();
+            keep_translatable(&mut root, flavor);
+            if root.size() < size {
+                diagnostics.push(Diagnostic::UntranslatableNodes {
+                    flavor,
+                    count: size - root.size(),
+                });
+            }
+        }
+        if examples > 0 && !guarded {
+            // Examples are real inputs, not their folded copies.
+            let kept: Vec<&str> = kept.iter().map(|&index| strings[index]).collect();
+            attach_examples(&mut root, &kept, examples);
+        }
+        let settings = format!(
+            "{} {:?} {:?} {} {:?} {} {} {:?}",
+            self.options.fingerprint(),
+            self.shape.algorithm,
+            self.shape.anchoring,
+            self.shape.min_substring_len,
+            self.shape.max_children,
+            self.lazy_wildcards,
+            self.case_insensitive,
+            self.flavor,
+        );
+        root.provenance = Some(Box::new(Provenance::record(
+            strings,
+            &settings,
+            self.dataset_id.as_deref(),
+        )));
+        Ok((root, diagnostics))
+    }
+
+    /// Generates the regex pattern tree of inputs that may not be valid
+    /// UTF-8, such as raw log lines, replacing their invalid sequences with
+    /// `U+FFFD` and reporting each input converted that way.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The raw inputs to generate the regex pattern tree from.
+    ///
+    /// # Returns
+    ///
+    /// The root node of the regex pattern tree with the diagnostics, or an
+    /// error as [`RegexTreeBuilder::build`] fails.
+    pub fn build_lossy(&self, inputs: &[&[u8]]) -> Result<(Node, Vec<Diagnostic>), GenerateError> {
+        let converted: Vec<Cow<str>> = inputs.iter().map(|i| String::from_utf8_lossy(i)).collect();
+        let lossy = converted
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| matches!(s, Cow::Owned(_)))
+            .map(|(input, _)| Diagnostic::LossyInput { input });
+        let mut diagnostics: Vec<Diagnostic> = lossy.collect();
+        let strings: Vec<&str> = converted.iter().map(|s| s.as_ref()).collect();
+        let (root, built) = self.build_with_diagnostics(&strings)?;
+        diagnostics.extend(built);
+        Ok((root, diagnostics))
+    }
+
+    /// Applies the wildcard and case settings to `node` and its
+    /// descendants, but for a bare `.*` such as the usual root.
+    fn rewrite(&self, node: &mut Node) {
+        for child in &mut node.children {
+            self.rewrite(Arc::make_mut(child));
+        }
+        if node.pattern == ".*" {
+            return;
+        }
+        if self.lazy_wildcards {
+            node.pattern = lazy(&node.pattern).into();
+        }
+        if self.case_insensitive {
+            node.pattern = format!("(?i){}", node.pattern).into();
+        }
+    }
+}
+
+/// Returns `s` with every character replaced by a fixed member of its
+/// simple case folding class, the one `(?i)` matches it with, so two
+/// strings fold alike exactly when `(?i)` makes them match each other.
+///
+/// Unlike [`str::to_lowercase`], this never changes the number of
+/// characters: `İ` has no simple folding and stays as it is.
+pub(crate) fn fold_case(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii() {
+                return c.to_ascii_lowercase();
+            }
+            let mut class = ClassUnicode::new([ClassUnicodeRange::new(c, c)]);
+            class.case_fold_simple();
+            let members: Vec<char> = class
+                .iter()
+                .flat_map(|range| range.start()..=range.end())
+                .collect();
+            // The smallest lowercase member, so `ſ`, `S` and `s` all give `s`.
+            let lowercase = members.iter().copied().filter(|m| m.is_lowercase());
+            lowercase.min().unwrap_or(members[0])
+        })
+        .collect()
+}
+
+/// Returns `pattern` with its leading and trailing `.*` made lazy.
+fn lazy(pattern: &str) -> String {
+    let mut lazy = String::with_capacity(pattern.len() + 2);
+    let rest = match pattern.strip_prefix(".*") {
+        Some(rest) => {
+            lazy.push_str(".*?");
+            rest
+        }
+        None => pattern,
+    };
+    lazy.push_str(rest);
+    // A trailing `.*` is a wildcard unless its dot is escaped, that is
+    // preceded by an odd number of backslashes.
+    if let Some(body) = rest.strip_suffix(".*") {
+        let backslashes = body.chars().rev().take_while(|&c| c == '\\').count();
+        if backslashes % 2 == 0 {
+            lazy.push('?');
+        }
+    }
+    lazy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_regex_tree_builder() {
+        let inputs = ["ERROR disk sda.", "error Disk sdb."];
+        let tree = RegexTreeBuilder::new()
+            .min_substring_len(3)
+            .max_children(1)
+            .lazy_wildcards(true)
+            .case_insensitive(true)
+            .build(&inputs)
+            .unwrap();
+        assert_eq!(tree.children.len(), 1);
+        for node in &tree.preorder()[1..] {
+            assert!(node.pattern.starts_with("(?i)"), "{}", node.pattern);
+            assert!(!node.pattern.ends_with(".*"), "{}", node.pattern);
+            let regex = Regex::new(&node.pattern).unwrap();
+            assert!(inputs.iter().all(|s| regex.is_match(s)), "{}", node.pattern);
+        }
+        assert!(tree
+            .preorder()
+            .iter()
+            .any(|n| n.pattern == r"(?i)^error disk sd.*?"));
+        assert_eq!(lazy(r".*a\.*"), r".*?a\.*");
+        assert_eq!(lazy(r"^a\\.*"), r"^a\\.*?");
+
+        let tree = RegexTreeBuilder::new()
+            .anchored(false)
+            .build(&["GET /a", "GET /b"])
+            .unwrap();
+        assert!(tree.preorder().iter().all(|n| !n.pattern.contains('^')));
+        let patterns = |tree: &Node| -> Vec<String> {
+            tree.preorder()
+                .iter()
+                .map(|n| n.pattern.to_string())
+                .collect()
+        };
+        assert_eq!(
+            patterns(&RegexTreeBuilder::new().build(&inputs).unwrap()),
+            patterns(&crate::generate_regex_tree(&inputs))
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive_non_ascii() {
+        assert_eq!(fold_case("İSTANBUL ſ K"), "İstanbul s k");
+        let inputs = ["İstanbul x", "istanbul y", "ISTANBUL z"];
+        let tree = RegexTreeBuilder::new()
+            .case_insensitive(true)
+            .build(&inputs)
+            .unwrap();
+        for node in tree.preorder() {
+            let regex = Regex::new(&node.pattern).unwrap();
+            for input in inputs {
+                assert!(regex.is_match(input), "{} {}", node.pattern, input);
+            }
+        }
+    }
+}
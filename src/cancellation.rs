@@ -0,0 +1,62 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag shared with a running generation, so a service can abort it, for
+/// instance when the client disconnects; see
+/// [`crate::RegexTreeBuilder::cancellation`].
+///
+/// Clones share the flag. Generation checks it between phases and while
+/// placing candidates, and gives up with
+/// [`crate::GenerateError::Cancelled`] once it is set.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the generations sharing the token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether [`CancellationToken::cancel`] was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancellationToken {
+    /// Wraps a flag the caller already shares, cancelled once it is `true`.
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        CancellationToken(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GenerateError, RegexTreeBuilder};
+    use regex::Regex;
+
+    #[test]
+    fn test_cancellation() {
+        let inputs = ["GET /index.html 200", "GET /about.html 404"];
+        let token = CancellationToken::new();
+        let builder = RegexTreeBuilder::new().cancellation(token.clone());
+        assert!(builder.build(&inputs).is_ok());
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let builder = RegexTreeBuilder::new().cancellation(CancellationToken::from(flag.clone()));
+        flag.store(true, Ordering::Relaxed);
+        match builder.build(&inputs) {
+            Err(GenerateError::Cancelled { partial }) => {
+                let regex = Regex::new(&partial).unwrap();
+                assert!(inputs.iter().all(|s| regex.is_match(s)));
+            }
+            other => panic!("expected a cancellation, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{find_common_substrings, CaptureError, Detector, Record, TemplateId};
+use regex::Regex;
+
+/// A regex over the inputs whose varying regions are named capture
+/// groups, as in `^user (?P<field1>\w+) logged in$`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturePattern {
+    parts: Vec<Part>,
+    /// Whether the pattern was generated from any input at all.
+    anchored: bool,
+}
+
+/// A region of a capture pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    /// Text shared verbatim by every input.
+    Literal(String),
+    /// A varying region.
+    Group {
+        /// The name of the capture group.
+        name: String,
+        /// The regex fragment matching every value of the region.
+        pattern: String,
+    },
+}
+
+impl CapturePattern {
+    /// Returns the anchored regex, with one named group per varying
+    /// region, or an empty one, matching everything, if there were no
+    /// inputs.
+    pub fn to_regex(&self) -> String {
+        if !self.anchored {
+            return String::new();
+        }
+        let mut regex = String::from("^");
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => regex_syntax::escape_into(literal, &mut regex),
+                Part::Group { name, pattern } => {
+                    regex.push_str("(?P<");
+                    regex.push_str(name);
+                    regex.push('>');
+                    regex.push_str(pattern);
+                    regex.push(')');
+                }
+            }
+        }
+        regex.push('$');
+        regex
+    }
+
+    /// Returns the names of the capture groups, from left to right.
+    pub fn group_names(&self) -> Vec<&str> {
+        self.parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Group { name, .. } => Some(name.as_str()),
+                Part::Literal(_) => None,
+            })
+            .collect()
+    }
+
+    /// Renames a capture group.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The current name of the group.
+    /// * `to` - The new name, made of ASCII letters, digits and
+    ///   underscores and not starting with a digit.
+    ///
+    /// # Returns
+    ///
+    /// An error if no group is named `from`, if `to` is not a valid group
+    /// name, or if another group already has it.
+    pub fn rename_group(&mut self, from: &str, to: &str) -> Result<(), CaptureError> {
+        let valid = to.chars().next().is_some_and(|c| !c.is_ascii_digit())
+            && to.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(CaptureError::InvalidName(to.to_string()));
+        }
+        if from != to && self.group_names().contains(&to) {
+            return Err(CaptureError::DuplicateName(to.to_string()));
+        }
+        let name = self
+            .parts
+            .iter_mut()
+            .find_map(|part| match part {
+                Part::Group { name, .. } if name == from => Some(name),
+                _ => None,
+            })
+            .ok_or_else(|| CaptureError::UnknownGroup(from.to_string()))?;
+        *name = to.to_string();
+        Ok(())
+    }
+
+    /// Applies the regex to each input and returns the values of its
+    /// groups.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The strings to extract values from.
+    ///
+    /// # Returns
+    ///
+    /// For each input, the record of its captured values, identified by
+    /// the template of the regex, or `None` if the regex does not match it.
+    pub fn extract(&self, inputs: &[&str]) -> Vec<Option<Record>> {
+        let pattern = self.to_regex();
+        let regex = Regex::new(&pattern).expect("capture patterns are valid regexes");
+        let template = TemplateId::of(&pattern);
+        inputs
+            .iter()
+            .map(|input| Record::capture(template, &regex, input))
+            .collect()
+    }
+}
+
+/// Generates an anchored regex matching all the given strings, in which
+/// the regions where they differ are named capture groups `field1`,
+/// `field2`, and so on, for use as an extractor.
+///
+/// The strings are split like in [`crate::generate_regex`], except that
+/// shared text only separates regions on whitespace, or after the `=` of
+/// a `key=value` pair, so `alice` and `alfred` or two IP addresses are
+/// captured whole. Each varying
+/// region is matched by the most specific [`Detector`] accepting all its
+/// values, such as `\w+`, or a wildcard if none does.
+///
+/// # Arguments
+///
+/// * `strings` - A slice of strings the regex must match.
+///
+/// # Returns
+///
+/// The capture pattern.
+pub fn generate_capture_pattern(strings: &[&str]) -> CapturePattern {
+    let mut pattern = CapturePattern {
+        parts: vec![],
+        anchored: !strings.is_empty(),
+    };
+    if !strings.is_empty() {
+        split_regions(strings, &mut pattern.parts);
+    }
+    pattern
+}
+
+/// Appends the parts matching each of `values` exactly.
+fn split_regions(values: &[&str], parts: &mut Vec<Part>) {
+    if values.iter().all(|v| *v == values[0]) {
+        push_literal(values[0], parts);
+        return;
+    }
+    let prefix = common_prefix(values);
+    let rest: Vec<&str> = values.iter().map(|v| &v[prefix.len()..]).collect();
+    let suffix = common_suffix(&rest);
+    let middles: Vec<&str> = rest.iter().map(|v| &v[..v.len() - suffix.len()]).collect();
+
+    push_literal(prefix, parts);
+    let shared = find_common_substrings(&middles)
+        .into_iter()
+        .find(|shared| middles.iter().all(|m| is_separator(m, shared)));
+    match shared {
+        Some(shared) => {
+            let splits: Vec<(&str, &str)> = middles
+                .iter()
+                .map(|m| {
+                    let at = m.find(shared).unwrap();
+                    (&m[..at], &m[at + shared.len()..])
+                })
+                .collect();
+            let left: Vec<&str> = splits.iter().map(|s| s.0).collect();
+            let right: Vec<&str> = splits.iter().map(|s| s.1).collect();
+            split_regions(&left, parts);
+            push_literal(shared, parts);
+            split_regions(&right, parts);
+        }
+        None => {
+            let name = format!(
+                "field{}",
+                parts
+                    .iter()
+                    .filter(|p| matches!(p, Part::Group { .. }))
+                    .count()
+                    + 1
+            );
+            parts.push(Part::Group {
+                name,
+                pattern: value_pattern(&middles),
+            });
+        }
+    }
+    push_literal(suffix, parts);
+}
+
+/// Returns whether a value may end at byte `at` of `s`: at its end or
+/// before whitespace.
+fn ends_token(s: &str, at: usize) -> bool {
+    s[at..].chars().next().is_none_or(char::is_whitespace)
+}
+
+/// Returns whether a value may start at byte `at` of `s`: at its start,
+/// after whitespace, or after the `=` of a `key=value` pair.
+fn starts_token(s: &str, at: usize) -> bool {
+    s[..at]
+        .chars()
+        .next_back()
+        .is_none_or(|c| c.is_whitespace() || c == '=')
+}
+
+/// Returns the longest prefix shared by all `values` after which a value
+/// may start.
+fn common_prefix<'a>(values: &[&'a str]) -> &'a str {
+    let first = values[0];
+    let mut end = 0;
+    for (i, c) in first.char_indices() {
+        let next = i + c.len_utf8();
+        if !values.iter().all(|v| v.get(..next) == Some(&first[..next])) {
+            break;
+        }
+        if starts_token(first, next) {
+            end = next;
+        }
+    }
+    &first[..end]
+}
+
+/// Returns the longest suffix shared by all `values` before which a value
+/// may end.
+fn common_suffix<'a>(values: &[&'a str]) -> &'a str {
+    let first = values[0];
+    let shortest = values.iter().map(|v| v.len()).min().unwrap_or(0);
+    let mut start = first.len();
+    for (i, _) in first.char_indices().rev() {
+        if first.len() - i > shortest || !values.iter().all(|v| v.ends_with(&first[i..])) {
+            break;
+        }
+        if ends_token(first, i) {
+            start = i;
+        }
+    }
+    &first[start..]
+}
+
+/// Returns whether the first occurrence of `shared` in `middle` leaves
+/// whole values on either side of it.
+fn is_separator(middle: &str, shared: &str) -> bool {
+    middle
+        .find(shared)
+        .is_some_and(|at| ends_token(middle, at) && starts_token(middle, at + shared.len()))
+}
+
+/// Returns the regex fragment matching every value of a varying region.
+fn value_pattern(values: &[&str]) -> String {
+    let present: Vec<&str> = values.iter().copied().filter(|v| !v.is_empty()).collect();
+    let pattern = match Detector::infer(&present) {
+        Some(detector) if !present.is_empty() => detector.pattern(),
+        _ if values.iter().any(|v| v.contains('\n')) => return "(?s:.*)".to_string(),
+        _ => return ".*".to_string(),
+    };
+    if present.len() < values.len() {
+        format!("(?:{pattern})?")
+    } else if pattern.contains('|') {
+        format!("(?:{pattern})")
+    } else {
+        pattern.to_string()
+    }
+}
+
+/// Appends `literal` to the parts, merged into a preceding literal.
+fn push_literal(literal: &str, parts: &mut Vec<Part>) {
+    if literal.is_empty() {
+        return;
+    }
+    match parts.last_mut() {
+        Some(Part::Literal(last)) => last.push_str(literal),
+        _ => parts.push(Part::Literal(literal.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_letters_are_words() {
+        let pattern = generate_capture_pattern(&["drive b failed", "drive c failed"]);
+        assert_eq!(pattern.to_regex(), r"^drive (?P<field1>\w+) failed$");
+    }
+
+    #[test]
+    fn test_generate_capture_pattern() {
+        let inputs = ["user alice logged in", "user bob logged in"];
+        let mut pattern = generate_capture_pattern(&inputs);
+        assert_eq!(pattern.to_regex(), r"^user (?P<field1>\w+) logged in$");
+
+        let inputs = [
+            "GET /a took 12 ms from 10.0.0.1",
+            "POST /bc took 7 ms from 10.0.0.22",
+            "GET /a took 130 ms from 192.168.1.1",
+        ];
+        pattern = generate_capture_pattern(&inputs);
+        assert_eq!(
+            pattern.to_regex(),
+            r"^(?P<field1>\w+) (?P<field2>\S+) took (?P<field3>[+-]?\d+) ms from (?P<field4>\d{1,3}(?:\.\d{1,3}){3})$"
+        );
+        assert_eq!(
+            pattern.group_names(),
+            ["field1", "field2", "field3", "field4"]
+        );
+        pattern.rename_group("field3", "ms").unwrap();
+        pattern.rename_group("field4", "client").unwrap();
+        assert_eq!(
+            pattern.rename_group("field1", "ms"),
+            Err(CaptureError::DuplicateName("ms".to_string()))
+        );
+        assert_eq!(
+            pattern.rename_group("nope", "x"),
+            Err(CaptureError::UnknownGroup("nope".to_string()))
+        );
+        assert_eq!(
+            pattern.rename_group("field1", "1st"),
+            Err(CaptureError::InvalidName("1st".to_string()))
+        );
+
+        let records = pattern.extract(&inputs);
+        let values: Vec<(&str, &str)> = records
+            .iter()
+            .map(|r| {
+                let record = r.as_ref().unwrap();
+                (record.get("ms").unwrap(), record.get("client").unwrap())
+            })
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("12", "10.0.0.1"),
+                ("7", "10.0.0.22"),
+                ("130", "192.168.1.1")
+            ]
+        );
+        assert!(pattern.extract(&["nothing alike"])[0].is_none());
+        assert_eq!(generate_capture_pattern(&[]).to_regex(), "");
+    }
+}
@@ -0,0 +1,83 @@
+use crate::subsumption::searcher;
+use crate::{LanguageError, Node};
+use regex_automata::dfa::Automaton;
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+use regex_automata::Anchored;
+use std::collections::HashMap;
+
+impl Node {
+    /// Counts the strings of at most `max_len` bytes the node's pattern
+    /// finds a match in, a concrete measure of how far it generalizes
+    /// beyond its inputs to show alongside its coverage: `^\d{2}$` admits
+    /// the 100 pairs of ASCII digits within 2 bytes, `.*` every string.
+    ///
+    /// The pattern is compiled to a deterministic automaton whose paths
+    /// are counted length by length, so the count is exact, though it
+    /// saturates at [`u128::MAX`] for broad patterns and long lengths.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_len` - The length, in bytes of UTF-8, of the longest strings
+    ///   counted.
+    ///
+    /// # Returns
+    ///
+    /// The number of strings, or an error if the pattern does not compile
+    /// or its automaton grows too large.
+    pub fn language_size(&self, max_len: usize) -> Result<u128, LanguageError> {
+        let invalid = |message: String| LanguageError::InvalidPattern {
+            pattern: self.pattern.to_string(),
+            message,
+        };
+        let dfa = searcher(&self.pattern)?;
+        let initial = dfa
+            .start_state(&start::Config::new().anchored(Anchored::Yes))
+            .map_err(|error| invalid(error.to_string()))?;
+
+        // How many strings of the current length lead to each state.
+        let mut paths: HashMap<StateID, u128> = HashMap::from([(initial, 1)]);
+        let mut total: u128 = 0;
+        for len in 0..=max_len {
+            for (&state, &count) in &paths {
+                if dfa.is_match_state(dfa.next_eoi_state(state)) {
+                    total = total.saturating_add(count);
+                }
+            }
+            if len == max_len {
+                break;
+            }
+            let mut next_paths = HashMap::new();
+            for (&state, &count) in &paths {
+                for byte in 0..=u8::MAX {
+                    let next = dfa.next_state(state, byte);
+                    if !dfa.is_dead_state(next) {
+                        let entry: &mut u128 = next_paths.entry(next).or_default();
+                        *entry = entry.saturating_add(count);
+                    }
+                }
+            }
+            paths = next_paths;
+        }
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Node;
+
+    #[test]
+    fn test_language_size() {
+        let size = |pattern: &str, max_len| Node::new(pattern.to_string()).language_size(max_len);
+        assert_eq!(size(r"^\d{2}$", 2).unwrap(), 100);
+        assert_eq!(size("^a*$", 3).unwrap(), 4);
+        assert_eq!(size("foo", 3).unwrap(), 1);
+        assert_eq!(size("foo", 4).unwrap(), 1 + 2 * 128);
+        assert_eq!(size("^(?:GET|POST)$", 10).unwrap(), 2);
+        assert_eq!(size("", 1).unwrap(), 1 + 128);
+        assert_eq!(size(r"[^\s\S]", 5).unwrap(), 0);
+        assert_eq!(size("", 64).unwrap(), u128::MAX);
+        assert!(size("(", 1).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{Node, TemplateId};
+use std::collections::HashMap;
+
+/// How a node of a regenerated tree compares with the previous tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeStatus {
+    /// The previous tree has the node's pattern, under the same parent and
+    /// with the same match count.
+    Unchanged,
+    /// The previous tree lacks the node's pattern.
+    Added,
+    /// The previous tree has the node's pattern, but under another parent
+    /// or with another match count.
+    Changed,
+    /// The node is only in the previous tree.
+    Removed,
+}
+
+impl NodeStatus {
+    /// Returns the lowercase name of the status, as written in exports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeStatus::Unchanged => "unchanged",
+            NodeStatus::Added => "added",
+            NodeStatus::Changed => "changed",
+            NodeStatus::Removed => "removed",
+        }
+    }
+}
+
+/// A node only in the previous tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovedNode {
+    pattern: String,
+    parent: Option<usize>,
+}
+
+impl RemovedNode {
+    /// Returns the node's pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the pre-order identifier, in the regenerated tree, of the
+    /// node's previous parent, if it is still there.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// The changes between a previous tree and its regeneration, matching
+/// nodes by their [`TemplateId`], so exports can mark what a reviewer
+/// needs to look at; see [`crate::DotOptions::diff`] and
+/// [`Node::to_json_with_diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeDiff {
+    statuses: Vec<NodeStatus>,
+    removed: Vec<RemovedNode>,
+}
+
+impl TreeDiff {
+    /// Compares a regenerated tree with the previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous` - The root node of the previous tree.
+    /// * `current` - The root node of the regenerated tree.
+    ///
+    /// # Returns
+    ///
+    /// The status of every node of the regenerated tree and the nodes
+    /// removed from the previous one.
+    pub fn new(previous: &Node, current: &Node) -> Self {
+        let before = placements(previous);
+        let after = placements(current);
+        let ids: HashMap<TemplateId, usize> = after
+            .iter()
+            .enumerate()
+            .map(|(id, placement)| (placement.template, id))
+            .collect();
+        let known: HashMap<TemplateId, &Placement> =
+            before.iter().map(|p| (p.template, p)).collect();
+        let statuses = after
+            .iter()
+            .map(|placement| match known.get(&placement.template) {
+                None => NodeStatus::Added,
+                Some(old)
+                    if old.parent == placement.parent
+                        && old.match_count == placement.match_count =>
+                {
+                    NodeStatus::Unchanged
+                }
+                Some(_) => NodeStatus::Changed,
+            })
+            .collect();
+        let removed = before
+            .iter()
+            .filter(|placement| !ids.contains_key(&placement.template))
+            .map(|placement| RemovedNode {
+                pattern: placement.pattern.clone(),
+                parent: placement
+                    .parent
+                    .and_then(|parent| ids.get(&parent).copied()),
+            })
+            .collect();
+        TreeDiff { statuses, removed }
+    }
+
+    /// Returns the status of the node of the regenerated tree with the
+    /// given pre-order identifier, the root being `0`.
+    pub fn status(&self, id: usize) -> NodeStatus {
+        self.statuses.get(id).copied().unwrap_or(NodeStatus::Added)
+    }
+
+    /// Returns the nodes only in the previous tree, in its pre-order.
+    pub fn removed(&self) -> &[RemovedNode] {
+        &self.removed
+    }
+
+    /// Returns whether the trees have the same nodes in the same places.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.statuses.iter().all(|&s| s == NodeStatus::Unchanged)
+    }
+}
+
+/// Where a node sits, by template.
+struct Placement {
+    template: TemplateId,
+    parent: Option<TemplateId>,
+    match_count: Option<usize>,
+    pattern: String,
+}
+
+/// Returns the placement of every node of the tree, in pre-order.
+fn placements(root: &Node) -> Vec<Placement> {
+    let mut placements = vec![];
+    let mut stack = vec![(root, None)];
+    while let Some((node, parent)) = stack.pop() {
+        let template = node.template_id();
+        placements.push(Placement {
+            template,
+            parent,
+            match_count: node.match_count,
+            pattern: node.pattern.to_string(),
+        });
+        stack.extend(node.children.iter().rev().map(|c| (&**c, Some(template))));
+    }
+    placements
+}
+
+#[cfg(feature = "serde")]
+impl Node {
+    /// Serializes the tree to JSON like [`Node::to_json`], marking every
+    /// node with a `status` field, `"unchanged"`, `"added"` or
+    /// `"changed"`, and adding the nodes removed since the previous tree as
+    /// children of their previous parent, or of the root, with the status
+    /// `"removed"`, so reviewers only look at what changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `diff` - The changes from the previous tree to this one.
+    ///
+    /// # Returns
+    ///
+    /// The JSON document.
+    pub fn to_json_with_diff(&self, diff: &TreeDiff) -> serde_json::Result<String> {
+        let mut document: serde_json::Value = serde_json::from_str(&self.to_json()?)?;
+        let mut paths = vec![];
+        mark(&mut document["root"], diff, vec![], &mut paths);
+        for removed in diff.removed() {
+            let ghost = serde_json::json!({
+                "pattern": removed.pattern(),
+                "children": [],
+                "status": NodeStatus::Removed.as_str(),
+            });
+            let path = &paths[removed.parent().unwrap_or(0)];
+            let mut parent = &mut document["root"];
+            for &index in path {
+                parent = &mut parent["children"][index];
+            }
+            if let Some(children) = parent["children"].as_array_mut() {
+                children.push(ghost);
+            }
+        }
+        serde_json::to_string(&document)
+    }
+}
+
+/// Adds the status of `node` and of its descendants, numbering them in
+/// pre-order, and records the path of child indices leading to each.
+#[cfg(feature = "serde")]
+fn mark(
+    node: &mut serde_json::Value,
+    diff: &TreeDiff,
+    path: Vec<usize>,
+    paths: &mut Vec<Vec<usize>>,
+) {
+    node["status"] = diff.status(paths.len()).as_str().into();
+    paths.push(path.clone());
+    let count = node["children"].as_array().map_or(0, Vec::len);
+    for index in 0..count {
+        let mut child = path.clone();
+        child.push(index);
+        mark(&mut node["children"][index], diff, child, paths);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export_dot_with_options, generate_regex_tree, DotOptions};
+
+    #[test]
+    fn test_tree_diff() {
+        let before = generate_regex_tree(&["disk sda full", "disk sdb full"]);
+        let after = generate_regex_tree(&["disk sda full", "disk sdb full", "disk nvme0 full"]);
+        assert!(TreeDiff::new(&before, &before).is_empty());
+
+        let diff = TreeDiff::new(&before, &after);
+        assert_eq!(diff.status(0), NodeStatus::Unchanged);
+        let patterns: Vec<&str> = diff.removed().iter().map(|r| r.pattern()).collect();
+        assert!(patterns.contains(&"^disk sd.*"), "{patterns:?}");
+        let reverted = TreeDiff::new(&after, &before);
+        assert!(reverted.removed().is_empty());
+        let added = before
+            .preorder()
+            .iter()
+            .position(|n| n.pattern == "^disk sd.*")
+            .unwrap();
+        assert_eq!(reverted.status(added), NodeStatus::Added);
+
+        let options = DotOptions {
+            diff: Some(diff.clone()),
+            ..DotOptions::default()
+        };
+        let dot = export_dot_with_options(&after, &options);
+        assert!(
+            dot.starts_with("digraph {\n    0 [ label = \".*\" ]\n"),
+            "{dot}"
+        );
+        assert!(dot.contains(r##"[ label = "^disk sd.*", style = dashed, color = "#d62728" ]"##));
+        let options = DotOptions {
+            diff: Some(reverted),
+            ..DotOptions::default()
+        };
+        let dot = export_dot_with_options(&before, &options);
+        let line =
+            format!(r##"{added} [ label = "^disk sd.*", style = dashed, color = "#2ca02c" ]"##);
+        assert!(dot.contains(&line), "{dot}");
+
+        #[cfg(feature = "serde")]
+        {
+            let json = after.to_json_with_diff(&diff).unwrap();
+            assert!(json.contains(r#""status":"unchanged""#), "{json}");
+            assert!(
+                json.contains(r#""pattern":"^disk sd.*","status":"removed""#),
+                "{json}"
+            );
+        }
+    }
+}
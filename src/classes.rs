@@ -0,0 +1,243 @@
+use crate::alternation::{region_pattern, region_values};
+use crate::privacy::SupportGuard;
+use crate::subsumption::anchored_literal;
+use crate::{GapStrategy, Node, Percentiles};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// A character class that literal runs may be generalized to.
+///
+/// Classes are listed from most to least specific; generalization picks the
+/// first configured one accepting every character of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharClassDetector {
+    /// An ASCII decimal digit, written `\d`.
+    Digit,
+    /// A lowercase hexadecimal digit, written `[0-9a-f]`.
+    Hex,
+    /// A whitespace character, written `\s`.
+    Whitespace,
+    /// A word character, written `\w`.
+    Word,
+}
+
+impl CharClassDetector {
+    /// All classes, from most to least specific.
+    pub const ALL: [CharClassDetector; 4] = [
+        CharClassDetector::Digit,
+        CharClassDetector::Hex,
+        CharClassDetector::Whitespace,
+        CharClassDetector::Word,
+    ];
+
+    /// Returns the regex fragment matching one character of this class.
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            CharClassDetector::Digit => r"\d",
+            CharClassDetector::Hex => "[0-9a-f]",
+            CharClassDetector::Whitespace => r"\s",
+            CharClassDetector::Word => r"\w",
+        }
+    }
+
+    /// Returns whether `c` belongs to this class.
+    pub fn matches(&self, c: char) -> bool {
+        match self {
+            CharClassDetector::Digit => c.is_ascii_digit(),
+            CharClassDetector::Hex => c.is_ascii_digit() || matches!(c, 'a'..='f'),
+            CharClassDetector::Whitespace => c.is_whitespace(),
+            CharClassDetector::Word => c.is_alphabetic() || c.is_ascii_digit() || c == '_',
+        }
+    }
+}
+
+/// One column of an aligned region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    /// A character shared by every value.
+    Literal(char),
+    /// A character varying within a class.
+    Class(CharClassDetector),
+}
+
+/// Replaces the wildcard regions of leaf patterns with character classes
+/// when the values the inputs take there can be aligned, such as
+/// `^order \d{4}$` for `order 1234` and `order 9871`.
+///
+/// Values of the same length are aligned column by column: a column
+/// keeps its character when every value agrees and otherwise takes the
+/// first of `classes` accepting all its characters. Values of different
+/// lengths become a single bounded run of the first class accepting all
+/// their characters. A region that cannot be described is written as `gaps`
+/// says, keeping its wildcard by default.
+///
+/// A region that would spell out part of a secret `guard` rejects, on its
+/// own or next to the literal, is generalized even when no classes are
+/// configured, trying them all, and keeps no literal column.
+///
+/// # Arguments
+///
+/// * `node` - The root of the tree to rewrite.
+/// * `inputs` - The strings the tree was mined from.
+/// * `classes` - The classes to generalize to, most specific first.
+/// * `gaps` - How regions no class describes are bounded.
+/// * `percentiles` - When set, the percentiles of the lengths a bounded
+///   run spans; the inputs left out are recorded on the leaf.
+/// * `guard` - Decides which values are secrets.
+pub(crate) fn generalize_classes(
+    node: &mut Node,
+    inputs: &[&str],
+    classes: &[CharClassDetector],
+    gaps: GapStrategy,
+    percentiles: Option<Percentiles>,
+    guard: &SupportGuard,
+) {
+    for child in &mut node.children {
+        generalize_classes(
+            Arc::make_mut(child),
+            inputs,
+            classes,
+            gaps,
+            percentiles,
+            guard,
+        );
+    }
+    if !node.children.is_empty() {
+        return;
+    }
+    let Some((role, literal)) = anchored_literal(&node.pattern) else {
+        return;
+    };
+    let regions = region_values(role, &literal, inputs);
+    let [before, after] = [0, 1].map(|side| {
+        let values = regions[side].as_ref()?;
+        let mut sides = [false; 2];
+        sides[side] = true;
+        let (classes, literals) = if guard.exposes_secret_around(role, &literal, inputs, sides) {
+            match classes {
+                [] => (&CharClassDetector::ALL[..], false),
+                classes => (classes, false),
+            }
+        } else if classes.is_empty() && gaps == GapStrategy::Wildcard {
+            return None;
+        } else {
+            (classes, true)
+        };
+        let lengths: HashSet<usize> = values.iter().map(|v| v.chars().count()).collect();
+        let (values, outliers) = match percentiles {
+            Some(percentiles) if lengths.len() > 1 => {
+                percentiles.trim(role, &literal, inputs, side == 0, values)
+            }
+            _ => (values.clone(), vec![]),
+        };
+        let fragment =
+            class_fragment(&values, classes, literals).or_else(|| gaps.fragment(&values))?;
+        Some((fragment, outliers))
+    });
+    if before.is_some() || after.is_some() {
+        node.pattern = region_pattern(
+            role,
+            &literal,
+            before.as_ref().map(|(fragment, _)| fragment.as_str()),
+            after.as_ref().map(|(fragment, _)| fragment.as_str()),
+        )
+        .into();
+        if !guard.is_active() {
+            record_outliers(
+                node,
+                [before, after].into_iter().flatten().flat_map(|(_, o)| o),
+            );
+        }
+    }
+}
+
+/// Adds the inputs a leaf's length bounds leave out to those it records.
+pub(crate) fn record_outliers<'a>(node: &mut Node, outliers: impl IntoIterator<Item = &'a str>) {
+    for outlier in outliers {
+        if !node.bound_outliers.iter().any(|o| o == outlier) {
+            node.bound_outliers.push(outlier.to_string());
+        }
+    }
+}
+
+/// Returns a regex fragment matching every value, made of literal
+/// characters, if `literals` allows them, and quantified classes, if the
+/// values can be described.
+fn class_fragment(
+    values: &[&str],
+    classes: &[CharClassDetector],
+    literals: bool,
+) -> Option<String> {
+    let rows: Vec<Vec<char>> = values.iter().map(|v| v.chars().collect()).collect();
+    let first = rows.first()?;
+    let mut fragment = String::new();
+    if rows.iter().any(|row| row.len() != first.len()) {
+        let class = classes
+            .iter()
+            .find(|class| rows.iter().flatten().all(|&c| class.matches(c)))?;
+        let min = rows.iter().map(Vec::len).min()?;
+        let max = rows.iter().map(Vec::len).max()?;
+        write!(fragment, "{}{{{min},{max}}}", class.pattern()).unwrap();
+        return Some(fragment);
+    }
+
+    let mut columns: Vec<(Column, usize)> = vec![];
+    for index in 0..first.len() {
+        let c = first[index];
+        let column = if literals && rows.iter().all(|row| row[index] == c) {
+            Column::Literal(c)
+        } else {
+            Column::Class(
+                *classes
+                    .iter()
+                    .find(|class| rows.iter().all(|row| class.matches(row[index])))?,
+            )
+        };
+        match columns.last_mut() {
+            Some((last, count)) if *last == column && matches!(column, Column::Class(_)) => {
+                *count += 1
+            }
+            _ => columns.push((column, 1)),
+        }
+    }
+    for (column, count) in columns {
+        match column {
+            Column::Literal(c) => {
+                regex_syntax::escape_into(c.encode_utf8(&mut [0; 4]), &mut fragment)
+            }
+            Column::Class(class) => {
+                fragment.push_str(class.pattern());
+                if count > 1 {
+                    write!(fragment, "{{{count}}}").unwrap();
+                }
+            }
+        }
+    }
+    Some(fragment)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_regex_tree_with_options, CharClassDetector, GenerateOptions};
+    use regex::Regex;
+
+    #[test]
+    fn test_generalize_classes() {
+        let inputs = ["order 1234", "order 9871", "order 5550"];
+        let options = GenerateOptions {
+            char_classes: CharClassDetector::ALL.to_vec(),
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let patterns: Vec<&str> = tree.preorder().iter().map(|n| n.pattern.as_str()).collect();
+        assert!(patterns.contains(&r"^order \d{4}$"), "{patterns:?}");
+        for pattern in patterns {
+            let regex = Regex::new(pattern).unwrap();
+            assert!(inputs.iter().all(|s| regex.is_match(s)), "{pattern}");
+        }
+
+        let tree = generate_regex_tree_with_options(&inputs, &GenerateOptions::default()).unwrap();
+        assert!(tree.preorder().iter().all(|n| !n.pattern.contains(r"\d")));
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+
+/// How the similarity of two inputs is measured when clustering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Similarity {
+    /// One minus the Levenshtein distance over chars, relative to the
+    /// longer input.
+    EditDistance,
+    /// The Jaccard similarity of the sets of whitespace-separated tokens
+    /// and pairs of consecutive tokens, which is cheaper on long lines and
+    /// ignores where the tokens are.
+    TokenShingles,
+}
+
+impl Similarity {
+    /// Returns the similarity of `a` and `b`, between `0.0` and `1.0`.
+    fn between(self, a: &str, b: &str) -> f64 {
+        match self {
+            Similarity::EditDistance => {
+                let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+                match a.len().max(b.len()) {
+                    0 => 1.0,
+                    longest => 1.0 - levenshtein(&a, &b) as f64 / longest as f64,
+                }
+            }
+            Similarity::TokenShingles => {
+                let (a, b) = (shingles(a), shingles(b));
+                match a.union(&b).count() {
+                    0 => 1.0,
+                    union => a.intersection(&b).count() as f64 / union as f64,
+                }
+            }
+        }
+    }
+}
+
+/// How inputs of several distinct shapes are partitioned before
+/// generation, see [`crate::GenerateOptions::clustering`].
+///
+/// Clusters are merged bottom-up, the two most similar on average first,
+/// for as long as they are similar enough or there are more than allowed.
+/// Every pair of distinct inputs is compared, so this suits up to a few
+/// thousand distinct inputs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clustering {
+    similarity: Similarity,
+    max_clusters: usize,
+    threshold: f64,
+}
+
+impl Clustering {
+    /// Creates a clustering into at most `max_clusters` clusters, at least
+    /// one, merging any whose average similarity reaches `threshold`.
+    pub fn new(similarity: Similarity, max_clusters: usize, threshold: f64) -> Self {
+        Clustering {
+            similarity,
+            max_clusters: max_clusters.max(1),
+            threshold,
+        }
+    }
+
+    /// Returns how similarity is measured.
+    pub fn similarity(&self) -> Similarity {
+        self.similarity
+    }
+
+    /// Returns the largest number of clusters.
+    pub fn max_clusters(&self) -> usize {
+        self.max_clusters
+    }
+
+    /// Returns the average similarity from which clusters are merged.
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Partitions `inputs` into clusters, in order of their first input,
+    /// keeping the order of the inputs within each.
+    pub(crate) fn clusters<'a>(&self, inputs: &[&'a str]) -> Vec<Vec<&'a str>> {
+        let mut distinct: Vec<&str> = vec![];
+        let mut seen = HashSet::new();
+        for &input in inputs {
+            if seen.insert(input) {
+                distinct.push(input);
+            }
+        }
+        let n = distinct.len();
+        // The average similarity between clusters, kept up to date as they
+        // merge, with the members of each; merged clusters are emptied.
+        let mut linkage = vec![vec![0.0; n]; n];
+        for i in 0..n {
+            for j in i + 1..n {
+                let similarity = self.similarity.between(distinct[i], distinct[j]);
+                linkage[i][j] = similarity;
+                linkage[j][i] = similarity;
+            }
+        }
+        let mut members: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+        let mut live = n;
+        while live > 1 {
+            let mut best: Option<(f64, usize, usize)> = None;
+            for i in (0..n).filter(|&i| !members[i].is_empty()) {
+                for j in (i + 1..n).filter(|&j| !members[j].is_empty()) {
+                    if best.is_none_or(|(b, _, _)| linkage[i][j] > b) {
+                        best = Some((linkage[i][j], i, j));
+                    }
+                }
+            }
+            let Some((similarity, i, j)) = best else {
+                break;
+            };
+            if live <= self.max_clusters && similarity < self.threshold {
+                break;
+            }
+            let (size_i, size_j) = (members[i].len() as f64, members[j].len() as f64);
+            let merged: Vec<f64> = linkage[i]
+                .iter()
+                .zip(&linkage[j])
+                .map(|(a, b)| (size_i * a + size_j * b) / (size_i + size_j))
+                .collect();
+            for (row, &similarity) in linkage.iter_mut().zip(&merged) {
+                row[i] = similarity;
+            }
+            linkage[i] = merged;
+            let moved = std::mem::take(&mut members[j]);
+            members[i].extend(moved);
+            live -= 1;
+        }
+
+        let mut cluster_of = vec![0; n];
+        for (cluster, indices) in members.iter().filter(|m| !m.is_empty()).enumerate() {
+            for &index in indices {
+                cluster_of[index] = cluster;
+            }
+        }
+        let mut clusters: Vec<(usize, Vec<&str>)> = vec![];
+        for &input in inputs {
+            let index = distinct.iter().position(|&d| d == input).unwrap_or(0);
+            let cluster = cluster_of[index];
+            match clusters.iter_mut().find(|(c, _)| *c == cluster) {
+                Some((_, cluster)) => cluster.push(input),
+                None => clusters.push((cluster, vec![input])),
+            }
+        }
+        clusters.into_iter().map(|(_, cluster)| cluster).collect()
+    }
+}
+
+/// Returns the Levenshtein distance between `a` and `b`.
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Returns the tokens of `s` and the pairs of consecutive ones.
+fn shingles(s: &str) -> HashSet<String> {
+    let tokens: Vec<&str> = s.split_whitespace().collect();
+    let pairs = tokens.windows(2).map(|pair| pair.join(" "));
+    tokens.iter().map(|t| t.to_string()).chain(pairs).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        best_patterns, generate_regex_tree_with_options, Clustering, GenerateOptions, Similarity,
+    };
+    use regex::Regex;
+
+    #[test]
+    fn test_clustering() {
+        let inputs = [
+            "GET /index.html 200",
+            "user alice action login",
+            "GET /about.html 404",
+            "user bob action logout",
+            "GET /img/logo.png 200",
+            "user carol action login",
+        ];
+        let tree = generate_regex_tree_with_options(&inputs, &GenerateOptions::default()).unwrap();
+        let best = &best_patterns(&tree, 1)[0].pattern;
+        assert!(!best.starts_with('^') && !best.ends_with('$'), "{best}");
+
+        for similarity in [Similarity::EditDistance, Similarity::TokenShingles] {
+            let options = GenerateOptions {
+                clustering: Some(Clustering::new(similarity, 2, 0.5)),
+                ..GenerateOptions::default()
+            };
+            let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+            assert_eq!(tree.children.len(), 2, "{similarity:?}");
+            let root = Regex::new(&tree.pattern).unwrap();
+            assert!(inputs.iter().all(|s| root.is_match(s)), "{}", tree.pattern);
+            for (cluster, start) in tree.children.iter().zip(["GET", "user"]) {
+                let regex = Regex::new(&cluster.pattern).unwrap();
+                for input in inputs {
+                    assert_eq!(regex.is_match(input), input.starts_with(start));
+                }
+            }
+        }
+    }
+}
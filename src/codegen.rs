@@ -0,0 +1,1132 @@
+//! Source code generation for vendoring generated patterns into
+//! applications.
+
+use crate::{Children, FlavorError, Node, Role, SubstitutionError};
+use regex_syntax::ast::{
+    self, AssertionKind, Ast, ClassAsciiKind, ClassPerlKind, ClassSet, ClassSetItem,
+    ClassUnicodeKind, Flag, FlagsItemKind, GroupKind, RepetitionKind, RepetitionRange,
+};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// The characters Rust's `\s` matches, the Unicode `White_Space` property.
+const WHITE_SPACE: &str =
+    r"\t\n\x0b\x0c\r \x85\xa0\u1680\u2000-\u200a\u2028\u2029\u202f\u205f\u3000";
+
+/// The class items JavaScript needs for Rust's Unicode `\w`.
+const JS_WORD: &str = r"\p{Alphabetic}\p{M}\p{Nd}\p{Pc}\p{Join_Control}";
+
+/// The characters Rust's `\s` matches, in the `\x{..}` notation of PCRE and RE2.
+const BRACED_WHITE_SPACE: &str =
+    r"\t\n\x0b\x0c\r \x{85}\x{a0}\x{1680}\x{2000}-\x{200a}\x{2028}\x{2029}\x{202f}\x{205f}\x{3000}";
+
+/// The class items PCRE and RE2 need for Rust's Unicode `\w`, which differ
+/// from it only on a few alphabetic symbols such as circled letters.
+const UNICODE_WORD: &str = r"\p{L}\p{Nl}\p{M}\p{Nd}\p{Pc}\x{200c}\x{200d}";
+
+/// A regex syntax patterns can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Flavor {
+    /// The `regex` crate's syntax, in which patterns are generated.
+    Rust,
+    /// RE2 and Go's `regexp`, also accepted by the `regex` crate.
+    Re2,
+    /// PCRE2, as used by `grep -P`, PHP and many other tools.
+    Pcre,
+    /// Python's `re` module, on `str` patterns.
+    Python,
+    /// JavaScript `RegExp` with the `u` flag.
+    JavaScript,
+    /// POSIX extended regular expressions, as used by `grep -E`.
+    ///
+    /// Perl classes are spelled with their ASCII equivalents, and
+    /// non-capturing groups become plain groups, renumbering any capture
+    /// groups after them.
+    PosixExtended,
+    /// Vim's default, magic, syntax, as typed after `/`.
+    ///
+    /// Perl classes are spelled with Vim's ASCII classes, `^` and `$`
+    /// match at line boundaries and a leading `(?i)` becomes `\c`; other
+    /// inline flags and word boundaries cannot be expressed.
+    Vim,
+    /// The syntax of ripgrep, written as for [`Flavor::Re2`] so the
+    /// pattern also suits its other engines and tools sharing RE2's
+    /// syntax, none of which support lookaround or backreferences.
+    Ripgrep,
+}
+
+impl Node {
+    /// Flattens the tree like [`Node::to_regex`], written in the syntax of
+    /// `flavor`.
+    ///
+    /// Escaping, class shorthands, anchors and group syntax are adjusted
+    /// so the pattern keeps its meaning: for instance `$` becomes `\z` in
+    /// PCRE, where it would also accept a trailing newline.
+    ///
+    /// # Arguments
+    ///
+    /// * `flavor` - The syntax to write the pattern in.
+    ///
+    /// # Returns
+    ///
+    /// The translated pattern, or an error naming the syntax the flavor
+    /// has no equivalent for.
+    pub fn to_regex_with_flavor(&self, flavor: Flavor) -> Result<String, FlavorError> {
+        let pattern = self.to_regex();
+        translate(&pattern, flavor).map_err(|reason| FlavorError::Untranslatable {
+            flavor,
+            pattern,
+            reason,
+        })
+    }
+}
+
+/// Removes the nodes below the root whose pattern `flavor` cannot express,
+/// their children taking their place, so every pattern left translates.
+pub(crate) fn keep_translatable(root: &mut Node, flavor: Flavor) {
+    fn relink(node: &Arc<Node>, flavor: Flavor) -> Children {
+        let children: Children = node
+            .children
+            .iter()
+            .flat_map(|child| relink(child, flavor))
+            .collect();
+        if node.pattern.render(flavor).is_err() {
+            return children;
+        }
+        let mut kept = Children::new();
+        kept.push(Arc::new(Node {
+            children,
+            ..(**node).clone()
+        }));
+        kept
+    }
+    let children = std::mem::take(&mut root.children);
+    root.children = children
+        .iter()
+        .flat_map(|child| relink(child, flavor))
+        .collect();
+}
+
+/// Emits a Rust source file with one lazily compiled regex per leaf.
+///
+/// Each leaf becomes a `pub static PATTERN_<id>: LazyLock<Regex>`, where
+/// `<id>` is its pre-order identifier, documented with what is known of
+/// where the pattern came from. The file depends on the `regex` crate and
+/// on Rust 1.80 or later.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// The source of the Rust module.
+pub fn to_rust(root: &Node) -> String {
+    let mut source = String::from(
+        "//! Regex patterns generated by regex_generator.\n\
+         \n\
+         use regex::Regex;\n\
+         use std::sync::LazyLock;\n",
+    );
+    for (id, node) in leaves(root) {
+        let _ = writeln!(source);
+        let _ = writeln!(
+            source,
+            "/// Node {id}: `{}`.",
+            node.pattern.replace('`', "\\`")
+        );
+        if let Some(provenance) = provenance(node) {
+            let _ = writeln!(source, "///");
+            let _ = writeln!(source, "/// {provenance}");
+        }
+        let _ = writeln!(
+            source,
+            "pub static PATTERN_{id}: LazyLock<Regex> =\n    \
+             LazyLock::new(|| Regex::new({}).expect(\"generated pattern compiles\"));",
+            raw_string(&node.pattern)
+        );
+    }
+    source
+}
+
+/// Emits a Python module with one compiled `re` pattern per leaf and a
+/// `matches` helper accepting values any of them finds a match in.
+///
+/// Patterns are translated to Python's syntax: named groups use `(?P<..>)`,
+/// `$` becomes `\Z` so a trailing newline is not ignored, and `\s` is
+/// spelled out as Rust's white space class. Python's `\w` is kept and may
+/// differ from Rust's on rare combining marks. Leaves whose pattern cannot
+/// be expressed are listed in a comment instead.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// The source of the Python module.
+pub fn to_python(root: &Node) -> String {
+    let mut source =
+        String::from("\"\"\"Regex patterns generated by regex_generator.\"\"\"\n\nimport re\n");
+    let mut names = vec![];
+    for (id, node) in leaves(root) {
+        let _ = writeln!(source);
+        let _ = writeln!(source, "# Node {id}: {}", comment_text(&node.pattern));
+        if let Some(provenance) = provenance(node) {
+            let _ = writeln!(source, "# {provenance}");
+        }
+        match translate(&node.pattern, Flavor::Python) {
+            Ok(pattern) => {
+                let _ = writeln!(
+                    source,
+                    "PATTERN_{id} = re.compile(r\"{}\")",
+                    pattern.replace('"', "\\\"")
+                );
+                names.push(format!("PATTERN_{id}"));
+            }
+            Err(reason) => {
+                let _ = writeln!(source, "# Skipped: {reason}.");
+            }
+        }
+    }
+    let _ = write!(
+        source,
+        "\nPATTERNS = [{}]\n\n\n\
+         def matches(value: str) -> bool:\n    \
+         \"\"\"Returns whether any generated pattern matches `value`.\"\"\"\n    \
+         return any(pattern.search(value) for pattern in PATTERNS)\n",
+        names.join(", ")
+    );
+    source
+}
+
+/// Emits a JavaScript module exporting one `RegExp` per leaf and a
+/// `matches` helper accepting values any of them finds a match in.
+///
+/// Patterns are translated to JavaScript's syntax with the `u` flag: named
+/// groups use `(?<..>)`, `.` excludes only `\n` as in Rust, and the Perl
+/// classes are spelled out with Unicode properties. Leaves whose pattern
+/// cannot be expressed, for instance because it uses inline flags, are
+/// listed in a comment instead.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// The source of the JavaScript module.
+pub fn to_javascript(root: &Node) -> String {
+    let mut source = String::from("// Regex patterns generated by regex_generator.\n");
+    let mut names = vec![];
+    for (id, node) in leaves(root) {
+        let _ = writeln!(source);
+        let _ = writeln!(source, "// Node {id}: {}", comment_text(&node.pattern));
+        if let Some(provenance) = provenance(node) {
+            let _ = writeln!(source, "// {provenance}");
+        }
+        match translate(&node.pattern, Flavor::JavaScript) {
+            Ok(pattern) => {
+                let pattern = if pattern.is_empty() {
+                    "(?:)".to_string()
+                } else {
+                    pattern
+                };
+                let _ = writeln!(source, "export const PATTERN_{id} = /{pattern}/u;");
+                names.push(format!("PATTERN_{id}"));
+            }
+            Err(reason) => {
+                let _ = writeln!(source, "// Skipped: {reason}.");
+            }
+        }
+    }
+    let _ = write!(
+        source,
+        "\nexport const PATTERNS = [{}];\n\n\
+         /** Returns whether any generated pattern matches `value`. */\n\
+         export function matches(value) {{\n  \
+         return PATTERNS.some((pattern) => pattern.test(value));\n}}\n",
+        names.join(", ")
+    );
+    source
+}
+
+/// A part of a replacement template.
+enum Replacement {
+    /// Text copied as is.
+    Text(String),
+    /// The text matched by the group of the POSIX pattern with this
+    /// number, `0` standing for the whole match.
+    Group(usize),
+}
+
+/// Emits a `sed -E` command replacing, on every line, the first match of
+/// the node's pattern with `replacement`, for applying a learned
+/// transform in a shell pipeline.
+///
+/// The replacement refers to the captures as [`regex::Regex::replace`]
+/// does: `$name` or `${name}` for a named group, `$1` for a numbered one,
+/// `$0` for the whole match and `$$` for a dollar sign. The node's own
+/// pattern, not the flattened tree, is translated as for
+/// [`Flavor::PosixExtended`], and the script is quoted for POSIX shells.
+///
+/// # Arguments
+///
+/// * `node` - The node whose pattern is applied.
+/// * `replacement` - The replacement template.
+///
+/// # Returns
+///
+/// The command, or an error if the pattern cannot be written as a POSIX
+/// extended regex, the replacement refers to a group the pattern lacks,
+/// or to one numbered beyond `\9`, the last sed can refer to.
+pub fn to_sed(node: &Node, replacement: &str) -> Result<String, SubstitutionError> {
+    let (pattern, parts) = substitution(node, replacement)?;
+    let mut script = format!("s/{}/", pattern.replace('/', "\\/"));
+    for part in parts {
+        match part {
+            Replacement::Text(text) => {
+                for c in text.chars() {
+                    if matches!(c, '\\' | '&' | '/') {
+                        script.push('\\');
+                    }
+                    script.push(c);
+                }
+            }
+            Replacement::Group(0) => script.push('&'),
+            Replacement::Group(number @ 1..=9) => {
+                let _ = write!(script, "\\{number}");
+            }
+            Replacement::Group(number) => return Err(SubstitutionError::UnreachableGroup(number)),
+        }
+    }
+    script.push('/');
+    Ok(format!("sed -E {}", shell_quote(&script)))
+}
+
+/// Emits a `gawk` command replacing, on every line, the first match of the
+/// node's pattern with `replacement`, like [`to_sed`] but without its
+/// limit of nine groups.
+///
+/// The program relies on GNU awk's `match` with an array argument, which
+/// POSIX awk lacks.
+///
+/// # Arguments
+///
+/// * `node` - The node whose pattern is applied.
+/// * `replacement` - The replacement template, as for [`to_sed`].
+///
+/// # Returns
+///
+/// The command, or an error if the pattern cannot be written as a POSIX
+/// extended regex or the replacement refers to a group the pattern lacks.
+pub fn to_awk(node: &Node, replacement: &str) -> Result<String, SubstitutionError> {
+    let (pattern, parts) = substitution(node, replacement)?;
+    let mut expression = String::from("substr($0, 1, RSTART - 1)");
+    for part in parts {
+        match part {
+            Replacement::Text(text) => {
+                let text = text
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n");
+                let _ = write!(expression, " \"{text}\"");
+            }
+            Replacement::Group(number) => {
+                let _ = write!(expression, " m[{number}]");
+            }
+        }
+    }
+    let program = format!(
+        "match($0, /{}/, m) {{ $0 = {expression} substr($0, RSTART + RLENGTH) }} 1",
+        pattern.replace('/', "\\/")
+    );
+    Ok(format!("gawk {}", shell_quote(&program)))
+}
+
+/// Translates the node's pattern to POSIX extended syntax and splits
+/// `replacement` into text and references to the groups of the
+/// translation.
+fn substitution(
+    node: &Node,
+    replacement: &str,
+) -> Result<(String, Vec<Replacement>), SubstitutionError> {
+    let pattern = translate(&node.pattern, Flavor::PosixExtended).map_err(|reason| {
+        SubstitutionError::Untranslatable(FlavorError::Untranslatable {
+            flavor: Flavor::PosixExtended,
+            pattern: node.pattern.to_string(),
+            reason,
+        })
+    })?;
+    let ast = ast::parse::Parser::new()
+        .parse(&node.pattern)
+        .expect("translated pattern parses");
+    let mut groups = vec![];
+    posix_groups(std::slice::from_ref(&ast), &mut groups);
+    let group = |reference: &str| {
+        let number = match reference.parse::<u32>() {
+            Ok(0) => return Ok(Replacement::Group(0)),
+            Ok(index) => groups
+                .iter()
+                .position(|g| matches!(g, Some((i, _)) if *i == index)),
+            Err(_) => groups
+                .iter()
+                .position(|g| matches!(g, Some((_, Some(name))) if name == reference)),
+        };
+        number
+            .map(|n| Replacement::Group(n + 1))
+            .ok_or_else(|| SubstitutionError::UnknownGroup(reference.to_string()))
+    };
+
+    let mut parts = vec![];
+    let mut text = String::new();
+    let mut rest = replacement;
+    while let Some(at) = rest.find('$') {
+        text.push_str(&rest[..at]);
+        rest = &rest[at + 1..];
+        let reference = if rest.starts_with('$') {
+            None
+        } else if let Some(braced) = rest.strip_prefix('{').and_then(|r| r.split_once('}')) {
+            Some(braced)
+        } else {
+            let end = rest
+                .find(|c: char| !(c == '_' || c.is_ascii_alphanumeric()))
+                .unwrap_or(rest.len());
+            (end > 0).then(|| rest.split_at(end))
+        };
+        match reference {
+            Some((reference, after)) => {
+                if !text.is_empty() {
+                    parts.push(Replacement::Text(std::mem::take(&mut text)));
+                }
+                parts.push(group(reference)?);
+                rest = after;
+            }
+            None => {
+                text.push('$');
+                rest = rest.strip_prefix('$').unwrap_or(rest);
+            }
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        parts.push(Replacement::Text(text));
+    }
+    Ok((pattern, parts))
+}
+
+/// Lists the groups a sequence is written with in POSIX extended syntax,
+/// where every group captures, in order: the index and name of those
+/// capturing in the original pattern, `None` for the others.
+fn posix_groups(asts: &[Ast], groups: &mut Vec<Option<(u32, Option<String>)>>) {
+    for ast in asts {
+        match ast {
+            Ast::Group(group) => {
+                groups.push(match &group.kind {
+                    GroupKind::CaptureIndex(index) => Some((*index, None)),
+                    GroupKind::CaptureName { name, .. } => {
+                        Some((name.index, Some(name.name.clone())))
+                    }
+                    GroupKind::NonCapturing(_) => None,
+                });
+                posix_groups(std::slice::from_ref(&group.ast), groups);
+            }
+            Ast::Repetition(repetition) => {
+                posix_groups(std::slice::from_ref(&repetition.ast), groups)
+            }
+            Ast::Alternation(alternation) => posix_groups(&alternation.asts, groups),
+            Ast::Concat(concat) => posix_groups(&concat.asts, groups),
+            _ => {}
+        }
+    }
+}
+
+/// Quotes `text` for POSIX shells, in single quotes, so it is passed as is.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Returns the leaves of the tree with their pre-order identifiers.
+fn leaves(root: &Node) -> impl Iterator<Item = (usize, &Node)> {
+    root.preorder()
+        .into_iter()
+        .enumerate()
+        .filter(|(_, node)| node.children.is_empty())
+}
+
+/// Makes a pattern safe to show on a single comment line.
+fn comment_text(pattern: &str) -> String {
+    pattern.replace('\n', "\\n").replace('\r', "\\r")
+}
+
+/// Describes how a node was mined and what it matched, if anything is
+/// known about it.
+fn provenance(node: &Node) -> Option<String> {
+    let role = node.role().map(|role| match role {
+        Role::Prefix => "Mined from a prefix shared by the inputs",
+        Role::Suffix => "Mined from a suffix shared by the inputs",
+        Role::Infix => "Mined from a substring shared by the inputs",
+        Role::Full => "Mined from inputs that are all identical",
+    });
+    let mut text = match (role, node.match_count()) {
+        (Some(role), Some(count)) => format!("{role}; matched {count} inputs."),
+        (Some(role), None) => format!("{role}."),
+        (None, Some(count)) => format!("Matched {count} inputs."),
+        (None, None) => String::new(),
+    };
+    if !node.examples().is_empty() {
+        // Quoted with escapes, so an example cannot end the comment line.
+        let examples: Vec<String> = node.examples().iter().map(|e| format!("{e:?}")).collect();
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        let _ = write!(text, "For example {}.", examples.join(", "));
+    }
+    (!text.is_empty()).then_some(text)
+}
+
+/// Quotes `text` as a Rust raw string literal.
+fn raw_string(text: &str) -> String {
+    let mut hashes = String::new();
+    while text.contains(&format!("\"{hashes}")) {
+        hashes.push('#');
+    }
+    format!("r{hashes}\"{text}\"{hashes}")
+}
+
+/// Translates a Rust pattern to `flavor`.
+///
+/// # Returns
+///
+/// The translated pattern, or the reason it cannot be expressed.
+fn translate(pattern: &str, flavor: Flavor) -> Result<String, String> {
+    let ast = ast::parse::Parser::new()
+        .parse(pattern)
+        .map_err(|e| e.to_string())?;
+    translate_ast(pattern, &ast, flavor)
+}
+
+/// Translates a Rust pattern, already parsed to `ast`, to `flavor`.
+///
+/// # Returns
+///
+/// The translated pattern, or the reason it cannot be expressed.
+pub(crate) fn translate_ast(pattern: &str, ast: &Ast, flavor: Flavor) -> Result<String, String> {
+    let mut out = String::with_capacity(pattern.len());
+    match flavor {
+        Flavor::Rust => return Ok(pattern.to_string()),
+        Flavor::Ripgrep => write_ast(ast, Flavor::Re2, &mut out)?,
+        Flavor::Vim => match pattern.strip_prefix("(?i)") {
+            // Vim only applies case insensitivity to a whole pattern.
+            Some(rest) => {
+                out.push_str("\\c");
+                out.push_str(&translate(rest, flavor)?);
+            }
+            None => write_ast(ast, flavor, &mut out)?,
+        },
+        _ => write_ast(ast, flavor, &mut out)?,
+    }
+    Ok(out)
+}
+
+fn write_ast(ast: &Ast, flavor: Flavor, out: &mut String) -> Result<(), String> {
+    match ast {
+        Ast::Empty(_) => {}
+        Ast::Flags(_) => return Err("inline flags outside a sequence".to_string()),
+        Ast::Literal(literal) => write_literal(literal.c, flavor, false, out),
+        Ast::Dot(_) => out.push_str(match flavor {
+            Flavor::JavaScript => r"[^\n]",
+            _ => ".",
+        }),
+        Ast::Assertion(assertion) => out.push_str(match (&assertion.kind, flavor) {
+            (AssertionKind::StartLine | AssertionKind::StartText, Flavor::Python) => r"\A",
+            (AssertionKind::EndLine | AssertionKind::EndText, Flavor::Python) => r"\Z",
+            (AssertionKind::StartLine, Flavor::Pcre) => "^",
+            (AssertionKind::EndLine | AssertionKind::EndText, Flavor::Pcre) => r"\z",
+            (AssertionKind::StartLine | AssertionKind::StartText, Flavor::JavaScript) => "^",
+            (AssertionKind::EndLine | AssertionKind::EndText, Flavor::JavaScript) => "$",
+            (AssertionKind::StartLine | AssertionKind::StartText, Flavor::PosixExtended) => "^",
+            (AssertionKind::EndLine | AssertionKind::EndText, Flavor::PosixExtended) => "$",
+            (AssertionKind::StartLine | AssertionKind::StartText, Flavor::Vim) => "^",
+            (AssertionKind::EndLine | AssertionKind::EndText, Flavor::Vim) => "$",
+            (AssertionKind::StartLine, _) => "^",
+            (AssertionKind::EndLine, _) => "$",
+            (AssertionKind::StartText, _) => r"\A",
+            (AssertionKind::EndText, _) => r"\z",
+            (_, Flavor::PosixExtended | Flavor::Vim) => return Err("word boundaries".to_string()),
+            (AssertionKind::WordBoundary, _) => r"\b",
+            (AssertionKind::NotWordBoundary, _) => r"\B",
+            _ => return Err("directional word boundaries".to_string()),
+        }),
+        Ast::ClassUnicode(class) => write_unicode_class(class, flavor, out)?,
+        Ast::ClassPerl(class) => write_perl_class(class, flavor, false, out)?,
+        Ast::ClassBracketed(class) if flavor == Flavor::PosixExtended => {
+            write_posix_bracketed(class, out)?
+        }
+        Ast::ClassBracketed(class) => write_bracketed(class, flavor, out)?,
+        Ast::Repetition(repetition) => {
+            let atomic = matches!(
+                *repetition.ast,
+                Ast::Literal(_)
+                    | Ast::Dot(_)
+                    | Ast::ClassPerl(_)
+                    | Ast::ClassUnicode(_)
+                    | Ast::ClassBracketed(_)
+                    | Ast::Group(_)
+            );
+            if atomic {
+                write_ast(&repetition.ast, flavor, out)?;
+            } else {
+                out.push_str(&open_group("", flavor));
+                write_ast(&repetition.ast, flavor, out)?;
+                out.push_str(close_group(flavor));
+            }
+            if flavor == Flavor::Vim {
+                write_vim_repetition(&repetition.op.kind, repetition.greedy, out);
+                return Ok(());
+            }
+            match &repetition.op.kind {
+                RepetitionKind::ZeroOrOne => out.push('?'),
+                RepetitionKind::ZeroOrMore => out.push('*'),
+                RepetitionKind::OneOrMore => out.push('+'),
+                RepetitionKind::Range(RepetitionRange::Exactly(n)) => {
+                    let _ = write!(out, "{{{n}}}");
+                }
+                RepetitionKind::Range(RepetitionRange::AtLeast(n)) => {
+                    let _ = write!(out, "{{{n},}}");
+                }
+                RepetitionKind::Range(RepetitionRange::Bounded(m, n)) => {
+                    let _ = write!(out, "{{{m},{n}}}");
+                }
+            }
+            if !repetition.greedy {
+                if flavor == Flavor::PosixExtended {
+                    return Err("lazy repetitions".to_string());
+                }
+                out.push('?');
+            }
+        }
+        Ast::Group(group) => {
+            match &group.kind {
+                GroupKind::CaptureIndex(_) | GroupKind::CaptureName { .. }
+                    if flavor == Flavor::Vim =>
+                {
+                    out.push_str("\\(")
+                }
+                GroupKind::CaptureIndex(_) => out.push('('),
+                GroupKind::CaptureName { name, .. } => match flavor {
+                    Flavor::PosixExtended | Flavor::Vim => out.push('('),
+                    Flavor::Python | Flavor::Re2 | Flavor::Ripgrep | Flavor::Rust => {
+                        let _ = write!(out, "(?P<{}>", name.name);
+                    }
+                    Flavor::JavaScript | Flavor::Pcre => {
+                        let _ = write!(out, "(?<{}>", name.name);
+                    }
+                },
+                GroupKind::NonCapturing(flags) => {
+                    let flags = flag_letters(flags, flavor)?;
+                    out.push_str(&open_group(&flags, flavor));
+                }
+            }
+            write_ast(&group.ast, flavor, out)?;
+            out.push_str(close_group(flavor));
+        }
+        Ast::Alternation(alternation) => {
+            for (n, branch) in alternation.asts.iter().enumerate() {
+                let sets_flags = matches!(branch, Ast::Flags(_))
+                    || matches!(branch, Ast::Concat(c) if c.asts.iter().any(|a| matches!(a, Ast::Flags(_))));
+                if sets_flags && n + 1 < alternation.asts.len() {
+                    return Err("inline flags spanning an alternation".to_string());
+                }
+                if n > 0 {
+                    out.push_str(match flavor {
+                        Flavor::Vim => "\\|",
+                        _ => "|",
+                    });
+                }
+                write_sequence(std::slice::from_ref(branch), flavor, out)?;
+            }
+        }
+        Ast::Concat(concat) => write_sequence(&concat.asts, flavor, out)?,
+    }
+    Ok(())
+}
+
+/// Writes a sequence of items, scoping any inline flags to the rest of it.
+fn write_sequence(asts: &[Ast], flavor: Flavor, out: &mut String) -> Result<(), String> {
+    for (n, ast) in asts.iter().enumerate() {
+        match ast {
+            Ast::Flags(set) => {
+                let flags = flag_letters(&set.flags, flavor)?;
+                out.push_str(&open_group(&flags, flavor));
+                write_sequence(&asts[n + 1..], flavor, out)?;
+                out.push_str(close_group(flavor));
+                return Ok(());
+            }
+            Ast::Concat(concat) => write_sequence(&concat.asts, flavor, out)?,
+            ast => write_ast(ast, flavor, out)?,
+        }
+    }
+    Ok(())
+}
+
+/// Opens a non-capturing group setting `flags`, or a plain group in POSIX
+/// syntax, which has no other kind.
+fn open_group(flags: &str, flavor: Flavor) -> String {
+    match flavor {
+        Flavor::PosixExtended => "(".to_string(),
+        Flavor::Vim => "\\%(".to_string(),
+        _ => format!("(?{flags}:"),
+    }
+}
+
+/// Closes a group.
+fn close_group(flavor: Flavor) -> &'static str {
+    match flavor {
+        Flavor::Vim => "\\)",
+        _ => ")",
+    }
+}
+
+/// Writes a repetition operator in Vim's syntax, where a lazy one is
+/// written with a `-` inside braces.
+fn write_vim_repetition(kind: &RepetitionKind, greedy: bool, out: &mut String) {
+    let (min, max) = match kind {
+        RepetitionKind::ZeroOrOne => (0, Some(1)),
+        RepetitionKind::ZeroOrMore => (0, None),
+        RepetitionKind::OneOrMore => (1, None),
+        RepetitionKind::Range(RepetitionRange::Exactly(n)) => (*n, Some(*n)),
+        RepetitionKind::Range(RepetitionRange::AtLeast(n)) => (*n, None),
+        RepetitionKind::Range(RepetitionRange::Bounded(m, n)) => (*m, Some(*n)),
+    };
+    let _ = match (min, max, greedy || max == Some(min)) {
+        (0, None, true) => write!(out, "*"),
+        (1, None, true) => write!(out, "\\+"),
+        (0, Some(1), true) => write!(out, "\\="),
+        (0, None, false) => write!(out, "\\{{-}}"),
+        (n, Some(m), _) if n == m => write!(out, "\\{{{n}}}"),
+        (n, None, greedy) => write!(out, "\\{{{}{n},}}", if greedy { "" } else { "-" }),
+        (n, Some(m), greedy) => write!(out, "\\{{{}{n},{m}}}", if greedy { "" } else { "-" }),
+    };
+}
+
+/// Returns the inline flags `flavor` understands with the same meaning.
+fn flag_letters(flags: &ast::Flags, flavor: Flavor) -> Result<String, String> {
+    if flags.items.is_empty() {
+        return Ok(String::new());
+    }
+    if matches!(
+        flavor,
+        Flavor::JavaScript | Flavor::PosixExtended | Flavor::Vim
+    ) {
+        return Err("inline flags".to_string());
+    }
+    let mut letters = String::new();
+    for item in &flags.items {
+        match (item.kind.clone(), flavor) {
+            (FlagsItemKind::Negation, _) => letters.push('-'),
+            (FlagsItemKind::Flag(Flag::CaseInsensitive), _) => letters.push('i'),
+            (FlagsItemKind::Flag(Flag::DotMatchesNewLine), _) => letters.push('s'),
+            (FlagsItemKind::Flag(Flag::IgnoreWhitespace), _) => {}
+            (FlagsItemKind::Flag(Flag::MultiLine), Flavor::Re2) => letters.push('m'),
+            (FlagsItemKind::Flag(Flag::SwapGreed), Flavor::Pcre | Flavor::Re2) => letters.push('U'),
+            (FlagsItemKind::Flag(flag), _) => return Err(format!("the {flag:?} flag")),
+        }
+    }
+    Ok(letters.trim_end_matches('-').to_string())
+}
+
+fn write_literal(c: char, flavor: Flavor, in_class: bool, out: &mut String) {
+    let special = match (in_class, flavor) {
+        (true, Flavor::Vim) => "\\]^-",
+        (true, _) => "\\[]^-",
+        (false, Flavor::Vim) => "\\.*[~^$/",
+        (false, Flavor::JavaScript) => "\\.^$*+?{}[]|()/",
+        (false, Flavor::PosixExtended) => "\\.^$*+?{[|()",
+        (false, _) => "\\.^$*+?{}[]|()",
+    };
+    if special.contains(c) {
+        out.push('\\');
+        out.push(c);
+    } else if flavor == Flavor::PosixExtended {
+        out.push(c);
+    } else if flavor == Flavor::Vim && !in_class && c.is_control() && (c as u32) < 0x100 {
+        let _ = write!(out, "\\%x{:02x}", c as u32);
+    } else if c.is_control() && (c as u32) < 0x100 {
+        let _ = write!(out, "\\x{:02x}", c as u32);
+    } else if flavor == Flavor::JavaScript && (c == '\u{2028}' || c == '\u{2029}') {
+        let _ = write!(out, "\\u{:04x}", c as u32);
+    } else {
+        out.push(c);
+    }
+}
+
+fn write_unicode_class(
+    class: &ast::ClassUnicode,
+    flavor: Flavor,
+    out: &mut String,
+) -> Result<(), String> {
+    let name = match (&class.kind, flavor) {
+        (_, Flavor::Python | Flavor::PosixExtended | Flavor::Vim) => {
+            return Err("Unicode classes".to_string())
+        }
+        (ClassUnicodeKind::OneLetter(letter), _) => letter.to_string(),
+        (ClassUnicodeKind::Named(name), _) => name.clone(),
+        (ClassUnicodeKind::NamedValue { .. }, _) => {
+            return Err("Unicode property values".to_string())
+        }
+    };
+    let negated = if class.negated { 'P' } else { 'p' };
+    let _ = write!(out, "\\{negated}{{{name}}}");
+    Ok(())
+}
+
+/// How a Perl class is written in a flavor.
+enum PerlSpelling {
+    /// A shorthand and the shorthand of its negation.
+    Shorthand(&'static str, &'static str),
+    /// Items to place in a bracketed class.
+    Items(&'static str),
+}
+
+fn write_perl_class(
+    class: &ast::ClassPerl,
+    flavor: Flavor,
+    in_class: bool,
+    out: &mut String,
+) -> Result<(), String> {
+    let spelling = match (&class.kind, flavor) {
+        (ClassPerlKind::Digit, Flavor::Python) => PerlSpelling::Shorthand(r"\d", r"\D"),
+        (ClassPerlKind::Word, Flavor::Python) => PerlSpelling::Shorthand(r"\w", r"\W"),
+        (ClassPerlKind::Space, Flavor::Python) => PerlSpelling::Items(WHITE_SPACE),
+        (ClassPerlKind::Digit, Flavor::PosixExtended) => PerlSpelling::Items("0-9"),
+        (ClassPerlKind::Space, Flavor::PosixExtended) => PerlSpelling::Items("[:space:]"),
+        (ClassPerlKind::Word, Flavor::PosixExtended) => PerlSpelling::Items("[:alnum:]_"),
+        // Inside a collection, Vim reads `\d` as a decimal character code.
+        (ClassPerlKind::Digit, Flavor::Vim) if in_class => PerlSpelling::Items("0-9"),
+        (ClassPerlKind::Space, Flavor::Vim) if in_class => PerlSpelling::Items("[:space:]"),
+        (ClassPerlKind::Word, Flavor::Vim) if in_class => PerlSpelling::Items("0-9A-Za-z_"),
+        (ClassPerlKind::Digit, Flavor::Vim) => PerlSpelling::Shorthand(r"\d", r"\D"),
+        (ClassPerlKind::Space, Flavor::Vim) => {
+            PerlSpelling::Shorthand(r"[[:space:]]", r"[^[:space:]]")
+        }
+        (ClassPerlKind::Word, Flavor::Vim) => PerlSpelling::Shorthand(r"\w", r"\W"),
+        (ClassPerlKind::Digit, _) => PerlSpelling::Shorthand(r"\p{Nd}", r"\P{Nd}"),
+        (ClassPerlKind::Space, Flavor::JavaScript) => {
+            PerlSpelling::Shorthand(r"\p{White_Space}", r"\P{White_Space}")
+        }
+        (ClassPerlKind::Space, _) => PerlSpelling::Items(BRACED_WHITE_SPACE),
+        (ClassPerlKind::Word, Flavor::JavaScript) => PerlSpelling::Items(JS_WORD),
+        (ClassPerlKind::Word, _) => PerlSpelling::Items(UNICODE_WORD),
+    };
+    match (spelling, class.negated, in_class) {
+        (PerlSpelling::Shorthand(positive, _), false, _) => out.push_str(positive),
+        (PerlSpelling::Shorthand(_, negative), true, _) => out.push_str(negative),
+        (PerlSpelling::Items(items), false, true) => out.push_str(items),
+        (PerlSpelling::Items(items), false, false) => {
+            let _ = write!(out, "[{items}]");
+        }
+        (PerlSpelling::Items(items), true, false) => {
+            let _ = write!(out, "[^{items}]");
+        }
+        (PerlSpelling::Items(_), true, true) => {
+            return Err("negated Perl classes inside a class".to_string())
+        }
+    }
+    Ok(())
+}
+
+fn write_bracketed(
+    class: &ast::ClassBracketed,
+    flavor: Flavor,
+    out: &mut String,
+) -> Result<(), String> {
+    out.push('[');
+    if class.negated {
+        out.push('^');
+    }
+    match &class.kind {
+        ClassSet::Item(item) => write_class_item(item, flavor, out)?,
+        ClassSet::BinaryOp(_) => return Err("class set operations".to_string()),
+    }
+    out.push(']');
+    Ok(())
+}
+
+fn write_class_item(item: &ClassSetItem, flavor: Flavor, out: &mut String) -> Result<(), String> {
+    match item {
+        ClassSetItem::Empty(_) => {}
+        ClassSetItem::Literal(literal) => write_literal(literal.c, flavor, true, out),
+        ClassSetItem::Range(range) => {
+            write_literal(range.start.c, flavor, true, out);
+            out.push('-');
+            write_literal(range.end.c, flavor, true, out);
+        }
+        ClassSetItem::Ascii(ascii) if ascii.negated => {
+            return Err("negated ASCII classes".to_string())
+        }
+        ClassSetItem::Ascii(ascii) => out.push_str(match ascii.kind {
+            ClassAsciiKind::Alnum => "0-9A-Za-z",
+            ClassAsciiKind::Alpha => "A-Za-z",
+            ClassAsciiKind::Ascii => r"\x00-\x7f",
+            ClassAsciiKind::Blank => r"\t ",
+            ClassAsciiKind::Cntrl => r"\x00-\x1f\x7f",
+            ClassAsciiKind::Digit => "0-9",
+            ClassAsciiKind::Graph => "!-~",
+            ClassAsciiKind::Lower => "a-z",
+            ClassAsciiKind::Print => " -~",
+            ClassAsciiKind::Punct => r"!-/:-@\[-`{-~",
+            ClassAsciiKind::Space => r"\t\n\x0b\x0c\r ",
+            ClassAsciiKind::Upper => "A-Z",
+            ClassAsciiKind::Word => "0-9A-Za-z_",
+            ClassAsciiKind::Xdigit => "0-9A-Fa-f",
+        }),
+        ClassSetItem::Unicode(class) => write_unicode_class(class, flavor, out)?,
+        ClassSetItem::Perl(class) => write_perl_class(class, flavor, true, out)?,
+        ClassSetItem::Bracketed(_) => return Err("nested classes".to_string()),
+        ClassSetItem::Union(union) => {
+            for item in &union.items {
+                write_class_item(item, flavor, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes a bracket expression in POSIX syntax, where a backslash is an
+/// ordinary character, so `]`, `^` and `-` are kept literal by their
+/// position instead.
+fn write_posix_bracketed(class: &ast::ClassBracketed, out: &mut String) -> Result<(), String> {
+    let mut body = String::new();
+    let (mut bracket, mut caret, mut dash) = (false, false, false);
+    let mut items = vec![match &class.kind {
+        ClassSet::Item(item) => item,
+        ClassSet::BinaryOp(_) => return Err("class set operations".to_string()),
+    }];
+    while let Some(item) = items.pop() {
+        match item {
+            ClassSetItem::Empty(_) => {}
+            ClassSetItem::Literal(literal) => match literal.c {
+                ']' => bracket = true,
+                '^' => caret = true,
+                '-' => dash = true,
+                c => body.push(c),
+            },
+            ClassSetItem::Range(range) => {
+                if [range.start.c, range.end.c]
+                    .iter()
+                    .any(|c| matches!(c, ']' | '^' | '-'))
+                {
+                    return Err("class ranges bounded by ], ^ or -".to_string());
+                }
+                let _ = write!(body, "{}-{}", range.start.c, range.end.c);
+            }
+            ClassSetItem::Ascii(ascii) if ascii.negated => {
+                return Err("negated ASCII classes".to_string())
+            }
+            ClassSetItem::Ascii(ascii) => {
+                let name = format!("{:?}", ascii.kind).to_lowercase();
+                let _ = write!(body, "[:{name}:]");
+            }
+            ClassSetItem::Unicode(_) => return Err("Unicode classes".to_string()),
+            ClassSetItem::Perl(class) => {
+                write_perl_class(class, Flavor::PosixExtended, true, &mut body)?
+            }
+            ClassSetItem::Bracketed(_) => return Err("nested classes".to_string()),
+            ClassSetItem::Union(union) => items.extend(union.items.iter().rev()),
+        }
+    }
+    if !class.negated && !bracket && !dash && body.is_empty() && caret {
+        out.push_str(r"\^");
+        return Ok(());
+    }
+    out.push('[');
+    if class.negated {
+        out.push('^');
+    }
+    if bracket {
+        out.push(']');
+    }
+    out.push_str(&body);
+    if caret {
+        out.push('^');
+    }
+    if dash {
+        out.push('-');
+    }
+    out.push(']');
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_regex_tree, generate_regex_tree_with_options, GenerateOptions};
+    use regex::Regex;
+
+    #[test]
+    fn test_to_rust() {
+        let tree = generate_regex_tree(&["disk \"sda\" full", "disk \"sdb\" full"]);
+        let source = to_rust(&tree);
+        assert!(source.contains("use std::sync::LazyLock;"));
+        assert!(source.contains("/// Mined from a prefix shared by the inputs."));
+        assert!(source.contains(r##"Regex::new(r#"^disk "sd.*"#)"##));
+        let leaves = tree
+            .preorder()
+            .iter()
+            .filter(|n| n.children.is_empty())
+            .count();
+        assert_eq!(source.matches("pub static PATTERN_").count(), leaves);
+
+        let options = GenerateOptions {
+            examples_per_node: 1,
+            ..GenerateOptions::default()
+        };
+        let inputs = ["disk \"sda\" full", "disk \"sdb\" full"];
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        assert!(to_rust(&tree).contains(r#"For example "disk \"sda\" full"."#));
+    }
+
+    #[test]
+    fn test_translate() {
+        let pattern = r"^(?P<id>[A-Z]{3}\-\d+)\s.*/x$";
+        assert_eq!(
+            translate(pattern, Flavor::Python).unwrap(),
+            format!(r"\A(?P<id>[A-Z]{{3}}-\d+)[{WHITE_SPACE}].*/x\Z")
+        );
+        assert_eq!(
+            translate(pattern, Flavor::JavaScript).unwrap(),
+            r"^(?<id>[A-Z]{3}-\p{Nd}+)\p{White_Space}[^\n]*\/x$"
+        );
+        assert_eq!(translate("a(?i)b", Flavor::Python).unwrap(), "a(?i:b)");
+        assert!(translate("a(?i)b", Flavor::JavaScript).is_err());
+        assert!(translate(r"\pL", Flavor::Python).is_err());
+
+        let tree = crate::generate_regex_tree(&["disk \"sda\" full", "disk \"sdb\" full"]);
+        let python = to_python(&tree);
+        assert!(python.contains(r#"re.compile(r"\Adisk \"sd.*")"#));
+        assert!(python.contains("def matches(value: str) -> bool:"));
+        let javascript = to_javascript(&tree);
+        assert!(javascript.contains(r#"export const PATTERN_"#));
+        assert!(javascript.contains(r#"= /^disk "sd[^\n]*/u;"#));
+    }
+
+    #[test]
+    fn test_to_regex_with_flavor() {
+        let pattern = r"^(?P<id>[A-Z]{3}\-\d+)\s[\w.]+?/x\{\}$";
+        let expected = [
+            (Flavor::Rust, pattern.to_string()),
+            (
+                Flavor::Re2,
+                format!(
+                    r"^(?P<id>[A-Z]{{3}}-\p{{Nd}}+)[{BRACED_WHITE_SPACE}][{UNICODE_WORD}.]+?/x\{{\}}$"
+                ),
+            ),
+            (
+                Flavor::Pcre,
+                format!(
+                    r"^(?<id>[A-Z]{{3}}-\p{{Nd}}+)[{BRACED_WHITE_SPACE}][{UNICODE_WORD}.]+?/x\{{\}}\z"
+                ),
+            ),
+            (
+                Flavor::Python,
+                format!(r"\A(?P<id>[A-Z]{{3}}-\d+)[{WHITE_SPACE}][\w.]+?/x\{{\}}\Z"),
+            ),
+            (
+                Flavor::JavaScript,
+                format!(r"^(?<id>[A-Z]{{3}}-\p{{Nd}}+)\p{{White_Space}}[{JS_WORD}.]+?\/x\{{\}}$"),
+            ),
+        ];
+        for (flavor, expected) in expected {
+            assert_eq!(translate(pattern, flavor).unwrap(), expected, "{flavor:?}");
+        }
+        assert_eq!(
+            translate(pattern, Flavor::PosixExtended).unwrap_err(),
+            "lazy repetitions"
+        );
+        assert_eq!(
+            translate(r"^(?:id|key)=[\w\-\]^]+\s\d$", Flavor::PosixExtended).unwrap(),
+            r"^(id|key)=[][:alnum:]_^-]+[[:space:]][0-9]$"
+        );
+        assert_eq!(translate(r"[\^]", Flavor::PosixExtended).unwrap(), r"\^");
+        assert_eq!(translate("(?i)a", Flavor::Re2).unwrap(), "(?i:a)");
+        assert!(translate("(?m)a$", Flavor::Pcre).is_err());
+
+        let tree = generate_regex_tree(&["disk \"sda\" full", "disk \"sdb\" full"]);
+        assert_eq!(
+            tree.to_regex_with_flavor(Flavor::Rust).unwrap(),
+            tree.to_regex()
+        );
+        assert!(tree
+            .to_regex_with_flavor(Flavor::PosixExtended)
+            .unwrap()
+            .starts_with("^disk \"sd"));
+        let error = Node::new(r"\bdisk".to_string()).to_regex_with_flavor(Flavor::PosixExtended);
+        assert!(matches!(
+            error,
+            Err(FlavorError::Untranslatable { reason, .. }) if reason == "word boundaries"
+        ));
+    }
+
+    #[test]
+    fn test_vim_and_ripgrep() {
+        let pattern = r"^(?P<id>[A-Z]{3}\-\d+)\s(?:a|b)*?/x\{\}[\d.]$";
+        assert_eq!(
+            translate(pattern, Flavor::Vim).unwrap(),
+            r"^\([A-Z]\{3}-\d\+\)[[:space:]]\%(a\|b\)\{-}\/x{}[0-9.]$"
+        );
+        assert_eq!(
+            translate(r"a{2,5}?b?", Flavor::Vim).unwrap(),
+            r"a\{-2,5}b\="
+        );
+        assert_eq!(
+            translate(r"(?i)^disk s.*", Flavor::Vim).unwrap(),
+            r"\c^disk s.*"
+        );
+        assert!(translate(r"a(?s:.)", Flavor::Vim).is_err());
+        assert_eq!(
+            translate(pattern, Flavor::Ripgrep),
+            translate(pattern, Flavor::Re2)
+        );
+        assert!(Regex::new(&translate(pattern, Flavor::Ripgrep).unwrap()).is_ok());
+
+        let inputs = ["disk sda full", "disk sdb full", "x"];
+        let options = GenerateOptions {
+            stratification: Some(crate::Stratification::Length),
+            ..GenerateOptions::default()
+        };
+        let builder = crate::RegexTreeBuilder::new().options(options);
+        let tree = builder.clone().build(&inputs).unwrap();
+        assert!(tree.iter().any(|n| n.pattern.contains("(?s:")));
+        let tree = builder.flavor(Flavor::Vim).build(&inputs).unwrap();
+        assert!(tree
+            .iter()
+            .all(|n| translate(&n.pattern, Flavor::Vim).is_ok()));
+        assert!(tree.iter().any(|n| n.pattern == "^disk s.*"));
+    }
+
+    #[test]
+    fn test_to_sed_and_awk() {
+        let node = Node::new(
+            r"^(?:user|admin) (?P<user>\w+) logged in from (?P<ip>[0-9.]+)(:\d+)?$".to_string(),
+        );
+        assert_eq!(
+            to_sed(&node, "${ip}/$3: $user & co, it's $$1").unwrap(),
+            r"sed -E 's/^(user|admin) ([[:alnum:]_]+) logged in from ([0-9.]+)(:[0-9]+)?$/\3\/\4: \2 \& co, it'\''s $1/'"
+        );
+        assert_eq!(
+            to_awk(&node, "$ip \"$0\"").unwrap(),
+            r#"gawk 'match($0, /^(user|admin) ([[:alnum:]_]+) logged in from ([0-9.]+)(:[0-9]+)?$/, m) { $0 = substr($0, 1, RSTART - 1) m[3] " \"" m[0] "\"" substr($0, RSTART + RLENGTH) } 1'"#
+        );
+        assert_eq!(
+            to_sed(&node, "$host"),
+            Err(SubstitutionError::UnknownGroup("host".to_string()))
+        );
+        let many = Node::new(format!("^{}$", "(a)".repeat(10)));
+        assert_eq!(
+            to_sed(&many, "$10"),
+            Err(SubstitutionError::UnreachableGroup(10))
+        );
+        assert!(to_awk(&many, "$10").is_ok());
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{LayoutError, Node, TreeError};
+use regex::Regex;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Identifies a serialized [`CompiledTree`].
+const MAGIC: &[u8; 4] = b"RGCT";
+
+/// The version of the serialized layout written by this crate.
+const LAYOUT_VERSION: u32 = 1;
+
+/// The size of the header: magic, version, node count and pattern bytes.
+const HEADER: usize = 16;
+
+/// Marks an unknown match count in the serialized layout.
+const UNKNOWN: u64 = u64::MAX;
+
+/// A frozen tree laid out for matching-heavy workloads.
+///
+/// Nodes are numbered breadth first, so the children of a node occupy a
+/// contiguous range, and each attribute is stored in its own array
+/// (structure of arrays) with all patterns in a single string. The layout
+/// can be written with [`CompiledTree::to_bytes`] and read back in place
+/// with [`TreeView`], for instance from a memory-mapped file.
+#[derive(Debug, Clone)]
+pub struct CompiledTree {
+    patterns: String,
+    pattern_ends: Vec<u32>,
+    match_counts: Vec<Option<u64>>,
+    first_child: Vec<u32>,
+    child_count: Vec<u32>,
+    preorder: Vec<u32>,
+    regexes: Vec<Regex>,
+}
+
+impl CompiledTree {
+    /// Freezes a tree and compiles its patterns.
+    ///
+    /// # Returns
+    ///
+    /// The compiled tree, or an error if a pattern does not compile.
+    pub fn new(root: &Node) -> Result<CompiledTree, TreeError> {
+        let mut tree = CompiledTree {
+            patterns: String::new(),
+            pattern_ends: vec![],
+            match_counts: vec![],
+            first_child: vec![],
+            child_count: vec![],
+            preorder: vec![],
+            regexes: vec![],
+        };
+        let mut queue = VecDeque::from([(root, 0usize)]);
+        let mut next = 1;
+        while let Some((node, id)) = queue.pop_front() {
+            tree.patterns.push_str(&node.pattern);
+            tree.pattern_ends.push(tree.patterns.len() as u32);
+            tree.match_counts.push(node.match_count.map(|c| c as u64));
+            tree.first_child.push(next as u32);
+            tree.child_count.push(node.children.len() as u32);
+            tree.preorder.push(id as u32);
+            next += node.children.len();
+
+            let mut child_id = id + 1;
+            for child in &node.children {
+                queue.push_back((child, child_id));
+                child_id += child.size();
+            }
+        }
+        tree.compile()?;
+        Ok(tree)
+    }
+
+    /// Loads a tree from a view of its serialized layout, compiling its
+    /// patterns.
+    pub fn from_view(view: &TreeView<'_>) -> Result<CompiledTree, TreeError> {
+        let mut tree = CompiledTree {
+            patterns: String::with_capacity(view.patterns.len()),
+            pattern_ends: Vec::with_capacity(view.len()),
+            match_counts: Vec::with_capacity(view.len()),
+            first_child: Vec::with_capacity(view.len()),
+            child_count: Vec::with_capacity(view.len()),
+            preorder: Vec::with_capacity(view.len()),
+            regexes: vec![],
+        };
+        for index in 0..view.len() {
+            tree.patterns.push_str(view.pattern(index));
+            tree.pattern_ends.push(tree.patterns.len() as u32);
+            tree.match_counts.push(view.match_count(index));
+            let children = view.children(index);
+            tree.first_child.push(children.start as u32);
+            tree.child_count.push(children.len() as u32);
+            tree.preorder.push(view.preorder_id(index) as u32);
+        }
+        tree.compile()?;
+        Ok(tree)
+    }
+
+    fn compile(&mut self) -> Result<(), TreeError> {
+        self.regexes = (0..self.len())
+            .map(|index| {
+                let pattern = self.pattern(index);
+                Regex::new(pattern).map_err(|e| TreeError::InvalidPattern {
+                    pattern: pattern.to_string(),
+                    message: e.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.pattern_ends.len()
+    }
+
+    /// Returns whether the tree has no node.
+    pub fn is_empty(&self) -> bool {
+        self.pattern_ends.is_empty()
+    }
+
+    /// Returns the pattern of the node at breadth-first index `index`.
+    pub fn pattern(&self, index: usize) -> &str {
+        let start = index
+            .checked_sub(1)
+            .map_or(0, |i| self.pattern_ends[i] as usize);
+        &self.patterns[start..self.pattern_ends[index] as usize]
+    }
+
+    /// Returns the match count of the node at breadth-first index `index`.
+    pub fn match_count(&self, index: usize) -> Option<u64> {
+        self.match_counts[index]
+    }
+
+    /// Returns the breadth-first indices of the children of a node.
+    pub fn children(&self, index: usize) -> Range<usize> {
+        let first = self.first_child[index] as usize;
+        first..first + self.child_count[index] as usize
+    }
+
+    /// Returns the pre-order identifier of the node at breadth-first index
+    /// `index`, as used by [`crate::Model`].
+    pub fn preorder_id(&self, index: usize) -> usize {
+        self.preorder[index] as usize
+    }
+
+    /// Routes `input` from the root into the first matching child, as long
+    /// as one matches.
+    ///
+    /// # Returns
+    ///
+    /// The pre-order identifier of the node reached, or `None` if not even
+    /// the root matches.
+    pub fn classify(&self, input: &str) -> Option<usize> {
+        if self.is_empty() || !self.regexes[0].is_match(input) {
+            return None;
+        }
+        let mut index = 0;
+        while let Some(child) = self
+            .children(index)
+            .find(|&child| self.regexes[child].is_match(input))
+        {
+            index = child;
+        }
+        Some(self.preorder_id(index))
+    }
+
+    /// Serializes the layout as little-endian arrays, readable in place
+    /// with [`TreeView::new`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let n = self.len();
+        let mut bytes = Vec::with_capacity(HEADER + n * 24 + self.patterns.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&LAYOUT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(n as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.patterns.len() as u32).to_le_bytes());
+        for count in &self.match_counts {
+            bytes.extend_from_slice(&count.unwrap_or(UNKNOWN).to_le_bytes());
+        }
+        for array in [
+            &self.pattern_ends,
+            &self.first_child,
+            &self.child_count,
+            &self.preorder,
+        ] {
+            for value in array {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(self.patterns.as_bytes());
+        bytes
+    }
+}
+
+/// A read-only view of a serialized [`CompiledTree`] that decodes fields
+/// on access, without copying the buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct TreeView<'a> {
+    len: usize,
+    match_counts: &'a [u8],
+    pattern_ends: &'a [u8],
+    first_child: &'a [u8],
+    child_count: &'a [u8],
+    preorder: &'a [u8],
+    patterns: &'a str,
+}
+
+impl<'a> TreeView<'a> {
+    /// Checks the layout of `bytes` and views it as a tree.
+    ///
+    /// # Returns
+    ///
+    /// The view, or an error if the buffer is not a complete and consistent
+    /// layout written by a supported version.
+    pub fn new(bytes: &'a [u8]) -> Result<TreeView<'a>, LayoutError> {
+        let header = bytes.get(..HEADER).ok_or(LayoutError::Truncated)?;
+        if &header[..4] != MAGIC {
+            return Err(LayoutError::BadMagic);
+        }
+        let word = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+        let version = word(4);
+        if version != LAYOUT_VERSION {
+            return Err(LayoutError::UnsupportedVersion(version));
+        }
+        let len = word(8) as usize;
+        let pattern_bytes = word(12) as usize;
+
+        let mut offset = HEADER;
+        let mut take = |size: usize| {
+            let section = bytes
+                .get(offset..offset + size)
+                .ok_or(LayoutError::Truncated)?;
+            offset += size;
+            Ok::<_, LayoutError>(section)
+        };
+        let view = TreeView {
+            len,
+            match_counts: take(len * 8)?,
+            pattern_ends: take(len * 4)?,
+            first_child: take(len * 4)?,
+            child_count: take(len * 4)?,
+            preorder: take(len * 4)?,
+            patterns: std::str::from_utf8(take(pattern_bytes)?)
+                .map_err(|_| LayoutError::InvalidUtf8)?,
+        };
+
+        let mut previous = 0;
+        for index in 0..len {
+            let end = read_u32(view.pattern_ends, index) as usize;
+            let children = view.children(index);
+            if end < previous
+                || end > pattern_bytes
+                || !view.patterns.is_char_boundary(end)
+                || children.end > len
+                || (children.start <= index && !children.is_empty())
+            {
+                return Err(LayoutError::InvalidOffsets { node: index });
+            }
+            previous = end;
+        }
+        Ok(view)
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the tree has no node.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the pattern of the node at breadth-first index `index`.
+    pub fn pattern(&self, index: usize) -> &'a str {
+        let start = index
+            .checked_sub(1)
+            .map_or(0, |i| read_u32(self.pattern_ends, i) as usize);
+        &self.patterns[start..read_u32(self.pattern_ends, index) as usize]
+    }
+
+    /// Returns the match count of the node at breadth-first index `index`.
+    pub fn match_count(&self, index: usize) -> Option<u64> {
+        let bytes = &self.match_counts[index * 8..index * 8 + 8];
+        let count = u64::from_le_bytes(bytes.try_into().unwrap());
+        (count != UNKNOWN).then_some(count)
+    }
+
+    /// Returns the breadth-first indices of the children of a node.
+    pub fn children(&self, index: usize) -> Range<usize> {
+        let first = read_u32(self.first_child, index) as usize;
+        first..first + read_u32(self.child_count, index) as usize
+    }
+
+    /// Returns the pre-order identifier of the node at breadth-first index
+    /// `index`.
+    pub fn preorder_id(&self, index: usize) -> usize {
+        read_u32(self.preorder, index) as usize
+    }
+}
+
+fn read_u32(array: &[u8], index: usize) -> u32 {
+    u32::from_le_bytes(array[index * 4..index * 4 + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Model;
+
+    #[test]
+    fn test_compiled_tree() {
+        let model = Model::train(&["disk full on sda", "disk full on sdb"]);
+        let compiled = CompiledTree::new(model.root()).unwrap();
+        assert_eq!(compiled.len(), model.root().size());
+        for input in ["disk full on sdc", "cpu hot", "disk full on sda"] {
+            assert_eq!(compiled.classify(input), model.classify(input));
+        }
+
+        let bytes = compiled.to_bytes();
+        let view = TreeView::new(&bytes).unwrap();
+        assert_eq!(view.len(), compiled.len());
+        assert_eq!(view.pattern(1), compiled.pattern(1));
+        assert_eq!(view.children(0), compiled.children(0));
+        let reloaded = CompiledTree::from_view(&view).unwrap();
+        assert_eq!(
+            reloaded.classify("disk full on sdc"),
+            compiled.classify("disk full on sdc")
+        );
+
+        assert_eq!(
+            TreeView::new(&bytes[..bytes.len() - 1]).unwrap_err(),
+            LayoutError::Truncated
+        );
+        assert_eq!(TreeView::new(b"nope").unwrap_err(), LayoutError::Truncated);
+    }
+}
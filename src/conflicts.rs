@@ -0,0 +1,153 @@
+use crate::{find_common_substrings, Model, Role};
+
+/// The most overlapping inputs reported per conflict.
+const MAX_CONFLICT_EXAMPLES: usize = 3;
+
+/// A suggested way to resolve a [`Conflict`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Refinement {
+    /// Replace the generated pattern with this tighter one, which keeps the
+    /// inputs only the generated pattern matches and rejects the shared
+    /// ones.
+    Tighten(String),
+    /// Move the generated node beneath the imported one, since every input
+    /// it matches is also matched by the imported template.
+    Nest,
+}
+
+/// An imported template and a generated pattern that both match some
+/// inputs without one refining the other, so the route of those inputs
+/// depends on sibling order rather than on the catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    catalog_id: String,
+    imported: usize,
+    generated: usize,
+    examples: Vec<String>,
+    suggestion: Option<Refinement>,
+}
+
+impl Conflict {
+    /// Returns the catalog identifier of the imported template.
+    pub fn catalog_id(&self) -> &str {
+        &self.catalog_id
+    }
+
+    /// Returns the identifier of the imported node.
+    pub fn imported(&self) -> usize {
+        self.imported
+    }
+
+    /// Returns the identifier of the generated node.
+    pub fn generated(&self) -> usize {
+        self.generated
+    }
+
+    /// Returns a few examples matched by both patterns.
+    pub fn examples(&self) -> &[String] {
+        &self.examples
+    }
+
+    /// Returns the suggested resolution, or `None` if the inputs only the
+    /// generated pattern matches share no literal absent from the others.
+    pub fn suggestion(&self) -> Option<&Refinement> {
+        self.suggestion.as_ref()
+    }
+}
+
+impl Model {
+    /// Reports the generated patterns overlapping a template imported with
+    /// [`Model::import_templates`] on the recorded examples, with a
+    /// suggested refinement for each.
+    ///
+    /// A generated node beneath an imported one, or above it, refines it
+    /// rather than conflicting with it.
+    ///
+    /// # Returns
+    ///
+    /// The conflicts, ordered by imported then generated node.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let nodes = self.root().preorder();
+        let matched: Vec<Vec<&str>> = self
+            .compiled()
+            .iter()
+            .map(|regex| {
+                self.examples()
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|e| regex.is_match(e))
+                    .collect()
+            })
+            .collect();
+        let related = |a: usize, b: usize| {
+            let (ancestor, id) = (a.min(b), a.max(b));
+            id < ancestor + nodes[ancestor].size()
+        };
+
+        let mut conflicts = vec![];
+        for imported in 1..nodes.len() {
+            let Some(catalog_id) = self.catalog_id(imported) else {
+                continue;
+            };
+            for generated in 1..nodes.len() {
+                if self.catalog_id(generated).is_some() || related(imported, generated) {
+                    continue;
+                }
+                let (shared, exclusive): (Vec<&str>, Vec<&str>) = matched[generated]
+                    .iter()
+                    .partition(|e| matched[imported].contains(e));
+                if shared.is_empty() {
+                    continue;
+                }
+                conflicts.push(Conflict {
+                    catalog_id: catalog_id.to_string(),
+                    imported,
+                    generated,
+                    examples: shared
+                        .iter()
+                        .take(MAX_CONFLICT_EXAMPLES)
+                        .map(|e| e.to_string())
+                        .collect(),
+                    suggestion: suggest(&shared, &exclusive),
+                });
+            }
+        }
+        conflicts
+    }
+}
+
+/// Suggests a refinement of a generated pattern matching both the `shared`
+/// inputs and the `exclusive` ones.
+fn suggest(shared: &[&str], exclusive: &[&str]) -> Option<Refinement> {
+    if exclusive.is_empty() {
+        return Some(Refinement::Nest);
+    }
+    let literal = find_common_substrings(exclusive)
+        .into_iter()
+        .find(|literal| !shared.iter().any(|s| s.contains(literal)))?;
+    Some(Refinement::Tighten(
+        Role::of(literal, exclusive).pattern(literal),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflicts() {
+        let mut model = Model::import_templates(&[("disk", r"^disk .*")]).unwrap();
+        model.extend(&["disk full on sda", "net full on eth0", "net full on eth1"]);
+        let conflicts = model.conflicts();
+        assert!(!conflicts.is_empty());
+        for conflict in &conflicts {
+            assert_eq!(conflict.catalog_id(), "disk");
+            assert_eq!(conflict.imported(), 1);
+            assert_eq!(conflict.examples(), ["disk full on sda"]);
+            assert_eq!(
+                conflict.suggestion(),
+                Some(&Refinement::Tighten(r"^net full on eth.*".to_string()))
+            );
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::hierarchy::template_tree;
+use crate::{DetectorDictionary, GenerateError, Node, TemplateId};
+
+/// A template of a [`MultiCorpus`], with the corpora it was generated from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourcedTemplate {
+    id: TemplateId,
+    pattern: String,
+    sources: Vec<String>,
+}
+
+impl SourcedTemplate {
+    /// Returns the stable identifier of the template.
+    pub fn id(&self) -> TemplateId {
+        self.id
+    }
+
+    /// Returns the pattern of the template.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the names of the corpora whose tree holds the template, in
+    /// the order the corpora were given.
+    pub fn sources(&self) -> &[String] {
+        &self.sources
+    }
+
+    /// Returns whether several corpora share the template.
+    pub fn is_shared(&self) -> bool {
+        self.sources.len() > 1
+    }
+}
+
+/// The template trees of several named corpora, generated with a shared
+/// vocabulary, and their templates across sources.
+#[derive(Debug, Clone)]
+pub struct MultiCorpus {
+    trees: Vec<(String, Node)>,
+    templates: Vec<SourcedTemplate>,
+}
+
+impl MultiCorpus {
+    /// Returns the name and tree of each corpus, in the order given.
+    pub fn trees(&self) -> impl Iterator<Item = (&str, &Node)> {
+        self.trees.iter().map(|(name, tree)| (name.as_str(), tree))
+    }
+
+    /// Returns the tree of the corpus called `name`, if any.
+    pub fn tree(&self, name: &str) -> Option<&Node> {
+        self.trees
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, tree)| tree)
+    }
+
+    /// Returns every template, in order of first appearance in the trees.
+    pub fn templates(&self) -> &[SourcedTemplate] {
+        &self.templates
+    }
+
+    /// Returns the templates several corpora share.
+    pub fn shared(&self) -> Vec<&SourcedTemplate> {
+        self.templates.iter().filter(|t| t.is_shared()).collect()
+    }
+
+    /// Returns the templates only the corpus called `name` has.
+    pub fn unique_to(&self, name: &str) -> Vec<&SourcedTemplate> {
+        self.templates
+            .iter()
+            .filter(|t| t.sources == [name])
+            .collect()
+    }
+}
+
+/// Generates a template tree per named corpus, such as the logs of each
+/// microservice, and reports which templates are shared across sources and
+/// which are unique to one.
+///
+/// Every corpus describes its fields with the named sub-patterns of the
+/// same `dictionary`, learning into it as it goes, so a field of the same
+/// shape gets the same name and pattern in every tree, and templates are
+/// compared by their [`TemplateId`].
+///
+/// # Arguments
+///
+/// * `corpora` - The name and inputs of each corpus.
+/// * `max_depth` - The maximum number of specialization levels below the
+///   coarse templates, see [`crate::generate_template_tree`].
+/// * `dictionary` - The shared vocabulary of named sub-patterns.
+///
+/// # Returns
+///
+/// The trees and templates, or an error if a corpus is empty or its
+/// inputs cannot be aligned.
+pub fn generate_multi_corpus(
+    corpora: &[(&str, &[&str])],
+    max_depth: usize,
+    dictionary: &mut DetectorDictionary,
+) -> Result<MultiCorpus, GenerateError> {
+    let mut trees = vec![];
+    let mut templates: Vec<SourcedTemplate> = vec![];
+    for &(name, inputs) in corpora {
+        let tree = template_tree(inputs, max_depth, Some(dictionary))?;
+        for node in tree.preorder().into_iter().skip(1) {
+            let id = node.template_id();
+            match templates.iter_mut().find(|t| t.id == id) {
+                Some(template) if template.sources.iter().any(|s| s == name) => {}
+                Some(template) => template.sources.push(name.to_string()),
+                None => templates.push(SourcedTemplate {
+                    id,
+                    pattern: node.pattern.to_string(),
+                    sources: vec![name.to_string()],
+                }),
+            }
+        }
+        trees.push((name.to_string(), tree));
+    }
+    Ok(MultiCorpus { trees, templates })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_multi_corpus() {
+        let auth: &[&str] = &[
+            "GET /health 200 ORD-1234",
+            "GET /health 200 ORD-9876",
+            "login user=alice ok",
+            "login user=bob ok",
+        ];
+        let billing: &[&str] = &[
+            "GET /health 200 ORD-5555",
+            "GET /health 200 ORD-4321",
+            "charge 12 EUR",
+            "charge 7 USD",
+        ];
+        let mut dictionary = DetectorDictionary::new();
+        let report =
+            generate_multi_corpus(&[("auth", auth), ("billing", billing)], 1, &mut dictionary)
+                .unwrap();
+
+        assert_eq!(report.trees().count(), 2);
+        assert!(report.tree("billing").is_some());
+        assert!(!dictionary.entries().is_empty());
+        let shared = report.shared();
+        assert_eq!(shared.len(), 1, "{:?}", report.templates());
+        assert!(shared[0].pattern().starts_with(r"^GET\s+/health\s+200\s+"));
+        assert_eq!(shared[0].sources(), ["auth", "billing"]);
+        assert!(report
+            .unique_to("auth")
+            .iter()
+            .all(|t| t.pattern().contains("login")));
+        assert!(report
+            .unique_to("billing")
+            .iter()
+            .all(|t| t.pattern().contains("charge")));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::matcher::required_literal;
+use crate::{Node, TreeError};
+use regex_automata::nfa::thompson::NFA;
+use regex_syntax::hir::{Hir, HirKind};
+
+/// A static estimate of how expensive a pattern is to match, computed
+/// without running it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    nfa_states: usize,
+    required_literal: Option<String>,
+    leading_wildcard: bool,
+}
+
+impl CostEstimate {
+    /// Returns the number of states of the pattern's Thompson NFA, which
+    /// bounds the work per input byte of automaton-based engines and the
+    /// size of the DFA they build lazily.
+    pub fn nfa_states(&self) -> usize {
+        self.nfa_states
+    }
+
+    /// Returns the longest literal every match contains, which engines use
+    /// as a prefilter to skip inputs and input regions quickly, or `None`
+    /// if there is none.
+    pub fn required_literal(&self) -> Option<&str> {
+        self.required_literal.as_deref()
+    }
+
+    /// Returns whether the pattern starts with an unbounded repetition such
+    /// as `.*`, which backtracking engines retry from every input position.
+    pub fn leading_wildcard(&self) -> bool {
+        self.leading_wildcard
+    }
+
+    /// Returns a relative score combining the other measures, higher being
+    /// slower: the NFA size, doubled by a leading wildcard and divided by
+    /// one plus the length of the required literal. Scores are only
+    /// meaningful compared with each other.
+    pub fn score(&self) -> f64 {
+        let wildcard = if self.leading_wildcard { 2.0 } else { 1.0 };
+        let literal = self.required_literal.as_ref().map_or(0, String::len);
+        self.nfa_states as f64 * wildcard / (1 + literal) as f64
+    }
+}
+
+impl Node {
+    /// Estimates how expensive the node's pattern is to match, so patterns
+    /// likely to be slow in the target engine can be caught before being
+    /// deployed.
+    ///
+    /// # Returns
+    ///
+    /// The estimate, or an error if the pattern does not compile.
+    pub fn estimated_cost(&self) -> Result<CostEstimate, TreeError> {
+        let invalid = |message: String| TreeError::InvalidPattern {
+            pattern: self.pattern.to_string(),
+            message,
+        };
+        let hir = regex_syntax::parse(&self.pattern).map_err(|e| invalid(e.to_string()))?;
+        let nfa = NFA::new(&self.pattern).map_err(|e| invalid(e.to_string()))?;
+        Ok(CostEstimate {
+            nfa_states: nfa.states().len(),
+            required_literal: required_literal(&hir)
+                .map(|literal| String::from_utf8_lossy(&literal).into_owned()),
+            leading_wildcard: leading_wildcard(&hir),
+        })
+    }
+}
+
+/// Returns whether every match of `hir` may start with an unbounded
+/// repetition.
+fn leading_wildcard(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Repetition(repetition) => repetition.max.is_none(),
+        HirKind::Capture(capture) => leading_wildcard(&capture.sub),
+        HirKind::Concat(parts) => parts.first().is_some_and(leading_wildcard),
+        HirKind::Alternation(branches) => branches.iter().any(leading_wildcard),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimated_cost() {
+        let literal = Node::new(r"^disk full on sd.*".to_string())
+            .estimated_cost()
+            .unwrap();
+        assert_eq!(literal.required_literal(), Some("disk full on sd"));
+        assert!(!literal.leading_wildcard());
+
+        let floating = Node::new(r".*a.*".to_string()).estimated_cost().unwrap();
+        assert_eq!(floating.required_literal(), Some("a"));
+        assert!(floating.leading_wildcard());
+        assert!(floating.score() > literal.score());
+        assert!(literal.nfa_states() > 0);
+
+        assert!(matches!(
+            Node::new("(".to_string()).estimated_cost(),
+            Err(TreeError::InvalidPattern { .. })
+        ));
+    }
+}
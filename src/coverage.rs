@@ -0,0 +1,191 @@
+use crate::{Children, Node, Role, TreeError};
+use regex::Regex;
+use std::sync::Arc;
+
+/// A leaf pattern and the indices of the inputs it matches.
+pub type CoveredLeaf = (String, Vec<usize>);
+
+/// Removes the nodes matching fewer than `min_coverage` of the inputs,
+/// re-linking their children to their parent in their place, to turn an
+/// over-grown tree into one a human can review.
+///
+/// The root is always kept. Every remaining node's match count is updated.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `inputs` - The strings coverage is measured on.
+/// * `min_coverage` - The smallest share of `inputs`, between `0` and `1`,
+///   a node must match to be kept.
+///
+/// # Returns
+///
+/// The number of nodes removed, or an error if a pattern does not compile.
+pub fn prune_by_coverage(
+    root: &mut Node,
+    inputs: &[&str],
+    min_coverage: f64,
+) -> Result<usize, TreeError> {
+    let threshold = min_coverage * inputs.len() as f64;
+    root.match_count = Some(count_matches(&root.pattern, inputs)?);
+    prune_children(root, inputs, threshold)
+}
+
+fn prune_children(node: &mut Node, inputs: &[&str], threshold: f64) -> Result<usize, TreeError> {
+    let mut removed = 0;
+    let mut pending: Vec<Arc<Node>> = std::mem::take(&mut node.children).into_vec();
+    pending.reverse();
+    let mut kept = Children::new();
+    while let Some(child) = pending.pop() {
+        let count = count_matches(&child.pattern, inputs)?;
+        if (count as f64) < threshold {
+            removed += 1;
+            // The grandchildren take the child's place, in order.
+            pending.extend(child.children.iter().rev().cloned());
+            continue;
+        }
+        let mut child = Arc::unwrap_or_clone(child);
+        child.match_count = Some(count);
+        removed += prune_children(&mut child, inputs, threshold)?;
+        kept.push(Arc::new(child));
+    }
+    node.children = kept;
+    Ok(removed)
+}
+
+/// Makes the leaves of the tree a cover of the inputs: every input is
+/// matched by at least one leaf, and every leaf matches at least one input.
+///
+/// Leaves matching no input are removed, which may turn their parent into
+/// a leaf in turn. Each input left unmatched by every leaf then gets an
+/// explicit `^input$` leaf beneath the deepest node matching it. The match
+/// count of every remaining node is updated.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `inputs` - The strings to cover.
+///
+/// # Returns
+///
+/// Each leaf pattern, in pre-order, with the indices of the inputs it
+/// matches, or an error if a pattern does not compile. Only the root, when
+/// there are no inputs, can match none.
+pub fn cover_inputs(root: &mut Node, inputs: &[&str]) -> Result<Vec<CoveredLeaf>, TreeError> {
+    remove_empty_leaves(root, inputs)?;
+    let mut covered = vec![false; inputs.len()];
+    for (_, matched) in leaf_matches(root, inputs)? {
+        for index in matched {
+            covered[index] = true;
+        }
+    }
+    for (index, input) in inputs.iter().enumerate() {
+        if !covered[index] {
+            add_explicit_leaf(root, input)?;
+        }
+    }
+    leaf_matches(root, inputs)
+}
+
+/// Removes the descendants of `node` that are leaves matching no input,
+/// deepest first, and records the match count of the others.
+fn remove_empty_leaves(node: &mut Node, inputs: &[&str]) -> Result<(), TreeError> {
+    node.match_count = Some(count_matches(&node.pattern, inputs)?);
+    let mut kept = Children::new();
+    for child in std::mem::take(&mut node.children) {
+        let mut child = Arc::unwrap_or_clone(child);
+        remove_empty_leaves(&mut child, inputs)?;
+        if !child.children.is_empty() || child.match_count != Some(0) {
+            kept.push(Arc::new(child));
+        }
+    }
+    node.children = kept;
+    Ok(())
+}
+
+/// Adds an explicit leaf for `input` beneath the deepest node matching it,
+/// following the first matching child at each level.
+fn add_explicit_leaf(node: &mut Node, input: &str) -> Result<(), TreeError> {
+    let mut leaf = Node::new(Role::Full.pattern(input));
+    for child in &mut node.children {
+        if child.pattern == leaf.pattern {
+            return Ok(());
+        }
+        if compile(&child.pattern)?.is_match(input) {
+            return add_explicit_leaf(Arc::make_mut(child), input);
+        }
+    }
+    leaf.role = Some(Role::Full);
+    leaf.match_count = Some(1);
+    node.children.push(Arc::new(leaf));
+    Ok(())
+}
+
+fn leaf_matches(root: &Node, inputs: &[&str]) -> Result<Vec<CoveredLeaf>, TreeError> {
+    let mut leaves = vec![];
+    for node in root.preorder() {
+        if node.children.is_empty() {
+            let regex = compile(&node.pattern)?;
+            let matched = (0..inputs.len())
+                .filter(|&index| regex.is_match(inputs[index]))
+                .collect();
+            leaves.push((node.pattern.to_string(), matched));
+        }
+    }
+    Ok(leaves)
+}
+
+fn count_matches(pattern: &str, inputs: &[&str]) -> Result<usize, TreeError> {
+    let regex = compile(pattern)?;
+    Ok(inputs.iter().filter(|input| regex.is_match(input)).count())
+}
+
+fn compile(pattern: &str) -> Result<Regex, TreeError> {
+    Regex::new(pattern).map_err(|e| TreeError::InvalidPattern {
+        pattern: pattern.to_string(),
+        message: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_by_coverage() {
+        let mut root = Node::new(".*".to_string());
+        let mut rare = Node::new(".*x.*".to_string());
+        rare.children.push(Arc::new(Node::new(".*a.*".to_string())));
+        root.children.push(Arc::new(rare));
+        root.children.push(Arc::new(Node::new(".*b.*".to_string())));
+
+        let inputs = ["ab", "a", "ax"];
+        assert_eq!(prune_by_coverage(&mut root, &inputs, 0.5), Ok(2));
+        let patterns: Vec<(&str, Option<usize>)> = root
+            .preorder()
+            .iter()
+            .map(|n| (n.pattern.as_str(), n.match_count()))
+            .collect();
+        assert_eq!(patterns, vec![(".*", Some(3)), (".*a.*", Some(3))]);
+    }
+
+    #[test]
+    fn test_cover_inputs() {
+        let mut root = Node::new(".*".to_string());
+        let mut a = Node::new(".*a.*".to_string());
+        a.children.push(Arc::new(Node::new(".*z.*".to_string())));
+        root.children.push(Arc::new(a));
+        root.children.push(Arc::new(Node::new(".*y.*".to_string())));
+
+        let inputs = ["ab", "c+d", "c+d"];
+        let cover = cover_inputs(&mut root, &inputs).unwrap();
+        assert_eq!(
+            cover,
+            vec![
+                (".*a.*".to_string(), vec![0]),
+                (r"^c\+d$".to_string(), vec![1, 2]),
+            ]
+        );
+        assert_eq!(root.match_count(), Some(3));
+    }
+}
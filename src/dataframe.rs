@@ -0,0 +1,127 @@
+use crate::{ExportError, Node};
+use polars::prelude::{Column, DataFrame};
+use regex::Regex;
+
+/// Extracts the named capture groups of a tree into a data frame.
+///
+/// Each input is routed from the root into the first child whose pattern
+/// matches it, as long as one does, and the named groups captured along
+/// that path are collected, deeper nodes overriding their ancestors.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `inputs` - The inputs to parse, one row each.
+///
+/// # Returns
+///
+/// A data frame with one string column per named capture group of the
+/// tree, in pre-order of first appearance, holding null where a group did
+/// not capture. A tree without named groups yields an empty data frame.
+pub fn extract_to_dataframe(root: &Node, inputs: &[&str]) -> Result<DataFrame, ExportError> {
+    let compiled = CompiledNode::new(root)?;
+    let mut names: Vec<&str> = vec![];
+    compiled.collect_names(&mut names);
+
+    let mut columns: Vec<Vec<Option<&str>>> = vec![Vec::with_capacity(inputs.len()); names.len()];
+    for input in inputs {
+        let mut row: Vec<Option<&str>> = vec![None; names.len()];
+        compiled.extract(input, &names, &mut row);
+        for (column, value) in columns.iter_mut().zip(row) {
+            column.push(value);
+        }
+    }
+
+    let columns = names
+        .iter()
+        .zip(columns)
+        .map(|(&name, values)| Column::new(name.into(), values))
+        .collect();
+    Ok(DataFrame::new(columns)?)
+}
+
+/// A node with its pattern compiled, mirroring the tree.
+struct CompiledNode {
+    regex: Regex,
+    children: Vec<CompiledNode>,
+}
+
+impl CompiledNode {
+    fn new(node: &Node) -> Result<CompiledNode, ExportError> {
+        let regex = Regex::new(&node.pattern).map_err(|e| ExportError::InvalidPattern {
+            pattern: node.pattern.to_string(),
+            message: e.to_string(),
+        })?;
+        let children = node
+            .children
+            .iter()
+            .map(|child| CompiledNode::new(child))
+            .collect::<Result<_, _>>()?;
+        Ok(CompiledNode { regex, children })
+    }
+
+    fn collect_names<'a>(&'a self, names: &mut Vec<&'a str>) {
+        for name in self.regex.capture_names().flatten() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        for child in &self.children {
+            child.collect_names(names);
+        }
+    }
+
+    /// Fills `row` with the groups captured on the path `input` follows,
+    /// returning `false` if this node does not match it.
+    fn extract<'i>(&self, input: &'i str, names: &[&str], row: &mut [Option<&'i str>]) -> bool {
+        let Some(captures) = self.regex.captures(input) else {
+            return false;
+        };
+        for (slot, name) in row.iter_mut().zip(names) {
+            if let Some(value) = captures.name(name) {
+                *slot = Some(value.as_str());
+            }
+        }
+        for child in &self.children {
+            if child.extract(input, names, row) {
+                break;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_extract_to_dataframe() {
+        let mut root = Node::new(r"^(?P<level>[A-Z]+) ".to_string());
+        root.children
+            .push(Arc::new(Node::new(r"disk (?P<device>\w+)".to_string())));
+        root.children
+            .push(Arc::new(Node::new(r"user (?P<user>\w+)".to_string())));
+
+        let frame =
+            extract_to_dataframe(&root, &["ERROR disk sda", "INFO user bob", "noise"]).unwrap();
+        assert_eq!(frame.get_column_names(), ["level", "device", "user"]);
+        assert_eq!(frame.height(), 3);
+        let column = |name: &str| -> Vec<Option<String>> {
+            frame
+                .column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_iter()
+                .map(|v| v.map(str::to_string))
+                .collect()
+        };
+        assert_eq!(
+            column("level"),
+            [Some("ERROR".to_string()), Some("INFO".to_string()), None]
+        );
+        assert_eq!(column("device"), [Some("sda".to_string()), None, None]);
+    }
+}
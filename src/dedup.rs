@@ -0,0 +1,149 @@
+use crate::bloom::HashFilter;
+use std::collections::HashMap;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Counts the repeated lines of a stream so that each distinct one is only
+/// processed once, and its repeats become a weight, as taken by
+/// [`crate::generate_regex_tree_weighted`].
+///
+/// Lines are keyed by their xxHash, so only the distinct lines themselves
+/// are stored. A bounded deduplicator stops storing lines past a limit and
+/// only remembers the hashes of the next ones in a Bloom filter, so memory
+/// stays bounded however many distinct lines arrive, at the cost of
+/// occasionally taking a new line for a repeat.
+#[derive(Debug, Clone, Default)]
+pub struct Deduplicator {
+    indices: HashMap<u64, usize>,
+    lines: Vec<(String, u64)>,
+    overflow: Option<Overflow>,
+    total: u64,
+}
+
+/// The lines past the limit of a bounded deduplicator.
+#[derive(Debug, Clone)]
+struct Overflow {
+    max_lines: usize,
+    filter: HashFilter,
+    repeats: u64,
+}
+
+impl Deduplicator {
+    /// Creates a deduplicator keeping every distinct line.
+    pub fn new() -> Self {
+        Deduplicator::default()
+    }
+
+    /// Creates a deduplicator keeping at most `max_lines` distinct lines
+    /// with their counts, and only the hashes of the next ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_lines` - The most distinct lines kept.
+    /// * `expected_overflow` - How many more distinct lines are expected,
+    ///   which sizes the filter.
+    /// * `false_positive_rate` - The probability, while no more than the
+    ///   expected lines overflowed, that a new line past the limit is taken
+    ///   for a repeat.
+    pub fn bounded(max_lines: usize, expected_overflow: usize, false_positive_rate: f64) -> Self {
+        Deduplicator {
+            overflow: Some(Overflow {
+                max_lines,
+                filter: HashFilter::new(expected_overflow, false_positive_rate),
+                repeats: 0,
+            }),
+            ..Deduplicator::default()
+        }
+    }
+
+    /// Records a line.
+    ///
+    /// # Returns
+    ///
+    /// Whether the line is new and should be processed, `false` if it was
+    /// seen before, or, past the limit of a bounded deduplicator, probably
+    /// was.
+    pub fn push(&mut self, line: &str) -> bool {
+        self.total += 1;
+        let hash = xxh3_64(line.as_bytes());
+        if let Some(&index) = self.indices.get(&hash) {
+            // Distinct lines sharing a hash are counted together, which a
+            // 64-bit hash makes vanishingly rare.
+            self.lines[index].1 += 1;
+            return false;
+        }
+        match &mut self.overflow {
+            Some(overflow) if self.lines.len() >= overflow.max_lines => {
+                let repeat = overflow.filter.insert(hash);
+                overflow.repeats += u64::from(repeat);
+                !repeat
+            }
+            _ => {
+                self.indices.insert(hash, self.lines.len());
+                self.lines.push((line.to_string(), 1));
+                true
+            }
+        }
+    }
+
+    /// Returns the distinct lines kept, in order of first occurrence, with
+    /// how many times each was pushed.
+    pub fn counts(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.lines
+            .iter()
+            .map(|(line, count)| (line.as_str(), *count))
+    }
+
+    /// Returns the distinct lines kept, weighted by how many times each was
+    /// pushed, for [`crate::generate_regex_tree_weighted`].
+    pub fn weighted(&self) -> Vec<(&str, f64)> {
+        self.counts()
+            .map(|(line, count)| (line, count as f64))
+            .collect()
+    }
+
+    /// Returns the number of distinct lines kept.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns whether no line was kept.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the number of lines pushed, repeats included.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the number of lines past the limit of a bounded
+    /// deduplicator taken for repeats, whose counts are not kept.
+    pub fn overflow_repeats(&self) -> u64 {
+        self.overflow.as_ref().map_or(0, |o| o.repeats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deduplicator() {
+        let lines = ["disk full", "cpu hot", "disk full", "disk full", "fan off"];
+        let mut exact = Deduplicator::new();
+        let fresh: Vec<bool> = lines.iter().map(|l| exact.push(l)).collect();
+        assert_eq!(fresh, [true, true, false, false, true]);
+        assert_eq!(
+            exact.weighted(),
+            [("disk full", 3.0), ("cpu hot", 1.0), ("fan off", 1.0)]
+        );
+        assert_eq!((exact.len(), exact.total()), (3, 5));
+
+        let mut bounded = Deduplicator::bounded(1, 100, 0.001);
+        let fresh: Vec<bool> = lines.iter().map(|l| bounded.push(l)).collect();
+        assert_eq!(fresh, [true, true, false, false, true]);
+        assert_eq!(bounded.weighted(), [("disk full", 3.0)]);
+        assert!(!bounded.push("cpu hot"));
+        assert_eq!(bounded.overflow_repeats(), 1);
+    }
+}
@@ -0,0 +1,110 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// A detector recognising a well-known kind of field value.
+///
+/// Detectors are listed from most to least specific; inference picks the
+/// first one that accepts every observed value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Detector {
+    /// A decimal integer, optionally signed.
+    Integer,
+    /// A hexadecimal number.
+    Hex,
+    /// An IPv4 address.
+    Ip,
+    /// An ISO 8601 date or date-time, or a clock time.
+    Timestamp,
+    /// A run of word characters.
+    Word,
+    /// Any run of non-whitespace characters.
+    Any,
+}
+
+impl Detector {
+    /// All detectors, from most to least specific.
+    pub const ALL: [Detector; 6] = [
+        Detector::Integer,
+        Detector::Hex,
+        Detector::Ip,
+        Detector::Timestamp,
+        Detector::Word,
+        Detector::Any,
+    ];
+
+    /// Returns the regex fragment matching a value of this kind.
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            Detector::Integer => r"[+-]?\d+",
+            Detector::Hex => r"(?:0x)?[0-9a-fA-F]+",
+            Detector::Ip => r"\d{1,3}(?:\.\d{1,3}){3}",
+            Detector::Timestamp => {
+                r"\d{4}-\d{2}-\d{2}(?:T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:?\d{2})?)?|\d{2}:\d{2}:\d{2}(?:\.\d+)?"
+            }
+            Detector::Word => r"\w+",
+            Detector::Any => r"\S+",
+        }
+    }
+
+    /// Returns whether `value` is entirely a value of this kind.
+    pub fn matches(&self, value: &str) -> bool {
+        static COMPILED: OnceLock<Vec<Regex>> = OnceLock::new();
+        let compiled = COMPILED.get_or_init(|| {
+            Detector::ALL
+                .iter()
+                .map(|d| Regex::new(&format!("^(?:{})$", d.pattern())).unwrap())
+                .collect()
+        });
+        compiled[*self as usize].is_match(value)
+    }
+
+    /// Returns the most specific detector accepting every value.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The observed values of a field.
+    ///
+    /// # Returns
+    ///
+    /// The first detector in [`Detector::ALL`] matching all `values`, or
+    /// `None` if there are no values. [`Detector::Hex`] is only inferred
+    /// when some value has a digit or a `0x` prefix, so letter-only values
+    /// such as `"b"` are typed as words.
+    pub fn infer<S: AsRef<str>>(values: &[S]) -> Option<Detector> {
+        if values.is_empty() {
+            return None;
+        }
+        let looks_hex = values.iter().any(|v| {
+            let v = v.as_ref();
+            v.starts_with("0x") || v.bytes().any(|b| b.is_ascii_digit())
+        });
+        Detector::ALL
+            .into_iter()
+            .filter(|d| *d != Detector::Hex || looks_hex)
+            .find(|d| values.iter().all(|v| d.matches(v.as_ref())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_detector() {
+        assert_eq!(Detector::infer(&["12", "-7"]), Some(Detector::Integer));
+        assert_eq!(Detector::infer(&["ff", "10"]), Some(Detector::Hex));
+        assert_eq!(Detector::infer(&["b", "c"]), Some(Detector::Word));
+        assert_eq!(
+            Detector::infer(&["10.0.0.1", "192.168.1.20"]),
+            Some(Detector::Ip)
+        );
+        assert_eq!(
+            Detector::infer(&["2024-01-02T03:04:05Z", "12:30:00"]),
+            Some(Detector::Timestamp)
+        );
+        assert_eq!(Detector::infer(&["alice", "bob_2"]), Some(Detector::Word));
+        assert_eq!(Detector::infer(&["a/b", "c"]), Some(Detector::Any));
+        assert_eq!(Detector::infer::<&str>(&[]), None);
+    }
+}
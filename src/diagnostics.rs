@@ -0,0 +1,184 @@
+use crate::codegen::Flavor;
+use std::fmt;
+
+/// Why candidate substrings were excluded from the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandidateFilter {
+    /// They overlap one of [`crate::GenerateOptions::stop_substrings`].
+    StopSubstrings,
+    /// They do not align on the boundaries of
+    /// [`crate::GenerateOptions::tokenizer`] in every input.
+    Tokenizer,
+    /// They occur in too few inputs for
+    /// [`crate::GenerateOptions::min_literal_support`], or expose a likely
+    /// secret.
+    LiteralSupport,
+    /// They split a cluster of [`crate::GenerateOptions::grapheme_mode`].
+    Graphemes,
+    /// They are shorter than [`crate::RegexTreeBuilder::min_substring_len`].
+    MinSubstringLen,
+}
+
+impl fmt::Display for CandidateFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CandidateFilter::StopSubstrings => write!(f, "the stop substrings"),
+            CandidateFilter::Tokenizer => write!(f, "the tokenizer"),
+            CandidateFilter::LiteralSupport => write!(f, "the literal support threshold"),
+            CandidateFilter::Graphemes => write!(f, "the grapheme boundaries"),
+            CandidateFilter::MinSubstringLen => write!(f, "the minimum substring length"),
+        }
+    }
+}
+
+/// An issue met while generating a tree which did not stop generation but
+/// degraded its result, reported by
+/// [`crate::RegexTreeBuilder::build_with_diagnostics`] so callers can
+/// surface it rather than silently getting a poorer tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// An input is not valid UTF-8, and its invalid sequences were replaced
+    /// with `U+FFFD`.
+    LossyInput {
+        /// The index of the input.
+        input: usize,
+    },
+    /// An input was left unmatched as allowed by
+    /// [`crate::GenerateOptions::tolerance`].
+    InputDropped {
+        /// The index of the input.
+        input: usize,
+    },
+    /// Candidate substrings were excluded by a filter.
+    CandidatesExcluded {
+        /// The filter excluding them.
+        filter: CandidateFilter,
+        /// How many were excluded.
+        count: usize,
+    },
+    /// The inputs, or a stratum or cluster of them, share no candidate
+    /// left, so their tree is only a wildcard.
+    NoCommonSubstring {
+        /// How many inputs share nothing.
+        inputs: usize,
+    },
+    /// Inputs hold likely secrets, which the tree was generalized not to
+    /// spell out, as set by [`crate::GenerateOptions::secret_policy`].
+    SecretsGeneralized {
+        /// How many inputs hold one.
+        inputs: usize,
+    },
+    /// Nodes were cut by [`crate::RegexTreeBuilder::max_children`], with
+    /// their subtrees.
+    ChildrenTruncated {
+        /// How many nodes were cut.
+        count: usize,
+    },
+    /// Nodes whose pattern the flavor set with
+    /// [`crate::RegexTreeBuilder::flavor`] cannot express were left out.
+    UntranslatableNodes {
+        /// The flavor.
+        flavor: Flavor,
+        /// How many nodes were left out.
+        count: usize,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::LossyInput { input } => {
+                write!(f, "input {input} is not UTF-8 and was lossily converted")
+            }
+            Diagnostic::InputDropped { input } => {
+                write!(f, "input {input} was left unmatched as an outlier")
+            }
+            Diagnostic::CandidatesExcluded { filter, count } => {
+                write!(f, "{filter} excluded {count} candidates")
+            }
+            Diagnostic::NoCommonSubstring { inputs } => {
+                write!(
+                    f,
+                    "{inputs} inputs share no substring, only `.*` matches them"
+                )
+            }
+            Diagnostic::SecretsGeneralized { inputs } => {
+                write!(
+                    f,
+                    "{inputs} inputs hold likely secrets, left out of the tree"
+                )
+            }
+            Diagnostic::ChildrenTruncated { count } => {
+                write!(f, "the children limit cut {count} nodes")
+            }
+            Diagnostic::UntranslatableNodes { flavor, count } => {
+                write!(
+                    f,
+                    "{count} patterns cannot be written in {flavor:?} and were left out"
+                )
+            }
+        }
+    }
+}
+
+/// Records the number of candidates a filter excluded, if any.
+pub(crate) fn excluded(diagnostics: &mut Vec<Diagnostic>, filter: CandidateFilter, count: usize) {
+    if count > 0 {
+        diagnostics.push(Diagnostic::CandidatesExcluded { filter, count });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GenerateOptions, RegexTreeBuilder, Tolerance};
+
+    #[test]
+    fn test_diagnostics() {
+        let options = GenerateOptions {
+            stop_substrings: vec!["acme".to_string()],
+            tolerance: Some(Tolerance::Inputs(1)),
+            ..GenerateOptions::default()
+        };
+        let builder = RegexTreeBuilder::new()
+            .options(options)
+            .min_substring_len(3)
+            .max_children(1);
+        let inputs = ["acme GET /a ok", "acme GET /b ok", "acme GET /c ok", "#"];
+        let (tree, diagnostics) = builder.build_with_diagnostics(&inputs).unwrap();
+        assert_eq!(tree.to_regex(), builder.build(&inputs).unwrap().to_regex());
+        assert!(diagnostics.contains(&Diagnostic::InputDropped { input: 3 }));
+        let filters: Vec<CandidateFilter> = diagnostics
+            .iter()
+            .filter_map(|d| match d {
+                Diagnostic::CandidatesExcluded { filter, .. } => Some(*filter),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            filters,
+            [
+                CandidateFilter::StopSubstrings,
+                CandidateFilter::MinSubstringLen
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .any(|d| matches!(d, Diagnostic::ChildrenTruncated { count } if *count > 0)));
+
+        let (_, diagnostics) = RegexTreeBuilder::new()
+            .build_lossy(&[b"id=\xff1", b"id=2", b"xyz"])
+            .unwrap();
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic::LossyInput { input: 0 },
+                Diagnostic::NoCommonSubstring { inputs: 3 }
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "input 0 is not UTF-8 and was lossily converted"
+        );
+    }
+}
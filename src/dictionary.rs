@@ -0,0 +1,278 @@
+use regex::Regex;
+use std::fmt::Write;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+/// A named sub-pattern learned from field values, such as a recurring ID shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedPattern {
+    name: String,
+    pattern: String,
+}
+
+impl NamedPattern {
+    /// Creates a sub-pattern called `name` matching `pattern`.
+    pub(crate) fn new(name: &str, pattern: &str) -> Self {
+        NamedPattern {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+        }
+    }
+
+    /// Returns the name of the sub-pattern.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the regex fragment of the sub-pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+/// A vocabulary of named sub-patterns shared across runs.
+///
+/// Template generation first reuses entries that accept every value of a
+/// field, and only learns a new entry when none does, so repeated runs
+/// converge on the same names.
+#[derive(Debug, Clone, Default)]
+pub struct DetectorDictionary {
+    entries: Vec<NamedPattern>,
+}
+
+impl DetectorDictionary {
+    /// Creates an empty dictionary.
+    pub fn new() -> Self {
+        DetectorDictionary::default()
+    }
+
+    /// Returns the entries of the dictionary, in the order they were learned.
+    pub fn entries(&self) -> &[NamedPattern] {
+        &self.entries
+    }
+
+    /// Adds a named sub-pattern, replacing any entry with the same name.
+    pub fn insert(&mut self, name: &str, pattern: &str) {
+        self.entries.retain(|e| e.name != name);
+        self.entries.push(NamedPattern::new(name, pattern));
+    }
+
+    /// Renames an entry, for instance to give a learned shape a meaningful name.
+    ///
+    /// # Returns
+    ///
+    /// `false` if no entry is called `from`.
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        match self.entries.iter_mut().find(|e| e.name == from) {
+            Some(entry) => {
+                entry.name = to.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the first entry whose pattern accepts every value, learning a
+    /// new one from the values' shape if none does.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The observed values of a field.
+    ///
+    /// # Returns
+    ///
+    /// The matching entry, or `None` if the values share no distinctive shape.
+    pub(crate) fn resolve(&mut self, values: &[&str]) -> Option<NamedPattern> {
+        let known = self.entries.iter().find(|entry| {
+            Regex::new(&format!("^(?:{})$", entry.pattern))
+                .map(|r| values.iter().all(|v| r.is_match(v)))
+                .unwrap_or(false)
+        });
+        if let Some(entry) = known {
+            return Some(entry.clone());
+        }
+
+        let pattern = infer_shape(values)?;
+        let name = format!("learned{}", self.entries.len() + 1);
+        self.insert(&name, &pattern);
+        self.entries.last().cloned()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|e| format!("{}\t{}\n", e.name, e.pattern))
+            .collect()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_text(text: &str) -> io::Result<Self> {
+        let mut dictionary = DetectorDictionary::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            let (name, pattern) = line.split_once('\t').ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("malformed entry {line:?}"),
+                )
+            })?;
+            dictionary.insert(name, pattern);
+        }
+        Ok(dictionary)
+    }
+}
+
+/// A place where a [`DetectorDictionary`] is persisted between runs.
+pub trait DictionaryStore {
+    /// Loads the stored dictionary, or an empty one if nothing was stored yet.
+    fn load(&self) -> io::Result<DetectorDictionary>;
+
+    /// Stores the dictionary, replacing any previous contents.
+    fn save(&self, dictionary: &DetectorDictionary) -> io::Result<()>;
+}
+
+/// Stores a dictionary in a text file, one tab-separated `name`/`pattern`
+/// entry per line.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStore {
+    /// Creates a store backed by the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileStore { path: path.into() }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DictionaryStore for FileStore {
+    fn load(&self) -> io::Result<DetectorDictionary> {
+        match fs::read_to_string(&self.path) {
+            Ok(text) => DetectorDictionary::from_text(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(DetectorDictionary::new()),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn save(&self, dictionary: &DetectorDictionary) -> io::Result<()> {
+        fs::write(&self.path, dictionary.to_text())
+    }
+}
+
+/// The class of a character in a value shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Digit,
+    Upper,
+    Lower,
+    Other(char),
+}
+
+impl CharClass {
+    fn of(c: char) -> CharClass {
+        if c.is_ascii_digit() {
+            CharClass::Digit
+        } else if c.is_ascii_uppercase() {
+            CharClass::Upper
+        } else if c.is_ascii_lowercase() {
+            CharClass::Lower
+        } else {
+            CharClass::Other(c)
+        }
+    }
+
+    fn write_pattern(&self, out: &mut String) {
+        match self {
+            CharClass::Digit => out.push_str(r"\d"),
+            CharClass::Upper => out.push_str("[A-Z]"),
+            CharClass::Lower => out.push_str("[a-z]"),
+            CharClass::Other(c) => regex_syntax::escape_into(c.encode_utf8(&mut [0; 4]), out),
+        }
+    }
+}
+
+/// Splits `value` into runs of characters of the same class.
+fn runs(value: &str) -> Vec<(CharClass, usize)> {
+    let mut runs: Vec<(CharClass, usize)> = vec![];
+    for c in value.chars() {
+        let class = CharClass::of(c);
+        match runs.last_mut() {
+            Some((last, count)) if *last == class => *count += 1,
+            _ => runs.push((class, 1)),
+        }
+    }
+    runs
+}
+
+/// Infers the shape shared by all values, such as `[A-Z]{3}-\d{4}`.
+///
+/// # Arguments
+///
+/// * `values` - The observed values of a field.
+///
+/// # Returns
+///
+/// A regex fragment with one quantified class per run of similar
+/// characters, or `None` if the values do not share the same sequence of
+/// runs or consist of a single run.
+pub(crate) fn infer_shape(values: &[&str]) -> Option<String> {
+    let shapes: Vec<Vec<(CharClass, usize)>> = values.iter().map(|v| runs(v)).collect();
+    let first = shapes.first()?;
+    if first.len() < 2 {
+        return None;
+    }
+    let aligned = shapes.iter().all(|shape| {
+        shape.len() == first.len() && shape.iter().zip(first).all(|(a, b)| a.0 == b.0)
+    });
+    if !aligned {
+        return None;
+    }
+
+    let mut pattern = String::new();
+    for (run, (class, _)) in first.iter().enumerate() {
+        let min = shapes.iter().map(|s| s[run].1).min()?;
+        let max = shapes.iter().map(|s| s[run].1).max()?;
+        class.write_pattern(&mut pattern);
+        match (min, max) {
+            (1, 1) => {}
+            (min, max) if min == max => write!(pattern, "{{{min}}}").unwrap(),
+            (min, max) => write!(pattern, "{{{min},{max}}}").unwrap(),
+        }
+    }
+    Some(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_learn_and_persist_dictionary() {
+        assert_eq!(
+            infer_shape(&["ORD-1234", "ORD-98765"]),
+            Some(r"[A-Z]{3}\-\d{4,5}".to_string())
+        );
+        assert_eq!(infer_shape(&["1234", "99"]), None);
+
+        let mut dictionary = DetectorDictionary::new();
+        let learned = dictionary.resolve(&["ORD-1234", "ORD-9876"]).unwrap();
+        assert_eq!(learned.name(), "learned1");
+        assert!(dictionary.rename("learned1", "order_id"));
+
+        let path = std::env::temp_dir().join("regex_generator_dictionary_test.tsv");
+        let store = FileStore::new(&path);
+        store.save(&dictionary).unwrap();
+        let mut reloaded = store.load().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let reused = reloaded.resolve(&["ABC-0001"]).unwrap();
+        assert_eq!(reused.name(), "order_id");
+        assert_eq!(reloaded.entries().len(), 1);
+    }
+}
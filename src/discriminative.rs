@@ -0,0 +1,97 @@
+use crate::{build_tree, DocumentFrequencies, Node};
+use std::collections::{HashMap, HashSet};
+
+/// Minimum fraction of positives a substring must occur in to be kept.
+const MIN_POSITIVE_SUPPORT: f64 = 0.5;
+
+/// Generates a regex pattern tree of substrings that are frequent in the
+/// positives but rare in the background.
+///
+/// Unlike strict negatives, the background is only used to estimate how
+/// common a substring is in general: a candidate is kept when it occurs in at
+/// least half of the positives and proportionally more often there than in
+/// the background. Candidates are ordered by that difference, longest first
+/// on ties.
+///
+/// # Arguments
+///
+/// * `positives` - The strings whose distinctive substrings are mined.
+/// * `background` - A corpus representative of ordinary text.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree.
+pub fn generate_discriminative(positives: &[&str], background: &[&str]) -> Node {
+    let frequencies = DocumentFrequencies::from_corpus(background);
+    let mut support: HashMap<&str, usize> = HashMap::new();
+    for positive in positives {
+        for substring in distinct_substrings(positive) {
+            *support.entry(substring).or_default() += 1;
+        }
+    }
+
+    let mut scored: Vec<(f64, &str)> = support
+        .into_iter()
+        .filter_map(|(substring, count)| {
+            let positive_ratio = count as f64 / positives.len() as f64;
+            if positive_ratio < MIN_POSITIVE_SUPPORT {
+                return None;
+            }
+            let background_ratio = if background.is_empty() {
+                0.0
+            } else {
+                frequencies.document_frequency(substring) as f64 / background.len() as f64
+            };
+            let score = positive_ratio - background_ratio;
+            (score > 0.0).then_some((score, substring))
+        })
+        .collect();
+    scored.sort_by(|a, b| {
+        b.0.total_cmp(&a.0)
+            .then(b.1.len().cmp(&a.1.len()))
+            .then(a.1.cmp(b.1))
+    });
+
+    let substrings: Vec<&str> = scored.into_iter().map(|(_, s)| s).collect();
+    build_tree(&substrings, positives)
+}
+
+/// Returns every distinct non-empty substring of `s`, split on char boundaries.
+pub(crate) fn distinct_substrings(s: &str) -> HashSet<&str> {
+    let mut boundaries: Vec<usize> = s.char_indices().map(|(i, _)| i).collect();
+    boundaries.push(s.len());
+
+    let mut substrings = HashSet::new();
+    for (n, &start) in boundaries.iter().enumerate() {
+        for &end in &boundaries[n + 1..] {
+            substrings.insert(&s[start..end]);
+        }
+    }
+    substrings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_discriminative() {
+        let positives = vec![
+            "the disk quota exceeded for alice",
+            "the disk quota exceeded for bob",
+            "the job finished for carol",
+        ];
+        let background = vec!["the weather is fine for now", "the cat sat on the mat"];
+        let tree = generate_discriminative(&positives, &background);
+        let patterns: Vec<&str> = tree.preorder().iter().map(|n| n.pattern.as_str()).collect();
+
+        assert!(patterns.contains(&".*ed for .*"));
+        assert!(patterns.contains(&"^the disk quota exceeded for .*"));
+        assert!(!tree
+            .children
+            .iter()
+            .any(|n| n.pattern == "^the disk quota exceeded for .*"));
+        assert!(!patterns.iter().any(|p| p.contains("the job")));
+        assert!(!patterns.contains(&"^the .*"));
+    }
+}
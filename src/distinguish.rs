@@ -0,0 +1,125 @@
+use crate::discriminative::distinct_substrings;
+use crate::{scoring, Node, Role};
+
+/// The most common substrings paired up when no single one tells the two
+/// sets apart.
+const MAX_PAIRED_SUBSTRINGS: usize = 64;
+
+/// Finds the simplest pattern matching every string of `a` and none of
+/// `b`, favouring discriminative minimality over describing `a`: the
+/// result spells out as little of `a` as is needed to reject `b`.
+///
+/// Candidates are tried from the simplest: a single substring shared by
+/// every string of `a`, shortest first and with as few anchors as
+/// possible, as in `.*ERR.*` or `^GET.*`; then two shared substrings in
+/// the order they occur, as in `.*id=.*ok.*`; and, failing both, the
+/// alternation of the strings of `a` spelled out in full.
+///
+/// # Arguments
+///
+/// * `a` - The strings the pattern must match.
+/// * `b` - The strings the pattern must reject.
+///
+/// # Returns
+///
+/// A node holding the pattern, with its role when it is a single
+/// substring and its score on `a`; it matches nothing if `a` is empty.
+/// When a string belongs to both sets no pattern exists, and the
+/// alternation, which still matches `b`'s copy, is returned.
+pub fn distinguish(a: &[&str], b: &[&str]) -> Node {
+    let mut node = distinguishing_node(a, b);
+    node.match_count = Some(a.len());
+    scoring::score_tree(&mut node, a);
+    node
+}
+
+/// Returns the node of the first candidate pattern telling `a` from `b`.
+fn distinguishing_node(a: &[&str], b: &[&str]) -> Node {
+    let Some(shortest) = a.iter().min_by_key(|s| s.len()) else {
+        return Node::new("[^\\s\\S]".to_string());
+    };
+    let mut shared: Vec<&str> = distinct_substrings(shortest)
+        .into_iter()
+        .filter(|substring| a.iter().all(|s| s.contains(substring)))
+        .collect();
+    shared.sort_by(|x, y| x.chars().count().cmp(&y.chars().count()).then(x.cmp(y)));
+
+    for &literal in &shared {
+        let role = [Role::Infix, Role::Prefix, Role::Suffix, Role::Full]
+            .into_iter()
+            .find(|&role| {
+                a.iter().all(|s| occurs(role, literal, s))
+                    && !b.iter().any(|s| occurs(role, literal, s))
+            });
+        if let Some(role) = role {
+            let mut node = Node::new(role.pattern(literal));
+            node.role = Some(role);
+            return node;
+        }
+    }
+
+    let paired = &shared[..shared.len().min(MAX_PAIRED_SUBSTRINGS)];
+    let mut pairs: Vec<(&str, &str)> = paired
+        .iter()
+        .flat_map(|&first| paired.iter().map(move |&second| (first, second)))
+        .filter(|&(first, second)| a.iter().all(|s| in_order(first, second, s)))
+        .collect();
+    pairs.sort_by_key(|(first, second)| first.chars().count() + second.chars().count());
+    if let Some((first, second)) = pairs
+        .into_iter()
+        .find(|&(first, second)| !b.iter().any(|s| in_order(first, second, s)))
+    {
+        return Node::new(format!(
+            ".*{}.*{}.*",
+            regex_syntax::escape(first),
+            regex_syntax::escape(second)
+        ));
+    }
+
+    let mut distinct: Vec<String> = a.iter().map(|s| regex_syntax::escape(s)).collect();
+    distinct.sort();
+    distinct.dedup();
+    Node::new(format!("^(?:{})$", distinct.join("|")))
+}
+
+/// Returns whether `literal` occurs in `s` as `role` requires.
+fn occurs(role: Role, literal: &str, s: &str) -> bool {
+    match role {
+        Role::Infix => s.contains(literal),
+        Role::Prefix => s.starts_with(literal),
+        Role::Suffix => s.ends_with(literal),
+        Role::Full => s == literal,
+    }
+}
+
+/// Returns whether `second` occurs in `s` after an occurrence of `first`.
+fn in_order(first: &str, second: &str, s: &str) -> bool {
+    s.find(first)
+        .is_some_and(|at| s[at + first.len()..].contains(second))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_distinguish() {
+        let check = |a: &[&str], b: &[&str], expected: &str| {
+            let node = distinguish(a, b);
+            assert_eq!(node.pattern(), expected);
+            let regex = Regex::new(node.pattern()).unwrap();
+            assert!(a.iter().all(|s| regex.is_match(s)));
+            assert!(!b.iter().any(|s| regex.is_match(s)));
+        };
+        check(
+            &["disk sda full", "disk sdb full"],
+            &["disk sda ok", "net down"],
+            ".*f.*",
+        );
+        check(&["GET /a", "GET /b"], &["POST /GET", "PUT /a"], "^G.*");
+        check(&["a1b", "a2b"], &["ba", "a", "b"], ".*a.*b.*");
+        check(&["ab", "ba"], &["aa", "bb"], "^(?:ab|ba)$");
+        assert_eq!(distinguish(&["x", "xy"], &[]).role(), Some(Role::Infix));
+    }
+}
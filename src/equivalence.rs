@@ -0,0 +1,271 @@
+use crate::LanguageError;
+use regex::Regex;
+use regex_syntax::hir::{Class, Hir, HirKind};
+use std::collections::HashSet;
+
+/// How strings are drawn to compare two patterns with [`compare_regexes`].
+///
+/// Strings are sampled from the language of each pattern by walking its
+/// syntax tree, picking alternatives, repetition counts and characters at
+/// random, and are checked alongside the corpus inputs, if any. Sampling is
+/// seeded, so a comparison is reproducible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sampler {
+    samples: usize,
+    max_repeat: u32,
+    seed: u64,
+    corpus: Vec<String>,
+}
+
+impl Sampler {
+    /// Creates a sampler drawing `samples` strings from each pattern.
+    pub fn new(samples: usize) -> Self {
+        Sampler {
+            samples,
+            max_repeat: 8,
+            seed: 0,
+            corpus: vec![],
+        }
+    }
+
+    /// Sets how many times beyond its minimum an unbounded repetition is
+    /// repeated at most, `8` by default.
+    pub fn max_repeat(mut self, max_repeat: u32) -> Self {
+        self.max_repeat = max_repeat;
+        self
+    }
+
+    /// Sets the seed of the random choices, `0` by default.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets inputs, such as the corpus the generated pattern was mined
+    /// from, checked in addition to the sampled strings.
+    pub fn corpus(mut self, inputs: &[&str]) -> Self {
+        self.corpus = inputs.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Returns how many strings are drawn from each pattern.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Draws strings of `pattern`'s language, or `None` if it does not
+    /// parse.
+    pub(crate) fn draw(&self, pattern: &str) -> Option<Vec<String>> {
+        let hir = regex_syntax::Parser::new().parse(pattern).ok()?;
+        Some(self.sample(&hir, &mut Random(self.seed)))
+    }
+
+    /// Draws the strings of `hir`'s language.
+    fn sample(&self, hir: &Hir, random: &mut Random) -> Vec<String> {
+        (0..self.samples)
+            .map(|_| {
+                let mut out = String::new();
+                self.walk(hir, random, &mut out);
+                out
+            })
+            .collect()
+    }
+
+    /// Appends a random string of `hir`'s language to `out`.
+    fn walk(&self, hir: &Hir, random: &mut Random, out: &mut String) {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => {}
+            HirKind::Literal(literal) => out.push_str(&String::from_utf8_lossy(&literal.0)),
+            HirKind::Class(Class::Unicode(class)) => {
+                // Printable ASCII is favoured, so the disagreements found
+                // read well, but the whole class is still explored.
+                let ranges = class.ranges();
+                let printable: Vec<(u32, u32)> = ranges
+                    .iter()
+                    .map(|r| (u32::from(r.start()).max(0x20), u32::from(r.end()).min(0x7e)))
+                    .filter(|(start, end)| start <= end)
+                    .collect();
+                let (start, end) = if !printable.is_empty() && random.below(4) != 0 {
+                    printable[random.below(printable.len())]
+                } else if let Some(range) = ranges.get(random.below(ranges.len().max(1))) {
+                    (range.start().into(), range.end().into())
+                } else {
+                    return;
+                };
+                let code = start + random.below((end - start) as usize + 1) as u32;
+                out.extend(char::from_u32(code));
+            }
+            HirKind::Class(Class::Bytes(class)) => {
+                let ranges = class.ranges();
+                if let Some(range) = ranges.get(random.below(ranges.len().max(1))) {
+                    let span = (range.end() - range.start()) as usize + 1;
+                    let byte = range.start() + random.below(span) as u8;
+                    if byte.is_ascii() {
+                        out.push(byte as char);
+                    }
+                }
+            }
+            HirKind::Repetition(repetition) => {
+                let max = repetition
+                    .max
+                    .unwrap_or(repetition.min.saturating_add(self.max_repeat));
+                let extra = random.below((max - repetition.min) as usize + 1) as u32;
+                for _ in 0..repetition.min + extra {
+                    self.walk(&repetition.sub, random, out);
+                }
+            }
+            HirKind::Capture(capture) => self.walk(&capture.sub, random, out),
+            HirKind::Concat(parts) => parts.iter().for_each(|part| self.walk(part, random, out)),
+            HirKind::Alternation(alternatives) => {
+                let alternative = &alternatives[random.below(alternatives.len())];
+                self.walk(alternative, random, out);
+            }
+        }
+    }
+}
+
+/// A splitmix64 generator, enough for reproducible sampling.
+struct Random(u64);
+
+impl Random {
+    /// Returns a number below `bound`, or `0` if it is `0`.
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z % bound.max(1) as u64) as usize
+    }
+}
+
+/// The strings found to tell two patterns apart by [`compare_regexes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    only_generated: Vec<String>,
+    only_handwritten: Vec<String>,
+    checked: usize,
+}
+
+impl Comparison {
+    /// Returns the strings the generated pattern matches but the
+    /// hand-written one does not, in the order found, without repeats.
+    pub fn only_generated(&self) -> &[String] {
+        &self.only_generated
+    }
+
+    /// Returns the strings the hand-written pattern matches but the
+    /// generated one does not, in the order found, without repeats.
+    pub fn only_handwritten(&self) -> &[String] {
+        &self.only_handwritten
+    }
+
+    /// Returns how many distinct strings were checked.
+    pub fn checked(&self) -> usize {
+        self.checked
+    }
+
+    /// Returns whether no checked string tells the patterns apart. This
+    /// does not prove they are equivalent, see
+    /// [`crate::contains_language`] for an exact check.
+    pub fn is_equivalent(&self) -> bool {
+        self.only_generated.is_empty() && self.only_handwritten.is_empty()
+    }
+}
+
+/// Compares a generated pattern with the hand-written one it is meant to
+/// replace by sampling strings from both languages, and from the corpus,
+/// and reporting those only one of them matches, so a legacy pattern can
+/// be migrated with confidence.
+///
+/// Unlike [`crate::contains_language`], this handles patterns too large to
+/// compile to automata, and yields readable disagreements, but can miss
+/// rare ones.
+///
+/// # Arguments
+///
+/// * `generated` - The generated pattern.
+/// * `handwritten` - The hand-written pattern.
+/// * `sampler` - How the strings checked are drawn.
+///
+/// # Returns
+///
+/// The disagreements found, or an error if either pattern does not compile.
+pub fn compare_regexes(
+    generated: &str,
+    handwritten: &str,
+    sampler: &Sampler,
+) -> Result<Comparison, LanguageError> {
+    let compile = |pattern: &str| {
+        let invalid = |message: String| LanguageError::InvalidPattern {
+            pattern: pattern.to_string(),
+            message,
+        };
+        let regex = Regex::new(pattern).map_err(|error| invalid(error.to_string()))?;
+        let hir = regex_syntax::Parser::new()
+            .parse(pattern)
+            .map_err(|error| invalid(error.to_string()))?;
+        Ok((regex, hir))
+    };
+    let (generated_regex, generated_hir) = compile(generated)?;
+    let (handwritten_regex, handwritten_hir) = compile(handwritten)?;
+
+    let mut random = Random(sampler.seed);
+    let candidates = sampler
+        .corpus
+        .iter()
+        .cloned()
+        .chain(sampler.sample(&generated_hir, &mut random))
+        .chain(sampler.sample(&handwritten_hir, &mut random));
+    let mut seen = HashSet::new();
+    let mut comparison = Comparison {
+        only_generated: vec![],
+        only_handwritten: vec![],
+        checked: 0,
+    };
+    for candidate in candidates {
+        if !seen.insert(candidate.clone()) {
+            continue;
+        }
+        comparison.checked += 1;
+        match (
+            generated_regex.is_match(&candidate),
+            handwritten_regex.is_match(&candidate),
+        ) {
+            (true, false) => comparison.only_generated.push(candidate),
+            (false, true) => comparison.only_handwritten.push(candidate),
+            _ => {}
+        }
+    }
+    Ok(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_regexes() {
+        let sampler = Sampler::new(200).seed(7);
+        let same = compare_regexes("^(?:GET|POST) /$", "^(?:POST|GET) /$", &sampler).unwrap();
+        assert!(same.is_equivalent());
+        assert_eq!(same.checked(), 2);
+
+        let sampler = sampler.corpus(&["id 12-34"]);
+        let comparison = compare_regexes(
+            r"^id [0-9]{2}-[0-9]{2,4}$",
+            r"^id [0-9]{2}-[0-9]{4}$",
+            &sampler,
+        )
+        .unwrap();
+        assert!(!comparison.is_equivalent());
+        assert_eq!(comparison.only_generated()[0], "id 12-34");
+        assert!(comparison.only_handwritten().is_empty());
+        assert!(comparison
+            .only_generated()
+            .iter()
+            .all(|s| s.len() < "id 00-0000".len()));
+
+        assert!(compare_regexes("(", ".*", &sampler).is_err());
+    }
+}
@@ -0,0 +1,563 @@
+use crate::codegen::Flavor;
+use crate::{Detector, SecretFinding};
+use std::fmt;
+
+/// Errors returned when a regex pattern tree cannot be generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    /// No input strings were given.
+    EmptyInput,
+    /// A literal the caller required is absent from one of the inputs.
+    MissingRequiredLiteral {
+        /// The required literal.
+        literal: String,
+        /// The index of the first input that lacks it.
+        input: usize,
+    },
+    /// An input does not have the same number of tokens as the first one.
+    TokenCountMismatch {
+        /// The number of tokens in the first input.
+        expected: usize,
+        /// The number of tokens in the offending input.
+        found: usize,
+        /// The index of the offending input.
+        input: usize,
+    },
+    /// A schema pins a token position the inputs do not have.
+    SchemaPositionOutOfRange {
+        /// The pinned position.
+        position: usize,
+        /// The number of tokens in the inputs.
+        width: usize,
+    },
+    /// A value contradicts the detector pinned for its field.
+    SchemaViolation {
+        /// The token position of the field.
+        position: usize,
+        /// The pinned detector.
+        detector: Detector,
+        /// The index of the first input whose value is rejected.
+        input: usize,
+    },
+    /// No pattern shared by the positives rejects every negative.
+    NoSeparatingPattern,
+    /// The inputs hold likely secrets and
+    /// [`crate::SecretPolicy::Abort`] was requested.
+    SecretsFound(Vec<SecretFinding>),
+    /// The inputs share no literal, so only `.*` matches them all.
+    NoCommonPattern,
+    /// The most specific pattern matching every input pins a single
+    /// character, so it matches almost any text.
+    PatternTooGeneral {
+        /// The pattern.
+        pattern: String,
+    },
+    /// A pattern of the generated tree does not compile, as when an
+    /// alternation exceeds the regex size limit.
+    CompileFailed {
+        /// The rejected pattern.
+        pattern: String,
+        /// The compiler's explanation.
+        message: String,
+    },
+    /// Generation was aborted through a [`crate::CancellationToken`].
+    Cancelled {
+        /// The flattened pattern of the tree built so far, which matches
+        /// every input but is less specific than the finished tree would
+        /// be, `.*` if cancelled before any was built.
+        partial: String,
+    },
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::EmptyInput => write!(f, "no input strings were given"),
+            GenerateError::MissingRequiredLiteral { literal, input } => {
+                write!(
+                    f,
+                    "required literal {literal:?} is missing from input {input}"
+                )
+            }
+            GenerateError::TokenCountMismatch {
+                expected,
+                found,
+                input,
+            } => write!(f, "input {input} has {found} tokens, expected {expected}"),
+            GenerateError::SchemaPositionOutOfRange { position, width } => write!(
+                f,
+                "schema pins token {position} but inputs only have {width} tokens"
+            ),
+            GenerateError::SchemaViolation {
+                position,
+                detector,
+                input,
+            } => write!(
+                f,
+                "token {position} of input {input} is not a {detector:?} value"
+            ),
+            GenerateError::NoSeparatingPattern => write!(
+                f,
+                "no pattern shared by the positives rejects every negative"
+            ),
+            GenerateError::SecretsFound(findings) => {
+                write!(f, "found {} likely secrets", findings.len())?;
+                for finding in findings {
+                    write!(
+                        f,
+                        "; {:?} in input {} at {:?}",
+                        finding.kind(),
+                        finding.input(),
+                        finding.span()
+                    )?;
+                }
+                Ok(())
+            }
+            GenerateError::NoCommonPattern => write!(f, "the inputs share no literal"),
+            GenerateError::PatternTooGeneral { pattern } => write!(
+                f,
+                "the most specific pattern shared by the inputs, {pattern:?}, pins a single character"
+            ),
+            GenerateError::CompileFailed { pattern, message } => {
+                write!(f, "pattern {pattern:?} does not compile: {message}")
+            }
+            GenerateError::Cancelled { .. } => write!(f, "generation was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+/// Errors returned when a [`Model`](crate::Model) cannot apply a correction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelError {
+    /// No node has the given identifier.
+    UnknownNode(usize),
+    /// A node wrongly matching the input cannot be made more specific
+    /// without losing its own examples.
+    CannotTighten {
+        /// The identifier of the node.
+        node: usize,
+    },
+    /// The expected node shares no substring with its examples and the input.
+    CannotWiden {
+        /// The identifier of the node.
+        node: usize,
+    },
+    /// The input still does not reach the expected node.
+    NotRouted {
+        /// The corrected input.
+        input: String,
+        /// The identifier of the node it should reach.
+        expected: usize,
+    },
+    /// A previously recorded example would be routed differently.
+    Regression {
+        /// The affected example.
+        example: String,
+    },
+    /// An imported template does not compile.
+    InvalidTemplate {
+        /// The catalog identifier of the template.
+        id: String,
+        /// The compiler's error message.
+        message: String,
+    },
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::UnknownNode(id) => write!(f, "no node has identifier {id}"),
+            ModelError::CannotTighten { node } => {
+                write!(f, "node {node} cannot be tightened to exclude the input")
+            }
+            ModelError::CannotWiden { node } => {
+                write!(f, "node {node} cannot be widened to include the input")
+            }
+            ModelError::NotRouted { input, expected } => {
+                write!(f, "input {input:?} still does not reach node {expected}")
+            }
+            ModelError::Regression { example } => {
+                write!(f, "correction would reroute example {example:?}")
+            }
+            ModelError::InvalidTemplate { id, message } => {
+                write!(f, "template {id:?} does not compile: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+/// Errors returned when a tree cannot be restructured or edited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeError {
+    /// No node has the given identifier.
+    UnknownNode(usize),
+    /// The root has no parent to be moved away from.
+    RootNotMovable,
+    /// A subtree cannot be moved beneath one of its own nodes.
+    Cycle {
+        /// The identifier of the moved node.
+        node: usize,
+        /// The identifier of the requested parent.
+        parent: usize,
+    },
+    /// The requested parent does not match everything the node matches.
+    NotSubsumed {
+        /// The pattern of the requested parent.
+        parent: String,
+        /// The pattern of the moved node.
+        child: String,
+    },
+    /// An edited pattern does not compile.
+    InvalidPattern {
+        /// The rejected pattern.
+        pattern: String,
+        /// The compiler's explanation.
+        message: String,
+    },
+    /// An edited pattern no longer matches an input its node supported.
+    LostCoverage {
+        /// The input that is no longer matched.
+        input: String,
+    },
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::UnknownNode(id) => write!(f, "no node has identifier {id}"),
+            TreeError::RootNotMovable => write!(f, "the root cannot be moved"),
+            TreeError::Cycle { node, parent } => {
+                write!(
+                    f,
+                    "node {node} cannot be moved beneath its descendant {parent}"
+                )
+            }
+            TreeError::NotSubsumed { parent, child } => {
+                write!(f, "pattern {parent:?} does not subsume {child:?}")
+            }
+            TreeError::InvalidPattern { pattern, message } => {
+                write!(f, "pattern {pattern:?} does not compile: {message}")
+            }
+            TreeError::LostCoverage { input } => {
+                write!(f, "edited pattern no longer matches {input:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+/// Errors returned when a group of a capture pattern cannot be renamed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureError {
+    /// No group has the given name.
+    UnknownGroup(String),
+    /// The new name is not a valid group name.
+    InvalidName(String),
+    /// Another group already has the new name.
+    DuplicateName(String),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::UnknownGroup(name) => write!(f, "no group is named {name:?}"),
+            CaptureError::InvalidName(name) => write!(f, "{name:?} is not a valid group name"),
+            CaptureError::DuplicateName(name) => {
+                write!(f, "another group is already named {name:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+/// Errors returned when a pattern cannot be written in another regex flavor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlavorError {
+    /// The pattern uses syntax the flavor has no equivalent for.
+    Untranslatable {
+        /// The requested flavor.
+        flavor: Flavor,
+        /// The rejected pattern.
+        pattern: String,
+        /// What cannot be expressed.
+        reason: String,
+    },
+}
+
+impl fmt::Display for FlavorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlavorError::Untranslatable {
+                flavor,
+                pattern,
+                reason,
+            } => write!(
+                f,
+                "pattern {pattern:?} cannot be written for {flavor:?}: {reason}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FlavorError {}
+
+/// Errors returned when a substitution command cannot be generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubstitutionError {
+    /// The pattern cannot be written as a POSIX extended regex.
+    Untranslatable(FlavorError),
+    /// The replacement refers to a group the pattern does not have.
+    UnknownGroup(String),
+    /// The replacement refers to a group numbered beyond `\9`, the last
+    /// one sed can refer to.
+    UnreachableGroup(usize),
+}
+
+impl fmt::Display for SubstitutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubstitutionError::Untranslatable(error) => error.fmt(f),
+            SubstitutionError::UnknownGroup(name) => write!(f, "no group is named {name:?}"),
+            SubstitutionError::UnreachableGroup(number) => {
+                write!(
+                    f,
+                    "group {number} is beyond \\9, the last one sed can refer to"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SubstitutionError {}
+
+/// Errors returned when a graph cannot be converted back into a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// Every node has a parent, or the graph is empty.
+    NoRoot,
+    /// Several nodes have no parent.
+    MultipleRoots(Vec<usize>),
+    /// A node has more than one parent.
+    MultipleParents {
+        /// The index of the node.
+        node: usize,
+    },
+    /// A node cannot be reached from the root, so it lies on a cycle.
+    Unreachable {
+        /// The index of the node.
+        node: usize,
+    },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::NoRoot => write!(f, "the graph has no root"),
+            GraphError::MultipleRoots(roots) => write!(f, "the graph has several roots: {roots:?}"),
+            GraphError::MultipleParents { node } => write!(f, "node {node} has several parents"),
+            GraphError::Unreachable { node } => {
+                write!(f, "node {node} cannot be reached from the root")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// Errors returned when the languages of two patterns cannot be compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LanguageError {
+    /// A pattern does not compile to an automaton.
+    InvalidPattern {
+        /// The rejected pattern.
+        pattern: String,
+        /// The compiler's explanation.
+        message: String,
+    },
+}
+
+impl fmt::Display for LanguageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LanguageError::InvalidPattern { pattern, message } => {
+                write!(f, "pattern {pattern:?} cannot be compared: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LanguageError {}
+
+/// Errors returned when a buffer is not a valid compiled tree layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The buffer ends before the layout does.
+    Truncated,
+    /// The buffer does not start with the layout's magic bytes.
+    BadMagic,
+    /// The layout was written by a newer, unknown version.
+    UnsupportedVersion(u32),
+    /// The patterns are not valid UTF-8.
+    InvalidUtf8,
+    /// A node's pattern or children lie outside the buffer or out of order.
+    InvalidOffsets {
+        /// The breadth-first index of the node.
+        node: usize,
+    },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::Truncated => write!(f, "the buffer is truncated"),
+            LayoutError::BadMagic => write!(f, "the buffer is not a compiled tree"),
+            LayoutError::UnsupportedVersion(version) => {
+                write!(f, "unsupported layout version {version}")
+            }
+            LayoutError::InvalidUtf8 => write!(f, "the patterns are not valid UTF-8"),
+            LayoutError::InvalidOffsets { node } => {
+                write!(f, "node {node} has invalid offsets")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// Errors returned when a serialized tree cannot be loaded.
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum LoadError {
+    /// The input is not a valid serialized tree.
+    Json(serde_json::Error),
+    /// The input is not a valid YAML document.
+    Yaml(serde_yaml::Error),
+    /// The tree was written by a newer, unknown format version.
+    UnsupportedVersion(u32),
+    /// An upgrade hook rejected the tree.
+    Hook {
+        /// The format version the failing hook upgrades from.
+        version: u32,
+        /// The reason given by the hook.
+        message: String,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Json(error) => write!(f, "invalid serialized tree: {error}"),
+            LoadError::Yaml(error) => write!(f, "invalid YAML: {error}"),
+            LoadError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            LoadError::Hook { version, message } => {
+                write!(f, "upgrade from version {version} failed: {message}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for LoadError {}
+
+/// Errors returned when a protobuf-encoded tree cannot be decoded.
+#[cfg(feature = "protobuf")]
+#[derive(Debug)]
+pub enum ProtobufError {
+    /// The bytes are not a valid encoding of a tree message.
+    Decode(prost::DecodeError),
+    /// The tree was written by a newer, unknown format version.
+    UnsupportedVersion(u32),
+    /// An edge refers to a node identifier that is not in the message.
+    UnknownNode(u64),
+    /// The nodes and edges do not form a tree.
+    Structure(GraphError),
+}
+
+#[cfg(feature = "protobuf")]
+impl fmt::Display for ProtobufError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtobufError::Decode(error) => write!(f, "invalid encoded tree: {error}"),
+            ProtobufError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            ProtobufError::UnknownNode(id) => write!(f, "edge refers to unknown node {id}"),
+            ProtobufError::Structure(error) => write!(f, "invalid tree structure: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "protobuf")]
+impl std::error::Error for ProtobufError {}
+
+/// Errors returned when a tree cannot be exported to a columnar format.
+#[cfg(any(feature = "arrow", feature = "polars"))]
+#[derive(Debug)]
+pub enum ExportError {
+    /// A pattern of the tree does not compile.
+    InvalidPattern {
+        /// The rejected pattern.
+        pattern: String,
+        /// The compiler's explanation.
+        message: String,
+    },
+    /// The match matrix cannot be assembled into Arrow arrays.
+    #[cfg(feature = "arrow")]
+    Arrow(arrow_schema::ArrowError),
+    /// The Parquet file cannot be written.
+    #[cfg(feature = "arrow")]
+    Parquet(parquet::errors::ParquetError),
+    /// The extracted columns cannot be assembled into a data frame.
+    #[cfg(feature = "polars")]
+    Polars(polars::prelude::PolarsError),
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow_schema::ArrowError> for ExportError {
+    fn from(error: arrow_schema::ArrowError) -> Self {
+        ExportError::Arrow(error)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<parquet::errors::ParquetError> for ExportError {
+    fn from(error: parquet::errors::ParquetError) -> Self {
+        ExportError::Parquet(error)
+    }
+}
+
+#[cfg(feature = "polars")]
+impl From<polars::prelude::PolarsError> for ExportError {
+    fn from(error: polars::prelude::PolarsError) -> Self {
+        ExportError::Polars(error)
+    }
+}
+
+#[cfg(any(feature = "arrow", feature = "polars"))]
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::InvalidPattern { pattern, message } => {
+                write!(f, "pattern {pattern:?} does not compile: {message}")
+            }
+            #[cfg(feature = "arrow")]
+            ExportError::Arrow(error) => write!(f, "cannot build the table: {error}"),
+            #[cfg(feature = "arrow")]
+            ExportError::Parquet(error) => write!(f, "cannot write the table: {error}"),
+            #[cfg(feature = "polars")]
+            ExportError::Polars(error) => write!(f, "cannot build the data frame: {error}"),
+        }
+    }
+}
+
+#[cfg(any(feature = "arrow", feature = "polars"))]
+impl std::error::Error for ExportError {}
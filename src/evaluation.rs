@@ -0,0 +1,222 @@
+use crate::{Children, Node, TreeError};
+use regex::Regex;
+use std::sync::Arc;
+
+/// How one node's pattern fares on a holdout corpus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeEvaluation {
+    id: usize,
+    pattern: String,
+    true_positives: usize,
+    false_negatives: usize,
+    false_positives: usize,
+    true_negatives: usize,
+}
+
+impl NodeEvaluation {
+    /// Returns the pre-order identifier of the node, the root being `0`.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the node's pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns how many strings of the matched corpus the pattern matches.
+    pub fn true_positives(&self) -> usize {
+        self.true_positives
+    }
+
+    /// Returns how many strings of the matched corpus the pattern misses.
+    pub fn false_negatives(&self) -> usize {
+        self.false_negatives
+    }
+
+    /// Returns how many strings of the unmatched corpus the pattern matches.
+    pub fn false_positives(&self) -> usize {
+        self.false_positives
+    }
+
+    /// Returns how many strings of the unmatched corpus the pattern rejects.
+    pub fn true_negatives(&self) -> usize {
+        self.true_negatives
+    }
+
+    /// Returns the share of the strings matched that belong to the matched
+    /// corpus, `1` if the pattern matches none.
+    pub fn precision(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+            1.0,
+        )
+    }
+
+    /// Returns the share of the matched corpus the pattern matches, `1` if
+    /// that corpus is empty.
+    pub fn recall(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+            1.0,
+        )
+    }
+
+    /// Returns the share of the unmatched corpus the pattern matches, `0`
+    /// if that corpus is empty.
+    pub fn false_positive_rate(&self) -> f64 {
+        ratio(
+            self.false_positives,
+            self.false_positives + self.true_negatives,
+            0.0,
+        )
+    }
+}
+
+/// Returns `part / whole`, or `empty` if `whole` is zero.
+fn ratio(part: usize, whole: usize, empty: f64) -> f64 {
+    match whole {
+        0 => empty,
+        whole => part as f64 / whole as f64,
+    }
+}
+
+/// The evaluation of every pattern of a tree on a holdout corpus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalReport {
+    nodes: Vec<NodeEvaluation>,
+}
+
+impl EvalReport {
+    /// Returns the evaluation of every node, in pre-order.
+    pub fn nodes(&self) -> &[NodeEvaluation] {
+        &self.nodes
+    }
+
+    /// Removes from the evaluated tree the nodes whose false positive rate
+    /// exceeds `max_false_positive_rate`, re-linking their children to
+    /// their parent in their place, so the patterns left do not over-match
+    /// unrelated text.
+    ///
+    /// The root is always kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root node of the tree the report was made for.
+    /// * `max_false_positive_rate` - The largest false positive rate,
+    ///   between `0` and `1`, of the nodes kept.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes removed.
+    pub fn prune(&self, root: &mut Node, max_false_positive_rate: f64) -> usize {
+        let rejected: Vec<bool> = self
+            .nodes
+            .iter()
+            .map(|node| node.id > 0 && node.false_positive_rate() > max_false_positive_rate)
+            .collect();
+        let mut next = 1;
+        let children = std::mem::take(&mut root.children);
+        root.children = children
+            .iter()
+            .flat_map(|child| relink(child, &mut next, &rejected))
+            .collect();
+        rejected.iter().filter(|&&r| r).count()
+    }
+}
+
+/// Returns `node`, numbered `next` in pre-order, with its descendants
+/// relinked, or them in its place if it is rejected.
+fn relink(node: &Arc<Node>, next: &mut usize, rejected: &[bool]) -> Children {
+    let id = *next;
+    *next += 1;
+    let children: Children = node
+        .children
+        .iter()
+        .flat_map(|child| relink(child, next, rejected))
+        .collect();
+    if rejected.get(id).copied().unwrap_or(false) {
+        return children;
+    }
+    let mut kept = Children::new();
+    kept.push(Arc::new(Node {
+        children,
+        ..(**node).clone()
+    }));
+    kept
+}
+
+/// Compiles every pattern of a tree and measures it on a holdout corpus,
+/// one part of which it should match and the other not, reporting the
+/// precision, recall and false positive rate of each node, so a pattern
+/// that generalizes without over-matching unrelated text can be picked.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `matched_corpus` - Strings the patterns should match, unseen during
+///   generation.
+/// * `unmatched_corpus` - Unrelated strings the patterns should reject.
+///
+/// # Returns
+///
+/// The report, or an error if a pattern does not compile.
+pub fn evaluate(
+    root: &Node,
+    matched_corpus: &[&str],
+    unmatched_corpus: &[&str],
+) -> Result<EvalReport, TreeError> {
+    let mut nodes = vec![];
+    for (id, node) in root.preorder().into_iter().enumerate() {
+        let regex = Regex::new(&node.pattern).map_err(|e| TreeError::InvalidPattern {
+            pattern: node.pattern.to_string(),
+            message: e.to_string(),
+        })?;
+        let true_positives = matched_corpus.iter().filter(|s| regex.is_match(s)).count();
+        let false_positives = unmatched_corpus
+            .iter()
+            .filter(|s| regex.is_match(s))
+            .count();
+        nodes.push(NodeEvaluation {
+            id,
+            pattern: node.pattern.to_string(),
+            true_positives,
+            false_negatives: matched_corpus.len() - true_positives,
+            false_positives,
+            true_negatives: unmatched_corpus.len() - false_positives,
+        });
+    }
+    Ok(EvalReport { nodes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_evaluate() {
+        let tree = generate_regex_tree(&["disk sda full", "disk sdb full"]);
+        let matched = ["disk sdc full", "disk nvme0 full"];
+        let unmatched = ["disk sda ok", "net down"];
+        let report = evaluate(&tree, &matched, &unmatched).unwrap();
+
+        let root = &report.nodes()[0];
+        assert_eq!((root.recall(), root.false_positive_rate()), (1.0, 1.0));
+        assert!(report
+            .nodes()
+            .iter()
+            .any(|n| n.recall() == 1.0 && n.precision() == 1.0));
+
+        let mut pruned = tree.clone();
+        let removed = report.prune(&mut pruned, 0.0);
+        assert!(removed > 0);
+        assert_eq!(pruned.size(), tree.size() - removed);
+        let report = evaluate(&pruned, &matched, &unmatched).unwrap();
+        assert!(report.nodes()[1..]
+            .iter()
+            .all(|n| n.false_positive_rate() == 0.0));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::Node;
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The most matched inputs compared with each other when picking examples,
+/// which bounds the quadratic part of the selection.
+const SAMPLE_SIZE: usize = 256;
+
+/// Attaches up to `count` examples to every node of the tree: the matched
+/// input most central to the others first, then the ones diverging most
+/// from those already picked.
+///
+/// # Arguments
+///
+/// * `node` - The root of the tree.
+/// * `inputs` - The strings the tree was mined from.
+/// * `count` - The largest number of examples per node.
+pub(crate) fn attach_examples(node: &mut Node, inputs: &[&str], count: usize) {
+    if let Ok(regex) = Regex::new(&node.pattern) {
+        let mut seen = HashSet::new();
+        let matched: Vec<&str> = inputs
+            .iter()
+            .copied()
+            .filter(|input| regex.is_match(input) && seen.insert(*input))
+            .take(SAMPLE_SIZE)
+            .collect();
+        node.examples = select_examples(&matched, count)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+    }
+    for child in &mut node.children {
+        attach_examples(Arc::make_mut(child), inputs, count);
+    }
+}
+
+/// Picks up to `count` distinct strings from `candidates`, the most central
+/// first, then by farthest-point sampling.
+fn select_examples<'a>(candidates: &[&'a str], count: usize) -> Vec<&'a str> {
+    if candidates.is_empty() || count == 0 {
+        return vec![];
+    }
+    let central = (0..candidates.len())
+        .min_by_key(|&i| {
+            candidates
+                .iter()
+                .map(|other| distance(candidates[i], other))
+                .sum::<usize>()
+        })
+        .unwrap();
+
+    let mut picked = vec![candidates[central]];
+    let mut nearest: Vec<usize> = candidates
+        .iter()
+        .map(|c| distance(c, candidates[central]))
+        .collect();
+    while picked.len() < count {
+        let (next, &gap) = nearest
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(&a.0)))
+            .unwrap();
+        if gap == 0 {
+            break;
+        }
+        picked.push(candidates[next]);
+        for (index, candidate) in candidates.iter().enumerate() {
+            nearest[index] = nearest[index].min(distance(candidate, candidates[next]));
+        }
+    }
+    picked
+}
+
+/// A cheap dissimilarity: the number of differing bytes at the same
+/// position plus the difference in length.
+fn distance(a: &str, b: &str) -> usize {
+    let differing = a.bytes().zip(b.bytes()).filter(|(x, y)| x != y).count();
+    differing + a.len().abs_diff(b.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_examples() {
+        let candidates = ["disk sda", "disk sdb", "disk sdc", "disk nvme0n1"];
+        assert_eq!(
+            select_examples(&candidates, 2),
+            vec!["disk sda", "disk nvme0n1"]
+        );
+        assert_eq!(select_examples(&["same", "same"], 3), vec!["same"]);
+        assert!(select_examples(&candidates, 0).is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{ModelError, Record, TemplateId};
+use regex::{Regex, RegexSet};
+
+/// Extracts fields from lines against a catalog of templates in two stages:
+/// a [`RegexSet`] identifies the matching template in a single pass, and
+/// only that template's regex then runs to resolve its capture groups.
+#[derive(Debug, Clone)]
+pub struct Extractor {
+    ids: Vec<String>,
+    templates: Vec<TemplateId>,
+    set: RegexSet,
+    captures: Vec<Regex>,
+}
+
+impl Extractor {
+    /// Compiles a catalog of templates.
+    ///
+    /// # Arguments
+    ///
+    /// * `templates` - The `(id, regex)` pairs of the catalog, whose named
+    ///   capture groups are the extracted fields. When several templates
+    ///   match a line, the earliest one wins.
+    ///
+    /// # Returns
+    ///
+    /// The extractor, or an error if a template does not compile.
+    pub fn new(templates: &[(&str, &str)]) -> Result<Extractor, ModelError> {
+        let captures = templates
+            .iter()
+            .map(|&(id, pattern)| {
+                Regex::new(pattern).map_err(|e| ModelError::InvalidTemplate {
+                    id: id.to_string(),
+                    message: e.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let set = RegexSet::new(templates.iter().map(|&(_, pattern)| pattern)).map_err(|e| {
+            ModelError::InvalidTemplate {
+                id: String::new(),
+                message: e.to_string(),
+            }
+        })?;
+        Ok(Extractor {
+            ids: templates.iter().map(|&(id, _)| id.to_string()).collect(),
+            templates: templates.iter().map(|&(_, p)| TemplateId::of(p)).collect(),
+            set,
+            captures,
+        })
+    }
+
+    /// Returns the identifier of the first template matching `line`, without
+    /// resolving its fields.
+    pub fn identify(&self, line: &str) -> Option<&str> {
+        let index = self.set.matches(line).iter().next()?;
+        Some(&self.ids[index])
+    }
+
+    /// Identifies the template matching `line` and extracts its fields.
+    ///
+    /// # Returns
+    ///
+    /// The template's catalog identifier with the record of each named
+    /// group that participated in the match, or `None` if no template
+    /// matches.
+    pub fn extract(&self, line: &str) -> Option<(&str, Record)> {
+        let index = self.set.matches(line).iter().next()?;
+        let record = Record::capture(self.templates[index], &self.captures[index], line)?;
+        Some((&self.ids[index], record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extractor() {
+        let extractor = Extractor::new(&[
+            (
+                "login",
+                r"^user (?P<user>\w+) logged in from (?P<ip>[\d.]+)$",
+            ),
+            ("disk", r"^disk (?P<device>\w+) full$"),
+            ("any-disk", r"^disk .*"),
+        ])
+        .unwrap();
+
+        let (id, fields) = extractor
+            .extract("user alice logged in from 10.0.0.1")
+            .unwrap();
+        assert_eq!(id, "login");
+        assert_eq!(fields.get("user"), Some("alice"));
+        assert_eq!(fields.get("ip"), Some("10.0.0.1"));
+
+        let (id, fields) = extractor.extract("disk sda full").unwrap();
+        assert_eq!((id, fields.get("device")), ("disk", Some("sda")));
+        assert_eq!(extractor.identify("disk sda degraded"), Some("any-disk"));
+        assert!(extractor.extract("kernel panic").is_none());
+
+        let invalid = Extractor::new(&[("broken", "(")]);
+        assert!(matches!(invalid, Err(ModelError::InvalidTemplate { id, .. }) if id == "broken"));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{Detector, Node, TreeError};
+use regex::Regex;
+use std::collections::BTreeSet;
+
+/// The most distinct values a field may take and still be typed as an
+/// enumeration.
+const MAX_ENUM_VALUES: usize = 8;
+
+/// The type of a field, inferred from its observed values, for exporters
+/// emitting typed schemas such as database columns or OpenTelemetry
+/// attributes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldType {
+    /// A decimal integer, optionally signed.
+    Integer,
+    /// A decimal number with a fractional part or an exponent, possibly
+    /// mixed with integers.
+    Float,
+    /// An ISO 8601 date or date-time, or a clock time.
+    Timestamp,
+    /// An IPv4 address.
+    Ip,
+    /// One of a few values, which repeat across the inputs, in ascending
+    /// order.
+    Enum(Vec<String>),
+    /// Any other text.
+    String,
+}
+
+impl FieldType {
+    /// Infers the type of a field.
+    ///
+    /// The numeric, timestamp and address types are tried first; a field
+    /// taking at most eight distinct values, at least one of them more than
+    /// once, is then an enumeration.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The observed values of the field.
+    ///
+    /// # Returns
+    ///
+    /// The type, or `None` if there are no values.
+    pub fn infer<S: AsRef<str>>(values: &[S]) -> Option<FieldType> {
+        if values.is_empty() {
+            return None;
+        }
+        let all = |accepts: &dyn Fn(&str) -> bool| values.iter().all(|v| accepts(v.as_ref()));
+        if all(&|v| Detector::Integer.matches(v)) {
+            return Some(FieldType::Integer);
+        }
+        if all(&is_float) {
+            return Some(FieldType::Float);
+        }
+        if all(&|v| Detector::Timestamp.matches(v)) {
+            return Some(FieldType::Timestamp);
+        }
+        if all(&|v| Detector::Ip.matches(v)) {
+            return Some(FieldType::Ip);
+        }
+        let distinct: BTreeSet<&str> = values.iter().map(AsRef::as_ref).collect();
+        if distinct.len() <= MAX_ENUM_VALUES && distinct.len() < values.len() {
+            return Some(FieldType::Enum(
+                distinct.into_iter().map(str::to_string).collect(),
+            ));
+        }
+        Some(FieldType::String)
+    }
+}
+
+/// Returns whether `value` is a decimal number, such as `-1.5` or `2e10`.
+fn is_float(value: &str) -> bool {
+    value.bytes().any(|b| b.is_ascii_digit())
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b))
+        && value.parse::<f64>().is_ok()
+}
+
+impl Node {
+    /// Infers the type of each named capture group of the node's pattern
+    /// from the values it captures in `inputs`.
+    ///
+    /// # Returns
+    ///
+    /// The group names with their types, in the order of the groups in the
+    /// pattern, omitting the groups that captured nothing, or an error if
+    /// the pattern does not compile.
+    pub fn capture_types(&self, inputs: &[&str]) -> Result<Vec<(String, FieldType)>, TreeError> {
+        let regex = Regex::new(&self.pattern).map_err(|e| TreeError::InvalidPattern {
+            pattern: self.pattern.to_string(),
+            message: e.to_string(),
+        })?;
+        let mut values: Vec<Vec<&str>> = vec![vec![]; regex.captures_len()];
+        for groups in inputs.iter().filter_map(|input| regex.captures(input)) {
+            for (index, found) in groups.iter().enumerate() {
+                if let Some(found) = found {
+                    values[index].push(found.as_str());
+                }
+            }
+        }
+        Ok(regex
+            .capture_names()
+            .zip(&values)
+            .filter_map(|(name, values)| Some((name?.to_string(), FieldType::infer(values)?)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_types() {
+        let node = Node::new(
+            r"^(?P<ts>\S+) (?P<level>\w+) (?P<client>\S+) took (?P<ms>\S+) ms(?: #(?P<n>\d+))?$"
+                .to_string(),
+        );
+        let inputs = [
+            "2024-01-02T10:00:00Z INFO 10.0.0.1 took 12 ms",
+            "2024-01-02T10:00:05Z WARN 10.0.0.2 took 3.5 ms",
+            "2024-01-02T10:00:09Z INFO 10.0.0.3 took 1e3 ms",
+        ];
+        let types = node.capture_types(&inputs).unwrap();
+        assert_eq!(
+            types,
+            [
+                ("ts".to_string(), FieldType::Timestamp),
+                (
+                    "level".to_string(),
+                    FieldType::Enum(vec!["INFO".into(), "WARN".into()])
+                ),
+                ("client".to_string(), FieldType::Ip),
+                ("ms".to_string(), FieldType::Float),
+            ]
+        );
+        assert_eq!(FieldType::infer(&["a", "b", "c"]), Some(FieldType::String));
+        assert_eq!(FieldType::infer::<&str>(&[]), None);
+        assert!(Node::new("(".to_string()).capture_types(&inputs).is_err());
+    }
+}
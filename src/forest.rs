@@ -0,0 +1,253 @@
+use crate::{Matcher, MultiCorpus, Node, TreeError};
+use std::collections::HashMap;
+
+/// Several named root trees, such as one per cluster or per log source,
+/// handled as one: their patterns are interned into a shared vocabulary,
+/// they serialize to a single document and match inputs together.
+#[derive(Debug, Clone, Default)]
+pub struct Forest {
+    trees: Vec<(String, Node)>,
+    vocabulary: Vec<String>,
+    words: HashMap<String, usize>,
+    // For each tree, the vocabulary index of each node in pre-order.
+    indices: Vec<Vec<usize>>,
+}
+
+impl Forest {
+    /// Creates a forest without trees.
+    pub fn new() -> Self {
+        Forest::default()
+    }
+
+    /// Adds a tree, interning its patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the tree, such as its cluster or source.
+    /// * `root` - The root node of the tree.
+    pub fn push(&mut self, name: &str, root: Node) {
+        let indices = root
+            .preorder()
+            .into_iter()
+            .map(|node| match self.words.get(node.pattern.as_str()) {
+                Some(&index) => index,
+                None => {
+                    self.vocabulary.push(node.pattern.to_string());
+                    self.words
+                        .insert(node.pattern.to_string(), self.vocabulary.len() - 1);
+                    self.vocabulary.len() - 1
+                }
+            })
+            .collect();
+        self.indices.push(indices);
+        self.trees.push((name.to_string(), root));
+    }
+
+    /// Returns the number of trees.
+    pub fn len(&self) -> usize {
+        self.trees.len()
+    }
+
+    /// Returns whether the forest has no tree.
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+
+    /// Returns the name and root of each tree, in the order added.
+    pub fn trees(&self) -> impl Iterator<Item = (&str, &Node)> {
+        self.trees.iter().map(|(name, tree)| (name.as_str(), tree))
+    }
+
+    /// Returns the tree called `name`, if any.
+    pub fn tree(&self, name: &str) -> Option<&Node> {
+        self.trees
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, tree)| tree)
+    }
+
+    /// Returns every distinct pattern of the trees, in order of first
+    /// appearance.
+    pub fn vocabulary(&self) -> &[String] {
+        &self.vocabulary
+    }
+
+    /// Returns the index in [`Forest::vocabulary`] of the pattern of a
+    /// node.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The index of the tree, in the order added.
+    /// * `id` - The pre-order identifier of the node in its tree.
+    ///
+    /// # Returns
+    ///
+    /// The index, or `None` if there is no such node.
+    pub fn word(&self, tree: usize, id: usize) -> Option<usize> {
+        self.indices.get(tree)?.get(id).copied()
+    }
+
+    /// Returns the names of the trees having `pattern`, in the order added.
+    pub fn sources(&self, pattern: &str) -> Vec<&str> {
+        let Some(&word) = self.words.get(pattern) else {
+            return vec![];
+        };
+        self.trees
+            .iter()
+            .zip(&self.indices)
+            .filter(|(_, indices)| indices.contains(&word))
+            .map(|((name, _), _)| name.as_str())
+            .collect()
+    }
+
+    /// Compiles the leaves of every tree to match inputs against the
+    /// whole forest.
+    ///
+    /// # Returns
+    ///
+    /// The matcher, or an error if a leaf pattern does not compile.
+    pub fn matcher(&self) -> Result<ForestMatcher, TreeError> {
+        let matchers = self
+            .trees
+            .iter()
+            .map(|(_, tree)| Matcher::new(tree))
+            .collect::<Result<_, _>>()?;
+        Ok(ForestMatcher { matchers })
+    }
+}
+
+impl From<MultiCorpus> for Forest {
+    /// Gathers the tree of every corpus, named after it.
+    fn from(corpus: MultiCorpus) -> Self {
+        let mut forest = Forest::new();
+        for (name, tree) in corpus.trees() {
+            forest.push(name, tree.clone());
+        }
+        forest
+    }
+}
+
+/// Matches inputs against the leaves of every tree of a [`Forest`].
+#[derive(Debug, Clone)]
+pub struct ForestMatcher {
+    matchers: Vec<Matcher>,
+}
+
+impl ForestMatcher {
+    /// Returns the index of the tree and the pre-order identifier of each
+    /// leaf matching `input`, trees in the order added.
+    pub fn matches(&self, input: &str) -> Vec<(usize, usize)> {
+        self.matchers
+            .iter()
+            .enumerate()
+            .flat_map(|(tree, matcher)| {
+                matcher.matches(input).into_iter().map(move |id| (tree, id))
+            })
+            .collect()
+    }
+
+    /// Returns whether a leaf of any tree matches `input`.
+    pub fn is_match(&self, input: &str) -> bool {
+        self.matchers.iter().any(|matcher| matcher.is_match(input))
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serialization {
+    use super::Forest;
+    use crate::{LoadError, Node, FORMAT_VERSION};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Document {
+        version: u32,
+        trees: Vec<Named>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Named {
+        name: String,
+        root: Node,
+    }
+
+    impl Forest {
+        /// Serializes the forest to JSON, as an object with the `version`
+        /// and the `trees`, each an object with its `name` and its `root`
+        /// node laid out as in [`Node::to_json`].
+        pub fn to_json(&self) -> serde_json::Result<String> {
+            serde_json::to_string(&Document {
+                version: FORMAT_VERSION,
+                trees: self
+                    .trees()
+                    .map(|(name, root)| Named {
+                        name: name.to_string(),
+                        root: root.clone(),
+                    })
+                    .collect(),
+            })
+        }
+
+        /// Loads a forest serialized by [`Forest::to_json`].
+        ///
+        /// # Arguments
+        ///
+        /// * `json` - The serialized forest.
+        ///
+        /// # Returns
+        ///
+        /// The forest, or an error if it cannot be parsed or was written by
+        /// a newer format version.
+        pub fn from_json(json: &str) -> Result<Forest, LoadError> {
+            let document: Document = serde_json::from_str(json).map_err(LoadError::Json)?;
+            if document.version > FORMAT_VERSION {
+                return Err(LoadError::UnsupportedVersion(document.version));
+            }
+            let mut forest = Forest::new();
+            for Named { name, root } in document.trees {
+                forest.push(&name, root);
+            }
+            Ok(forest)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_forest() {
+        let mut forest = Forest::new();
+        forest.push(
+            "disk",
+            generate_regex_tree(&["disk sda full", "disk sdb full"]),
+        );
+        forest.push("net", generate_regex_tree(&["eth0 down", "eth1 down"]));
+        assert_eq!(forest.len(), 2);
+        assert!(forest.tree("net").is_some());
+
+        // Both roots are `.*`, interned once.
+        assert_eq!(forest.word(0, 0), Some(0));
+        assert_eq!(forest.word(1, 0), Some(0));
+        assert_eq!(forest.vocabulary()[0], ".*");
+        assert_eq!(forest.sources(".*"), ["disk", "net"]);
+        assert!(forest.word(2, 0).is_none());
+
+        let matcher = forest.matcher().unwrap();
+        let matches = matcher.matches("eth7 down");
+        assert!(!matches.is_empty() && matches.iter().all(|&(tree, _)| tree == 1));
+        assert!(matcher
+            .matches("disk sdc full")
+            .iter()
+            .any(|&(tree, _)| tree == 0));
+        assert!(!matcher.is_match("CPU"));
+
+        #[cfg(feature = "serde")]
+        {
+            let reloaded = Forest::from_json(&forest.to_json().unwrap()).unwrap();
+            assert_eq!(reloaded.len(), 2);
+            assert_eq!(reloaded.vocabulary(), forest.vocabulary());
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::CharClassDetector;
+
+/// How the wildcard regions of leaf patterns, the gaps around their common
+/// substrings, are written when no other generalization applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GapStrategy {
+    /// Gaps are left as `.*`.
+    #[default]
+    Wildcard,
+    /// Gaps match any characters, as many as observed across the inputs,
+    /// as in `.{3,8}`.
+    BoundedAny,
+    /// Gaps match the most specific of [`CharClassDetector::ALL`] holding
+    /// every character observed there, as many as observed, as in
+    /// `\w{2,5}`, or any characters when none does.
+    InferredClassWithBounds,
+}
+
+impl GapStrategy {
+    /// Returns the regex fragment matching every value a gap takes, or
+    /// `None` to leave it a wildcard.
+    pub(crate) fn fragment(self, values: &[&str]) -> Option<String> {
+        if self == GapStrategy::Wildcard {
+            return None;
+        }
+        let min = values.iter().map(|v| v.chars().count()).min()?;
+        let max = values.iter().map(|v| v.chars().count()).max()?;
+        let class = match self {
+            GapStrategy::InferredClassWithBounds => CharClassDetector::ALL
+                .iter()
+                .find(|class| {
+                    values
+                        .iter()
+                        .flat_map(|v| v.chars())
+                        .all(|c| class.matches(c))
+                })
+                .map(|class| class.pattern()),
+            _ => None,
+        };
+        let class = class.unwrap_or(if values.iter().any(|v| v.contains('\n')) {
+            "(?s:.)"
+        } else {
+            "."
+        });
+        Some(match (min, max) {
+            (_, 0) => String::new(),
+            (1, 1) => class.to_string(),
+            (min, max) if min == max => format!("{class}{{{min}}}"),
+            (min, max) => format!("{class}{{{min},{max}}}"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_regex_tree_with_options, GapStrategy, GenerateOptions};
+    use regex::Regex;
+
+    #[test]
+    fn test_gap_strategy() {
+        let inputs = ["id=abc", "id=xy_z12", "id=q1"];
+        let leaf = |strategy| {
+            let options = GenerateOptions {
+                gap_strategy: strategy,
+                ..GenerateOptions::default()
+            };
+            let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+            let mut leaf = &tree;
+            while let Some(child) = leaf.children.first() {
+                leaf = child;
+            }
+            leaf.pattern.clone()
+        };
+        assert_eq!(leaf(GapStrategy::Wildcard), "^id=.*");
+        assert_eq!(leaf(GapStrategy::BoundedAny), "^id=.{2,6}$");
+        let inferred = leaf(GapStrategy::InferredClassWithBounds);
+        assert_eq!(inferred, r"^id=\w{2,6}$");
+        let regex = Regex::new(&inferred).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+        assert!(!regex.is_match("id=a b"));
+    }
+}
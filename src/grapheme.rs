@@ -0,0 +1,72 @@
+use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
+
+/// Which grapheme clusters literals must not split, see
+/// [`crate::GenerateOptions::grapheme_mode`].
+///
+/// Without one, literals only keep to char boundaries, so an accent
+/// written as a combining mark, a skin tone modifier or a flag may be cut
+/// from the letter or emoji it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphemeMode {
+    /// Extended grapheme clusters, as users perceive characters.
+    Extended,
+    /// Legacy grapheme clusters, which split some spacing marks off.
+    Legacy,
+}
+
+impl GraphemeMode {
+    fn is_extended(self) -> bool {
+        self == GraphemeMode::Extended
+    }
+
+    /// Returns whether `index` falls between two clusters of `s`.
+    pub(crate) fn is_boundary(self, s: &str, index: usize) -> bool {
+        s.is_char_boundary(index)
+            && GraphemeCursor::new(index, s.len(), self.is_extended())
+                .is_boundary(s, 0)
+                .unwrap_or(false)
+    }
+
+    /// Returns the number of clusters in `s`.
+    pub(crate) fn len(self, s: &str) -> usize {
+        s.graphemes(self.is_extended()).count()
+    }
+
+    /// Returns whether every input holds `literal` as a whole run of
+    /// clusters at least once.
+    pub(crate) fn is_aligned(self, literal: &str, inputs: &[&str]) -> bool {
+        inputs.iter().all(|input| {
+            input.match_indices(literal).any(|(start, _)| {
+                self.is_boundary(input, start) && self.is_boundary(input, start + literal.len())
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_regex_tree_with_options, GenerateOptions, GraphemeMode};
+
+    #[test]
+    fn test_grapheme_mode() {
+        let inputs = ["José 👍🏽 ok", "José 👍🏿 ok", "Zoë e\u{301}t 👍 ok"];
+        let patterns = |grapheme_mode| {
+            let options = GenerateOptions {
+                grapheme_mode,
+                ..GenerateOptions::default()
+            };
+            let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+            tree.iter()
+                .map(|n| n.pattern().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let chars = patterns(None);
+        assert!(chars.contains(&".*👍.*".to_string()), "{chars:?}");
+        assert!(chars.contains(&".* ok$".to_string()));
+        let clusters = patterns(Some(GraphemeMode::Extended));
+        assert!(!clusters.iter().any(|p| p.contains('👍')), "{clusters:?}");
+        assert!(clusters.contains(&".* ok$".to_string()));
+        assert!(clusters.contains(&".*o.*".to_string()));
+    }
+}
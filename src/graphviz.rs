@@ -0,0 +1,374 @@
+use crate::{Node, NodeStatus, TreeDiff};
+use std::fmt::Write as _;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+/// How the edges of an exported graph are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeStyle {
+    /// Plain lines.
+    #[default]
+    Solid,
+    /// Dashed lines.
+    Dashed,
+    /// Dotted lines.
+    Dotted,
+    /// Thick lines.
+    Bold,
+}
+
+impl EdgeStyle {
+    fn name(&self) -> &'static str {
+        match self {
+            EdgeStyle::Solid => "solid",
+            EdgeStyle::Dashed => "dashed",
+            EdgeStyle::Dotted => "dotted",
+            EdgeStyle::Bold => "bold",
+        }
+    }
+}
+
+/// The fill colors of [`GraphStyle::default`], from light to dark blue.
+const DEFAULT_PALETTE: [&str; 4] = ["#eff3ff", "#bdd7e7", "#6baed6", "#2171b5"];
+
+/// What the fill color of a node shows in a DOT export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMetric {
+    /// How many inputs the node matched; nodes without a count are left
+    /// unfilled.
+    MatchCount,
+    /// The specificity of the node's pattern; unscored nodes are left
+    /// unfilled.
+    Specificity,
+    /// The depth of the node.
+    Depth,
+}
+
+/// The label format and coloring of a DOT export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStyle {
+    /// When set, patterns longer than this many characters are cut short
+    /// with an ellipsis in labels.
+    pub max_pattern_chars: Option<usize>,
+    /// Whether to show the specificity of each node's pattern, when scored.
+    pub specificity: bool,
+    /// When set, nodes are filled with the color of the bucket their value
+    /// of the metric falls in.
+    pub color_by: Option<ColorMetric>,
+    /// The fill colors of the buckets, lowest values first. The range of
+    /// the metric across the tree is split evenly between them.
+    pub palette: Vec<String>,
+}
+
+impl Default for GraphStyle {
+    fn default() -> Self {
+        GraphStyle {
+            max_pattern_chars: None,
+            specificity: false,
+            color_by: None,
+            palette: DEFAULT_PALETTE.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+/// The metric nodes are colored by, with its range across the tree.
+struct Coloring<'a> {
+    metric: ColorMetric,
+    palette: &'a [String],
+    min: f64,
+    max: f64,
+}
+
+impl Coloring<'_> {
+    /// Returns the fill color of a node, if it has a value of the metric.
+    fn color(&self, node: &Node, depth: usize) -> Option<&str> {
+        let value = metric(self.metric, node, depth)?;
+        let buckets = self.palette.len();
+        let position = match self.max > self.min {
+            true => (value - self.min) / (self.max - self.min),
+            false => 0.0,
+        };
+        let bucket = ((position * buckets as f64) as usize).min(buckets.checked_sub(1)?);
+        Some(&self.palette[bucket])
+    }
+}
+
+/// Returns the value of `metric` for a node at `depth`, if known.
+fn metric(metric: ColorMetric, node: &Node, depth: usize) -> Option<f64> {
+    match metric {
+        ColorMetric::MatchCount => node.match_count.map(|count| count as f64),
+        ColorMetric::Specificity => node.score.map(|score| score.specificity()),
+        ColorMetric::Depth => Some(depth as f64),
+    }
+}
+
+/// Options controlling what a DOT export shows.
+///
+/// Node labels always start with the pattern; the other lines are opt-in.
+#[derive(Debug, Clone, Default)]
+pub struct DotOptions {
+    /// Whether to show how many inputs each node matched, when known.
+    pub match_count: bool,
+    /// Whether to show the depth of each node, the root being `0`.
+    pub depth: bool,
+    /// Whether to show the example inputs attached to each node.
+    pub examples: bool,
+    /// How edges are drawn.
+    pub edge_style: EdgeStyle,
+    /// How labels are formatted and nodes colored.
+    pub style: GraphStyle,
+    /// When set, the nodes added or changed since the previous tree get a
+    /// dashed green or orange border, and the nodes removed since are drawn
+    /// with a dashed red border under their previous parent, or the root.
+    pub diff: Option<TreeDiff>,
+}
+
+/// Serializes a tree to the Graphviz DOT language with the default options,
+/// which label each node with its pattern only.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// The DOT source, nodes being numbered in pre-order from the root at `0`.
+pub fn export_dot(root: &Node) -> String {
+    export_dot_with_options(root, &DotOptions::default())
+}
+
+/// Serializes a tree to the Graphviz DOT language.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `options` - What node labels show and how edges are drawn.
+///
+/// # Returns
+///
+/// The DOT source, nodes being numbered in pre-order from the root at `0`.
+pub fn export_dot_with_options(root: &Node, options: &DotOptions) -> String {
+    let mut dot = String::from("digraph {\n");
+    let mut edges = String::new();
+    let mut next = 0;
+    let coloring = options.style.color_by.map(|metric| {
+        let mut values = vec![];
+        let mut stack = vec![(root, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            values.extend(self::metric(metric, node, depth));
+            stack.extend(node.children.iter().map(|child| (&**child, depth + 1)));
+        }
+        Coloring {
+            metric,
+            palette: &options.style.palette,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    });
+    let writer = Writer {
+        options,
+        coloring: coloring.as_ref(),
+    };
+    writer.write_node(root, 0, &mut next, &mut dot, &mut edges);
+    for removed in options.diff.iter().flat_map(|diff| diff.removed()) {
+        let id = next;
+        next += 1;
+        writeln!(
+            dot,
+            "    {id} [ label = \"{}\", style = dashed, color = \"{}\" ]",
+            escape(removed.pattern()),
+            REMOVED_COLOR
+        )
+        .unwrap();
+        writeln!(
+            edges,
+            "    {} -> {id} [ style = dashed ]",
+            removed.parent().unwrap_or(0)
+        )
+        .unwrap();
+    }
+    dot.push_str(&edges);
+    dot.push_str("}\n");
+    dot
+}
+
+/// Writes a tree to `path` in the Graphviz DOT language with the default
+/// options, ready for `dot -Tpng`.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `path` - The file to create or overwrite.
+///
+/// # Returns
+///
+/// An error if the file cannot be written.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_dot(root: &Node, path: impl AsRef<Path>) -> io::Result<()> {
+    std::fs::write(path, export_dot(root))
+}
+
+/// The settings a DOT export is written with.
+struct Writer<'a> {
+    options: &'a DotOptions,
+    coloring: Option<&'a Coloring<'a>>,
+}
+
+impl Writer<'_> {
+    /// Appends the statement of `node` and the edges to its children, then
+    /// recurses, returning the node's identifier.
+    fn write_node(
+        &self,
+        node: &Node,
+        depth: usize,
+        next: &mut usize,
+        dot: &mut String,
+        edges: &mut String,
+    ) -> usize {
+        let (options, style) = (self.options, &self.options.style);
+        let id = *next;
+        *next += 1;
+
+        let mut label = match style.max_pattern_chars {
+            Some(max) if node.pattern.chars().count() > max => {
+                let cut: String = node.pattern.chars().take(max).collect();
+                escape(&format!("{cut}…"))
+            }
+            _ => escape(&node.pattern),
+        };
+        if options.match_count {
+            if let Some(count) = node.match_count {
+                write!(label, "\\nmatches: {count}").unwrap();
+            }
+        }
+        if options.depth {
+            write!(label, "\\ndepth: {depth}").unwrap();
+        }
+        if style.specificity {
+            if let Some(score) = node.score {
+                write!(label, "\\nspecificity: {:.2}", score.specificity()).unwrap();
+            }
+        }
+        if options.examples {
+            for example in &node.examples {
+                write!(label, "\\ne.g. {}", escape(&format!("{example:?}"))).unwrap();
+            }
+        }
+        let fill = self.coloring.and_then(|c| c.color(node, depth));
+        let status = options.diff.as_ref().map(|diff| diff.status(id));
+        let change = status.and_then(border_color);
+        write!(dot, "    {id} [ label = \"{label}\"").unwrap();
+        match (fill, change) {
+            (Some(_), Some(_)) => dot.push_str(", style = \"filled,dashed\""),
+            (Some(_), None) => dot.push_str(", style = filled"),
+            (None, Some(_)) => dot.push_str(", style = dashed"),
+            (None, None) => {}
+        }
+        if let Some(color) = fill {
+            write!(dot, ", fillcolor = \"{}\"", escape(color)).unwrap();
+        }
+        if let Some(color) = change {
+            write!(dot, ", color = \"{color}\"").unwrap();
+        }
+        dot.push_str(" ]\n");
+
+        for child in &node.children {
+            let child_id = self.write_node(child, depth + 1, next, dot, edges);
+            write!(edges, "    {id} -> {child_id}").unwrap();
+            match options.edge_style {
+                EdgeStyle::Solid => edges.push('\n'),
+                style => writeln!(edges, " [ style = {} ]", style.name()).unwrap(),
+            }
+        }
+        id
+    }
+}
+
+/// The border color of the nodes removed since the previous tree.
+const REMOVED_COLOR: &str = "#d62728";
+
+/// Returns the border color marking a node with `status`, if it changed.
+fn border_color(status: NodeStatus) -> Option<&'static str> {
+    match status {
+        NodeStatus::Unchanged => None,
+        NodeStatus::Added => Some("#2ca02c"),
+        NodeStatus::Changed => Some("#ff7f0e"),
+        NodeStatus::Removed => Some(REMOVED_COLOR),
+    }
+}
+
+/// Escapes `text` for a double-quoted DOT string.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_regex_tree_with_options, GenerateOptions};
+
+    #[test]
+    fn test_export_dot() {
+        let inputs = ["disk \"sda\" full", "disk \"sdb\" full"];
+        let options = GenerateOptions {
+            examples_per_node: 1,
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+
+        let dot = export_dot(&tree);
+        assert!(dot.starts_with("digraph {\n    0 [ label = \".*\" ]\n"));
+        assert!(dot.contains("0 -> 1\n"));
+        assert_eq!(dot.matches(" -> ").count(), tree.size() - 1);
+
+        let options = DotOptions {
+            depth: true,
+            examples: true,
+            edge_style: EdgeStyle::Dashed,
+            ..DotOptions::default()
+        };
+        let dot = export_dot_with_options(&tree, &options);
+        assert!(
+            dot.contains(r#"\ndepth: 1\ne.g. \"disk \\\"sda\\\" full\""#),
+            "{dot}"
+        );
+        assert!(dot.contains("0 -> 1 [ style = dashed ]"));
+
+        let options = DotOptions {
+            style: GraphStyle {
+                max_pattern_chars: Some(4),
+                specificity: true,
+                color_by: Some(ColorMetric::Depth),
+                palette: vec!["white".to_string(), "red".to_string()],
+            },
+            ..DotOptions::default()
+        };
+        let dot = export_dot_with_options(&tree, &options);
+        assert!(
+            dot.contains(
+                r#"0 [ label = ".*\nspecificity: 0.00", style = filled, fillcolor = "white" ]"#
+            ),
+            "{dot}"
+        );
+        assert!(dot.contains(r#"[ label = "^dis…\nspecificity: "#), "{dot}");
+        assert!(dot.contains(r#"fillcolor = "red""#));
+
+        let path = std::env::temp_dir().join("regex_generator_test_export_dot.dot");
+        write_dot(&tree, &path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), export_dot(&tree));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{contains_language, NamedPattern, Node, Sampler, TreeError};
+use regex::Regex;
+use regex_syntax::ast::{self, Ast, GroupKind};
+use std::collections::HashMap;
+
+/// How many strings are drawn from a part of a pattern to quickly rule
+/// out the entries not matching it.
+const SAMPLES: usize = 16;
+
+/// The well-known Grok patterns of [`GrokDictionary::new`], by priority,
+/// with the regex fragments they are recognised by.
+const STANDARD: [(&str, &str); 10] = [
+    (
+        "TIMESTAMP_ISO8601",
+        r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?",
+    ),
+    ("IPV4", r"\d{1,3}(?:\.\d{1,3}){3}"),
+    (
+        "UUID",
+        r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+    ),
+    ("TIME", r"\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?"),
+    ("INT", r"[+-]?\d+"),
+    ("NUMBER", r"[+-]?(?:\d+(?:\.\d*)?|\.\d+)"),
+    ("BASE16NUM", r"(?:0[xX])?[0-9a-fA-F]+"),
+    ("WORD", r"\w+"),
+    ("NOTSPACE", r"\S+"),
+    ("GREEDYDATA", r".*"),
+];
+
+/// The named patterns a Grok template is written with, such as
+/// `TIMESTAMP_ISO8601` or `IPV4`, each with the regex fragment recognising
+/// the parts of a pattern it stands for.
+///
+/// Entries are tried by priority, so composite patterns like timestamps
+/// come before the numbers they are made of, and catch-alls last.
+#[derive(Debug, Clone)]
+pub struct GrokDictionary {
+    entries: Vec<NamedPattern>,
+}
+
+impl GrokDictionary {
+    /// Creates a dictionary of well-known Grok patterns: timestamps, IPv4
+    /// addresses, UUIDs, numbers, words, `NOTSPACE` and `GREEDYDATA`.
+    pub fn new() -> Self {
+        GrokDictionary {
+            entries: STANDARD
+                .iter()
+                .map(|&(name, pattern)| NamedPattern::new(name, pattern))
+                .collect(),
+        }
+    }
+
+    /// Returns the entries of the dictionary, by priority.
+    pub fn entries(&self) -> &[NamedPattern] {
+        &self.entries
+    }
+
+    /// Adds a named pattern, such as a custom pattern defined in the log
+    /// pipeline, tried before the existing entries and replacing any entry
+    /// with the same name.
+    pub fn insert(&mut self, name: &str, pattern: &str) {
+        self.entries.retain(|e| e.name() != name);
+        self.entries.insert(0, NamedPattern::new(name, pattern));
+    }
+
+    /// Removes the entry called `name`, so it is never emitted.
+    ///
+    /// # Returns
+    ///
+    /// `false` if no entry is called `name`.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|e| e.name() != name);
+        self.entries.len() < len
+    }
+
+    /// Returns whether the entry at `index` matches every string `fragment`
+    /// matches, as a whole.
+    fn covers(&self, index: usize, fragment: &str) -> bool {
+        let entry = format!("^(?:{})$", self.entries[index].pattern());
+        let fragment = format!("^(?:{fragment})$");
+        // Sampling rejects most entries long before automata would.
+        let Ok(regex) = Regex::new(&entry) else {
+            return false;
+        };
+        let samples = Sampler::new(SAMPLES).draw(&fragment).unwrap_or_default();
+        samples.iter().all(|s| regex.is_match(s))
+            && contains_language(&entry, &fragment).unwrap_or(false)
+    }
+}
+
+impl Default for GrokDictionary {
+    fn default() -> Self {
+        GrokDictionary::new()
+    }
+}
+
+impl Node {
+    /// Writes the node's pattern as a Grok template for Logstash, Vector
+    /// and other log pipelines, with the well-known patterns of
+    /// [`GrokDictionary::new`], as in
+    /// `^%{TIMESTAMP_ISO8601:timestamp_iso8601} %{WORD:word} %{GREEDYDATA:greedydata}$`.
+    ///
+    /// See [`Node::to_grok_with_dictionary`].
+    ///
+    /// # Returns
+    ///
+    /// The template, or an error if the pattern does not parse.
+    pub fn to_grok(&self) -> Result<String, TreeError> {
+        self.to_grok_with_dictionary(&GrokDictionary::new())
+    }
+
+    /// Writes the node's pattern as a Grok template, replacing its variable
+    /// parts, the classes, repetitions, wildcards and groups between its
+    /// literals, with the named patterns of `dictionary` matching all they
+    /// match.
+    ///
+    /// At each variable part, the first entry by priority matching a run of
+    /// parts starting there is used, over the longest such run, so an IPv4
+    /// address spans its four numbers; a run never swallows a part that an
+    /// entry of higher priority would name on its own. Fields are named
+    /// after their named capture group, or else after their pattern, in
+    /// lowercase and numbered from the second on. Parts no entry matches
+    /// are kept as regex, which Grok accepts.
+    ///
+    /// # Arguments
+    ///
+    /// * `dictionary` - The named patterns to write the template with.
+    ///
+    /// # Returns
+    ///
+    /// The template, or an error if the pattern does not parse.
+    pub fn to_grok_with_dictionary(
+        &self,
+        dictionary: &GrokDictionary,
+    ) -> Result<String, TreeError> {
+        let ast = ast::parse::Parser::new()
+            .parse(&self.pattern)
+            .map_err(|e| TreeError::InvalidPattern {
+                pattern: self.pattern.to_string(),
+                message: e.to_string(),
+            })?;
+        let parts: Vec<&Ast> = match &ast {
+            Ast::Concat(concat) => concat.asts.iter().collect(),
+            ast => vec![ast],
+        };
+        let text =
+            |from: &Ast, to: &Ast| &self.pattern[from.span().start.offset..to.span().end.offset];
+        let variable = |part: &Ast| {
+            !matches!(
+                part,
+                Ast::Empty(_) | Ast::Flags(_) | Ast::Literal(_) | Ast::Assertion(_)
+            )
+        };
+        // The first entry matching each variable part on its own.
+        let alone: Vec<Option<usize>> = parts
+            .iter()
+            .map(|&part| {
+                let fragment = text(part, part);
+                variable(part)
+                    .then(|| {
+                        (0..dictionary.entries.len()).find(|&e| dictionary.covers(e, fragment))
+                    })
+                    .flatten()
+            })
+            .collect();
+
+        let mut template = String::with_capacity(self.pattern.len());
+        let mut used: HashMap<String, usize> = HashMap::new();
+        let mut i = 0;
+        while i < parts.len() {
+            let field = variable(parts[i])
+                .then(|| {
+                    let last = alone[i].unwrap_or(dictionary.entries.len() - 1);
+                    (0..=last).find_map(|entry| {
+                        // The parts the run may extend over.
+                        let end = (i + 1..parts.len())
+                            .find(|&k| alone[k].is_some_and(|other| other < entry))
+                            .unwrap_or(parts.len());
+                        (i..end)
+                            .rev()
+                            .filter(|&j| variable(parts[j]))
+                            .find(|&j| dictionary.covers(entry, text(parts[i], parts[j])))
+                            .map(|j| (entry, j))
+                    })
+                })
+                .flatten();
+            let Some((entry, j)) = field else {
+                template.push_str(text(parts[i], parts[i]));
+                i += 1;
+                continue;
+            };
+            let name = dictionary.entries[entry].name();
+            let field = match parts[i] {
+                Ast::Group(group) if i == j => match &group.kind {
+                    GroupKind::CaptureName { name, .. } => Some(name.name.clone()),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let field = field.unwrap_or_else(|| {
+                let count = used.entry(name.to_string()).or_default();
+                *count += 1;
+                match *count {
+                    1 => name.to_lowercase(),
+                    n => format!("{}{n}", name.to_lowercase()),
+                }
+            });
+            template.push_str(&format!("%{{{name}:{field}}}"));
+            i = j + 1;
+        }
+        Ok(template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_grok() {
+        let node = Node::new(
+            r"^(?P<ts>\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}) \w+ client=\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3} took \d+ms, \d+ retries: .*$"
+                .to_string(),
+        );
+        assert_eq!(
+            node.to_grok().unwrap(),
+            r"^%{TIMESTAMP_ISO8601:ts} %{WORD:word} client=%{IPV4:ipv4} took %{INT:int}ms, %{INT:int2} retries: %{GREEDYDATA:greedydata}$"
+        );
+
+        let node = Node::new(r"^order [A-Z]{3}\d{4} (?:shipped|lost)$".to_string());
+        let mut dictionary = GrokDictionary::new();
+        dictionary.insert("ORDER_ID", r"[A-Z]{3}\d{4}");
+        assert_eq!(
+            node.to_grok_with_dictionary(&dictionary).unwrap(),
+            r"^order %{ORDER_ID:order_id} %{WORD:word}$"
+        );
+        for name in ["WORD", "NOTSPACE", "GREEDYDATA"] {
+            assert!(dictionary.remove(name));
+        }
+        assert_eq!(
+            node.to_grok_with_dictionary(&dictionary).unwrap(),
+            r"^order %{ORDER_ID:order_id} (?:shipped|lost)$"
+        );
+        assert!(Node::new("(".to_string()).to_grok().is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::discriminative::distinct_substrings;
+use crate::ContainmentMatrix;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Which inputs contain which of the best supported candidate substrings,
+/// exported as a CSV matrix or an HTML heatmap so analysts can see why a
+/// candidate was or was not selected: only those every input contains are
+/// mined into patterns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportHeatmap {
+    inputs: Vec<String>,
+    matrix: ContainmentMatrix,
+}
+
+impl SupportHeatmap {
+    /// Picks the `top` candidate substrings contained in the most inputs,
+    /// longer ones first on ties, and records which inputs contain them.
+    ///
+    /// A candidate is left out when a longer one containing it occurs in
+    /// exactly as many inputs, so the columns are not filled with the
+    /// pieces of one substring.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The inputs, one row each.
+    /// * `top` - The most candidates kept, one column each.
+    ///
+    /// # Returns
+    ///
+    /// The heatmap.
+    pub fn new(inputs: &[&str], top: usize) -> Self {
+        let mut support: HashMap<&str, usize> = HashMap::new();
+        for input in inputs {
+            for substring in distinct_substrings(input) {
+                *support.entry(substring).or_default() += 1;
+            }
+        }
+        let mut ranked: Vec<(&str, usize)> = support.into_iter().collect();
+        ranked.sort_unstable_by(|a, b| {
+            (b.1, b.0.len())
+                .cmp(&(a.1, a.0.len()))
+                .then_with(|| a.0.cmp(b.0))
+        });
+
+        let mut kept: Vec<(&str, usize)> = vec![];
+        for (candidate, count) in ranked {
+            if kept.len() == top {
+                break;
+            }
+            let redundant = kept
+                .iter()
+                .any(|&(other, other_count)| other_count == count && other.contains(candidate));
+            if !redundant {
+                kept.push((candidate, count));
+            }
+        }
+        let candidates: Vec<&str> = kept.iter().map(|&(candidate, _)| candidate).collect();
+        SupportHeatmap {
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            matrix: ContainmentMatrix::new(&candidates, inputs),
+        }
+    }
+
+    /// Returns the inputs, in row order.
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// Returns which inputs contain which candidates, the candidates being
+    /// in column order.
+    pub fn matrix(&self) -> &ContainmentMatrix {
+        &self.matrix
+    }
+
+    /// Serializes the heatmap as CSV: a header row naming the candidates,
+    /// then one row per input holding `1` under the candidates it contains
+    /// and `0` under the others.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("input");
+        for candidate in self.matrix.candidates() {
+            write!(csv, ",{}", csv_field(candidate)).unwrap();
+        }
+        csv.push('\n');
+        for (row, input) in self.inputs.iter().enumerate() {
+            csv.push_str(&csv_field(input));
+            for column in 0..self.matrix.candidates().len() {
+                let cell = if self.matrix.contains(column, row) {
+                    1
+                } else {
+                    0
+                };
+                write!(csv, ",{cell}").unwrap();
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Serializes the heatmap as a standalone HTML page holding a table
+    /// with one row per input and one column per candidate, headed by how
+    /// many inputs contain it, where the cells of the candidates an input
+    /// contains are filled.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n\
+             table { border-collapse: collapse; font-family: monospace; }\n\
+             th, td { border: 1px solid #ccc; padding: 2px 6px; white-space: pre; }\n\
+             td.present { background: #2171b5; }\n\
+             </style>\n</head>\n<body>\n<table>\n<tr><th>input</th>",
+        );
+        let total = self.inputs.len();
+        for (column, candidate) in self.matrix.candidates().iter().enumerate() {
+            let support = self.matrix.support(column);
+            write!(
+                html,
+                "<th>{}<br>{support}/{total}</th>",
+                html_escape(candidate)
+            )
+            .unwrap();
+        }
+        html.push_str("</tr>\n");
+        for (row, input) in self.inputs.iter().enumerate() {
+            write!(html, "<tr><th>{}</th>", html_escape(input)).unwrap();
+            for column in 0..self.matrix.candidates().len() {
+                html.push_str(match self.matrix.contains(column, row) {
+                    true => "<td class=\"present\"></td>",
+                    false => "<td></td>",
+                });
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+/// Quotes `text` as a CSV field when it holds a separator, quote or line
+/// break.
+fn csv_field(text: &str) -> String {
+    match text.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_string(),
+    }
+}
+
+/// Escapes `text` for HTML element content.
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_support_heatmap() {
+        let inputs = ["GET /a", "GET /b", "POST /a"];
+        let heatmap = SupportHeatmap::new(&inputs, 3);
+        assert_eq!(heatmap.matrix().candidates(), ["T /", "GET /", "T /a"]);
+        assert_eq!(
+            heatmap.to_csv(),
+            "input,T /,GET /,T /a\nGET /a,1,1,1\nGET /b,1,1,0\nPOST /a,1,0,1\n"
+        );
+
+        let heatmap = SupportHeatmap::new(&["a<b", "a,\"b\""], 1);
+        assert_eq!(heatmap.to_csv(), "input,a\na<b,1\n\"a,\"\"b\"\"\",1\n");
+        let html = heatmap.to_html();
+        assert!(html.contains("<th>a<br>2/2</th>"));
+        assert!(html.contains("<tr><th>a&lt;b</th><td class=\"present\"></td></tr>"));
+    }
+}
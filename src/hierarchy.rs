@@ -0,0 +1,133 @@
+use crate::template::tokenize;
+use crate::{
+    generate_template, generate_template_with_dictionary, DetectorDictionary, FieldSchema,
+    GenerateError, Node, Segment,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Generates a regex pattern tree by alternating template generation and
+/// specialization.
+///
+/// The inputs are grouped by token count and a coarse template is
+/// generated for each group. Its inputs are then bucketed by the values of
+/// the variable token with the fewest distinct values shared by several
+/// inputs, such as a log level, and a template is generated within each
+/// bucket of at least two inputs, recursively. Each level therefore pins
+/// down one more token than its parent, giving a hierarchy of templates
+/// rather than a flat list of substrings.
+///
+/// # Arguments
+///
+/// * `strings` - The inputs.
+/// * `max_depth` - The maximum number of specialization levels below the
+///   coarse templates.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree, whose children are the coarse
+/// templates, or an error if there are no inputs.
+pub fn generate_template_tree(strings: &[&str], max_depth: usize) -> Result<Node, GenerateError> {
+    template_tree(strings, max_depth, None)
+}
+
+/// Generates a template tree like [`generate_template_tree`], describing
+/// fields with the named sub-patterns of `dictionary` when given.
+pub(crate) fn template_tree(
+    strings: &[&str],
+    max_depth: usize,
+    mut dictionary: Option<&mut DetectorDictionary>,
+) -> Result<Node, GenerateError> {
+    if strings.is_empty() {
+        return Err(GenerateError::EmptyInput);
+    }
+    let mut by_width: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    for s in strings {
+        by_width.entry(tokenize(s).len()).or_default().push(s);
+    }
+
+    let mut root = Node::new(".*".to_string());
+    for group in by_width.into_values() {
+        let node = specialize(&group, max_depth, dictionary.as_deref_mut())?;
+        root.children.push(Arc::new(node));
+    }
+    Ok(root)
+}
+
+/// Returns the template node of `strings`, which share a token count, with
+/// a child per bucket of the most selective shared token.
+fn specialize(
+    strings: &[&str],
+    depth: usize,
+    mut dictionary: Option<&mut DetectorDictionary>,
+) -> Result<Node, GenerateError> {
+    let template = match dictionary.as_deref_mut() {
+        Some(dictionary) => {
+            generate_template_with_dictionary(strings, &FieldSchema::new(), dictionary)?
+        }
+        None => generate_template(strings, &FieldSchema::new())?,
+    };
+    let mut node = Node::new(template.to_regex());
+    node.match_count = Some(strings.len());
+    if depth == 0 {
+        return Ok(node);
+    }
+
+    let rows: Vec<Vec<&str>> = strings.iter().map(|s| tokenize(s)).collect();
+    let mut best: Option<BTreeMap<&str, Vec<&str>>> = None;
+    for (position, segment) in template.segments().iter().enumerate() {
+        if matches!(segment, Segment::Literal(_)) {
+            continue;
+        }
+        let mut buckets: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (row, s) in rows.iter().zip(strings) {
+            buckets.entry(row[position]).or_default().push(s);
+        }
+        let selective = buckets.len() > 1 && buckets.len() < strings.len();
+        if selective && best.as_ref().is_none_or(|b| buckets.len() < b.len()) {
+            best = Some(buckets);
+        }
+    }
+
+    for bucket in best.into_iter().flat_map(BTreeMap::into_values) {
+        if bucket.len() > 1 {
+            node.children.push(Arc::new(specialize(
+                &bucket,
+                depth - 1,
+                dictionary.as_deref_mut(),
+            )?));
+        }
+    }
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_generate_template_tree() {
+        let inputs = vec![
+            "ERROR disk sda full",
+            "ERROR disk sdb full",
+            "WARN disk sda slow",
+            "WARN disk sdc slow",
+            "INFO boot took 3 s",
+        ];
+        let tree = generate_template_tree(&inputs, 2).unwrap();
+        assert_eq!(tree.children.len(), 2);
+
+        let coarse = &tree.children[0];
+        assert_eq!(coarse.match_count(), Some(4));
+        let levels: Vec<&str> = coarse.children.iter().map(|n| n.pattern.as_str()).collect();
+        assert_eq!(levels.len(), 2);
+        assert!(levels[0].starts_with(r"^ERROR\s+disk\s+"));
+        assert!(levels[1].starts_with(r"^WARN\s+disk\s+"));
+        for node in tree.preorder() {
+            let regex = Regex::new(&node.pattern).unwrap();
+            let matched = inputs.iter().filter(|s| regex.is_match(s)).count();
+            assert_eq!(node.match_count.unwrap_or(inputs.len()), matched);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::bloom::NgramFilter;
+use std::collections::HashMap;
+
+/// Document frequencies of substrings in a background corpus.
+///
+/// Frequencies are either looked up in a precomputed table or counted on
+/// demand against the stored background documents.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentFrequencies {
+    documents: Vec<String>,
+    filters: Vec<NgramFilter>,
+    counts: HashMap<String, usize>,
+    total: usize,
+}
+
+impl DocumentFrequencies {
+    /// Describes the background deterministically, for hashing.
+    pub(crate) fn fingerprint(&self) -> String {
+        let mut counts: Vec<(&String, &usize)> = self.counts.iter().collect();
+        counts.sort();
+        format!("{:?} {counts:?} {}", self.documents, self.total)
+    }
+
+    /// Creates document frequencies backed by a background corpus.
+    ///
+    /// # Arguments
+    ///
+    /// * `corpus` - The background documents, typically general text.
+    ///
+    /// # Returns
+    ///
+    /// Document frequencies counted against `corpus`.
+    pub fn from_corpus(corpus: &[&str]) -> Self {
+        DocumentFrequencies {
+            documents: corpus.iter().map(|s| s.to_string()).collect(),
+            filters: vec![],
+            counts: HashMap::new(),
+            total: corpus.len(),
+        }
+    }
+
+    /// Creates document frequencies from a precomputed table.
+    ///
+    /// # Arguments
+    ///
+    /// * `counts` - The number of documents containing each substring.
+    /// * `total` - The total number of documents the counts were taken over.
+    ///
+    /// # Returns
+    ///
+    /// Document frequencies answering from `counts`; unknown substrings have
+    /// a frequency of zero.
+    pub fn from_counts(counts: HashMap<String, usize>, total: usize) -> Self {
+        DocumentFrequencies {
+            documents: vec![],
+            filters: vec![],
+            counts,
+            total,
+        }
+    }
+
+    /// Builds an n-gram Bloom filter per background document, so counting
+    /// skips the documents that certainly do not contain a substring.
+    ///
+    /// Worth it for large corpora queried many times; the filters take
+    /// about `-ln(rate) / ln(2)^2` bits per byte of text.
+    ///
+    /// # Arguments
+    ///
+    /// * `false_positive_rate` - The probability that a filter fails to
+    ///   rule out a single absent n-gram.
+    pub fn with_prefilter(mut self, false_positive_rate: f64) -> Self {
+        self.filters = self
+            .documents
+            .iter()
+            .map(|d| NgramFilter::new(d, false_positive_rate))
+            .collect();
+        self
+    }
+
+    /// Returns the number of background documents containing `substring`.
+    pub fn document_frequency(&self, substring: &str) -> usize {
+        if let Some(&count) = self.counts.get(substring) {
+            return count;
+        }
+        if self.filters.is_empty() {
+            return self
+                .documents
+                .iter()
+                .filter(|d| d.contains(substring))
+                .count();
+        }
+        self.documents
+            .iter()
+            .zip(&self.filters)
+            .filter(|(d, filter)| filter.may_contain(substring) && d.contains(substring))
+            .count()
+    }
+
+    /// Returns the smoothed inverse document frequency of `substring`.
+    ///
+    /// Substrings found in every background document get a weight close to
+    /// one, substrings never seen get the highest weight.
+    pub fn idf(&self, substring: &str) -> f64 {
+        let df = self.document_frequency(substring) as f64;
+        ((self.total as f64 + 1.0) / (df + 1.0)).ln() + 1.0
+    }
+}
+
+/// Reorders candidate substrings by length weighted with their IDF.
+///
+/// # Arguments
+///
+/// * `substrings` - The candidate substrings to reorder in place.
+/// * `frequencies` - The background document frequencies.
+pub(crate) fn rank_by_idf(substrings: &mut [&str], frequencies: &DocumentFrequencies) {
+    let mut scored: Vec<(f64, &str)> = substrings
+        .iter()
+        .map(|&s| (s.len() as f64 * frequencies.idf(s), s))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (slot, (_, s)) in substrings.iter_mut().zip(scored) {
+        *slot = s;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idf_down_weights_common_substrings() {
+        let frequencies =
+            DocumentFrequencies::from_corpus(&["the cat", "the dog", "then", "other"]);
+        assert!(frequencies.idf("the") < frequencies.idf("cat"));
+        let filtered = frequencies.clone().with_prefilter(0.01);
+        assert_eq!(filtered.document_frequency("the "), 2);
+        assert_eq!(filtered.document_frequency("other"), 1);
+
+        let mut counts = HashMap::new();
+        counts.insert("tion".to_string(), 90);
+        let precomputed = DocumentFrequencies::from_counts(counts, 100);
+        assert_eq!(precomputed.document_frequency("tion"), 90);
+        assert_eq!(precomputed.document_frequency("zzz"), 0);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{build_tree_with_roles, exact_tree, find_common_substrings, Node, Role};
+
+/// A candidate substring and where it occurs in the examples seen so far.
+#[derive(Debug, Clone)]
+struct Candidate {
+    literal: String,
+    /// Whether every example is the literal, starts with it, and ends with
+    /// it, respectively.
+    anchors: [bool; 3],
+}
+
+impl Candidate {
+    fn role(&self) -> Role {
+        match self.anchors {
+            [true, _, _] => Role::Full,
+            [_, true, _] => Role::Prefix,
+            [_, _, true] => Role::Suffix,
+            _ => Role::Infix,
+        }
+    }
+}
+
+/// Maintains the tree [`crate::generate_regex_tree`] would generate from
+/// the examples of a live stream, without keeping or re-mining them.
+///
+/// The substrings common to all examples can only narrow as examples
+/// arrive: they are mined once, from the first two distinct examples, and
+/// each later example merely drops the candidates it does not contain and
+/// may float the anchors of others.
+/// The tree is only rebuilt, from the remaining candidates, when that
+/// changes a pattern, so examples matching the established structure,
+/// typically most of a steady stream, cost one scan of the candidates.
+#[derive(Debug, Clone)]
+pub struct IncrementalGenerator {
+    candidates: Vec<Candidate>,
+    // The example every one seen so far is, before candidates are mined:
+    // every substring of a single example would be one.
+    first: Option<String>,
+    tree: Node,
+    seen: usize,
+}
+
+impl IncrementalGenerator {
+    /// Creates a generator that has seen no example.
+    pub fn new() -> Self {
+        IncrementalGenerator {
+            candidates: vec![],
+            first: None,
+            tree: Node::new(String::new()),
+            seen: 0,
+        }
+    }
+
+    /// Adds an example, updating the tree if it invalidates some pattern.
+    pub fn push(&mut self, example: &str) {
+        self.seen += 1;
+        if self.seen == 1 {
+            self.tree = exact_tree(example);
+            self.first = Some(example.to_string());
+            return;
+        }
+        if let Some(first) = self.first.take() {
+            if first == example {
+                self.first = Some(first);
+                return;
+            }
+            let pair = [first.as_str(), example];
+            self.candidates = find_common_substrings(&pair)
+                .into_iter()
+                .map(|literal| Candidate {
+                    literal: literal.to_string(),
+                    anchors: [
+                        false,
+                        pair.iter().all(|e| e.starts_with(literal)),
+                        pair.iter().all(|e| e.ends_with(literal)),
+                    ],
+                })
+                .collect();
+            self.rebuild();
+            return;
+        }
+
+        let before = self.candidates.len();
+        let mut changed = false;
+        self.candidates.retain_mut(|candidate| {
+            if !example.contains(&candidate.literal) {
+                return false;
+            }
+            let role = candidate.role();
+            let literal = candidate.literal.as_str();
+            candidate.anchors[0] &= example == literal;
+            candidate.anchors[1] &= example.starts_with(literal);
+            candidate.anchors[2] &= example.ends_with(literal);
+            changed |= candidate.role() != role;
+            true
+        });
+        if changed || self.candidates.len() != before {
+            self.rebuild();
+        }
+    }
+
+    /// Returns the tree generated from the examples pushed so far.
+    pub fn current_tree(&self) -> &Node {
+        &self.tree
+    }
+
+    /// Returns how many examples were pushed.
+    pub fn len(&self) -> usize {
+        self.seen
+    }
+
+    /// Returns whether no example was pushed.
+    pub fn is_empty(&self) -> bool {
+        self.seen == 0
+    }
+
+    fn rebuild(&mut self) {
+        // A candidate every example is means they are all the same.
+        if let Some(full) = self.candidates.iter().find(|c| c.role() == Role::Full) {
+            self.tree = exact_tree(&full.literal);
+            return;
+        }
+        let literals: Vec<&str> = self.candidates.iter().map(|c| c.literal.as_str()).collect();
+        let roles: Vec<Role> = self.candidates.iter().map(Candidate::role).collect();
+        self.tree = build_tree_with_roles(&literals, &roles);
+    }
+}
+
+impl Default for IncrementalGenerator {
+    fn default() -> Self {
+        IncrementalGenerator::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_incremental_generator() {
+        let stream = [
+            "ERROR disk sda full",
+            "ERROR disk sdb full",
+            "ERROR disk sdb full",
+            "WARN disk sdc full",
+            "disk full",
+        ];
+        let mut generator = IncrementalGenerator::new();
+        assert_eq!(generator.current_tree().pattern, "");
+        for (seen, example) in stream.iter().enumerate() {
+            generator.push(example);
+            let batch = generate_regex_tree(&stream[..=seen]);
+            let patterns = |tree: &Node| -> Vec<String> {
+                tree.preorder()
+                    .iter()
+                    .map(|n| n.pattern.to_string())
+                    .collect()
+            };
+            assert_eq!(
+                patterns(generator.current_tree()),
+                patterns(&batch),
+                "{seen}"
+            );
+        }
+        assert_eq!(generator.len(), stream.len());
+    }
+
+    #[test]
+    fn test_incremental_long_lines() {
+        let line = |seed: u32| {
+            (0..60)
+                .map(|i| {
+                    format!(
+                        "user={:06} latency_ms={} ",
+                        seed * 7919 + i,
+                        seed * 31 % 97 + i
+                    )
+                })
+                .collect::<String>()
+        };
+        let stream = [line(1), line(1), line(2), line(3)];
+        let mut generator = IncrementalGenerator::new();
+        for (seen, example) in stream.iter().enumerate() {
+            generator.push(example);
+            let inputs: Vec<&str> = stream[..=seen].iter().map(String::as_str).collect();
+            let batch = generate_regex_tree(&inputs);
+            assert_eq!(generator.current_tree().size(), batch.size(), "{seen}");
+        }
+        // Candidates are mined from two distinct examples, not from every
+        // substring of the first.
+        assert!(generator.candidates.len() < stream[0].len());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{Node, Pattern};
+use std::collections::VecDeque;
+use std::iter::FusedIterator;
+
+/// An iterator over the nodes of a tree in depth-first pre-order, the order
+/// of node identifiers. Created by [`Node::iter`].
+#[derive(Debug, Clone)]
+pub struct DepthFirst<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<&'a Node> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev().map(|c| &**c));
+        Some(node)
+    }
+}
+
+impl FusedIterator for DepthFirst<'_> {}
+
+/// An iterator over the nodes of a tree level by level, from the most
+/// general to the most specific. Created by [`Node::iter_breadth_first`].
+#[derive(Debug, Clone)]
+pub struct BreadthFirst<'a> {
+    queue: VecDeque<&'a Node>,
+}
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<&'a Node> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.children.iter().map(|c| &**c));
+        Some(node)
+    }
+}
+
+impl FusedIterator for BreadthFirst<'_> {}
+
+impl Node {
+    /// Returns the regex pattern of the node, which dereferences to its
+    /// source and renders in any [`crate::codegen::Flavor`].
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    /// Returns the children of the node, from the best-ranked one.
+    pub fn children(&self) -> impl ExactSizeIterator<Item = &Node> + DoubleEndedIterator {
+        self.children.iter().map(|c| &**c)
+    }
+
+    /// Returns whether the node has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Returns an iterator over the tree rooted at this node in depth-first
+    /// pre-order, so the `n`th node yielded has identifier `n`.
+    pub fn iter(&self) -> DepthFirst<'_> {
+        DepthFirst { stack: vec![self] }
+    }
+
+    /// Returns an iterator over the tree rooted at this node level by
+    /// level.
+    pub fn iter_breadth_first(&self) -> BreadthFirst<'_> {
+        BreadthFirst {
+            queue: VecDeque::from([self]),
+        }
+    }
+
+    /// Returns the depth of the tree rooted at this node: the number of
+    /// edges on the longest path down to a leaf, `0` for a leaf.
+    pub fn depth(&self) -> usize {
+        self.children().map(|c| c.depth() + 1).max().unwrap_or(0)
+    }
+
+    /// Returns the first node in pre-order satisfying `predicate`.
+    pub fn find<P>(&self, mut predicate: P) -> Option<&Node>
+    where
+        P: FnMut(&Node) -> bool,
+    {
+        self.iter().find(|node| predicate(node))
+    }
+}
+
+impl<'a> IntoIterator for &'a Node {
+    type Item = &'a Node;
+    type IntoIter = DepthFirst<'a>;
+
+    fn into_iter(self) -> DepthFirst<'a> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_node_iterators() {
+        let tree = generate_regex_tree(&["disk full on sda", "disk full on sdb"]);
+        let patterns: Vec<&str> = tree.iter().map(|n| n.pattern().as_str()).collect();
+        let preorder: Vec<&str> = tree
+            .preorder()
+            .iter()
+            .map(|n| n.pattern().as_str())
+            .collect();
+        assert_eq!(patterns, preorder);
+        let levels: Vec<&str> = tree
+            .iter_breadth_first()
+            .map(|n| n.pattern().as_str())
+            .collect();
+        let topo: Vec<&str> = tree
+            .topo_order()
+            .iter()
+            .map(|n| n.pattern().as_str())
+            .collect();
+        assert_eq!(levels, topo);
+
+        assert_eq!(tree.pattern(), ".*");
+        assert_eq!(tree.children().len(), tree.children.len());
+        let leaf = tree.find(|n| n.pattern().contains("on sd")).unwrap();
+        assert!(leaf.is_leaf());
+        assert_eq!(leaf.depth(), 0);
+        assert!(tree.depth() > 0);
+        assert_eq!((&tree).into_iter().count(), tree.size());
+        assert!(tree.find(|n| n.pattern() == "absent").is_none());
+    }
+}
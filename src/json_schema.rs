@@ -0,0 +1,138 @@
+use crate::{FieldType, Node, TreeError};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// The JSON Schema dialect the exported schemas declare.
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Describes the fields extracted from inputs by the named capture groups
+/// of a tree, as a JSON Schema of an object keyed by field name, so API
+/// teams can validate downstream consumers against the learned structure.
+///
+/// Each property is typed from the values its group captures in the
+/// examples attached to the nodes, see
+/// [`crate::GenerateOptions::examples_per_node`]: integers and floats
+/// become `integer` and `number`, timestamps and addresses strings with a
+/// `date-time` or `ipv4` format, and enumerations strings with an `enum`.
+/// A field captured by several nodes takes the narrowest type covering
+/// all of them, and is left untyped when no example shows it. Since a
+/// record only holds the fields of the template it matched, none is
+/// required.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// The schema, or an error if a pattern does not compile.
+pub fn export_json_schema(root: &Node) -> Result<Value, TreeError> {
+    let mut fields: BTreeMap<String, Option<FieldType>> = BTreeMap::new();
+    for node in root.preorder() {
+        let regex = Regex::new(&node.pattern).map_err(|e| TreeError::InvalidPattern {
+            pattern: node.pattern.to_string(),
+            message: e.to_string(),
+        })?;
+        let examples: Vec<&str> = node.examples.iter().map(String::as_str).collect();
+        let types: BTreeMap<String, FieldType> =
+            node.capture_types(&examples)?.into_iter().collect();
+        for name in regex.capture_names().flatten() {
+            let observed = types.get(name).cloned();
+            fields
+                .entry(name.to_string())
+                .and_modify(|known| *known = merge(known.take(), observed.clone()))
+                .or_insert(observed);
+        }
+    }
+
+    let properties: Map<String, Value> = fields
+        .into_iter()
+        .map(|(name, field_type)| (name, field_type.map_or(json!({}), type_schema)))
+        .collect();
+    Ok(json!({
+        "$schema": DIALECT,
+        "title": "Record",
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    }))
+}
+
+/// Returns the narrowest type covering both, an unknown type covering
+/// nothing.
+fn merge(known: Option<FieldType>, observed: Option<FieldType>) -> Option<FieldType> {
+    let (Some(known), Some(observed)) = (&known, &observed) else {
+        return known.or(observed);
+    };
+    Some(match (known, observed) {
+        _ if known == observed => observed.clone(),
+        (FieldType::Integer | FieldType::Float, FieldType::Integer | FieldType::Float) => {
+            FieldType::Float
+        }
+        (FieldType::Enum(a), FieldType::Enum(b)) => {
+            let mut values = a.clone();
+            values.extend(b.iter().filter(|v| !a.contains(v)).cloned());
+            values.sort();
+            FieldType::Enum(values)
+        }
+        _ => FieldType::String,
+    })
+}
+
+/// Returns the schema of a value of the given type.
+fn type_schema(field_type: FieldType) -> Value {
+    match field_type {
+        FieldType::Integer => json!({ "type": "integer" }),
+        FieldType::Float => json!({ "type": "number" }),
+        FieldType::Timestamp => json!({ "type": "string", "format": "date-time" }),
+        FieldType::Ip => json!({ "type": "string", "format": "ipv4" }),
+        FieldType::Enum(values) => json!({ "type": "string", "enum": values }),
+        FieldType::String => json!({ "type": "string" }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_json_schema() {
+        let mut login =
+            Node::new(r"^(?P<user>\w+) from (?P<client>\S+) in (?P<ms>\S+)$".to_string());
+        login.examples = vec![
+            "alice from 10.0.0.1 in 12".to_string(),
+            "bob from 10.0.0.2 in 7".to_string(),
+        ];
+        let mut retry =
+            Node::new(r"^(?P<user>\w+) retried in (?P<ms>\S+) \((?P<level>\w+)\)$".to_string());
+        retry.examples = vec![
+            "alice retried in 1.5 (WARN)".to_string(),
+            "carol retried in 2 (WARN)".to_string(),
+        ];
+        let mut root = Node::new(".*".to_string());
+        root.children.push(login.into());
+        root.children.push(retry.into());
+
+        let schema = export_json_schema(&root).unwrap();
+        assert_eq!(schema["type"], "object");
+        let properties = &schema["properties"];
+        assert_eq!(
+            properties["client"],
+            json!({ "type": "string", "format": "ipv4" })
+        );
+        assert_eq!(properties["ms"], json!({ "type": "number" }));
+        assert_eq!(
+            properties["level"],
+            json!({ "type": "string", "enum": ["WARN"] })
+        );
+        assert_eq!(properties["user"], json!({ "type": "string" }));
+
+        root.children
+            .push(Node::new(r"^(?P<code>\d+)$".to_string()).into());
+        assert_eq!(
+            export_json_schema(&root).unwrap()["properties"]["code"],
+            json!({})
+        );
+    }
+}
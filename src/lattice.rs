@@ -0,0 +1,154 @@
+use crate::bitset::{self, ContainmentMatrix};
+use crate::discriminative::distinct_substrings;
+use petgraph::graph::{DiGraph, NodeIndex};
+use std::collections::{BTreeSet, HashSet};
+
+/// A formal concept: a set of inputs together with the substrings they all
+/// share.
+///
+/// The two sides determine each other: no other input contains every
+/// substring of the intent, and no other substring occurs in every input of
+/// the extent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Concept {
+    extent: Vec<usize>,
+    intent: Vec<String>,
+}
+
+impl Concept {
+    /// Returns the indices of the inputs in the concept, in increasing order.
+    pub fn extent(&self) -> &[usize] {
+        &self.extent
+    }
+
+    /// Returns the maximal substrings shared by the inputs of the concept,
+    /// longest first; every other shared substring is contained in one of
+    /// them.
+    pub fn intent(&self) -> &[String] {
+        &self.intent
+    }
+}
+
+/// Builds the concept lattice of the inputs and their substrings.
+///
+/// Where the tree keeps a single chain of specializations per pattern, the
+/// lattice holds every set of inputs that is exactly characterized by the
+/// substrings it shares, ordered by inclusion. It is the complete structure
+/// the tree approximates, and can grow exponentially with the number of
+/// inputs.
+///
+/// # Arguments
+///
+/// * `strings` - A slice of strings to build the lattice from.
+///
+/// # Returns
+///
+/// A directed acyclic graph with an edge from each concept to the concepts
+/// directly below it. The first node is the concept of all inputs; the
+/// concept of no input is left out.
+pub fn concept_lattice(strings: &[&str]) -> DiGraph<Concept, ()> {
+    let mut attributes: Vec<&str> = strings
+        .iter()
+        .flat_map(|s| distinct_substrings(s))
+        .collect::<HashSet<&str>>()
+        .into_iter()
+        .collect();
+    attributes.sort_unstable();
+    let matrix = ContainmentMatrix::new(&attributes, strings);
+
+    let mut all = vec![0u64; strings.len().div_ceil(64)];
+    for input in 0..strings.len() {
+        all[input / 64] |= 1 << (input % 64);
+    }
+    let mut extents: BTreeSet<Vec<u64>> = (0..attributes.len())
+        .map(|a| matrix.row(a).to_vec())
+        .collect();
+    extents.insert(all);
+    extents.retain(|e| bitset::count(e) > 0);
+    loop {
+        let closed: Vec<Vec<u64>> = extents
+            .iter()
+            .flat_map(|a| extents.iter().map(move |b| bitset::intersect(a, b)))
+            .filter(|e| bitset::count(e) > 0 && !extents.contains(e))
+            .collect();
+        if closed.is_empty() {
+            break;
+        }
+        extents.extend(closed);
+    }
+
+    let mut extents: Vec<(Vec<usize>, Vec<u64>)> = extents
+        .into_iter()
+        .map(|bits| (bitset::indices(&bits), bits))
+        .collect();
+    extents.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(&b.0)));
+
+    let mut graph = DiGraph::with_capacity(extents.len(), extents.len());
+    for (extent, bits) in &extents {
+        let intent = maximal_intent(bits, &matrix);
+        graph.add_node(Concept {
+            extent: extent.clone(),
+            intent,
+        });
+    }
+
+    let below = |a: usize, b: usize| a != b && bitset::is_subset(&extents[b].1, &extents[a].1);
+    for general in 0..extents.len() {
+        for specific in general + 1..extents.len() {
+            let covers = below(general, specific)
+                && !(general + 1..specific)
+                    .any(|between| below(general, between) && below(between, specific));
+            if covers {
+                graph.add_edge(NodeIndex::new(general), NodeIndex::new(specific), ());
+            }
+        }
+    }
+    graph
+}
+
+/// Returns the substrings shared by every input of `extent` that are not
+/// part of a longer shared substring, longest first.
+fn maximal_intent(extent: &[u64], matrix: &ContainmentMatrix) -> Vec<String> {
+    let mut shared: Vec<&str> = matrix
+        .candidates()
+        .iter()
+        .enumerate()
+        .filter(|&(a, _)| bitset::is_subset(extent, matrix.row(a)))
+        .map(|(_, substring)| substring.as_str())
+        .collect();
+    shared.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+
+    let mut maximal: Vec<&str> = vec![];
+    for substring in shared {
+        if !maximal.iter().any(|m| m.contains(substring)) {
+            maximal.push(substring);
+        }
+    }
+    maximal.into_iter().map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concept_lattice() {
+        let lattice = concept_lattice(&["disk full", "disk slow", "net slow"]);
+        let extents: Vec<&[usize]> = lattice.node_weights().map(|c| c.extent()).collect();
+        assert_eq!(extents[0], [0, 1, 2]);
+        assert!(extents.contains(&&[0, 1][..]));
+        assert!(extents.contains(&&[1, 2][..]));
+        assert!(extents.contains(&&[1][..]));
+
+        let concept = |extent: &[usize]| {
+            lattice
+                .node_indices()
+                .find(|&i| lattice[i].extent() == extent)
+                .unwrap()
+        };
+        assert_eq!(lattice[concept(&[0, 1])].intent()[0], "disk ");
+        assert_eq!(lattice[concept(&[1, 2])].intent()[0], " slow");
+        assert!(lattice.contains_edge(concept(&[0, 1]), concept(&[1])));
+        assert!(!lattice.contains_edge(concept(&[0, 1, 2]), concept(&[1])));
+    }
+}
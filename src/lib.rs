@@ -1,12 +1,698 @@
-use petgraph::dot::{Config, Dot};
 use petgraph::graph::{DiGraph, NodeIndex};
-use std::collections::HashSet;
+use petgraph::stable_graph::StableDiGraph;
+use petgraph::visit::{Dfs, Walker};
+use petgraph::Direction;
+use privacy::SupportGuard;
+use regex::Regex;
+use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+mod alignment;
+mod alternation;
+mod anchoring;
+mod annotated;
+mod anonymize;
+#[cfg(feature = "arrow")]
+mod arrow;
+mod automaton;
+mod bitset;
+mod bloom;
+mod builder;
+mod cancellation;
+mod capture;
+mod cardinality;
+mod changes;
+mod classes;
+mod clustering;
+pub mod codegen;
+mod compiled;
+mod conflicts;
+mod corpora;
+mod cost;
+mod coverage;
+#[cfg(feature = "polars")]
+mod dataframe;
+mod dedup;
+mod detector;
+mod diagnostics;
+mod dictionary;
+mod discriminative;
+mod distinguish;
+mod equivalence;
+mod error;
+mod evaluation;
+mod examples;
+mod extractor;
+mod field_type;
+mod forest;
+mod gaps;
+mod grapheme;
+mod graphviz;
+mod grok;
+mod heatmap;
+mod hierarchy;
+mod idf;
+mod incremental;
+mod iter;
+#[cfg(feature = "serde")]
+mod json_schema;
+mod lattice;
+mod literal_matcher;
+mod literal_set;
+mod matcher;
+mod model;
+mod negatives;
+mod numeric;
+mod options;
+#[cfg(feature = "serde")]
+mod otel;
+mod outliers;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod pattern;
+mod percentiles;
+mod positions;
+mod posix;
+mod privacy;
+mod profile;
+#[cfg(feature = "protobuf")]
+mod protobuf;
+mod provenance;
+mod readability;
+mod record;
+mod role;
+mod rolling;
+mod rules;
+mod scoring;
+mod secrets;
+#[cfg(feature = "serde")]
+mod serialization;
+mod session;
+mod simplify;
+mod stable_id;
+mod strata;
+#[cfg(feature = "tokio")]
+mod stream;
+mod subsumption;
+mod synthesis;
+mod template;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod tie_break;
+mod tokenizer;
+mod trie;
+mod url;
+mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod weighted;
+mod windows;
+mod witness;
+
+pub use alignment::{align_corpora, AlignmentReport, TemplateChange};
+pub use alternation::AlternationOrder;
+pub use anchoring::Anchoring;
+pub use annotated::{tree_to_annotated_graph, Containment, GraphNode};
+#[cfg(feature = "arrow")]
+pub use arrow::write_match_matrix;
+pub use bitset::ContainmentMatrix;
+pub use builder::RegexTreeBuilder;
+use builder::TreeShape;
+pub use cancellation::CancellationToken;
+pub use capture::{generate_capture_pattern, CapturePattern};
+pub use changes::{NodeStatus, RemovedNode, TreeDiff};
+pub use classes::CharClassDetector;
+pub use clustering::{Clustering, Similarity};
+pub use compiled::{CompiledTree, TreeView};
+pub use conflicts::{Conflict, Refinement};
+pub use corpora::{generate_multi_corpus, MultiCorpus, SourcedTemplate};
+pub use cost::CostEstimate;
+pub use coverage::{cover_inputs, prune_by_coverage, CoveredLeaf};
+#[cfg(feature = "polars")]
+pub use dataframe::extract_to_dataframe;
+pub use dedup::Deduplicator;
+pub use detector::Detector;
+pub use diagnostics::{CandidateFilter, Diagnostic};
+#[cfg(not(target_arch = "wasm32"))]
+pub use dictionary::FileStore;
+pub use dictionary::{DetectorDictionary, DictionaryStore, NamedPattern};
+pub use discriminative::generate_discriminative;
+pub use distinguish::distinguish;
+pub use equivalence::{compare_regexes, Comparison, Sampler};
+#[cfg(any(feature = "arrow", feature = "polars"))]
+pub use error::ExportError;
+#[cfg(feature = "serde")]
+pub use error::LoadError;
+#[cfg(feature = "protobuf")]
+pub use error::ProtobufError;
+pub use error::{
+    CaptureError, FlavorError, GenerateError, GraphError, LanguageError, LayoutError, ModelError,
+    SubstitutionError, TreeError,
+};
+pub use evaluation::{evaluate, EvalReport, NodeEvaluation};
+pub use extractor::Extractor;
+pub use field_type::FieldType;
+pub use forest::{Forest, ForestMatcher};
+pub use gaps::GapStrategy;
+pub use grapheme::GraphemeMode;
+#[cfg(not(target_arch = "wasm32"))]
+pub use graphviz::write_dot;
+pub use graphviz::{
+    export_dot, export_dot_with_options, ColorMetric, DotOptions, EdgeStyle, GraphStyle,
+};
+pub use grok::GrokDictionary;
+pub use heatmap::SupportHeatmap;
+pub use hierarchy::generate_template_tree;
+use idf::rank_by_idf;
+pub use idf::DocumentFrequencies;
+pub use incremental::IncrementalGenerator;
+pub use iter::{BreadthFirst, DepthFirst};
+#[cfg(feature = "serde")]
+pub use json_schema::export_json_schema;
+pub use lattice::{concept_lattice, Concept};
+pub use literal_matcher::LiteralMatcher;
+pub use literal_set::LiteralSet;
+pub use matcher::Matcher;
+pub use model::Model;
+pub use negatives::generate_regex_tree_with_negatives;
+pub use numeric::NumericStrategy;
+pub use options::{GenerateOptions, Tolerance};
+#[cfg(feature = "serde")]
+pub use otel::export_otel_mapping;
+pub use outliers::{find_outliers, Outlier};
+pub use pattern::Pattern;
+pub use percentiles::Percentiles;
+pub use positions::{analyze_positions, PositionReport, TokenPositions};
+pub use profile::{profile, NodeProfile, Profile};
+pub use provenance::Provenance;
+pub use readability::Readability;
+pub use record::{Record, RecordField};
+pub use role::Role;
+pub use rules::{Partition, Rule, RuleList};
+pub use scoring::{best_patterns, pareto_patterns, PatternScore};
+pub use secrets::{scan_secrets, SecretFinding, SecretKind, SecretPolicy};
+#[cfg(feature = "serde")]
+pub use serialization::{Upgrader, FORMAT_VERSION};
+pub use session::{Candidate, Session};
+pub use stable_id::TemplateId;
+pub use strata::Stratification;
+#[cfg(feature = "tokio")]
+pub use stream::{Classified, ClassifiedLines, Classifier, DeduplicatedLines};
+use subsumption::subsumes;
+pub use subsumption::{contains_language, dominator_tree, equivalent, subsumption_graph};
+pub use synthesis::generate_regex;
+pub use template::{
+    generate_template, generate_template_with_dictionary, Field, FieldSchema, Segment, Template,
+};
+pub use tie_break::TieBreak;
+pub use tokenizer::Tokenizer;
+pub use trie::Algorithm;
+pub use url::{generate_url_tree, normalize_url};
+pub use verify::{verify_tree, NodeVerification, VerificationReport};
+pub use weighted::generate_regex_tree_weighted;
+pub use windows::{generate_windows, Drift, Window};
+
+/// The children of a node; most nodes have at most two, kept inline.
+type Children = SmallVec<[Arc<Node>; 2]>;
 
 /// Node structure representing a regex pattern in the tree.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
-    pattern: String,
-    children: Vec<Node>,
+    pattern: Pattern,
+    children: Children,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    collapsed: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    match_count: Option<usize>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    role: Option<Role>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    observed_values: Vec<Vec<String>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    examples: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    bound_outliers: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    provenance: Option<Box<Provenance>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    score: Option<PatternScore>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    alternation_order: Option<AlternationOrder>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    weight: Option<f64>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    exact: bool,
+}
+
+impl Node {
+    fn new(pattern: String) -> Node {
+        Node {
+            pattern: Pattern::new(pattern),
+            children: Children::new(),
+            collapsed: vec![],
+            match_count: None,
+            role: None,
+            observed_values: vec![],
+            examples: vec![],
+            bound_outliers: vec![],
+            provenance: None,
+            score: None,
+            alternation_order: None,
+            weight: None,
+            exact: false,
+        }
+    }
+
+    /// Returns how many inputs matched the pattern when it was last checked,
+    /// or `None` if it never was.
+    pub fn match_count(&self) -> Option<usize> {
+        self.match_count
+    }
+
+    /// Returns where the node's substring occurred in the inputs, which
+    /// decides how its pattern is anchored, or `None` for nodes not mined
+    /// from a substring, such as the root.
+    pub fn role(&self) -> Option<Role> {
+        self.role
+    }
+
+    /// Returns the distinct values each wildcard region of the node's
+    /// pattern took across the inputs, left to right, as recorded when
+    /// [`GenerateOptions::max_alternatives`] is set.
+    ///
+    /// Together with the pattern, this is a data dictionary of the field's
+    /// domain. A region that took more values than the cutoff has an empty
+    /// set, and a tree generated without the option has none at all.
+    pub fn observed_values(&self) -> &[Vec<String>] {
+        &self.observed_values
+    }
+
+    /// Returns example inputs the node's pattern matched, as recorded when
+    /// [`GenerateOptions::examples_per_node`] is set: the most typical one
+    /// first, then the ones differing most from it.
+    pub fn examples(&self) -> &[String] {
+        &self.examples
+    }
+
+    /// Returns the order in which the values of the node's alternations
+    /// are written, as chosen with [`GenerateOptions::alternation_order`],
+    /// or `None` if the node spells out no alternation.
+    pub fn alternation_order(&self) -> Option<AlternationOrder> {
+        self.alternation_order
+    }
+
+    /// Returns the total weight of the inputs the node's pattern matches,
+    /// as recorded by [`generate_regex_tree_weighted`], or `None` for trees
+    /// generated otherwise.
+    pub fn weight(&self) -> Option<f64> {
+        self.weight
+    }
+
+    /// Returns whether the node is the root of a tree short-circuited
+    /// because its inputs were all the same literal: its only child is that
+    /// literal, anchored, and matches exactly them.
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    /// Returns the inputs this leaf's pattern leaves out because the length
+    /// of one of their generalized regions falls outside the bounds set by
+    /// [`GenerateOptions::length_percentiles`]. Nothing is recorded when
+    /// literals are guarded, as for examples.
+    pub fn bound_outliers(&self) -> &[String] {
+        &self.bound_outliers
+    }
+
+    /// Replaces the pattern with a hand-tweaked one, keeping the model valid.
+    ///
+    /// The supporting inputs are those matched by the current pattern or by
+    /// any descendant. The new pattern must compile and still match all of
+    /// them; on success [`Node::match_count`] is updated to the number of
+    /// `inputs` it matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_pattern` - The replacement pattern.
+    /// * `inputs` - The inputs the tree was generated from.
+    ///
+    /// # Returns
+    ///
+    /// An error, leaving the node untouched, if the pattern does not compile
+    /// or loses a supporting input.
+    pub fn edit_pattern(&mut self, new_pattern: &str, inputs: &[&str]) -> Result<(), TreeError> {
+        let compile = |pattern: &str| {
+            Regex::new(pattern).map_err(|e| TreeError::InvalidPattern {
+                pattern: pattern.to_string(),
+                message: e.to_string(),
+            })
+        };
+        let replacement = compile(new_pattern)?;
+        let current: Vec<Regex> = self
+            .preorder()
+            .iter()
+            .map(|n| compile(&n.pattern))
+            .collect::<Result<_, _>>()?;
+
+        let supporting = inputs
+            .iter()
+            .filter(|input| current.iter().any(|r| r.is_match(input)));
+        for input in supporting {
+            if !replacement.is_match(input) {
+                return Err(TreeError::LostCoverage {
+                    input: input.to_string(),
+                });
+            }
+        }
+
+        self.pattern = new_pattern.to_string().into();
+        self.match_count = Some(inputs.iter().filter(|i| replacement.is_match(i)).count());
+        Ok(())
+    }
+
+    /// Returns the nodes of the tree rooted at this node in pre-order.
+    ///
+    /// A node's position in this list is its identifier, the root being `0`.
+    pub(crate) fn preorder(&self) -> Vec<&Node> {
+        let mut nodes = vec![self];
+        for child in &self.children {
+            nodes.extend(child.preorder());
+        }
+        nodes
+    }
+
+    /// Returns the nodes of the tree from the most general to the most
+    /// specific.
+    ///
+    /// Nodes are listed level by level, so every node comes after all of its
+    /// ancestors; this is the order in which rules must be tried when the
+    /// most general one should win.
+    pub fn topo_order(&self) -> Vec<&Node> {
+        let mut order = vec![self];
+        let mut next = 0;
+        while next < order.len() {
+            let node = order[next];
+            order.extend(node.children.iter().map(|c| &**c));
+            next += 1;
+        }
+        order
+    }
+
+    /// Returns the nodes of the tree from the most specific to the most
+    /// general, the order needed by first-match-wins rule engines.
+    pub fn reverse_topo_order(&self) -> Vec<&Node> {
+        let mut order = self.topo_order();
+        order.reverse();
+        order
+    }
+
+    /// Returns the number of nodes in the tree rooted at this node.
+    pub(crate) fn size(&self) -> usize {
+        1 + self.children.iter().map(|c| c.size()).sum::<usize>()
+    }
+
+    /// Returns the node with pre-order identifier `id`.
+    pub(crate) fn node_mut(&mut self, id: usize) -> Option<&mut Node> {
+        if id == 0 {
+            return Some(self);
+        }
+        let mut offset = 1;
+        for child in &mut self.children {
+            let size = child.size();
+            if id < offset + size {
+                return Arc::make_mut(child).node_mut(id - offset);
+            }
+            offset += size;
+        }
+        None
+    }
+
+    /// Extracts the subtree rooted at a node as a standalone tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The pre-order identifier of the node, the root being `0`.
+    ///
+    /// # Returns
+    ///
+    /// A copy of the node and its descendants, with their metadata, or
+    /// `None` if no node has this identifier.
+    pub fn extract_subtree(&self, id: usize) -> Option<Node> {
+        self.preorder().get(id).map(|&node| node.clone())
+    }
+
+    /// Removes and returns the node with pre-order identifier `id` and its
+    /// descendants. The root itself cannot be detached.
+    fn detach(&mut self, id: usize) -> Option<Node> {
+        let mut offset = 1;
+        for index in 0..self.children.len() {
+            let size = self.children[index].size();
+            if id == offset {
+                return Some(Arc::unwrap_or_clone(self.children.remove(index)));
+            }
+            if id < offset + size {
+                return Arc::make_mut(&mut self.children[index]).detach(id - offset);
+            }
+            offset += size;
+        }
+        None
+    }
+
+    /// Moves a node and its descendants beneath another parent.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The pre-order identifier of the node to move.
+    /// * `new_parent` - The pre-order identifier of its new parent, taken
+    ///   before the move.
+    ///
+    /// # Returns
+    ///
+    /// An error, leaving the tree untouched, if either node is unknown, the
+    /// node is the root or an ancestor of `new_parent`, or the new parent's
+    /// pattern does not subsume the node's pattern.
+    pub fn move_subtree(&mut self, id: usize, new_parent: usize) -> Result<(), TreeError> {
+        let nodes = self.preorder();
+        let node = nodes.get(id).ok_or(TreeError::UnknownNode(id))?;
+        let parent = nodes
+            .get(new_parent)
+            .ok_or(TreeError::UnknownNode(new_parent))?;
+        if id == 0 {
+            return Err(TreeError::RootNotMovable);
+        }
+        let size = node.size();
+        if (id..id + size).contains(&new_parent) {
+            return Err(TreeError::Cycle {
+                node: id,
+                parent: new_parent,
+            });
+        }
+        if !subsumes(&parent.pattern, &node.pattern) {
+            return Err(TreeError::NotSubsumed {
+                parent: parent.pattern.to_string(),
+                child: node.pattern.to_string(),
+            });
+        }
+
+        let subtree = self.detach(id).expect("identifier was checked");
+        let new_parent = if new_parent > id {
+            new_parent - size
+        } else {
+            new_parent
+        };
+        self.node_mut(new_parent)
+            .expect("identifier was checked")
+            .children
+            .push(Arc::new(subtree));
+        Ok(())
+    }
+
+    /// Promotes a node to be the root of the tree.
+    ///
+    /// The promoted node keeps its descendants. Every other node is
+    /// re-attached beneath the new root, with its descendants, if the new
+    /// root's pattern subsumes it, and detached otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The pre-order identifier of the node to promote.
+    ///
+    /// # Returns
+    ///
+    /// The nodes that no longer fit in the tree, without their children,
+    /// or an error if no node has this identifier.
+    pub fn reroot(&mut self, id: usize) -> Result<Vec<Node>, TreeError> {
+        if id == 0 {
+            return Ok(vec![]);
+        }
+        let mut new_root = self.detach(id).ok_or(TreeError::UnknownNode(id))?;
+        let old_root = std::mem::replace(self, Node::new(String::new()));
+
+        let mut detached = vec![];
+        adopt(&mut new_root, old_root, &mut detached);
+        *self = new_root;
+        Ok(detached)
+    }
+
+    /// Returns the patterns of the nodes merged into this one by
+    /// [`Node::compress_chains`], from the most general to the most specific.
+    pub fn collapsed(&self) -> &[String] {
+        &self.collapsed
+    }
+
+    /// Collapses linear chains of single-child nodes below this node.
+    ///
+    /// A node with exactly one child is replaced by that child, which is the
+    /// most specific pattern of the chain; the patterns it replaces are kept
+    /// in [`Node::collapsed`]. This node itself is left in place so the root
+    /// of a tree stays the root.
+    pub fn compress_chains(&mut self) {
+        if let Some(children) = self.compressed_children() {
+            self.children = children;
+        }
+    }
+
+    /// Returns the children with their chains collapsed, or `None` if there
+    /// is no chain below this node. Unchanged subtrees are shared.
+    fn compressed_children(&self) -> Option<Children> {
+        let mut changed = false;
+        let children = self
+            .children
+            .iter()
+            .map(|child| {
+                let mut merged: Option<Node> = None;
+                while merged.as_ref().unwrap_or(child).children.len() == 1 {
+                    let current = merged.take().unwrap_or_else(|| (**child).clone());
+                    let grandchild = &current.children[0];
+                    let mut collapsed = current.collapsed.clone();
+                    collapsed.push(current.pattern.to_string());
+                    collapsed.extend(grandchild.collapsed.iter().cloned());
+                    merged = Some(Node {
+                        collapsed,
+                        ..(**grandchild).clone()
+                    });
+                }
+
+                let nested = merged.as_ref().unwrap_or(child).compressed_children();
+                match (merged, nested) {
+                    (None, None) => Arc::clone(child),
+                    (merged, nested) => {
+                        changed = true;
+                        let mut node = merged.unwrap_or_else(|| (**child).clone());
+                        if let Some(children) = nested {
+                            node.children = children;
+                        }
+                        Arc::new(node)
+                    }
+                }
+            })
+            .collect();
+        changed.then_some(children)
+    }
+
+    /// Returns a copy of the tree with its chains collapsed, sharing the
+    /// unchanged subtrees with this one.
+    ///
+    /// See [`Node::compress_chains`].
+    pub fn with_compressed_chains(&self) -> Node {
+        match self.compressed_children() {
+            Some(children) => Node {
+                children,
+                ..self.clone()
+            },
+            None => self.clone(),
+        }
+    }
+
+    /// Returns a copy of the tree without the nodes rejected by `keep`,
+    /// sharing the unchanged subtrees with this one.
+    ///
+    /// A rejected node is removed together with its descendants; the root is
+    /// always kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep` - Decides whether a node stays in the tree.
+    pub fn with_pruned<F: Fn(&Node) -> bool>(&self, keep: F) -> Node {
+        match self.pruned_children(&keep) {
+            Some(children) => Node {
+                children,
+                ..self.clone()
+            },
+            None => self.clone(),
+        }
+    }
+
+    /// Returns the children without the nodes rejected by `keep`, or `None`
+    /// if nothing below this node is rejected.
+    fn pruned_children<F: Fn(&Node) -> bool>(&self, keep: &F) -> Option<Children> {
+        let mut changed = false;
+        let mut children = Children::with_capacity(self.children.len());
+        for child in &self.children {
+            if !keep(child) {
+                changed = true;
+                continue;
+            }
+            match child.pruned_children(keep) {
+                Some(grandchildren) => {
+                    changed = true;
+                    children.push(Arc::new(Node {
+                        children: grandchildren,
+                        ..(**child).clone()
+                    }));
+                }
+                None => children.push(Arc::clone(child)),
+            }
+        }
+        changed.then_some(children)
+    }
+}
+
+/// Attaches `node` beneath `root` if `root` subsumes it, or detaches it and
+/// tries again with each of its children.
+fn adopt(root: &mut Node, mut node: Node, detached: &mut Vec<Node>) {
+    if subsumes(&root.pattern, &node.pattern) {
+        root.children.push(Arc::new(node));
+        return;
+    }
+    for child in std::mem::take(&mut node.children) {
+        adopt(root, Arc::unwrap_or_clone(child), detached);
+    }
+    detached.push(node);
 }
 
 /// Generates a common regex pattern tree that matches all the given strings.
@@ -18,85 +704,711 @@ pub struct Node {
 /// # Returns
 ///
 /// The root node of the regex pattern tree.
+///
+/// Use a [`RegexTreeBuilder`] to change the defaults.
 pub fn generate_regex_tree(strings: &[&str]) -> Node {
+    build_with_options(strings, &GenerateOptions::default(), &TreeShape::default())
+}
+
+/// Generates a regex pattern tree, favouring substrings that are rare in a
+/// background corpus.
+///
+/// Candidates are ranked by their length weighted with their inverse
+/// document frequency, so substrings that are ubiquitous in general text
+/// (such as `"the "` or `"tion"`) are pushed behind the ones distinctive to
+/// `strings`.
+///
+/// # Arguments
+///
+/// * `strings` - A slice of strings to generate the regex pattern tree from.
+/// * `background` - Document frequencies of a background corpus.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree.
+pub fn generate_regex_tree_with_background(
+    strings: &[&str],
+    background: &DocumentFrequencies,
+) -> Node {
+    let options = GenerateOptions {
+        background: Some(background.clone()),
+        ..GenerateOptions::default()
+    };
+    build_with_options(strings, &options, &TreeShape::default())
+}
+
+/// Generates a common regex pattern tree using the given options.
+///
+/// # Arguments
+///
+/// * `strings` - A slice of strings to generate the regex pattern tree from.
+/// * `options` - Options controlling candidate selection and ranking.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree, or an error if a required
+/// literal is missing from one of the inputs.
+pub fn generate_regex_tree_with_options(
+    strings: &[&str],
+    options: &GenerateOptions,
+) -> Result<Node, GenerateError> {
+    generate_regex_tree_tolerant(strings, options).map(|(root, _)| root)
+}
+
+/// Generates a common regex pattern tree like
+/// [`generate_regex_tree_with_options`], but reports inputs that would only
+/// yield a degenerate tree as errors rather than returning it.
+///
+/// A single distinct input yields the pattern matching exactly it beneath
+/// the `.*` root, rather than a node for each of its substrings.
+///
+/// # Arguments
+///
+/// * `strings` - A slice of strings to generate the regex pattern tree from.
+/// * `options` - Options controlling candidate selection and ranking.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree, or an error if there are no
+/// inputs, if they share no literal, if the most specific pattern they all
+/// match pins a single character, such as `.*e.*`, if a pattern of the
+/// tree does not compile, or as [`generate_regex_tree_with_options`] fails.
+pub fn try_generate_regex_tree(
+    strings: &[&str],
+    options: &GenerateOptions,
+) -> Result<Node, GenerateError> {
     if strings.is_empty() {
-        return Node {
-            pattern: String::new(),
-            children: vec![],
+        return Err(GenerateError::EmptyInput);
+    }
+    let root = match literal_tree(strings) {
+        Some(root) => root,
+        None => generate_regex_tree_with_options(strings, options)?,
+    };
+    for node in root.iter() {
+        Regex::new(&node.pattern).map_err(|error| GenerateError::CompileFailed {
+            pattern: node.pattern.to_string(),
+            message: error.to_string(),
+        })?;
+    }
+    match best_patterns(&root, 1)
+        .first()
+        .and_then(|best| Some((best, best.score?)))
+    {
+        Some((_, score)) if score.pinned_chars() == 0 => Err(GenerateError::NoCommonPattern),
+        None => Err(GenerateError::NoCommonPattern),
+        Some((best, score)) if score.pinned_chars() == 1 => Err(GenerateError::PatternTooGeneral {
+            pattern: best.pattern.to_string(),
+        }),
+        Some(_) => Ok(root),
+    }
+}
+
+/// Generates a regex pattern tree that may leave some inputs unmatched, as
+/// allowed by [`GenerateOptions::tolerance`].
+///
+/// Inputs are dropped one at a time, each time the one whose removal
+/// allows the most specific common pattern (see [`find_outliers`]), until
+/// the budget is spent or no input stands out.
+///
+/// # Arguments
+///
+/// * `strings` - A slice of strings to generate the regex pattern tree from.
+/// * `options` - Options controlling candidate selection and ranking.
+///
+/// # Returns
+///
+/// The root node of the tree built from the remaining inputs, with the
+/// indices of the dropped ones in increasing order, or an error if a
+/// required literal is missing from one of the remaining inputs.
+pub fn generate_regex_tree_tolerant(
+    strings: &[&str],
+    options: &GenerateOptions,
+) -> Result<(Node, Vec<usize>), GenerateError> {
+    generate_tolerant(strings, options, &TreeShape::default(), &mut vec![])
+}
+
+/// Generates a tree like [`generate_regex_tree_tolerant`], shaped by
+/// `shape`, recording the issues met in `diagnostics`.
+fn generate_tolerant(
+    strings: &[&str],
+    options: &GenerateOptions,
+    shape: &TreeShape,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(Node, Vec<usize>), GenerateError> {
+    if options.secret_policy == Some(SecretPolicy::Abort) {
+        let findings = scan_secrets(strings);
+        if !findings.is_empty() {
+            return Err(GenerateError::SecretsFound(findings));
+        }
+    }
+    let budget = options.tolerance.map_or(0, |t| t.budget(strings.len()));
+    let mut kept: Vec<usize> = (0..strings.len()).collect();
+    let mut dropped = vec![];
+    while dropped.len() < budget && !shape.is_cancelled() {
+        let remaining: Vec<&str> = kept.iter().map(|&i| strings[i]).collect();
+        let Some(worst) = find_outliers(&remaining, options).into_iter().next() else {
+            break;
         };
+        dropped.push(kept.remove(worst.index()));
+    }
+    dropped.sort_unstable();
+
+    let remaining: Vec<&str> = kept.iter().map(|&i| strings[i]).collect();
+    for literal in options.required_literals.iter().filter(|l| !l.is_empty()) {
+        if let Some(position) = remaining.iter().position(|s| !s.contains(literal.as_str())) {
+            return Err(GenerateError::MissingRequiredLiteral {
+                literal: literal.clone(),
+                input: kept[position],
+            });
+        }
     }
 
-    let substrings = find_common_substrings(strings);
-    build_tree(&substrings)
+    let root = build_diagnosed(&remaining, options, shape, diagnostics);
+    Ok((root, dropped))
+}
+
+/// Builds the tree from the candidates selected by `options`, shaped by
+/// `shape`. Required literals are assumed to have been validated.
+fn build_with_options(strings: &[&str], options: &GenerateOptions, shape: &TreeShape) -> Node {
+    build_diagnosed(strings, options, shape, &mut vec![])
+}
+
+/// Builds a tree like [`build_with_options`], recording the issues met in
+/// `diagnostics`.
+fn build_diagnosed(
+    strings: &[&str],
+    options: &GenerateOptions,
+    shape: &TreeShape,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Node {
+    if strings.is_empty() {
+        return Node::new(String::new());
+    }
+    if let Some(stratification) = options.stratification {
+        let strata = stratification.strata(strings);
+        if strata.len() > 1 {
+            return build_stratified(
+                strings,
+                &strata,
+                stratification,
+                options,
+                shape,
+                diagnostics,
+            );
+        }
+    }
+    if let Some(clustering) = options.clustering {
+        let clusters = clustering.clusters(strings);
+        if clusters.len() > 1 {
+            return build_clustered(strings, &clusters, options, shape, diagnostics);
+        }
+    }
+
+    let secrets = match options.secret_policy {
+        Some(SecretPolicy::Generalize) => {
+            let findings = scan_secrets(strings);
+            let mut inputs: Vec<usize> = findings.iter().map(SecretFinding::input).collect();
+            inputs.dedup();
+            if !inputs.is_empty() {
+                diagnostics.push(Diagnostic::SecretsGeneralized {
+                    inputs: inputs.len(),
+                });
+            }
+            secrets::secret_values(strings, &findings)
+        }
+        _ => vec![],
+    };
+    let guard = SupportGuard::new(strings, options.min_literal_support).with_secrets(secrets);
+    if !guard.is_active() && options.stop_substrings.is_empty() {
+        if let Some(mut root) = literal_tree(strings) {
+            if options.examples_per_node > 0 {
+                examples::attach_examples(&mut root, strings, options.examples_per_node);
+            }
+            return root;
+        }
+    }
+    if shape.algorithm == Algorithm::Trie && !guard.is_active() {
+        let mut root = trie::build_trie(strings);
+        scoring::score_tree(&mut root, strings);
+        return root;
+    }
+    let mut substrings = select_diagnosed(strings, options, diagnostics);
+    if guard.is_active() {
+        let mut seen = HashSet::new();
+        let fragmented = substrings
+            .iter()
+            .filter(|s| guard.safe_fragments(s) != [**s])
+            .count();
+        diagnostics::excluded(diagnostics, CandidateFilter::LiteralSupport, fragmented);
+        substrings = substrings
+            .into_iter()
+            .flat_map(|s| guard.safe_fragments(s))
+            .filter(|s| seen.insert(*s))
+            .collect();
+    }
+    let required = |s: &str| options.required_literals.iter().any(|r| r == s);
+    if let Some(mode) = options.grapheme_mode {
+        let before = substrings.len();
+        substrings.retain(|s| mode.is_aligned(s, strings) || required(s));
+        diagnostics::excluded(
+            diagnostics,
+            CandidateFilter::Graphemes,
+            before - substrings.len(),
+        );
+    }
+    if shape.min_substring_len > 1 {
+        let before = substrings.len();
+        substrings.retain(|s| {
+            let len = match options.grapheme_mode {
+                Some(mode) => mode.len(s),
+                None => s.chars().count(),
+            };
+            len >= shape.min_substring_len || required(s)
+        });
+        let short = before - substrings.len();
+        diagnostics::excluded(diagnostics, CandidateFilter::MinSubstringLen, short);
+    }
+    if !guard.allows_common() {
+        diagnostics::excluded(
+            diagnostics,
+            CandidateFilter::LiteralSupport,
+            substrings.len(),
+        );
+        substrings.clear();
+    }
+    if shape.is_cancelled() {
+        let mut root = Node::new(".*".to_string());
+        scoring::score_tree(&mut root, strings);
+        return root;
+    }
+    if substrings.is_empty() && strings.iter().any(|s| s != &strings[0]) {
+        diagnostics.push(Diagnostic::NoCommonSubstring {
+            inputs: strings.len(),
+        });
+    }
+    let mut root = build_shaped_tree(&substrings, strings, shape);
+    if shape.anchoring == Anchoring::PrefixSuffix {
+        anchoring::anchor_prefix_suffix(&mut root, strings, options.grapheme_mode, &guard);
+    }
+    if let Some(max_children) = shape.max_children {
+        let count = truncate_children(&mut root, max_children);
+        if count > 0 {
+            diagnostics.push(Diagnostic::ChildrenTruncated { count });
+        }
+    }
+    // Once cancelled, the tree built so far is left unrefined; it still
+    // matches every input.
+    'refine: {
+        if let Some(max_alternatives) = options.max_alternatives {
+            if shape.is_cancelled() {
+                break 'refine;
+            }
+            alternation::infer_alternations(
+                &mut root,
+                strings,
+                max_alternatives,
+                options.alternation_order,
+                &guard,
+            );
+        }
+        if let Some(strategy) = options.numeric_strategy {
+            if shape.is_cancelled() {
+                break 'refine;
+            }
+            numeric::infer_numeric_ranges(
+                &mut root,
+                strings,
+                strategy,
+                options.length_percentiles,
+                &guard,
+            );
+        }
+        if shape.is_cancelled() {
+            break 'refine;
+        }
+        classes::generalize_classes(
+            &mut root,
+            strings,
+            &options.char_classes,
+            options.gap_strategy,
+            options.length_percentiles,
+            &guard,
+        );
+        if options.humanize {
+            readability::humanize_tree(&mut root, None);
+        }
+        if options.posix_classes {
+            posix::use_posix_classes(&mut root);
+        }
+        if options.examples_per_node > 0 && !guard.is_active() && !shape.is_cancelled() {
+            examples::attach_examples(&mut root, strings, options.examples_per_node);
+        }
+    }
+    scoring::score_tree(&mut root, strings);
+    root
+}
+
+/// Returns the exact tree of inputs that are all the same literal: the
+/// anchored literal beneath a `.*` root, without mining any substring.
+fn literal_tree(strings: &[&str]) -> Option<Node> {
+    let first = strings.first()?;
+    if strings.iter().any(|s| s != first) {
+        return None;
+    }
+    let mut root = exact_tree(first);
+    scoring::score_tree(&mut root, strings);
+    Some(root)
+}
+
+/// Returns the unscored exact tree of inputs that are all `literal`.
+pub(crate) fn exact_tree(literal: &str) -> Node {
+    let mut root = Node::new(".*".to_string());
+    let mut exact = Node::new(format!("^{}$", regex_syntax::escape(literal)));
+    exact.role = Some(Role::Full);
+    root.children.push(Arc::new(exact));
+    root.exact = true;
+    root
+}
+
+/// Builds one tree per stratum of the inputs, each beneath a node matching
+/// the sizes of its inputs, below a root matching everything.
+fn build_stratified(
+    strings: &[&str],
+    strata: &[Vec<&str>],
+    stratification: Stratification,
+    options: &GenerateOptions,
+    shape: &TreeShape,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Node {
+    let options = GenerateOptions {
+        stratification: None,
+        ..options.clone()
+    };
+    let mut root = Node::new(".*".to_string());
+    for stratum in strata {
+        let mut node = build_diagnosed(stratum, &options, shape, diagnostics);
+        node.pattern = stratification.pattern(stratum).into();
+        node.role = None;
+        root.children.push(Arc::new(node));
+    }
+    scoring::score_tree(&mut root, strings);
+    root
+}
+
+/// Builds a subtree per cluster of `strings`, each beneath a node whose
+/// pattern is the most specific one of the cluster's tree, under a root
+/// alternating between those patterns.
+fn build_clustered(
+    strings: &[&str],
+    clusters: &[Vec<&str>],
+    options: &GenerateOptions,
+    shape: &TreeShape,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Node {
+    let options = GenerateOptions {
+        clustering: None,
+        ..options.clone()
+    };
+    let mut root = Node::new(String::new());
+    for cluster in clusters {
+        let mut node = build_diagnosed(cluster, &options, shape, diagnostics);
+        node.pattern = best_patterns(&node, 1)
+            .first()
+            .map_or(".*", |best| best.pattern.as_str())
+            .to_string()
+            .into();
+        node.role = None;
+        root.children.push(Arc::new(node));
+    }
+    let branches: Vec<&str> = root.children.iter().map(|n| n.pattern.as_str()).collect();
+    root.pattern = format!("(?:{})", branches.join("|")).into();
+    scoring::score_tree(&mut root, strings);
+    root
+}
+
+/// Selects, ranks and pins candidate substrings according to `options`.
+///
+/// # Arguments
+///
+/// * `strings` - A non-empty slice of strings to mine candidates from.
+/// * `options` - Options controlling candidate selection and ranking.
+///
+/// # Returns
+///
+/// The candidate substrings, best first.
+fn select_candidates<'a>(strings: &[&'a str], options: &'a GenerateOptions) -> Vec<&'a str> {
+    select_diagnosed(strings, options, &mut vec![])
+}
+
+/// Selects candidates like [`select_candidates`], recording those the
+/// filters excluded in `diagnostics`.
+fn select_diagnosed<'a>(
+    strings: &[&'a str],
+    options: &'a GenerateOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<&'a str> {
+    let mut substrings = match options.chunk_length {
+        Some(length) if strings.iter().any(|s| s.len() > length) => {
+            find_common_substrings_chunked(strings, length)
+        }
+        _ => find_common_substrings(strings),
+    };
+    let before = substrings.len();
+    substrings
+        .retain(|s| options.required_literals.iter().any(|r| r == s) || !options.is_stopped(s));
+    diagnostics::excluded(
+        diagnostics,
+        CandidateFilter::StopSubstrings,
+        before - substrings.len(),
+    );
+    if let Some(tokenizer) = &options.tokenizer {
+        let before = substrings.len();
+        substrings.retain(|s| {
+            options.required_literals.iter().any(|r| r == s)
+                || strings.iter().all(|input| tokenizer.is_aligned(input, s))
+        });
+        diagnostics::excluded(
+            diagnostics,
+            CandidateFilter::Tokenizer,
+            before - substrings.len(),
+        );
+    }
+    if let Some(background) = &options.background {
+        rank_by_idf(&mut substrings, background);
+    }
+    options
+        .tie_break
+        .break_ties(&mut substrings, strings, options.background.as_ref());
+    pin_required_literals(&mut substrings, &options.required_literals);
+    substrings
+}
+
+/// Moves the required literals to the front of the candidates, followed by
+/// the candidates that extend one of them, so the tree is built around them.
+///
+/// # Arguments
+///
+/// * `substrings` - The ranked candidate substrings.
+/// * `required` - The literals pinned by the caller.
+fn pin_required_literals<'a>(substrings: &mut Vec<&'a str>, required: &'a [String]) {
+    let required: Vec<&str> = required
+        .iter()
+        .map(String::as_str)
+        .filter(|l| !l.is_empty())
+        .collect();
+    if required.is_empty() {
+        return;
+    }
+
+    substrings.retain(|s| !required.contains(s));
+    let (extending, rest): (Vec<&str>, Vec<&str>) = substrings
+        .drain(..)
+        .partition(|s| required.iter().any(|r| s.contains(r)));
+    substrings.extend(required);
+    substrings.extend(extending);
+    substrings.extend(rest);
 }
 
 /// Finds common substrings among the given strings.
 ///
+/// A suffix automaton of the first string is built, and every other string
+/// is run through it once, recording for each state the longest substring
+/// of its class occurring there. The substrings occurring everywhere are
+/// then read off the automaton, so the search is linear in the total length
+/// of the inputs plus the size of the result, instead of checking each
+/// substring of the first string against every other string.
+///
 /// # Arguments
 ///
 /// * `strings` - A slice of strings to find common substrings.
 ///
 /// # Returns
 ///
-/// A vector of common substrings.
-fn find_common_substrings(strings: &[&str]) -> Vec<String> {
-    let mut common_substrings = HashSet::new();
-    let first = strings[0];
+/// A vector of common substrings, borrowed from the first string so that
+/// candidates are only copied once they become nodes. Longer substrings
+/// come first, then substrings appearing earlier in the first string.
+fn find_common_substrings<'a>(strings: &[&'a str]) -> Vec<&'a str> {
+    automaton::common_substrings(strings, usize::MAX)
+}
 
-    for i in 0..first.len() {
-        for j in i + 1..=first.len() {
-            let substring = &first[i..j];
-            if strings.iter().all(|s| s.contains(substring)) {
-                common_substrings.insert(substring.to_string());
+/// Finds common substrings of at most half a chunk, mining the first
+/// string one overlapping chunk at a time.
+///
+/// Every window that short lies entirely within some chunk, so the merged
+/// result is the same as an unchunked search capped at that width, while
+/// each automaton only spans one chunk of the first string.
+///
+/// # Arguments
+///
+/// * `strings` - A slice of strings to find common substrings.
+/// * `chunk_length` - The length of a chunk, in bytes.
+///
+/// # Returns
+///
+/// The common substrings, longer ones first, then in order of first
+/// occurrence.
+fn find_common_substrings_chunked<'a>(strings: &[&'a str], chunk_length: usize) -> Vec<&'a str> {
+    let overlap = (chunk_length / 2).max(1);
+    let mut chunked = strings.to_vec();
+    let mut seen = HashSet::new();
+    let mut merged: Vec<&str> = vec![];
+    for chunk in rolling::overlapping_chunks(strings[0], chunk_length.max(2), overlap) {
+        chunked[0] = chunk;
+        for substring in automaton::common_substrings(&chunked, overlap) {
+            if seen.insert(substring) {
+                merged.push(substring);
             }
         }
     }
-
-    let mut substrings: Vec<String> = common_substrings.into_iter().collect();
-    substrings.sort_by_key(|s| s.len());
-    substrings.reverse();
-    substrings
+    merged.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    merged
 }
 
 /// Builds a tree of regex patterns from the common substrings.
 ///
+/// Each substring is anchored according to its [`Role`] in the inputs and
+/// attached under its nearest generalization among the other substrings,
+/// that is the longest one whose pattern matches everything its own
+/// pattern matches, so the tree only keeps the covering edges of the
+/// containment order and no transitive shortcuts. Substrings without a
+/// generalization hang off the root. Siblings keep the order of
+/// `substrings`.
+///
 /// # Arguments
 ///
 /// * `substrings` - A vector of common substrings to build the tree from.
+/// * `inputs` - The strings the substrings were mined from.
 ///
 /// # Returns
 ///
 /// The root node of the regex pattern tree.
-fn build_tree(substrings: &[String]) -> Node {
-    let root = Node {
-        pattern: ".*".to_string(),
-        children: vec![],
+fn build_tree(substrings: &[&str], inputs: &[&str]) -> Node {
+    build_shaped_tree(substrings, inputs, &TreeShape::default())
+}
+
+/// Builds a tree like [`build_tree`], with every pattern floating as an
+/// infix unless `anchored` is set.
+fn build_shaped_tree(substrings: &[&str], inputs: &[&str], shape: &TreeShape) -> Node {
+    let mut seen: HashSet<&str> = HashSet::with_capacity(substrings.len());
+    let unique: Vec<&str> = substrings
+        .iter()
+        .copied()
+        .filter(|s| seen.insert(s))
+        .collect();
+    let roles: Vec<Role> = match shape.anchoring {
+        Anchoring::Floating => vec![Role::Infix; unique.len()],
+        _ => roles_of(&unique, inputs, shape),
     };
+    // Cancellation leaves the roles of the worst-ranked candidates unknown,
+    // and the tree is built from the best-ranked ones.
+    build_tree_with_roles(&unique[..roles.len()], &roles)
+}
 
-    let mut nodes: Vec<Node> = vec![root.clone()];
+/// Determines the role of each candidate, checking the candidates on
+/// rayon's thread pool when the shape is parallel, until cancelled.
+fn roles_of(candidates: &[&str], inputs: &[&str], shape: &TreeShape) -> Vec<Role> {
+    let role = |s: &&str| (!shape.is_cancelled()).then(|| Role::of(s, inputs));
+    #[cfg(feature = "rayon")]
+    if shape.parallel {
+        use rayon::prelude::*;
+        let roles: Vec<Option<Role>> = candidates.par_iter().map(role).collect();
+        return roles.into_iter().map_while(|role| role).collect();
+    }
+    candidates.iter().map_while(role).collect()
+}
 
-    for substring in substrings {
-        let new_node = Node {
-            pattern: format!(".*{}.*", regex::escape(substring)),
-            children: vec![],
-        };
-        nodes.push(new_node);
+/// Returns whether `predicate` holds for every input, checking them on
+/// rayon's thread pool when `parallel` is set.
+pub(crate) fn all_inputs<F>(inputs: &[&str], parallel: bool, predicate: F) -> bool
+where
+    F: Fn(&str) -> bool + Sync,
+{
+    #[cfg(feature = "rayon")]
+    if parallel {
+        use rayon::prelude::*;
+        return inputs.par_iter().all(|s| predicate(s));
     }
+    #[cfg(not(feature = "rayon"))]
+    let _ = parallel;
+    inputs.iter().all(|s| predicate(s))
+}
 
-    let mut root = nodes.remove(0);
+/// Builds a tree from distinct candidate substrings, best first, whose
+/// roles are already known.
+pub(crate) fn build_tree_with_roles(unique: &[&str], roles: &[Role]) -> Node {
+    let rank: HashMap<&str, usize> = unique.iter().enumerate().map(|(i, &s)| (s, i)).collect();
+    let mut children: Vec<Vec<usize>> = vec![vec![]; unique.len() + 1];
+    for (index, substring) in unique.iter().enumerate() {
+        let covers = |p: usize| roles[p].covers(unique[p], roles[index], substring);
+        let parent = nearest_generalization(substring, &rank, covers).map_or(0, |p| p + 1);
+        children[parent].push(index + 1);
+    }
 
-    for i in 0..nodes.len() {
-        let mut children = vec![];
-        for j in i + 1..nodes.len() {
-            if nodes[j].pattern.contains(&nodes[i].pattern) {
-                children.push(nodes[j].clone());
-            }
-        }
-        nodes[i].children = children;
+    let mut nodes: Vec<Node> = Vec::with_capacity(unique.len() + 1);
+    nodes.push(Node::new(".*".to_string()));
+    // Patterns are assembled in one scratch buffer and copied out at their
+    // exact length, so each node costs a single allocation.
+    let mut scratch = String::new();
+    for (substring, &role) in unique.iter().zip(roles) {
+        scratch.clear();
+        role.write_pattern(substring, &mut scratch);
+        let mut node = Node::new(scratch.as_str().to_owned());
+        node.role = Some(role);
+        nodes.push(node);
     }
+    assemble(&mut nodes, &children, 0)
+}
 
-    root.children = nodes;
-    root
+/// Returns the rank of the longest proper substring of `substring` found in
+/// `rank` and accepted by `covers`, preferring the best-ranked one among
+/// equally long candidates.
+fn nearest_generalization<F>(
+    substring: &str,
+    rank: &HashMap<&str, usize>,
+    covers: F,
+) -> Option<usize>
+where
+    F: Fn(usize) -> bool,
+{
+    let bounds: Vec<usize> = substring
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(substring.len()))
+        .collect();
+    let chars = bounds.len() - 1;
+    (1..chars).rev().find_map(|width| {
+        (0..=chars - width)
+            .filter_map(|start| rank.get(&substring[bounds[start]..bounds[start + width]]))
+            .copied()
+            .filter(|&p| covers(p))
+            .min()
+    })
+}
+
+/// Keeps the first `max` children of every node, dropping the others with
+/// their subtrees, and returns how many nodes were dropped.
+fn truncate_children(node: &mut Node, max: usize) -> usize {
+    let dropped: usize = node.children.iter().skip(max).map(|c| c.size()).sum();
+    node.children.truncate(max);
+    let mut count = dropped;
+    for child in &mut node.children {
+        count += truncate_children(Arc::make_mut(child), max);
+    }
+    count
+}
+
+fn assemble(nodes: &mut [Node], children: &[Vec<usize>], index: usize) -> Node {
+    let mut node = std::mem::replace(&mut nodes[index], Node::new(String::new()));
+    node.children = children[index]
+        .iter()
+        .map(|&child| Arc::new(assemble(nodes, children, child)))
+        .collect();
+    node
+}
+
+/// Returns the floating pattern matching any string containing `substring`.
+fn contains_pattern(substring: &str) -> String {
+    Role::Infix.pattern(substring)
 }
 
 /// Converts the regex tree to a graph for visualization.
@@ -110,7 +1422,7 @@ fn build_tree(substrings: &[String]) -> Node {
 /// A directed graph representation of the regex pattern tree.
 pub fn tree_to_graph(root: &Node) -> DiGraph<String, ()> {
     let mut graph = DiGraph::new();
-    let root_index = graph.add_node(root.pattern.clone());
+    let root_index = graph.add_node(root.pattern.to_string());
     add_children_to_graph(&mut graph, root_index, &root.children);
     graph
 }
@@ -125,15 +1437,104 @@ pub fn tree_to_graph(root: &Node) -> DiGraph<String, ()> {
 fn add_children_to_graph(
     graph: &mut DiGraph<String, ()>,
     parent_index: NodeIndex,
-    children: &[Node],
+    children: &[Arc<Node>],
 ) {
     for child in children {
-        let child_index = graph.add_node(child.pattern.clone());
+        let child_index = graph.add_node(child.pattern.to_string());
         graph.add_edge(parent_index, child_index, ());
         add_children_to_graph(graph, child_index, &child.children);
     }
 }
 
+/// Converts the regex tree to a stable graph, whose node indices survive
+/// the removal of other nodes.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// The graph, and a mapping from each graph index to the pre-order
+/// identifier of its tree node, so annotations keyed by graph indices can be
+/// reconciled with the tree after external edits.
+pub fn tree_to_stable_graph(root: &Node) -> (StableDiGraph<String, ()>, HashMap<NodeIndex, usize>) {
+    let mut graph = StableDiGraph::new();
+    let mut ids = HashMap::new();
+    let mut stack: Vec<(&Node, Option<NodeIndex>)> = vec![(root, None)];
+    while let Some((node, parent)) = stack.pop() {
+        let index = graph.add_node(node.pattern.to_string());
+        ids.insert(index, ids.len());
+        if let Some(parent) = parent {
+            graph.add_edge(parent, index, ());
+        }
+        stack.extend(node.children.iter().rev().map(|c| (&**c, Some(index))));
+    }
+    (graph, ids)
+}
+
+/// Converts a graph back into a regex pattern tree.
+///
+/// This is the inverse of [`tree_to_graph`], so a graph that was exported,
+/// edited in an external tool and re-imported can be used as a model again.
+/// Children keep the order of their node indices.
+///
+/// # Arguments
+///
+/// * `graph` - A graph whose nodes are patterns and whose edges point from
+///   parents to children.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree, or an error if the graph is
+/// not a single tree.
+pub fn tree_from_graph(graph: &DiGraph<String, ()>) -> Result<Node, GraphError> {
+    let mut roots = vec![];
+    for index in graph.node_indices() {
+        match graph.neighbors_directed(index, Direction::Incoming).count() {
+            0 => roots.push(index),
+            1 => {}
+            _ => {
+                return Err(GraphError::MultipleParents {
+                    node: index.index(),
+                })
+            }
+        }
+    }
+    let root = match roots[..] {
+        [] => return Err(GraphError::NoRoot),
+        [root] => root,
+        _ => {
+            return Err(GraphError::MultipleRoots(
+                roots.iter().map(|r| r.index()).collect(),
+            ))
+        }
+    };
+
+    let tree = node_from_graph(graph, root);
+    if tree.size() < graph.node_count() {
+        let reachable: HashSet<NodeIndex> = Dfs::new(graph, root).iter(graph).collect();
+        let node = graph
+            .node_indices()
+            .find(|n| !reachable.contains(n))
+            .expect("some node is unreachable");
+        return Err(GraphError::Unreachable { node: node.index() });
+    }
+    Ok(tree)
+}
+
+/// Builds the subtree rooted at `index`, assuming every node has one parent.
+fn node_from_graph(graph: &DiGraph<String, ()>, index: NodeIndex) -> Node {
+    let mut node = Node::new(graph[index].clone());
+    let mut children: Vec<NodeIndex> = graph.neighbors(index).collect();
+    children.sort();
+    node.children = children
+        .into_iter()
+        .map(|child| Arc::new(node_from_graph(graph, child)))
+        .collect();
+    node
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,10 +1551,327 @@ mod tests {
         ];
         let tree = generate_regex_tree(&inputs);
         assert_eq!(tree.pattern, ".*");
-        assert!(tree
-            .children
+        let parent = tree
+            .preorder()
+            .into_iter()
+            .find(|n| n.children.iter().any(|c| c.pattern == "^highlighted .*"))
+            .unwrap();
+        assert!(["^highlighted.*", ".*ighlighted .*"].contains(&parent.pattern.as_str()));
+        assert!(!tree.children.iter().any(|n| n.pattern == "^highlighted .*"));
+        assert_eq!(
+            tree.preorder().len(),
+            find_common_substrings(&inputs).len() + 1
+        );
+    }
+
+    #[test]
+    fn test_build_tree_nests_each_pattern_once() {
+        let inputs = ["x a.b.c y", "z a.b.c w"];
+        let tree = build_tree(&["a.b.c", "a.b", ".b", "a.b", "b"], &inputs);
+
+        let patterns: Vec<&str> = tree.preorder().iter().map(|n| n.pattern.as_str()).collect();
+        assert_eq!(
+            patterns,
+            [".*", ".*b.*", r".*\.b.*", r".*a\.b.*", r".*a\.b\.c.*"]
+        );
+        let mut node = &tree;
+        while let Some(child) = node.children.first() {
+            assert_eq!(node.children.len(), 1);
+            node = child;
+        }
+    }
+
+    #[test]
+    fn test_generate_regex_tree_with_background() {
+        let inputs = vec!["the order 1234 shipped", "the order 9871 shipped"];
+        let background =
+            DocumentFrequencies::from_corpus(&["the cat sat", "the dog ran", "tea for the two"]);
+        let tree = generate_regex_tree_with_background(&inputs, &background);
+        assert!(tree.preorder().iter().any(|n| n.pattern == ".*shipp.*"));
+
+        let options = GenerateOptions {
+            background: Some(background),
+            ..GenerateOptions::default()
+        };
+        let ranked = select_candidates(&inputs, &options);
+        let position = |literal: &str| ranked.iter().position(|&s| s == literal).unwrap();
+        assert!(position("shipp") < position("the "));
+    }
+
+    #[test]
+    fn test_generate_regex_tree_with_stop_substrings() {
+        let inputs = vec!["Acme Corp: disk full", "Acme Corp: disk slow"];
+        let options = GenerateOptions {
+            stop_substrings: vec!["Acme Corp".to_string()],
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let nodes = tree.preorder();
+        assert!(nodes.iter().any(|n| n.pattern == ".*: disk .*"));
+        assert!(!nodes.iter().any(|n| n.pattern.contains("Acme")));
+        assert!(!nodes.iter().any(|n| n.pattern == ".*Corp.*"));
+    }
+
+    #[test]
+    fn test_generate_regex_tree_with_required_literals() {
+        let inputs = vec!["ERROR disk full on sda", "ERROR disk full on sdb"];
+        let options = GenerateOptions {
+            required_literals: vec!["ERROR".to_string()],
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        assert_eq!(select_candidates(&inputs, &options)[0], "ERROR");
+        let error = tree
+            .preorder()
+            .into_iter()
+            .find(|n| n.pattern == "^ERROR.*")
+            .unwrap();
+        assert_eq!(error.role(), Some(Role::Prefix));
+        assert!(error.preorder().iter().all(|n| n.pattern.contains("ERROR")));
+
+        let missing = generate_regex_tree_with_options(&["ERROR a", "WARN b"], &options);
+        assert_eq!(
+            missing.unwrap_err(),
+            GenerateError::MissingRequiredLiteral {
+                literal: "ERROR".to_string(),
+                input: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_try_generate_regex_tree() {
+        let options = GenerateOptions::default();
+        let attempt = |inputs: &[&str]| try_generate_regex_tree(inputs, &options);
+        assert_eq!(attempt(&[]).unwrap_err(), GenerateError::EmptyInput);
+        assert_eq!(
+            attempt(&["abc", "xyz"]).unwrap_err(),
+            GenerateError::NoCommonPattern
+        );
+        assert_eq!(
+            attempt(&["ab", "ba"]).unwrap_err(),
+            GenerateError::PatternTooGeneral {
+                pattern: ".*a.*".to_string()
+            }
+        );
+
+        let single = attempt(&["hello world"]).unwrap();
+        let patterns: Vec<&str> = single.iter().map(|n| n.pattern.as_str()).collect();
+        assert_eq!(patterns, [".*", "^hello world$"]);
+        assert!(attempt(&["disk sda full", "disk sdb full"]).is_ok());
+    }
+
+    #[test]
+    fn test_literal_corpus_short_circuit() {
+        let tree = generate_regex_tree(&["GET /health", "GET /health", "GET /health"]);
+        assert!(tree.is_exact());
+        let patterns: Vec<&str> = tree.iter().map(|n| n.pattern.as_str()).collect();
+        assert_eq!(patterns, [".*", "^GET /health$"]);
+        assert!(!generate_regex_tree(&["GET /a", "GET /b"]).is_exact());
+    }
+
+    #[test]
+    fn test_generate_regex_tree_tolerant() {
+        let inputs = vec![
+            "user alice logged in",
+            "\u{0}garbage",
+            "user bob logged in",
+            "user carol logged in",
+        ];
+        let options = GenerateOptions {
+            tolerance: Some(Tolerance::Percent(25.0)),
+            ..GenerateOptions::default()
+        };
+        let (tree, dropped) = generate_regex_tree_tolerant(&inputs, &options).unwrap();
+        assert_eq!(dropped, vec![1]);
+        assert!(tree.preorder().iter().any(|n| n.pattern == ".* logged in$"));
+
+        let (_, dropped) =
+            generate_regex_tree_tolerant(&inputs, &GenerateOptions::default()).unwrap();
+        assert!(dropped.is_empty());
+    }
+
+    #[test]
+    fn test_compress_chains() {
+        let mut root = Node::new(".*".to_string());
+        let mut a = Node::new(".*a.*".to_string());
+        let mut ab = Node::new(".*ab.*".to_string());
+        ab.children.push(Arc::new(Node::new(".*abc.*".to_string())));
+        a.children.push(Arc::new(ab));
+        root.children.push(Arc::new(a));
+        root.children.push(Arc::new(Node::new(".*x.*".to_string())));
+
+        root.compress_chains();
+        assert_eq!(root.pattern, ".*");
+        assert_eq!(root.children[0].pattern, ".*abc.*");
+        assert_eq!(root.children[0].collapsed(), [".*a.*", ".*ab.*"]);
+        assert!(root.children[0].children.is_empty());
+        assert!(root.children[1].collapsed().is_empty());
+    }
+
+    #[test]
+    fn test_extract_subtree() {
+        let mut root = Node::new(".*".to_string());
+        let mut a = Node::new(".*a.*".to_string());
+        a.children.push(Arc::new(Node::new(".*ab.*".to_string())));
+        root.children.push(Arc::new(a));
+        root.children.push(Arc::new(Node::new(".*x.*".to_string())));
+
+        let branch = root.extract_subtree(1).unwrap();
+        assert_eq!(branch.pattern, ".*a.*");
+        assert_eq!(branch.children[0].pattern, ".*ab.*");
+        assert_eq!(root.extract_subtree(3).unwrap().pattern, ".*x.*");
+        assert!(root.extract_subtree(4).is_none());
+        assert_eq!(root.node_mut(2).unwrap().pattern, ".*ab.*");
+    }
+
+    #[test]
+    fn test_move_subtree_and_reroot() {
+        let mut root = Node::new(".*".to_string());
+        root.children.push(Arc::new(Node::new(".*a.*".to_string())));
+        root.children
+            .push(Arc::new(Node::new(".*ab.*".to_string())));
+        root.children.push(Arc::new(Node::new(".*x.*".to_string())));
+
+        assert_eq!(
+            root.move_subtree(1, 2),
+            Err(TreeError::NotSubsumed {
+                parent: ".*ab.*".to_string(),
+                child: ".*a.*".to_string(),
+            })
+        );
+        root.move_subtree(2, 1).unwrap();
+        assert_eq!(root.children[0].children[0].pattern, ".*ab.*");
+        assert_eq!(
+            root.move_subtree(1, 2),
+            Err(TreeError::Cycle { node: 1, parent: 2 })
+        );
+        assert_eq!(root.move_subtree(0, 1), Err(TreeError::RootNotMovable));
+
+        let detached = root.reroot(1).unwrap();
+        assert_eq!(root.pattern, ".*a.*");
+        assert_eq!(root.children[0].pattern, ".*ab.*");
+        let detached: Vec<&str> = detached.iter().map(|n| n.pattern.as_str()).collect();
+        assert_eq!(detached, vec![".*x.*", ".*"]);
+    }
+
+    #[test]
+    fn test_edit_pattern() {
+        let inputs = vec!["disk full on sda", "disk slow on sdb", "net down"];
+        let mut node = Node::new(".*disk .*".to_string());
+        node.children
+            .push(Arc::new(Node::new(".*disk full.*".to_string())));
+
+        node.edit_pattern("^disk (full|slow)", &inputs).unwrap();
+        assert_eq!(node.pattern, "^disk (full|slow)");
+        assert_eq!(node.match_count(), Some(2));
+
+        assert_eq!(
+            node.edit_pattern("^disk slow", &inputs),
+            Err(TreeError::LostCoverage {
+                input: "disk full on sda".to_string(),
+            })
+        );
+        assert!(matches!(
+            node.edit_pattern("(", &inputs),
+            Err(TreeError::InvalidPattern { .. })
+        ));
+        assert_eq!(node.pattern, "^disk (full|slow)");
+    }
+
+    #[test]
+    fn test_immutable_transformations_share_subtrees() {
+        let mut root = Node::new(".*".to_string());
+        let mut a = Node::new(".*a.*".to_string());
+        a.children.push(Arc::new(Node::new(".*ab.*".to_string())));
+        root.children.push(Arc::new(a));
+        root.children.push(Arc::new(Node::new(".*x.*".to_string())));
+        root.children.push(Arc::new(Node::new(".*y.*".to_string())));
+
+        let pruned = root.with_pruned(|n| n.pattern != ".*x.*");
+        assert_eq!(pruned.children.len(), 2);
+        assert!(Arc::ptr_eq(&pruned.children[0], &root.children[0]));
+        assert!(Arc::ptr_eq(&pruned.children[1], &root.children[2]));
+
+        let compressed = pruned.with_compressed_chains();
+        assert_eq!(compressed.children[0].pattern, ".*ab.*");
+        assert_eq!(compressed.children[0].collapsed(), [".*a.*"]);
+        assert!(Arc::ptr_eq(&compressed.children[1], &root.children[2]));
+        assert_eq!(root.children.len(), 3);
+        assert_eq!(root.children[0].pattern, ".*a.*");
+    }
+
+    #[test]
+    fn test_tree_from_graph() {
+        let tree = generate_regex_tree(&["disk full", "disk slow"]);
+        let mut graph = tree_to_graph(&tree);
+        let rebuilt = tree_from_graph(&graph).unwrap();
+        let patterns = |n: &Node| {
+            n.preorder()
+                .iter()
+                .map(|n| n.pattern.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(patterns(&rebuilt), patterns(&tree));
+
+        graph.remove_node(NodeIndex::new(0));
+        assert!(matches!(
+            tree_from_graph(&graph),
+            Err(GraphError::MultipleRoots(_))
+        ));
+
+        let mut cyclic = DiGraph::new();
+        let root = cyclic.add_node(".*".to_string());
+        let a = cyclic.add_node(".*a.*".to_string());
+        let b = cyclic.add_node(".*b.*".to_string());
+        cyclic.add_edge(a, b, ());
+        cyclic.add_edge(b, a, ());
+        assert_eq!(
+            tree_from_graph(&cyclic).unwrap_err(),
+            GraphError::Unreachable { node: a.index() }
+        );
+        cyclic.add_edge(root, a, ());
+        assert_eq!(
+            tree_from_graph(&cyclic).unwrap_err(),
+            GraphError::MultipleParents { node: a.index() }
+        );
+    }
+
+    #[test]
+    fn test_tree_to_stable_graph() {
+        let tree = generate_regex_tree(&["disk full", "disk slow"]);
+        let (mut graph, ids) = tree_to_stable_graph(&tree);
+        let nodes = tree.preorder();
+        assert_eq!(ids.len(), nodes.len());
+
+        let removed = NodeIndex::new(1);
+        graph.remove_node(removed);
+        for index in graph.node_indices() {
+            assert_eq!(graph[index], nodes[ids[&index]].pattern);
+        }
+        assert_eq!(ids[&removed], 1);
+    }
+
+    #[test]
+    fn test_topo_order() {
+        let mut root = Node::new(".*".to_string());
+        let mut a = Node::new(".*a.*".to_string());
+        a.children.push(Arc::new(Node::new(".*ab.*".to_string())));
+        root.children.push(Arc::new(a));
+        root.children.push(Arc::new(Node::new(".*x.*".to_string())));
+
+        let order: Vec<&str> = root
+            .topo_order()
+            .iter()
+            .map(|n| n.pattern.as_str())
+            .collect();
+        assert_eq!(order, vec![".*", ".*a.*", ".*x.*", ".*ab.*"]);
+        let reverse: Vec<&str> = root
+            .reverse_topo_order()
             .iter()
-            .any(|n| n.pattern == ".*highlighted .*"));
+            .map(|n| n.pattern.as_str())
+            .collect();
+        assert_eq!(reverse, vec![".*ab.*", ".*x.*", ".*a.*", ".*"]);
     }
 
     #[test]
@@ -164,7 +1882,20 @@ mod tests {
             "highlighted section",
         ];
         let common_substrings = find_common_substrings(&inputs);
-        assert!(common_substrings.contains(&"highlighted ".to_string()));
+        assert!(common_substrings.contains(&"highlighted "));
+    }
+
+    #[test]
+    fn test_find_common_substrings_chunked() {
+        let inputs = vec![
+            "highlighted text",
+            "highlighted part",
+            "highlighted section",
+        ];
+        assert_eq!(
+            find_common_substrings_chunked(&inputs, 8),
+            automaton::common_substrings(&inputs, 4)
+        );
     }
 
     #[test]
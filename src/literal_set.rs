@@ -0,0 +1,214 @@
+use crate::Node;
+use regex_syntax::hir::{Class, Hir, HirKind, Look};
+
+/// The most literals a pattern may expand to and still be reported as a
+/// literal set.
+const MAX_LITERALS: usize = 64;
+
+/// The language of a pattern described as a finite set of literals, for
+/// consumers that would rather match with a hash map or an Aho–Corasick
+/// automaton than with a regex.
+///
+/// The wildcards around the literals are `.*`, which does not match a line
+/// break, so the equivalence holds for single-line inputs such as log
+/// lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiteralSet {
+    /// The pattern matches exactly these strings.
+    Exact(Vec<String>),
+    /// The pattern matches the strings starting with one of these.
+    Prefixes(Vec<String>),
+    /// The pattern matches the strings ending with one of these.
+    Suffixes(Vec<String>),
+    /// The pattern matches the strings containing one of these.
+    Contains(Vec<String>),
+}
+
+impl LiteralSet {
+    /// Returns the literals, whatever their position.
+    pub fn literals(&self) -> &[String] {
+        match self {
+            LiteralSet::Exact(literals)
+            | LiteralSet::Prefixes(literals)
+            | LiteralSet::Suffixes(literals)
+            | LiteralSet::Contains(literals) => literals,
+        }
+    }
+}
+
+impl Node {
+    /// Detects when the node's pattern amounts to a small set of literals,
+    /// possibly anchored at the start or end of inputs, such as
+    /// `^(?:GET|POST) /index$` or `^disk .*`.
+    ///
+    /// # Returns
+    ///
+    /// The literal set, or `None` if the pattern does not parse, matches
+    /// everything, or has an unbounded or too large language, such as
+    /// `\d+`.
+    pub fn as_literal_set(&self) -> Option<LiteralSet> {
+        let hir = regex_syntax::parse(&self.pattern).ok()?;
+        let mut parts: &[Hir] = match hir.kind() {
+            HirKind::Concat(parts) => parts,
+            _ => std::slice::from_ref(&hir),
+        };
+        let mut anchored = [false; 2];
+        if let Some((first, rest)) = parts.split_first() {
+            if matches!(first.kind(), HirKind::Look(Look::Start)) {
+                anchored[0] = true;
+                parts = rest;
+            } else if is_wildcard(first) {
+                parts = rest;
+            }
+        }
+        if let Some((last, rest)) = parts.split_last() {
+            if matches!(last.kind(), HirKind::Look(Look::End)) {
+                anchored[1] = true;
+                parts = rest;
+            } else if is_wildcard(last) {
+                parts = rest;
+            }
+        }
+
+        let literals = expand_concat(parts)?;
+        if !anchored[0] && !anchored[1] && literals.iter().any(String::is_empty) {
+            return None;
+        }
+        Some(match anchored {
+            [true, true] => LiteralSet::Exact(literals),
+            [true, false] => LiteralSet::Prefixes(literals),
+            [false, true] => LiteralSet::Suffixes(literals),
+            [false, false] => LiteralSet::Contains(literals),
+        })
+    }
+}
+
+/// Returns whether `hir` is an unbounded repetition of any character.
+fn is_wildcard(hir: &Hir) -> bool {
+    let HirKind::Repetition(repetition) = hir.kind() else {
+        return false;
+    };
+    repetition.min == 0
+        && repetition.max.is_none()
+        && matches!(repetition.sub.kind(), HirKind::Class(Class::Unicode(class))
+            if class.ranges().first().is_some_and(|r| r.start() == '\0')
+                && class.ranges().last().is_some_and(|r| r.end() == char::MAX))
+}
+
+/// Expands a sequence of expressions into the strings it matches, if there
+/// are at most [`MAX_LITERALS`] of them.
+fn expand_concat(parts: &[Hir]) -> Option<Vec<String>> {
+    let mut literals = vec![String::new()];
+    for part in parts {
+        literals = concat_lists(&literals, &expand(part)?)?;
+    }
+    Some(literals)
+}
+
+fn expand(hir: &Hir) -> Option<Vec<String>> {
+    match hir.kind() {
+        HirKind::Empty => Some(vec![String::new()]),
+        HirKind::Literal(literal) => Some(vec![String::from_utf8(literal.0.to_vec()).ok()?]),
+        HirKind::Class(Class::Unicode(class)) => {
+            let size: usize = class
+                .ranges()
+                .iter()
+                .map(|r| r.end() as usize - r.start() as usize + 1)
+                .sum();
+            if size > MAX_LITERALS {
+                return None;
+            }
+            Some(
+                class
+                    .ranges()
+                    .iter()
+                    .flat_map(|r| r.start()..=r.end())
+                    .map(String::from)
+                    .collect(),
+            )
+        }
+        HirKind::Capture(capture) => expand(&capture.sub),
+        HirKind::Concat(parts) => expand_concat(parts),
+        HirKind::Alternation(branches) => {
+            let mut literals = vec![];
+            for branch in branches {
+                literals.extend(expand(branch)?);
+                if literals.len() > MAX_LITERALS {
+                    return None;
+                }
+            }
+            Some(literals)
+        }
+        HirKind::Repetition(repetition) => {
+            let max = repetition.max? as usize;
+            let once = expand(&repetition.sub)?;
+            let mut literals = vec![];
+            let mut current = vec![String::new()];
+            for count in 0..=max {
+                if count >= repetition.min as usize {
+                    literals.extend(current.iter().cloned());
+                }
+                if count < max {
+                    current = concat_lists(&current, &once)?;
+                }
+                if literals.len() > MAX_LITERALS {
+                    return None;
+                }
+            }
+            Some(literals)
+        }
+        _ => None,
+    }
+}
+
+/// Returns every prefix followed by every suffix, if there are at most
+/// [`MAX_LITERALS`] combinations.
+fn concat_lists(prefixes: &[String], suffixes: &[String]) -> Option<Vec<String>> {
+    if prefixes.len() * suffixes.len() > MAX_LITERALS {
+        return None;
+    }
+    Some(
+        prefixes
+            .iter()
+            .flat_map(|prefix| suffixes.iter().map(move |s| format!("{prefix}{s}")))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal_set(pattern: &str) -> Option<LiteralSet> {
+        Node::new(pattern.to_string()).as_literal_set()
+    }
+
+    #[test]
+    fn test_as_literal_set() {
+        assert_eq!(
+            literal_set(r"^(?:GET|POST) /index$"),
+            Some(LiteralSet::Exact(vec![
+                "GET /index".to_string(),
+                "POST /index".to_string()
+            ]))
+        );
+        assert_eq!(
+            literal_set(r"^disk sd[ab].*"),
+            Some(LiteralSet::Prefixes(vec![
+                "disk sda".to_string(),
+                "disk sdb".to_string()
+            ]))
+        );
+        assert_eq!(
+            literal_set(r".*\.log$"),
+            Some(LiteralSet::Suffixes(vec![".log".to_string()]))
+        );
+        assert_eq!(
+            literal_set(r".*disk.*").unwrap().literals(),
+            ["disk".to_string()]
+        );
+        assert_eq!(literal_set(".*"), None);
+        assert_eq!(literal_set(r"^\d+$"), None);
+        assert_eq!(literal_set(r"^\w$"), None);
+    }
+}
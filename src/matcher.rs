@@ -0,0 +1,158 @@
+use crate::{Node, TreeError};
+use aho_corasick::AhoCorasick;
+use regex::Regex;
+use regex_syntax::hir::{Hir, HirKind};
+
+/// Matches inputs against the leaves of a tree.
+///
+/// Every leaf pattern is analysed for a literal that any match must
+/// contain, and all these literals are searched at once with an
+/// Aho–Corasick automaton, so a leaf's regex only runs on inputs
+/// containing its literal. Leaves without such a literal are always tried.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    leaves: Vec<Leaf>,
+    prefilter: Option<AhoCorasick>,
+}
+
+#[derive(Debug, Clone)]
+struct Leaf {
+    id: usize,
+    regex: Regex,
+    literal: Option<usize>,
+}
+
+impl Matcher {
+    /// Compiles the leaves of a tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root node of the regex pattern tree.
+    ///
+    /// # Returns
+    ///
+    /// The matcher, or an error if a leaf pattern does not compile.
+    pub fn new(root: &Node) -> Result<Matcher, TreeError> {
+        let mut literals: Vec<Vec<u8>> = vec![];
+        let mut leaves = vec![];
+        for (id, node) in root.preorder().into_iter().enumerate() {
+            if !node.children.is_empty() {
+                continue;
+            }
+            let invalid = |message: String| TreeError::InvalidPattern {
+                pattern: node.pattern.to_string(),
+                message,
+            };
+            let regex = Regex::new(&node.pattern).map_err(|e| invalid(e.to_string()))?;
+            let hir = regex_syntax::parse(&node.pattern).map_err(|e| invalid(e.to_string()))?;
+            let literal = required_literal(&hir).map(|literal| {
+                match literals.iter().position(|l| *l == literal) {
+                    Some(index) => index,
+                    None => {
+                        literals.push(literal);
+                        literals.len() - 1
+                    }
+                }
+            });
+            leaves.push(Leaf { id, regex, literal });
+        }
+
+        let prefilter = if literals.is_empty() {
+            None
+        } else {
+            Some(AhoCorasick::new(&literals).expect("literal sets always build"))
+        };
+        Ok(Matcher { leaves, prefilter })
+    }
+
+    /// Returns the pre-order identifiers of the leaves matching `input`.
+    pub fn matches(&self, input: &str) -> Vec<usize> {
+        let present = self.present_literals(input);
+        self.leaves
+            .iter()
+            .filter(|leaf| leaf.literal.is_none_or(|l| present[l]))
+            .filter(|leaf| leaf.regex.is_match(input))
+            .map(|leaf| leaf.id)
+            .collect()
+    }
+
+    /// Returns whether any leaf matches `input`.
+    pub fn is_match(&self, input: &str) -> bool {
+        let present = self.present_literals(input);
+        self.leaves
+            .iter()
+            .any(|leaf| leaf.literal.is_none_or(|l| present[l]) && leaf.regex.is_match(input))
+    }
+
+    /// Returns the number of leaves whose regex runs only on inputs
+    /// containing a required literal.
+    pub fn prefiltered_leaves(&self) -> usize {
+        self.leaves.iter().filter(|l| l.literal.is_some()).count()
+    }
+
+    fn present_literals(&self, input: &str) -> Vec<bool> {
+        let Some(prefilter) = &self.prefilter else {
+            return vec![];
+        };
+        let mut present = vec![false; prefilter.patterns_len()];
+        for found in prefilter.find_overlapping_iter(input) {
+            present[found.pattern().as_usize()] = true;
+        }
+        present
+    }
+}
+
+/// Returns the longest literal every match of `hir` contains, if any.
+pub(crate) fn required_literal(hir: &Hir) -> Option<Vec<u8>> {
+    match hir.kind() {
+        HirKind::Literal(literal) => Some(literal.0.to_vec()),
+        HirKind::Capture(capture) => required_literal(&capture.sub),
+        HirKind::Repetition(repetition) if repetition.min > 0 => required_literal(&repetition.sub),
+        HirKind::Concat(parts) => {
+            let mut longest: Option<Vec<u8>> = None;
+            let mut run: Vec<u8> = vec![];
+            for part in parts {
+                if let HirKind::Literal(literal) = part.kind() {
+                    run.extend_from_slice(&literal.0);
+                    continue;
+                }
+                let candidates = [
+                    std::mem::take(&mut run),
+                    required_literal(part).unwrap_or_default(),
+                ];
+                for candidate in candidates {
+                    if candidate.len() > longest.as_ref().map_or(0, Vec::len) {
+                        longest = Some(candidate);
+                    }
+                }
+            }
+            if run.len() > longest.as_ref().map_or(0, Vec::len) {
+                longest = Some(run);
+            }
+            longest
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_matcher_prefilter() {
+        let mut root = Node::new(".*".to_string());
+        for pattern in [r"^disk (sd\w+) full", r"net(work)? down", r"\d+%", "[xy]z?"] {
+            root.children.push(Arc::new(Node::new(pattern.to_string())));
+        }
+        let matcher = Matcher::new(&root).unwrap();
+        assert_eq!(matcher.prefiltered_leaves(), 3);
+        assert_eq!(matcher.matches("disk sda full at 93%"), [1, 3]);
+        assert_eq!(matcher.matches("network down"), [2]);
+        assert!(!matcher.is_match("cpu hot"));
+
+        let hir = regex_syntax::parse(r"^disk (sd\w+) full").unwrap();
+        assert_eq!(required_literal(&hir), Some(b"disk ".to_vec()));
+    }
+}
@@ -0,0 +1,374 @@
+use crate::{
+    contains_pattern, find_common_substrings, generate_regex_tree, subsumes, ModelError, Node,
+    Record, TemplateId,
+};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// A regex pattern tree together with the examples it was trained on.
+///
+/// Nodes are identified by their position in a pre-order walk of the tree,
+/// the root being `0`. An input is routed by descending from the root into
+/// the first child whose pattern matches it, so its node is the most
+/// specific one on that path.
+#[derive(Debug, Clone)]
+pub struct Model {
+    root: Node,
+    examples: Vec<String>,
+    compiled: Vec<Regex>,
+    catalog: HashMap<TemplateId, String>,
+}
+
+impl Model {
+    /// Generates a tree from `strings` and records them as examples.
+    ///
+    /// # Arguments
+    ///
+    /// * `strings` - A slice of strings to generate the regex pattern tree from.
+    ///
+    /// # Returns
+    ///
+    /// The trained model.
+    pub fn train(strings: &[&str]) -> Model {
+        let root = generate_regex_tree(strings);
+        let examples = strings.iter().map(|s| s.to_string()).collect();
+        Model::from_parts(root, examples)
+    }
+
+    /// Seeds a model with an existing catalog of templates, so later
+    /// training extends and refines it rather than starting from zero.
+    ///
+    /// Each template is nested under the most specific other template
+    /// matching everything it matches, and the others hang off a `.*`
+    /// root, siblings keeping the catalog order. The model starts without
+    /// examples.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - The `(id, regex)` pairs of the catalog.
+    ///
+    /// # Returns
+    ///
+    /// The seeded model, or an error if a template does not compile.
+    pub fn import_templates(patterns: &[(&str, &str)]) -> Result<Model, ModelError> {
+        let mut root = Node::new(".*".to_string());
+        let mut catalog = HashMap::new();
+        for &(id, pattern) in patterns {
+            Regex::new(pattern).map_err(|e| ModelError::InvalidTemplate {
+                id: id.to_string(),
+                message: e.to_string(),
+            })?;
+            nest(&mut root, Node::new(pattern.to_string()));
+            catalog.insert(TemplateId::of(pattern), id.to_string());
+        }
+        let mut model = Model::from_parts(root, vec![]);
+        model.catalog = catalog;
+        Ok(model)
+    }
+
+    fn from_parts(root: Node, examples: Vec<String>) -> Model {
+        let mut model = Model {
+            root,
+            examples,
+            compiled: vec![],
+            catalog: HashMap::new(),
+        };
+        model.compile();
+        model
+    }
+
+    /// Returns the catalog identifier of a node imported with
+    /// [`Model::import_templates`], or `None` if the node was generated or
+    /// its pattern has been corrected since.
+    pub fn catalog_id(&self, id: usize) -> Option<&str> {
+        let node = self.root.preorder().get(id).copied()?;
+        self.catalog.get(&node.template_id()).map(String::as_str)
+    }
+
+    /// Records new examples and refines the tree with them.
+    ///
+    /// The examples are routed, and those reaching the same node, when
+    /// there are at least two, are mined for a subtree grafted beneath it,
+    /// so imported templates are refined without being replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `strings` - The new examples.
+    pub fn extend(&mut self, strings: &[&str]) {
+        let mut routed: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+        for input in strings {
+            if let Some(id) = self.classify(input) {
+                routed.entry(id).or_default().push(input);
+            }
+        }
+        // Grafting beneath a node only shifts the identifiers after it, so
+        // the later nodes are refined first.
+        for (id, group) in routed.into_iter().rev() {
+            if group.len() < 2 {
+                continue;
+            }
+            let mined = generate_regex_tree(&group);
+            let Some(node) = self.root.node_mut(id) else {
+                continue;
+            };
+            for child in mined.children {
+                let known = child.pattern == node.pattern
+                    || node.children.iter().any(|c| c.pattern == child.pattern);
+                if !known {
+                    node.children.push(child);
+                }
+            }
+        }
+        self.examples.extend(strings.iter().map(|s| s.to_string()));
+        self.compile();
+    }
+
+    /// Returns the root node of the model's tree.
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// Returns the compiled pattern of every node, in pre-order.
+    pub(crate) fn compiled(&self) -> &[Regex] {
+        &self.compiled
+    }
+
+    /// Returns the examples recorded so far.
+    pub fn examples(&self) -> &[String] {
+        &self.examples
+    }
+
+    /// Routes `input` to the most specific node matching it.
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the node, or `None` if not even the root matches.
+    pub fn classify(&self, input: &str) -> Option<usize> {
+        if !self.compiled[0].is_match(input) {
+            return None;
+        }
+        Some(self.route(&self.root, 0, input))
+    }
+
+    /// Routes `input` like [`Model::classify`] and extracts the named
+    /// capture groups of the node it reaches.
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the node with the record of each named group that
+    /// participated in the match, or `None` if not even the root matches.
+    pub fn extract(&self, input: &str) -> Option<(usize, Record)> {
+        let id = self.classify(input)?;
+        let regex = &self.compiled[id];
+        let record = Record::capture(TemplateId::of(regex.as_str()), regex, input)?;
+        Some((id, record))
+    }
+
+    fn route(&self, node: &Node, id: usize, input: &str) -> usize {
+        let mut child_id = id + 1;
+        for child in &node.children {
+            if self.compiled[child_id].is_match(input) {
+                return self.route(child, child_id, input);
+            }
+            child_id += child.size();
+        }
+        id
+    }
+
+    /// Records a misrouted example and adjusts the affected patterns.
+    ///
+    /// Every node that wrongly captures `input` is tightened to a longer
+    /// substring shared by the examples it matches but absent from `input`,
+    /// and the expected node is widened to a substring shared by its
+    /// examples and `input`. The correction is only kept if `input` then
+    /// reaches `expected` and every previous example is routed as before.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The misrouted example.
+    /// * `expected` - The identifier of the node it should reach.
+    ///
+    /// # Returns
+    ///
+    /// An error, leaving the model untouched, if the patterns cannot be
+    /// adjusted without breaking earlier examples.
+    pub fn correct(&mut self, input: &str, expected: usize) -> Result<(), ModelError> {
+        if expected >= self.compiled.len() {
+            return Err(ModelError::UnknownNode(expected));
+        }
+
+        let before: Vec<Option<usize>> = self.examples.iter().map(|e| self.classify(e)).collect();
+        let snapshot = self.root.clone();
+
+        let result = self.adjust(input, expected).and_then(|()| {
+            for (example, route) in self.examples.iter().zip(&before) {
+                if self.classify(example) != *route {
+                    return Err(ModelError::Regression {
+                        example: example.clone(),
+                    });
+                }
+            }
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {
+                self.examples.push(input.to_string());
+                Ok(())
+            }
+            Err(error) => {
+                self.root = snapshot;
+                self.compile();
+                Err(error)
+            }
+        }
+    }
+
+    fn adjust(&mut self, input: &str, expected: usize) -> Result<(), ModelError> {
+        if !self.compiled[expected].is_match(input) {
+            let mut supporting = self.matched_examples(expected);
+            supporting.push(input);
+            let literal = find_common_substrings(&supporting)
+                .into_iter()
+                .next()
+                .ok_or(ModelError::CannotWiden { node: expected })?;
+            let pattern = contains_pattern(literal);
+            self.set_pattern(expected, pattern);
+        }
+
+        for _ in 0..self.compiled.len() {
+            match self.classify(input) {
+                Some(actual) if actual == expected => return Ok(()),
+                Some(actual) if actual != 0 && !self.is_ancestor(actual, expected) => {
+                    let supporting = self.matched_examples(actual);
+                    let literal = if supporting.is_empty() {
+                        None
+                    } else {
+                        find_common_substrings(&supporting)
+                            .into_iter()
+                            .find(|l| !input.contains(l))
+                    };
+                    let literal = literal.ok_or(ModelError::CannotTighten { node: actual })?;
+                    let pattern = contains_pattern(literal);
+                    self.set_pattern(actual, pattern);
+                }
+                _ => break,
+            }
+        }
+        Err(ModelError::NotRouted {
+            input: input.to_string(),
+            expected,
+        })
+    }
+
+    fn matched_examples(&self, id: usize) -> Vec<&str> {
+        self.examples
+            .iter()
+            .filter(|e| self.compiled[id].is_match(e))
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn is_ancestor(&self, ancestor: usize, id: usize) -> bool {
+        let size = self.root.preorder()[ancestor].size();
+        ancestor < id && id < ancestor + size
+    }
+
+    fn set_pattern(&mut self, id: usize, pattern: String) {
+        if let Some(node) = self.root.node_mut(id) {
+            self.compiled[id] = Regex::new(&pattern).expect("escaped literal patterns compile");
+            node.pattern = pattern.into();
+        }
+    }
+
+    fn compile(&mut self) {
+        self.compiled = self
+            .root
+            .preorder()
+            .iter()
+            .map(|n| Regex::new(&n.pattern).expect("generated patterns compile"))
+            .collect();
+    }
+}
+
+/// Inserts `node` beneath the deepest descendant of `parent` subsuming it,
+/// moving beneath it the children it subsumes in turn.
+fn nest(parent: &mut Node, mut node: Node) {
+    for child in &mut parent.children {
+        if subsumes(&child.pattern, &node.pattern) {
+            nest(Arc::make_mut(child), node);
+            return;
+        }
+    }
+    let (subsumed, kept) = std::mem::take(&mut parent.children)
+        .into_iter()
+        .partition(|child| subsumes(&node.pattern, &child.pattern));
+    node.children = subsumed;
+    parent.children = kept;
+    parent.children.push(Arc::new(node));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correct_misrouted_example() {
+        let mut model = Model::train(&["disk full on sda", "disk full on sdb"]);
+        let expected = model
+            .root()
+            .preorder()
+            .iter()
+            .position(|n| n.pattern == "^disk .*")
+            .unwrap();
+        assert_ne!(model.classify("disk full on nvme0"), Some(expected));
+        let sda = model.classify("disk full on sda");
+        let sdb = model.classify("disk full on sdb");
+
+        model.correct("disk full on nvme0", expected).unwrap();
+
+        assert_eq!(model.classify("disk full on nvme0"), Some(expected));
+        assert_eq!(model.classify("disk full on sda"), sda);
+        assert_eq!(model.classify("disk full on sdb"), sdb);
+        assert_eq!(model.examples().len(), 3);
+
+        assert_eq!(
+            model.correct("disk full on sdc", 10_000),
+            Err(ModelError::UnknownNode(10_000))
+        );
+    }
+
+    #[test]
+    fn test_import_templates() {
+        let catalog = [
+            ("disk-full", r"^disk full .*"),
+            ("disk", r"^disk .*"),
+            ("net", r"^net .*"),
+        ];
+        let mut model = Model::import_templates(&catalog).unwrap();
+        let patterns: Vec<&str> = model
+            .root()
+            .preorder()
+            .iter()
+            .map(|n| n.pattern.as_str())
+            .collect();
+        assert_eq!(patterns, [".*", "^disk .*", "^disk full .*", "^net .*"]);
+        let id = model.classify("disk full on sda").unwrap();
+        assert_eq!(model.catalog_id(id), Some("disk-full"));
+
+        model.extend(&["net down on eth0", "net down on eth1"]);
+        let id = model.classify("net down on eth2").unwrap();
+        assert!(model.root().preorder()[id]
+            .pattern
+            .contains("net down on eth"));
+        assert_eq!(model.catalog_id(id), None);
+        assert_eq!(model.catalog_id(3), Some("net"));
+        assert_eq!(model.examples().len(), 2);
+
+        assert!(matches!(
+            Model::import_templates(&[("broken", "(")]),
+            Err(ModelError::InvalidTemplate { .. })
+        ));
+    }
+}
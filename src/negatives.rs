@@ -0,0 +1,121 @@
+use crate::alternation::{alternation_pattern, region_values};
+use crate::subsumption::anchored_literal;
+use crate::{build_tree, find_common_substrings, Children, GenerateError, Node};
+use regex::Regex;
+use std::sync::Arc;
+
+/// The most distinct values a region may take when a leaf is refined into
+/// an alternation to reject the negatives.
+const MAX_REFINED_ALTERNATIVES: usize = 16;
+
+/// Generates a regex pattern tree whose nodes match every positive and none
+/// of the negatives.
+///
+/// Candidates are mined from the positives as usual, then each node is
+/// verified against the negatives. A leaf matching a negative is refined by
+/// spelling out its wildcard regions as alternations of the values the
+/// positives take there, when there are few enough; a node that still
+/// matches a negative is removed and its children re-linked to its parent.
+/// Only the root, which matches everything, is exempt.
+///
+/// # Arguments
+///
+/// * `positives` - The strings every pattern must match.
+/// * `negatives` - The strings no pattern may match.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree, or an error if there are no
+/// positives or no shared pattern rejects all negatives.
+pub fn generate_regex_tree_with_negatives(
+    positives: &[&str],
+    negatives: &[&str],
+) -> Result<Node, GenerateError> {
+    if positives.is_empty() {
+        return Err(GenerateError::EmptyInput);
+    }
+    let rejects = |pattern: &str| {
+        Regex::new(pattern).is_ok_and(|regex| !negatives.iter().any(|n| regex.is_match(n)))
+    };
+    let mut root = build_tree(&find_common_substrings(positives), positives);
+    separate(&mut root, positives, &rejects);
+    if root.children.is_empty() {
+        return Err(GenerateError::NoSeparatingPattern);
+    }
+    Ok(root)
+}
+
+/// Refines or removes the descendants of `node` whose pattern is not
+/// accepted by `rejects`, deepest first.
+fn separate<F: Fn(&str) -> bool>(node: &mut Node, positives: &[&str], rejects: &F) {
+    let mut kept = Children::new();
+    for child in std::mem::take(&mut node.children) {
+        let mut child = Arc::unwrap_or_clone(child);
+        separate(&mut child, positives, rejects);
+        if rejects(&child.pattern) {
+            kept.push(Arc::new(child));
+            continue;
+        }
+        if child.children.is_empty() {
+            if let Some(refined) = refine(&child.pattern, positives).filter(|p| rejects(p)) {
+                child.pattern = refined.into();
+                kept.push(Arc::new(child));
+            }
+            continue;
+        }
+        kept.extend(child.children);
+    }
+    node.children = kept;
+}
+
+/// Returns the pattern with its wildcard regions restricted to the values
+/// the positives take there, if any region takes few enough.
+fn refine(pattern: &str, positives: &[&str]) -> Option<String> {
+    let (role, literal) = anchored_literal(pattern)?;
+    let [before, after] = region_values(role, &literal, positives)
+        .map(|region| region.filter(|values| values.len() <= MAX_REFINED_ALTERNATIVES));
+    if before.is_none() && after.is_none() {
+        return None;
+    }
+    Some(alternation_pattern(
+        role,
+        &literal,
+        before.as_deref(),
+        after.as_deref(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_regex_tree_with_negatives() {
+        let positives = ["ERROR disk sda full", "ERROR disk sdb full"];
+        let negatives = ["WARN disk sda full", "ERROR cpu hot"];
+        let tree = generate_regex_tree_with_negatives(&positives, &negatives).unwrap();
+        assert!(!tree.children.is_empty());
+        for node in &tree.preorder()[1..] {
+            let regex = Regex::new(&node.pattern).unwrap();
+            assert!(
+                positives.iter().all(|p| regex.is_match(p)),
+                "{}",
+                node.pattern
+            );
+            assert!(
+                !negatives.iter().any(|n| regex.is_match(n)),
+                "{}",
+                node.pattern
+            );
+        }
+        assert!(tree
+            .preorder()
+            .iter()
+            .any(|n| n.pattern == "^ERROR disk sd.*"));
+
+        assert_eq!(
+            generate_regex_tree_with_negatives(&positives, &positives).unwrap_err(),
+            GenerateError::NoSeparatingPattern
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::alternation::{region_pattern, region_values, write_alternation};
+use crate::classes::record_outliers;
+use crate::privacy::SupportGuard;
+use crate::subsumption::anchored_literal;
+use crate::{Node, Percentiles};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// How a region of a leaf pattern holding an integer in every input, such
+/// as a port or a status code, is described.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NumericStrategy {
+    /// The observed numbers spelled out in ascending order, such as
+    /// `(?:200|404|503)`.
+    Alternation,
+    /// The range of the leading digit followed by the remaining digits,
+    /// such as `[2-5]\d{2}`, or a bounded run of digits, such as `\d{2,4}`,
+    /// when the numbers have different lengths.
+    Range,
+}
+
+/// Replaces the wildcard regions of leaf patterns where every input holds
+/// an unsigned integer with a bounded numeric pattern, such as
+/// `^code [2-5]\d{2}$` for `code 200` and `code 503`.
+///
+/// # Arguments
+///
+/// * `node` - The root of the tree to rewrite.
+/// * `inputs` - The strings the tree was mined from.
+/// * `strategy` - How the numbers are described.
+/// * `percentiles` - When set, the percentiles of the lengths a bounded
+///   run of digits spans; the inputs left out are recorded on the leaf.
+/// * `guard` - Decides which numbers are common enough to be spelled out
+///   in an alternation; a region with a rarer one falls back to a range.
+pub(crate) fn infer_numeric_ranges(
+    node: &mut Node,
+    inputs: &[&str],
+    strategy: NumericStrategy,
+    percentiles: Option<Percentiles>,
+    guard: &SupportGuard,
+) {
+    for child in &mut node.children {
+        infer_numeric_ranges(Arc::make_mut(child), inputs, strategy, percentiles, guard);
+    }
+    if !node.children.is_empty() {
+        return;
+    }
+    let Some((role, literal)) = anchored_literal(&node.pattern) else {
+        return;
+    };
+    let [before, after] = region_values(role, &literal, inputs)
+        .into_iter()
+        .enumerate()
+        .map(|(side, region)| {
+            let values = region?;
+            let strategy = match strategy {
+                NumericStrategy::Alternation if !values.iter().all(|v| guard.allows(v)) => {
+                    NumericStrategy::Range
+                }
+                strategy => strategy,
+            };
+            let varying = values.iter().any(|v| v.len() != values[0].len());
+            let (values, outliers) = match percentiles {
+                Some(percentiles)
+                    if strategy == NumericStrategy::Range
+                        && varying
+                        && values.iter().all(|v| is_unsigned_integer(v)) =>
+                {
+                    percentiles.trim(role, &literal, inputs, side == 0, &values)
+                }
+                _ => (values, vec![]),
+            };
+            Some((numeric_fragment(values, strategy)?, outliers))
+        })
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap_or([None, None]);
+    if before.is_some() || after.is_some() {
+        node.pattern = region_pattern(
+            role,
+            &literal,
+            before.as_ref().map(|(fragment, _)| fragment.as_str()),
+            after.as_ref().map(|(fragment, _)| fragment.as_str()),
+        )
+        .into();
+        if !guard.is_active() {
+            record_outliers(
+                node,
+                [before, after].into_iter().flatten().flat_map(|(_, o)| o),
+            );
+        }
+    }
+}
+
+/// Returns whether `value` is a non-empty run of ASCII digits.
+fn is_unsigned_integer(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Returns a regex fragment matching every value, if they are all
+/// unsigned integers.
+fn numeric_fragment(mut values: Vec<&str>, strategy: NumericStrategy) -> Option<String> {
+    if values.is_empty() || !values.iter().all(|v| is_unsigned_integer(v)) {
+        return None;
+    }
+    let mut fragment = String::new();
+    match strategy {
+        NumericStrategy::Alternation => {
+            values.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+            write_alternation(&values, &mut fragment);
+        }
+        NumericStrategy::Range => {
+            let min = values.iter().map(|v| v.len()).min()?;
+            let max = values.iter().map(|v| v.len()).max()?;
+            if min != max {
+                write!(fragment, r"\d{{{min},{max}}}").unwrap();
+                return Some(fragment);
+            }
+            let low = values.iter().map(|v| v.as_bytes()[0]).min()? as char;
+            let high = values.iter().map(|v| v.as_bytes()[0]).max()? as char;
+            match (low, high) {
+                ('0', '9') => fragment.push_str(r"\d"),
+                _ if low == high => fragment.push(low),
+                _ => write!(fragment, "[{low}-{high}]").unwrap(),
+            }
+            match min - 1 {
+                0 => {}
+                1 => fragment.push_str(r"\d"),
+                rest => write!(fragment, r"\d{{{rest}}}").unwrap(),
+            }
+        }
+    }
+    Some(fragment)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_regex_tree_with_options, GenerateOptions, NumericStrategy};
+
+    #[test]
+    fn test_infer_numeric_ranges() {
+        let inputs = ["code 200", "code 404", "code 503"];
+        let patterns = |strategy| {
+            let options = GenerateOptions {
+                numeric_strategy: Some(strategy),
+                ..GenerateOptions::default()
+            };
+            let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+            tree.preorder()
+                .iter()
+                .map(|n| n.pattern.to_string())
+                .collect::<Vec<String>>()
+        };
+        let range = patterns(NumericStrategy::Range);
+        assert!(
+            range.contains(&r"^code [2-5]\d{2}$".to_string()),
+            "{range:?}"
+        );
+        let alternation = patterns(NumericStrategy::Alternation);
+        assert!(
+            alternation.contains(&"^code (?:200|404|503)$".to_string()),
+            "{alternation:?}"
+        );
+
+        let tree = generate_regex_tree_with_options(&inputs, &GenerateOptions::default()).unwrap();
+        assert!(tree.preorder().iter().all(|n| !n.pattern.contains(r"\d")));
+    }
+}
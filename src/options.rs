@@ -0,0 +1,183 @@
+use crate::{
+    AlternationOrder, CharClassDetector, Clustering, DocumentFrequencies, GapStrategy,
+    GraphemeMode, NumericStrategy, Percentiles, SecretPolicy, Stratification, TieBreak, Tokenizer,
+};
+
+/// Options controlling how candidate substrings are selected and ranked.
+#[derive(Debug, Clone, Default)]
+pub struct GenerateOptions {
+    /// Background document frequencies used to down-weight substrings that
+    /// are ubiquitous in general text.
+    pub background: Option<DocumentFrequencies>,
+    /// Substrings that are never offered as candidates, such as a company
+    /// name present in every line.
+    ///
+    /// A candidate is dropped when it contains a stop substring or is itself
+    /// part of one.
+    pub stop_substrings: Vec<String>,
+    /// Literals that must appear in the tree, such as the word `ERROR`.
+    ///
+    /// They are placed first and the remaining candidates are arranged
+    /// around them; generation fails if one is absent from some input.
+    /// A required literal takes precedence over the stop list.
+    pub required_literals: Vec<String>,
+    /// When set, an input longer than this many bytes is mined in chunks of
+    /// this length overlapping by half, so a single pathological line, such
+    /// as minified JSON, cannot dominate the search. Candidates are then at
+    /// most half a chunk long.
+    pub chunk_length: Option<usize>,
+    /// When set, a wildcard region of a leaf pattern that takes at most
+    /// this many distinct values across the inputs, such as an HTTP method,
+    /// is replaced with an alternation of those values, with the text they
+    /// share up to a punctuation character factored out of the group, as in
+    /// `status=(?:OK|FAIL)`. A region with more values falls back to one of
+    /// [`GenerateOptions::char_classes`] when it fits, and stays a wildcard
+    /// otherwise.
+    pub max_alternatives: Option<usize>,
+    /// The order in which the values of those alternations are written,
+    /// which decides how quickly a backtracking engine, such as PCRE or
+    /// JavaScript's, finds the branch matching an input. The order is
+    /// recorded in [`crate::Node::alternation_order`].
+    pub alternation_order: AlternationOrder,
+    /// When set, a wildcard region of a leaf pattern holding an unsigned
+    /// integer in every input, such as a status code, is replaced with a
+    /// bounded numeric pattern, unless already spelled out as an
+    /// alternation.
+    pub numeric_strategy: Option<NumericStrategy>,
+    /// The character classes a wildcard region of a leaf pattern may be
+    /// generalized to when the inputs align there, such as `\d{4}` for an
+    /// order number. Regions already spelled out as alternations are left
+    /// alone. Empty, and so disabled, by default.
+    pub char_classes: Vec<CharClassDetector>,
+    /// How a wildcard region of a leaf pattern left after the other
+    /// generalizations is written: by default it stays `.*`, but it may be
+    /// bounded to the lengths observed across the inputs, as in `.{3,8}`,
+    /// or also narrowed to the class of the characters seen there, as in
+    /// `\w{2,5}`.
+    pub gap_strategy: GapStrategy,
+    /// When set, the patterns are rewritten to read more easily without
+    /// changing what they match, such as `\d+` for `[0-9]{1,}`, before any
+    /// POSIX rewrite; see [`crate::Node::humanize`].
+    pub humanize: bool,
+    /// When set, the `\d`, `\s` and `\w` classes of every pattern are
+    /// written as the POSIX bracket expressions `[[:digit:]]`,
+    /// `[[:space:]]` and `[[:alnum:]_]`, which grep, sed, awk and databases
+    /// supporting only POSIX extended regular expressions understand. Those
+    /// only match ASCII characters, where the shorthands match any Unicode
+    /// digit, space or word character.
+    pub posix_classes: bool,
+    /// When set, only candidates starting and ending on token boundaries
+    /// in every input are kept, so structured text such as CSV rows or log
+    /// lines yields whole fields like `"GET` instead of fragments shared by
+    /// unrelated values, such as a `0` common to every IP address.
+    pub tokenizer: Option<Tokenizer>,
+    /// How candidates of equal score are ordered, which decides the ones
+    /// the tree is built around: by default the longer one, then the one
+    /// occurring earlier in the first input.
+    pub tie_break: TieBreak,
+    /// When set, literals keep to the boundaries of these grapheme
+    /// clusters in every input rather than only to char boundaries, so an
+    /// accent written as a combining mark or an emoji modifier is never
+    /// split from the character it belongs to.
+    pub grapheme_mode: Option<GraphemeMode>,
+    /// When set, the `{m,n}` bounds of regions generalized to a run of a
+    /// character class or of digits span these percentiles of the lengths
+    /// observed across inputs, rather than the shortest to the longest, so
+    /// a single anomalous input cannot stretch them. The inputs left out
+    /// are reported in [`crate::Node::bound_outliers`].
+    pub length_percentiles: Option<Percentiles>,
+    /// How many example inputs to attach to each node for reviewers, and
+    /// to include in exports. None are attached by default.
+    pub examples_per_node: usize,
+    /// When set, no literal occurring in fewer than this many distinct
+    /// inputs is emitted, since it may be a secret or personal data from a
+    /// single record: such values are not spelled out in alternations or
+    /// recorded as observed values, their regions being generalized
+    /// instead, the common substrings are dropped altogether when there
+    /// are fewer distinct inputs, and no examples are attached.
+    pub min_literal_support: Option<usize>,
+    /// When set, the inputs are scanned for likely secrets, such as API
+    /// keys, bearer tokens and passwords in URLs, see
+    /// [`crate::scan_secrets`], and generation either fails listing them
+    /// or avoids spelling out any part of them.
+    pub secret_policy: Option<SecretPolicy>,
+    /// When set, inputs of very different sizes are generated apart, each
+    /// band of sizes yielding its own subtree beneath a node bounding the
+    /// size, so long inputs do not wash out the structure of short ones.
+    pub stratification: Option<Stratification>,
+    /// When set, inputs of several distinct shapes, such as two log formats
+    /// mixed together, are partitioned into clusters of similar inputs,
+    /// each yielding its own subtree beneath a node matching it, rather
+    /// than all collapsing to `.*`. The root is then the alternation of
+    /// those nodes.
+    pub clustering: Option<Clustering>,
+    /// When set, up to this many inputs may be left unmatched, so that a
+    /// few corrupted lines do not force the whole tree down to `.*`. The
+    /// inputs blocking the most specific pattern are dropped first; see
+    /// [`crate::generate_regex_tree_tolerant`].
+    pub tolerance: Option<Tolerance>,
+}
+
+impl GenerateOptions {
+    /// Describes the options deterministically, for hashing. A custom
+    /// tokenizer is only described as such, since its address varies
+    /// between builds.
+    pub(crate) fn fingerprint(&self) -> String {
+        let background = self.background.as_ref().map(|b| b.fingerprint());
+        let tokenizer = match &self.tokenizer {
+            Some(Tokenizer::Custom(_)) => "Some(Custom)".to_string(),
+            tokenizer => format!("{tokenizer:?}"),
+        };
+        format!(
+            "{background:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {} {} {:?} {tokenizer} {:?} {:?} {} {:?} {:?} {:?} {:?} {:?}",
+            self.stop_substrings,
+            self.required_literals,
+            self.chunk_length,
+            self.max_alternatives,
+            self.alternation_order,
+            self.numeric_strategy,
+            self.char_classes,
+            self.gap_strategy,
+            self.humanize,
+            self.posix_classes,
+            self.length_percentiles,
+            self.tie_break,
+            self.grapheme_mode,
+            self.examples_per_node,
+            self.min_literal_support,
+            self.secret_policy,
+            self.stratification,
+            self.clustering,
+            self.tolerance,
+        )
+    }
+
+    /// Returns whether `candidate` overlaps one of the stop substrings.
+    pub(crate) fn is_stopped(&self, candidate: &str) -> bool {
+        self.stop_substrings
+            .iter()
+            .filter(|stop| !stop.is_empty())
+            .any(|stop| candidate.contains(stop.as_str()) || stop.contains(candidate))
+    }
+}
+
+/// How many inputs generation may leave unmatched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// At most this many inputs.
+    Inputs(usize),
+    /// At most this percentage of the inputs, rounded down.
+    Percent(f64),
+}
+
+impl Tolerance {
+    /// Returns how many of `total` inputs may be left unmatched.
+    pub(crate) fn budget(&self, total: usize) -> usize {
+        match *self {
+            Tolerance::Inputs(count) => count.min(total),
+            Tolerance::Percent(percent) => {
+                ((total as f64 * percent.clamp(0.0, 100.0) / 100.0).floor() as usize).min(total)
+            }
+        }
+    }
+}
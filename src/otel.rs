@@ -0,0 +1,124 @@
+use crate::{FieldType, Node, TreeError};
+use serde_json::{json, Map, Value};
+
+/// The severity names recognised in a severity field, compared without
+/// regard to case.
+const SEVERITIES: [&str; 10] = [
+    "TRACE", "DEBUG", "INFO", "NOTICE", "WARN", "WARNING", "ERROR", "CRITICAL", "FATAL", "PANIC",
+];
+
+/// The request methods recognised in an HTTP method field.
+const HTTP_METHODS: [&str; 9] = [
+    "GET", "HEAD", "POST", "PUT", "DELETE", "CONNECT", "OPTIONS", "TRACE", "PATCH",
+];
+
+/// Maps the named captures of a tree to OpenTelemetry log-record fields
+/// and semantic-convention attributes, as a mapping file for collectors.
+///
+/// A capture is recognised from the values it takes in the examples
+/// attached to its node, see [`crate::GenerateOptions::examples_per_node`]:
+/// timestamps map to the record's `timestamp`, severity names such as
+/// `WARN` to its `severity_text`, HTTP verbs to `http.method` and IPv4
+/// addresses to `net.peer.ip`. The other captures keep their group name.
+///
+/// The file lists, for each node with named captures, its stable template
+/// identifier, its pattern and the target of each capture:
+///
+/// ```text
+/// {"templates": [{"id": "…", "regex": "…", "fields": {"level": "severity_text"}}]}
+/// ```
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// The mapping, or an error if a pattern does not compile.
+pub fn export_otel_mapping(root: &Node) -> Result<Value, TreeError> {
+    let mut templates = vec![];
+    for node in root.preorder() {
+        let examples: Vec<&str> = node.examples.iter().map(String::as_str).collect();
+        let types = node.capture_types(&examples)?;
+        let names = regex::Regex::new(&node.pattern)
+            .map_err(|e| TreeError::InvalidPattern {
+                pattern: node.pattern.to_string(),
+                message: e.to_string(),
+            })?
+            .capture_names()
+            .flatten()
+            .map(str::to_string)
+            .collect::<Vec<String>>();
+        if names.is_empty() {
+            continue;
+        }
+        let fields: Map<String, Value> = names
+            .into_iter()
+            .map(|name| {
+                let field_type = types.iter().find(|(n, _)| *n == name).map(|(_, t)| t);
+                let target = field_type.and_then(attribute).unwrap_or(&name).to_string();
+                (name, Value::String(target))
+            })
+            .collect();
+        templates.push(json!({
+            "id": node.template_id().to_string(),
+            "regex": node.pattern,
+            "fields": fields,
+        }));
+    }
+    Ok(json!({ "templates": templates }))
+}
+
+/// Returns the OpenTelemetry field or attribute a capture of the given
+/// type maps to, if any.
+fn attribute(field_type: &FieldType) -> Option<&'static str> {
+    let all_in = |values: &[String], names: &[&str]| {
+        values
+            .iter()
+            .all(|v| names.iter().any(|n| n.eq_ignore_ascii_case(v)))
+    };
+    match field_type {
+        FieldType::Timestamp => Some("timestamp"),
+        FieldType::Ip => Some("net.peer.ip"),
+        FieldType::Enum(values) if all_in(values, &SEVERITIES) => Some("severity_text"),
+        FieldType::Enum(values) if all_in(values, &HTTP_METHODS) => Some("http.method"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_otel_mapping() {
+        let mut node = Node::new(
+            r"^(?P<ts>\S+) (?P<level>\w+) (?P<client>\S+) (?P<verb>\w+) (?P<path>\S+)$".to_string(),
+        );
+        node.examples = vec![
+            "2024-01-02T10:00:00Z INFO 10.0.0.1 GET /index".to_string(),
+            "2024-01-02T10:00:01Z WARN 10.0.0.2 POST /login".to_string(),
+            "2024-01-02T10:00:02Z INFO 10.0.0.1 GET /about".to_string(),
+        ];
+        let mut root = Node::new(".*".to_string());
+        root.children.push(node.into());
+
+        let mapping = export_otel_mapping(&root).unwrap();
+        let templates = mapping["templates"].as_array().unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(
+            templates[0]["id"],
+            root.children[0].template_id().to_string()
+        );
+        assert_eq!(
+            templates[0]["fields"],
+            json!({
+                "ts": "timestamp",
+                "level": "severity_text",
+                "client": "net.peer.ip",
+                "verb": "http.method",
+                "path": "path",
+            })
+        );
+    }
+}
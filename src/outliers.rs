@@ -0,0 +1,104 @@
+use crate::{select_candidates, GenerateOptions};
+
+/// An input whose removal allows a more specific common pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outlier {
+    index: usize,
+    input: String,
+    impact: f64,
+}
+
+impl Outlier {
+    /// Returns the position of the input among those given.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the input.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Returns the share of the most specific common literal, in chars,
+    /// that only becomes available without this input: `0.75` means the
+    /// other inputs share a literal four times as long as the one all
+    /// inputs share.
+    pub fn impact(&self) -> f64 {
+        self.impact
+    }
+}
+
+/// Finds the inputs that, if removed, would allow a more specific common
+/// pattern, which are often data-quality problems such as a truncated or
+/// corrupted line.
+///
+/// Each input is left out in turn, and the longest candidate the others
+/// share under `options` is compared with the longest one all inputs
+/// share.
+///
+/// # Arguments
+///
+/// * `strings` - The inputs.
+/// * `options` - Options controlling candidate selection.
+///
+/// # Returns
+///
+/// The outliers with a positive impact, the most impactful first. Fewer
+/// than three inputs have none, since a single input is always specific.
+pub fn find_outliers(strings: &[&str], options: &GenerateOptions) -> Vec<Outlier> {
+    if strings.len() < 3 {
+        return vec![];
+    }
+    let baseline = specificity(strings, options);
+
+    let mut outliers: Vec<Outlier> = (0..strings.len())
+        .filter_map(|index| {
+            let others: Vec<&str> = strings
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != index)
+                .map(|(_, s)| *s)
+                .collect();
+            let without = specificity(&others, options);
+            (without > baseline).then(|| Outlier {
+                index,
+                input: strings[index].to_string(),
+                impact: (without - baseline) as f64 / without as f64,
+            })
+        })
+        .collect();
+    outliers.sort_by(|a, b| b.impact.total_cmp(&a.impact).then(a.index.cmp(&b.index)));
+    outliers
+}
+
+/// Returns the length, in chars, of the longest candidate shared by all
+/// `strings`.
+fn specificity(strings: &[&str], options: &GenerateOptions) -> usize {
+    select_candidates(strings, options)
+        .iter()
+        .map(|candidate| candidate.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_outliers() {
+        let inputs = [
+            "user alice logged in from 10.0.0.1",
+            "user bob logged in from 10.0.0.2",
+            "#!corrupted",
+            "user carol logged in from 10.0.0.3",
+        ];
+        let outliers = find_outliers(&inputs, &GenerateOptions::default());
+        assert_eq!(outliers.len(), 1);
+        assert_eq!(outliers[0].index(), 2);
+        assert_eq!(outliers[0].input(), "#!corrupted");
+        assert!(outliers[0].impact() > 0.9);
+
+        assert!(find_outliers(&inputs[..2], &GenerateOptions::default()).is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{Model, Record};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+impl Model {
+    /// Classifies inputs in parallel, as [`Model::classify`] does one by one.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Anything rayon can iterate over in parallel yielding
+    ///   `&str`, such as `lines.par_iter().copied()`.
+    ///
+    /// # Returns
+    ///
+    /// A parallel iterator of node identifiers; indexed inputs keep their
+    /// order when collected.
+    pub fn par_classify<'a, I>(
+        &'a self,
+        inputs: I,
+    ) -> impl ParallelIterator<Item = Option<usize>> + 'a
+    where
+        I: IntoParallelIterator<Item = &'a str>,
+        I::Iter: 'a,
+    {
+        inputs
+            .into_par_iter()
+            .map(move |input| self.classify(input))
+    }
+
+    /// Extracts inputs in parallel, as [`Model::extract`] does one by one.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Anything rayon can iterate over in parallel yielding
+    ///   `&str`.
+    ///
+    /// # Returns
+    ///
+    /// A parallel iterator of node identifiers with their records.
+    pub fn par_extract<'a, I>(
+        &'a self,
+        inputs: I,
+    ) -> impl ParallelIterator<Item = Option<(usize, Record)>> + 'a
+    where
+        I: IntoParallelIterator<Item = &'a str>,
+        I::Iter: 'a,
+    {
+        inputs.into_par_iter().map(move |input| self.extract(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RegexTreeBuilder;
+    use rayon::iter::IntoParallelRefIterator;
+
+    #[test]
+    fn test_par_classify_and_extract() {
+        let model = Model::train(&["disk full on sda", "disk full on sdb"]);
+        let inputs = vec!["disk full on sdc", "cpu hot", "disk full on sda"];
+        let sequential: Vec<Option<usize>> = inputs.iter().map(|i| model.classify(i)).collect();
+        let parallel: Vec<Option<usize>> = model.par_classify(inputs.par_iter().copied()).collect();
+        assert_eq!(parallel, sequential);
+
+        let extracted: Vec<_> = model.par_extract(inputs.par_iter().copied()).collect();
+        assert_eq!(
+            extracted[2].as_ref().map(|(id, _)| Some(*id)),
+            Some(sequential[2])
+        );
+    }
+
+    #[test]
+    fn test_parallel_builder() {
+        let inputs: Vec<String> = (0..200)
+            .map(|i| format!("GET /item/{i} took {}ms", i % 17))
+            .collect();
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let serial = RegexTreeBuilder::new().build(&inputs).unwrap();
+        let parallel = RegexTreeBuilder::new()
+            .parallel(true)
+            .build(&inputs)
+            .unwrap();
+        assert_eq!(parallel.to_regex(), serial.to_regex());
+        assert!(parallel
+            .iter()
+            .zip(serial.iter())
+            .all(|(a, b)| a.pattern() == b.pattern()));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::codegen::{translate_ast, Flavor};
+use crate::FlavorError;
+use regex_syntax::ast::{self, Ast};
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
+
+/// The pattern of a node: its source, in the `regex` crate's syntax, and
+/// its syntax tree, parsed the first time it is needed and then shared by
+/// every rendering, so the same tree can be written in several flavors
+/// without generating it again or editing pattern strings by hand.
+///
+/// A pattern dereferences to its source, and compares, hashes and
+/// serializes as it.
+#[derive(Clone, Default)]
+pub struct Pattern {
+    source: String,
+    ast: OnceLock<Option<Arc<Ast>>>,
+}
+
+impl Pattern {
+    /// Creates a pattern from its source, which is only parsed when needed.
+    pub fn new(source: impl Into<String>) -> Self {
+        Pattern {
+            source: source.into(),
+            ast: OnceLock::new(),
+        }
+    }
+
+    /// Returns the source of the pattern.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the syntax tree of the pattern, or `None` if it does not
+    /// parse.
+    pub fn ast(&self) -> Option<&Ast> {
+        self.ast
+            .get_or_init(|| {
+                ast::parse::Parser::new()
+                    .parse(&self.source)
+                    .ok()
+                    .map(Arc::new)
+            })
+            .as_deref()
+    }
+
+    /// Writes the pattern in the syntax of `flavor`, as
+    /// [`crate::Node::to_regex_with_flavor`] does for a whole tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `flavor` - The syntax to write the pattern in.
+    ///
+    /// # Returns
+    ///
+    /// The rendered pattern, or an error naming the syntax the flavor has
+    /// no equivalent for.
+    pub fn render(&self, flavor: Flavor) -> Result<String, FlavorError> {
+        let untranslatable = |reason: String| FlavorError::Untranslatable {
+            flavor,
+            pattern: self.source.clone(),
+            reason,
+        };
+        let ast = match self.ast() {
+            Some(ast) => ast,
+            None => return Err(untranslatable("the pattern does not parse".to_string())),
+        };
+        translate_ast(&self.source, ast, flavor).map_err(untranslatable)
+    }
+
+    /// Writes the pattern in the syntax of `flavor` with inline flags set
+    /// over all of it, such as `i` for case insensitivity.
+    ///
+    /// # Arguments
+    ///
+    /// * `flavor` - The syntax to write the pattern in.
+    /// * `flags` - The flag letters, as written after `(?`; none leaves the
+    ///   pattern as [`Pattern::render`] writes it.
+    ///
+    /// # Returns
+    ///
+    /// The rendered pattern, or an error naming the syntax the flavor has
+    /// no equivalent for.
+    pub fn render_with_flags(&self, flavor: Flavor, flags: &str) -> Result<String, FlavorError> {
+        if flags.is_empty() {
+            return self.render(flavor);
+        }
+        Pattern::new(format!("(?{flags}){}", self.source)).render(flavor)
+    }
+
+    /// Returns the source of the pattern, consuming it.
+    pub fn into_string(self) -> String {
+        self.source
+    }
+}
+
+impl Deref for Pattern {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.source
+    }
+}
+
+impl AsRef<str> for Pattern {
+    fn as_ref(&self) -> &str {
+        &self.source
+    }
+}
+
+impl Borrow<str> for Pattern {
+    fn borrow(&self) -> &str {
+        &self.source
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.source, f)
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Pattern) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for Pattern {}
+
+impl PartialEq<str> for Pattern {
+    fn eq(&self, other: &str) -> bool {
+        self.source == other
+    }
+}
+
+impl PartialEq<&str> for Pattern {
+    fn eq(&self, other: &&str) -> bool {
+        self.source == *other
+    }
+}
+
+impl PartialEq<String> for Pattern {
+    fn eq(&self, other: &String) -> bool {
+        &self.source == other
+    }
+}
+
+impl PartialEq<Pattern> for str {
+    fn eq(&self, other: &Pattern) -> bool {
+        self == other.source
+    }
+}
+
+impl PartialEq<Pattern> for &str {
+    fn eq(&self, other: &Pattern) -> bool {
+        *self == other.source
+    }
+}
+
+impl PartialEq<Pattern> for String {
+    fn eq(&self, other: &Pattern) -> bool {
+        *self == other.source
+    }
+}
+
+impl Hash for Pattern {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source.hash(state)
+    }
+}
+
+impl From<String> for Pattern {
+    fn from(source: String) -> Self {
+        Pattern::new(source)
+    }
+}
+
+impl From<&str> for Pattern {
+    fn from(source: &str) -> Self {
+        Pattern::new(source)
+    }
+}
+
+impl From<Pattern> for String {
+    fn from(pattern: Pattern) -> Self {
+        pattern.source
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Pattern {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Pattern {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Pattern::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_rendering() {
+        let pattern = Pattern::from(r"^id-\d+ (?:ok|ko)$");
+        assert_eq!(pattern, r"^id-\d+ (?:ok|ko)$");
+        assert!(pattern.starts_with("^id"));
+        assert!(pattern.ast().is_some());
+        assert_eq!(pattern.render(Flavor::Rust).unwrap(), pattern.as_str());
+        assert_eq!(
+            pattern.render(Flavor::PosixExtended).unwrap(),
+            "^id-[0-9]+ (ok|ko)$"
+        );
+        assert_eq!(
+            pattern.render_with_flags(Flavor::Vim, "i").unwrap(),
+            pattern
+                .render(Flavor::Vim)
+                .map(|p| format!(r"\c{p}"))
+                .unwrap()
+        );
+
+        let broken = Pattern::new("(");
+        assert!(broken.ast().is_none());
+        assert!(matches!(
+            broken.render(Flavor::Pcre),
+            Err(FlavorError::Untranslatable { .. })
+        ));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::alternation::literal_position;
+use crate::Role;
+
+/// The percentiles of the observed lengths that bound the `{m,n}`
+/// quantifiers of generalized regions, see
+/// [`crate::GenerateOptions::length_percentiles`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Percentiles {
+    lower: f64,
+    upper: f64,
+}
+
+impl Percentiles {
+    /// Creates bounds at the `lower` and `upper` percentiles, such as `1.0`
+    /// and `99.0`, clamped between 0 and 100.
+    pub fn new(lower: f64, upper: f64) -> Self {
+        let (lower, upper) = (lower.clamp(0.0, 100.0), upper.clamp(0.0, 100.0));
+        Percentiles {
+            lower: lower.min(upper),
+            upper: lower.max(upper),
+        }
+    }
+
+    /// Returns the lower percentile.
+    pub fn lower(&self) -> f64 {
+        self.lower
+    }
+
+    /// Returns the upper percentile.
+    pub fn upper(&self) -> f64 {
+        self.upper
+    }
+
+    /// Returns the nearest-rank value of `percentile` in the sorted
+    /// `lengths`.
+    fn rank(percentile: f64, lengths: &[usize]) -> usize {
+        let rank = (percentile / 100.0 * lengths.len() as f64).ceil() as usize;
+        lengths[rank.clamp(1, lengths.len()) - 1]
+    }
+
+    /// Splits the distinct `values` taken on one side of the anchored
+    /// occurrence of `literal` into those whose length, in characters, lies
+    /// within the percentiles of the lengths across `inputs`, and the
+    /// inputs holding the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The role of the literal.
+    /// * `literal` - The literal the region is next to.
+    /// * `inputs` - The strings the tree was mined from.
+    /// * `before` - Whether the region precedes the literal.
+    /// * `values` - The distinct values of the region.
+    ///
+    /// # Returns
+    ///
+    /// The values to generalize and the distinct inputs left outside.
+    pub(crate) fn trim<'a>(
+        &self,
+        role: Role,
+        literal: &str,
+        inputs: &[&'a str],
+        before: bool,
+        values: &[&'a str],
+    ) -> (Vec<&'a str>, Vec<&'a str>) {
+        let samples: Vec<(&str, usize)> = inputs
+            .iter()
+            .filter_map(|&input| {
+                let at = literal_position(role, literal, input)?;
+                let value = match before {
+                    true => &input[..at],
+                    false => &input[at + literal.len()..],
+                };
+                Some((input, value.chars().count()))
+            })
+            .collect();
+        let mut lengths: Vec<usize> = samples.iter().map(|&(_, length)| length).collect();
+        if lengths.is_empty() {
+            return (values.to_vec(), vec![]);
+        }
+        lengths.sort_unstable();
+        let bounds = Self::rank(self.lower, &lengths)..=Self::rank(self.upper, &lengths);
+
+        let kept = values
+            .iter()
+            .copied()
+            .filter(|v| bounds.contains(&v.chars().count()))
+            .collect();
+        let mut outliers: Vec<&str> = vec![];
+        for (input, length) in samples {
+            if !bounds.contains(&length) && !outliers.contains(&input) {
+                outliers.push(input);
+            }
+        }
+        (kept, outliers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        generate_regex_tree_with_options, CharClassDetector, GenerateOptions, Percentiles,
+    };
+
+    #[test]
+    fn test_length_percentiles() {
+        let mut inputs: Vec<String> = (0..99)
+            .map(|i| format!("user {}", "x".repeat(3 + i % 5)))
+            .collect();
+        inputs.push(format!("user {}", "y".repeat(60)));
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let mut options = GenerateOptions {
+            char_classes: CharClassDetector::ALL.to_vec(),
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        assert!(tree.iter().any(|n| n.pattern() == r"^user \w{3,60}$"));
+        assert!(tree.iter().all(|n| n.bound_outliers().is_empty()));
+
+        options.length_percentiles = Some(Percentiles::new(1.0, 99.0));
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let leaf = tree.find(|n| n.pattern() == r"^user \w{3,7}$").unwrap();
+        assert_eq!(leaf.bound_outliers(), [inputs[99]]);
+    }
+}
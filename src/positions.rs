@@ -0,0 +1,161 @@
+use crate::Tokenizer;
+use std::collections::{BTreeMap, HashMap};
+
+/// Where one token occurs across the inputs, by token index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenPositions {
+    token: String,
+    inputs: usize,
+    from_start: BTreeMap<usize, usize>,
+    from_end: BTreeMap<usize, usize>,
+}
+
+impl TokenPositions {
+    /// Returns the token.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Returns how many inputs contain the token at least once.
+    pub fn input_count(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns how many times the token occurs across the inputs.
+    pub fn occurrences(&self) -> usize {
+        self.from_start.values().sum()
+    }
+
+    /// Returns how many occurrences of the token are at each position, the
+    /// first token of an input being at `0`.
+    pub fn positions(&self) -> &BTreeMap<usize, usize> {
+        &self.from_start
+    }
+
+    /// Returns how many occurrences of the token are at each position
+    /// counted from the end, the last token of an input being at `0`.
+    pub fn positions_from_end(&self) -> &BTreeMap<usize, usize> {
+        &self.from_end
+    }
+}
+
+/// The position histograms of every token of a corpus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionReport {
+    inputs: usize,
+    tokens: Vec<TokenPositions>,
+}
+
+impl PositionReport {
+    /// Returns the number of inputs analyzed.
+    pub fn input_count(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns the histograms of every token, those contained in the most
+    /// inputs first, then by token.
+    pub fn tokens(&self) -> &[TokenPositions] {
+        &self.tokens
+    }
+
+    /// Returns the histogram of `token`, if it occurs.
+    pub fn get(&self, token: &str) -> Option<&TokenPositions> {
+        self.tokens.iter().find(|t| t.token == token)
+    }
+
+    /// Returns the token every input starts with, which a pattern can
+    /// anchor with `^`, if any.
+    pub fn leading(&self) -> Option<&TokenPositions> {
+        self.tokens
+            .iter()
+            .find(|t| self.inputs > 0 && t.from_start.get(&0) == Some(&self.inputs))
+    }
+
+    /// Returns the token every input ends with, which a pattern can anchor
+    /// with `$`, if any.
+    pub fn trailing(&self) -> Option<&TokenPositions> {
+        self.tokens
+            .iter()
+            .find(|t| self.inputs > 0 && t.from_end.get(&0) == Some(&self.inputs))
+    }
+}
+
+/// Splits every input into tokens and records, for each distinct token,
+/// the positions at which it occurs, from the start and from the end, so
+/// fields that sit in a fixed column can be told from those that drift.
+///
+/// # Arguments
+///
+/// * `strings` - The inputs to analyze.
+/// * `tokenizer` - How inputs are split into tokens.
+///
+/// # Returns
+///
+/// The histogram of every token.
+pub fn analyze_positions(strings: &[&str], tokenizer: &Tokenizer) -> PositionReport {
+    let mut histograms: HashMap<&str, TokenPositions> = HashMap::new();
+    for s in strings {
+        let tokens = tokenizer.tokens(s);
+        for (position, range) in tokens.iter().enumerate() {
+            let token = &s[range.clone()];
+            let histogram = histograms.entry(token).or_insert_with(|| TokenPositions {
+                token: token.to_string(),
+                inputs: 0,
+                from_start: BTreeMap::new(),
+                from_end: BTreeMap::new(),
+            });
+            let first_in_input = tokens[..position].iter().all(|r| &s[r.clone()] != token);
+            if first_in_input {
+                histogram.inputs += 1;
+            }
+            *histogram.from_start.entry(position).or_default() += 1;
+            *histogram
+                .from_end
+                .entry(tokens.len() - 1 - position)
+                .or_default() += 1;
+        }
+    }
+    let mut tokens: Vec<TokenPositions> = histograms.into_values().collect();
+    tokens.sort_unstable_by(|a, b| b.inputs.cmp(&a.inputs).then_with(|| a.token.cmp(&b.token)));
+    PositionReport {
+        inputs: strings.len(),
+        tokens,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_positions() {
+        let inputs = [
+            "ERROR disk full",
+            "ERROR disk sda full",
+            "WARN disk disk low",
+        ];
+        let report = analyze_positions(&inputs, &Tokenizer::Whitespace);
+        assert_eq!(report.input_count(), 3);
+        assert_eq!(report.tokens()[0].token(), "disk");
+
+        let disk = report.get("disk").unwrap();
+        assert_eq!((disk.input_count(), disk.occurrences()), (3, 4));
+        assert_eq!(disk.positions(), &BTreeMap::from([(1, 3), (2, 1)]));
+        assert_eq!(
+            report.get("full").unwrap().positions_from_end(),
+            &BTreeMap::from([(0, 2)])
+        );
+        assert_eq!(
+            report.get("ERROR").unwrap().positions(),
+            &BTreeMap::from([(0, 2)])
+        );
+        assert!(report.leading().is_none() && report.trailing().is_none());
+
+        let report = analyze_positions(&["a,b,c", "a,,c"], &Tokenizer::Delimiters(vec![',']));
+        assert_eq!(report.leading().unwrap().token(), "a");
+        assert_eq!(report.trailing().unwrap().token(), "c");
+        assert!(analyze_positions(&[], &Tokenizer::Whitespace)
+            .leading()
+            .is_none());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::Node;
+use std::sync::Arc;
+
+/// Rewrites the Perl class shorthands of every pattern of the tree as POSIX
+/// bracket expressions, see [`crate::GenerateOptions::posix_classes`].
+pub(crate) fn use_posix_classes(node: &mut Node) {
+    node.pattern = posix_classes(&node.pattern).into();
+    for child in &mut node.children {
+        use_posix_classes(Arc::make_mut(child));
+    }
+}
+
+/// Returns the POSIX class items standing for the Perl class `letter`, if
+/// it is one.
+fn items(letter: char) -> Option<&'static str> {
+    match letter.to_ascii_lowercase() {
+        'd' => Some("[:digit:]"),
+        's' => Some("[:space:]"),
+        'w' => Some("[:alnum:]_"),
+        _ => None,
+    }
+}
+
+/// Returns `pattern` with `\d`, `\s` and `\w` and their negations written
+/// as `[[:digit:]]`, `[[:space:]]` and `[[:alnum:]_]`, or as those items
+/// inside a bracketed class. A negated shorthand inside a class has no such
+/// spelling and is kept.
+fn posix_classes(pattern: &str) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    // How deep in bracketed classes the next char is, and whether it opens
+    // the class, where `]` is a literal.
+    let (mut depth, mut opening) = (0, false);
+    while let Some(c) = chars.next() {
+        let first = std::mem::take(&mut opening);
+        match c {
+            '\\' => {
+                let Some(next) = chars.next() else {
+                    out.push(c);
+                    break;
+                };
+                let negated = next.is_ascii_uppercase();
+                match (items(next), depth, negated) {
+                    (Some(items), 0, false) => out.push_str(&format!("[{items}]")),
+                    (Some(items), 0, true) => out.push_str(&format!("[^{items}]")),
+                    (Some(items), _, false) => out.push_str(items),
+                    _ => out.extend([c, next]),
+                }
+            }
+            '[' if depth > 0 && chars.peek() == Some(&':') => {
+                // A POSIX class already, copied up to its closing `:]`.
+                out.push(c);
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    if c == ':' && chars.peek() == Some(&']') {
+                        out.extend(chars.next());
+                        break;
+                    }
+                }
+            }
+            '[' => {
+                out.push(c);
+                depth += 1;
+                opening = true;
+                if chars.peek() == Some(&'^') {
+                    out.extend(chars.next());
+                }
+            }
+            ']' if depth > 0 && !first => {
+                out.push(c);
+                depth -= 1;
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_regex_tree_with_options, CharClassDetector, GenerateOptions};
+    use regex::Regex;
+
+    #[test]
+    fn test_posix_classes() {
+        assert_eq!(
+            posix_classes(r"^\d{4}\s[\w.]+\D\\d[]\d][^[:alpha:]\w]$"),
+            r"^[[:digit:]]{4}[[:space:]][[:alnum:]_.]+[^[:digit:]]\\d[][:digit:]][^[:alpha:][:alnum:]_]$"
+        );
+
+        let inputs = ["order 1234 ok", "order 5678 ok"];
+        let options = GenerateOptions {
+            char_classes: vec![CharClassDetector::Digit],
+            posix_classes: true,
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let patterns: Vec<&str> = tree.iter().map(|n| n.pattern.as_str()).collect();
+        assert!(patterns.iter().all(|p| !p.contains(r"\d")), "{patterns:?}");
+        let leaf = patterns.iter().find(|p| p.contains("[[:digit:]]")).unwrap();
+        let regex = Regex::new(leaf).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::alternation::literal_position;
+use crate::secrets::{exposes, safe_fragments};
+use crate::Role;
+use std::collections::HashSet;
+
+/// Decides whether a literal may be emitted, when
+/// [`crate::GenerateOptions::min_literal_support`] is set or secrets are
+/// to be generalized.
+pub(crate) struct SupportGuard<'a> {
+    /// The distinct inputs.
+    inputs: Vec<&'a str>,
+    min_support: usize,
+    /// The secret values no literal may spell out.
+    secrets: Vec<&'a str>,
+}
+
+impl<'a> SupportGuard<'a> {
+    /// Creates a guard requiring literals to occur in at least
+    /// `min_support` distinct inputs; a guard requiring one or none
+    /// allows every literal not exposing a secret.
+    pub(crate) fn new(inputs: &[&'a str], min_support: Option<usize>) -> Self {
+        let min_support = min_support.unwrap_or(0);
+        let mut seen = HashSet::new();
+        let inputs = match min_support {
+            0 | 1 => vec![],
+            _ => inputs.iter().copied().filter(|s| seen.insert(*s)).collect(),
+        };
+        SupportGuard {
+            inputs,
+            min_support,
+            secrets: vec![],
+        }
+    }
+
+    /// Also rejects literals sharing more than a few consecutive
+    /// characters with one of `secrets`.
+    pub(crate) fn with_secrets(mut self, secrets: Vec<&'a str>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Returns whether the guard restricts anything.
+    pub(crate) fn is_active(&self) -> bool {
+        self.min_support > 1 || !self.secrets.is_empty()
+    }
+
+    /// Returns whether every distinct input taken together is enough to
+    /// emit the literals they all share.
+    pub(crate) fn allows_common(&self) -> bool {
+        self.min_support <= 1 || self.inputs.len() >= self.min_support
+    }
+
+    /// Returns whether `literal` occurs in enough distinct inputs and
+    /// exposes no secret.
+    pub(crate) fn allows(&self, literal: &str) -> bool {
+        let supported = self.min_support <= 1
+            || self
+                .inputs
+                .iter()
+                .filter(|input| input.contains(literal))
+                .take(self.min_support)
+                .count()
+                >= self.min_support;
+        supported && !self.exposes_secret(literal)
+    }
+
+    /// Returns whether `literal` spells out part of a secret.
+    pub(crate) fn exposes_secret(&self, literal: &str) -> bool {
+        !self.secrets.is_empty() && exposes(literal, &self.secrets)
+    }
+
+    /// Returns whether spelling out the text on the given `sides` of the
+    /// anchored occurrence of `literal`, together with it, would expose a
+    /// secret in one of the `inputs`.
+    pub(crate) fn exposes_secret_around(
+        &self,
+        role: Role,
+        literal: &str,
+        inputs: &[&str],
+        sides: [bool; 2],
+    ) -> bool {
+        !self.secrets.is_empty()
+            && sides.contains(&true)
+            && inputs.iter().any(|input| {
+                literal_position(role, literal, input).is_some_and(|at| {
+                    let start = if sides[0] { 0 } else { at };
+                    let end = if sides[1] {
+                        input.len()
+                    } else {
+                        at + literal.len()
+                    };
+                    self.exposes_secret(&input[start..end])
+                })
+            })
+    }
+
+    /// Returns the parts of `literal` that expose no secret, which is
+    /// `literal` itself when it exposes none.
+    pub(crate) fn safe_fragments<'s>(&self, literal: &'s str) -> Vec<&'s str> {
+        if self.secrets.is_empty() {
+            vec![literal]
+        } else {
+            safe_fragments(literal, &self.secrets)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_regex_tree_with_options, GenerateOptions};
+
+    #[test]
+    fn test_min_literal_support() {
+        let inputs = [
+            "login ok user=alice",
+            "login ok user=alice",
+            "login ok user=bob",
+            "login ok user=bob",
+            "login ok user=carol",
+            "login ok user=carol",
+            "login ok user=s3cr3t",
+        ];
+        let options = GenerateOptions {
+            max_alternatives: Some(4),
+            examples_per_node: 2,
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        assert!(tree.iter().any(|n| n.pattern().contains("s3cr3t")));
+
+        let guarded = GenerateOptions {
+            min_literal_support: Some(2),
+            ..options
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &guarded).unwrap();
+        assert!(tree.iter().any(|n| n.pattern() == "^login ok user=.*"));
+        for node in tree.iter() {
+            assert!(!node.pattern().contains("s3cr3t"), "{}", node.pattern());
+            assert!(node.examples().is_empty());
+            assert!(node
+                .observed_values()
+                .iter()
+                .flatten()
+                .all(|v| v != "s3cr3t"));
+        }
+
+        let few = GenerateOptions {
+            min_literal_support: Some(3),
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs[..4], &few).unwrap();
+        assert_eq!(tree.to_regex(), ".*");
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{Node, TreeError};
+use regex::Regex;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How many times the sample is matched against each pattern, after one
+/// warm-up pass, to smooth out timer noise.
+const ROUNDS: u32 = 3;
+
+/// The measured match latency of one node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeProfile {
+    id: usize,
+    pattern: String,
+    mean: Duration,
+    max: Duration,
+    matches: usize,
+}
+
+impl NodeProfile {
+    /// Returns the pre-order identifier of the node, the root being `0`.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the node's pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the mean time to match one sample input.
+    pub fn mean(&self) -> Duration {
+        self.mean
+    }
+
+    /// Returns the longest time taken by a single sample input.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns how many sample inputs the pattern matched.
+    pub fn matches(&self) -> usize {
+        self.matches
+    }
+}
+
+/// The measured match latencies of every node of a tree, slowest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    nodes: Vec<NodeProfile>,
+    sample_size: usize,
+}
+
+impl Profile {
+    /// Returns the profile of every node, by decreasing mean latency.
+    pub fn nodes(&self) -> &[NodeProfile] {
+        &self.nodes
+    }
+
+    /// Returns the `count` slowest nodes.
+    pub fn slowest(&self, count: usize) -> &[NodeProfile] {
+        &self.nodes[..count.min(self.nodes.len())]
+    }
+}
+
+impl fmt::Display for Profile {
+    /// Formats a report with one line per node, slowest first.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5}  {:>12}  {:>12}  {:>9}  pattern",
+            "node", "mean", "max", "matches"
+        )?;
+        for node in &self.nodes {
+            writeln!(
+                f,
+                "{:>5}  {:>12.2?}  {:>12.2?}  {:>4}/{:<4}  {}",
+                node.id, node.mean, node.max, node.matches, self.sample_size, node.pattern
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Measures the actual match latency of every node of a tree over a
+/// sample, so the slowest rules can be reworked before production.
+///
+/// Each pattern is compiled, run once over the sample to warm up, then
+/// timed over a few more rounds. Timings depend on the machine and its
+/// load, so they are best compared within one profile.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `sample` - Inputs representative of production traffic.
+///
+/// # Returns
+///
+/// The profile, or an error if a pattern does not compile.
+pub fn profile(root: &Node, sample: &[&str]) -> Result<Profile, TreeError> {
+    let mut nodes = vec![];
+    for (id, node) in root.preorder().into_iter().enumerate() {
+        let regex = Regex::new(&node.pattern).map_err(|e| TreeError::InvalidPattern {
+            pattern: node.pattern.to_string(),
+            message: e.to_string(),
+        })?;
+        let matches = sample.iter().filter(|s| regex.is_match(s)).count();
+
+        let mut total = Duration::ZERO;
+        let mut max = Duration::ZERO;
+        for _ in 0..ROUNDS {
+            for input in sample {
+                let started = Instant::now();
+                std::hint::black_box(regex.is_match(std::hint::black_box(input)));
+                let elapsed = started.elapsed();
+                total += elapsed;
+                max = max.max(elapsed);
+            }
+        }
+        let runs = (ROUNDS as usize * sample.len()).max(1) as u32;
+        nodes.push(NodeProfile {
+            id,
+            pattern: node.pattern.to_string(),
+            mean: total / runs,
+            max,
+            matches,
+        });
+    }
+    nodes.sort_by(|a, b| b.mean.cmp(&a.mean).then(a.id.cmp(&b.id)));
+    Ok(Profile {
+        nodes,
+        sample_size: sample.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_profile() {
+        let inputs = ["disk full on sda", "disk full on sdb"];
+        let tree = generate_regex_tree(&inputs);
+        let profile = profile(&tree, &inputs).unwrap();
+
+        assert_eq!(profile.nodes().len(), tree.size());
+        assert!(profile
+            .nodes()
+            .windows(2)
+            .all(|w| w[0].mean() >= w[1].mean()));
+        assert!(profile.nodes().iter().all(|n| n.matches() == 2));
+        assert_eq!(profile.slowest(1)[0], profile.nodes()[0]);
+        let report = profile.to_string();
+        assert_eq!(report.lines().count(), tree.size() + 1);
+        assert!(report.contains("2/2"));
+    }
+}
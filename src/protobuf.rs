@@ -0,0 +1,234 @@
+use crate::{Children, GraphError, Node, ProtobufError, Role};
+use prost::Message;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The version of the protobuf tree format written by this crate.
+const PROTOBUF_VERSION: u32 = 1;
+
+/// A tree as described by `proto/regex_tree.proto`.
+#[derive(Clone, PartialEq, Message)]
+struct Tree {
+    #[prost(uint32, tag = "1")]
+    version: u32,
+    #[prost(message, repeated, tag = "2")]
+    nodes: Vec<TreeNode>,
+    #[prost(message, repeated, tag = "3")]
+    edges: Vec<Edge>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TreeNode {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    #[prost(string, tag = "2")]
+    pattern: String,
+    #[prost(string, repeated, tag = "3")]
+    collapsed: Vec<String>,
+    #[prost(uint64, optional, tag = "4")]
+    match_count: Option<u64>,
+    #[prost(enumeration = "WireRole", tag = "5")]
+    role: i32,
+    #[prost(string, repeated, tag = "6")]
+    examples: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Edge {
+    #[prost(uint64, tag = "1")]
+    parent: u64,
+    #[prost(uint64, tag = "2")]
+    child: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+enum WireRole {
+    Unspecified = 0,
+    Prefix = 1,
+    Suffix = 2,
+    Infix = 3,
+    Full = 4,
+}
+
+impl Node {
+    /// Encodes the tree in the protobuf format described by
+    /// `proto/regex_tree.proto`.
+    ///
+    /// Unlike JSON, the encoding relies on field numbers rather than field
+    /// names, so it can be read by services in other languages from the
+    /// schema alone.
+    pub fn to_protobuf(&self) -> Vec<u8> {
+        let mut tree = Tree {
+            version: PROTOBUF_VERSION,
+            nodes: vec![],
+            edges: vec![],
+        };
+        encode_node(self, &mut tree);
+        tree.encode_to_vec()
+    }
+
+    /// Decodes a tree encoded with [`Node::to_protobuf`].
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The encoded tree.
+    ///
+    /// # Returns
+    ///
+    /// The tree, or an error if the bytes are not a valid encoding, were
+    /// written by a newer version, or do not describe a tree.
+    pub fn from_protobuf(bytes: &[u8]) -> Result<Node, ProtobufError> {
+        let tree = Tree::decode(bytes).map_err(ProtobufError::Decode)?;
+        if tree.version > PROTOBUF_VERSION {
+            return Err(ProtobufError::UnsupportedVersion(tree.version));
+        }
+
+        let index: HashMap<u64, usize> = tree
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id, i))
+            .collect();
+        let lookup = |id: u64| {
+            index
+                .get(&id)
+                .copied()
+                .ok_or(ProtobufError::UnknownNode(id))
+        };
+        let mut children: Vec<Vec<usize>> = vec![vec![]; tree.nodes.len()];
+        let mut parents = vec![0usize; tree.nodes.len()];
+        for edge in &tree.edges {
+            let (parent, child) = (lookup(edge.parent)?, lookup(edge.child)?);
+            parents[child] += 1;
+            if parents[child] > 1 {
+                return Err(ProtobufError::Structure(GraphError::MultipleParents {
+                    node: child,
+                }));
+            }
+            children[parent].push(child);
+        }
+
+        let roots: Vec<usize> = (0..tree.nodes.len()).filter(|&i| parents[i] == 0).collect();
+        let root = match roots.as_slice() {
+            [] => return Err(ProtobufError::Structure(GraphError::NoRoot)),
+            [root] => *root,
+            _ => return Err(ProtobufError::Structure(GraphError::MultipleRoots(roots))),
+        };
+
+        let mut visited = vec![false; tree.nodes.len()];
+        let node = decode_node(&tree.nodes, &children, root, &mut visited);
+        match visited.iter().position(|&v| !v) {
+            Some(node) => Err(ProtobufError::Structure(GraphError::Unreachable { node })),
+            None => Ok(node),
+        }
+    }
+}
+
+fn encode_node(node: &Node, tree: &mut Tree) -> u64 {
+    let id = tree.nodes.len() as u64;
+    let role = match node.role {
+        None => WireRole::Unspecified,
+        Some(Role::Prefix) => WireRole::Prefix,
+        Some(Role::Suffix) => WireRole::Suffix,
+        Some(Role::Infix) => WireRole::Infix,
+        Some(Role::Full) => WireRole::Full,
+    };
+    tree.nodes.push(TreeNode {
+        id,
+        pattern: node.pattern.to_string(),
+        collapsed: node.collapsed.clone(),
+        match_count: node.match_count.map(|count| count as u64),
+        role: role as i32,
+        examples: node.examples.clone(),
+    });
+    for child in &node.children {
+        let child = encode_node(child, tree);
+        tree.edges.push(Edge { parent: id, child });
+    }
+    id
+}
+
+fn decode_node(
+    nodes: &[TreeNode],
+    children: &[Vec<usize>],
+    index: usize,
+    visited: &mut [bool],
+) -> Node {
+    visited[index] = true;
+    let wire = &nodes[index];
+    let mut node = Node::new(wire.pattern.clone());
+    node.collapsed = wire.collapsed.clone();
+    node.match_count = wire.match_count.map(|count| count as usize);
+    node.examples = wire.examples.clone();
+    node.role = match WireRole::try_from(wire.role) {
+        Ok(WireRole::Prefix) => Some(Role::Prefix),
+        Ok(WireRole::Suffix) => Some(Role::Suffix),
+        Ok(WireRole::Infix) => Some(Role::Infix),
+        Ok(WireRole::Full) => Some(Role::Full),
+        Ok(WireRole::Unspecified) | Err(_) => None,
+    };
+    node.children = children[index]
+        .iter()
+        .map(|&child| Arc::new(decode_node(nodes, children, child, visited)))
+        .collect::<Children>();
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_protobuf_round_trip() {
+        let mut tree = generate_regex_tree(&["disk full on sda", "disk full on sdb"]);
+        tree.compress_chains();
+        let decoded = Node::from_protobuf(&tree.to_protobuf()).unwrap();
+        let summary = |root: &Node| -> Vec<(String, Vec<String>, Option<Role>, usize)> {
+            root.preorder()
+                .iter()
+                .map(|n| {
+                    (
+                        n.pattern.to_string(),
+                        n.collapsed.clone(),
+                        n.role,
+                        n.children.len(),
+                    )
+                })
+                .collect()
+        };
+        assert_eq!(summary(&decoded), summary(&tree));
+
+        let cyclic = Tree {
+            version: PROTOBUF_VERSION,
+            nodes: (0..3)
+                .map(|id| TreeNode {
+                    id,
+                    pattern: ".*".to_string(),
+                    ..TreeNode::default()
+                })
+                .collect(),
+            edges: vec![
+                Edge {
+                    parent: 1,
+                    child: 2,
+                },
+                Edge {
+                    parent: 2,
+                    child: 1,
+                },
+            ],
+        };
+        assert!(matches!(
+            Node::from_protobuf(&cyclic.encode_to_vec()),
+            Err(ProtobufError::Structure(GraphError::Unreachable {
+                node: 1
+            }))
+        ));
+        assert!(matches!(
+            Node::from_protobuf(&[0xff]),
+            Err(ProtobufError::Decode(_))
+        ));
+    }
+}
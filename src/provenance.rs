@@ -0,0 +1,112 @@
+use crate::stable_id::fnv1a;
+use crate::Node;
+
+/// Where a tree came from: the data and settings it was generated from,
+/// recorded on the root so an audit can tell which dataset produced which
+/// deployed rule.
+///
+/// Hashes are 64-bit FNV-1a, the same in every process and on every
+/// platform; they identify rather than authenticate the data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Provenance {
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    dataset_id: Option<String>,
+    input_count: usize,
+    input_hash: u64,
+    options_hash: u64,
+}
+
+impl Provenance {
+    /// Records the provenance of a tree generated from `inputs` with the
+    /// settings described by `settings`.
+    pub(crate) fn record(inputs: &[&str], settings: &str, dataset_id: Option<&str>) -> Provenance {
+        Provenance {
+            dataset_id: dataset_id.map(str::to_string),
+            input_count: inputs.len(),
+            input_hash: multiset_hash(inputs),
+            options_hash: fnv1a(settings.as_bytes()),
+        }
+    }
+
+    /// Returns the dataset identifier supplied by the caller, if any.
+    pub fn dataset_id(&self) -> Option<&str> {
+        self.dataset_id.as_deref()
+    }
+
+    /// Returns how many inputs, duplicates included, the tree was
+    /// generated from.
+    pub fn input_count(&self) -> usize {
+        self.input_count
+    }
+
+    /// Returns the hash of the inputs as a multiset: the same strings in
+    /// any order hash alike, but not with a different number of copies.
+    pub fn input_hash(&self) -> u64 {
+        self.input_hash
+    }
+
+    /// Returns the hash of the generation settings.
+    pub fn options_hash(&self) -> u64 {
+        self.options_hash
+    }
+}
+
+/// Hashes each input, then the sorted input hashes.
+fn multiset_hash(inputs: &[&str]) -> u64 {
+    let mut hashes: Vec<u64> = inputs.iter().map(|s| fnv1a(s.as_bytes())).collect();
+    hashes.sort_unstable();
+    let bytes: Vec<u8> = hashes.iter().flat_map(|h| h.to_le_bytes()).collect();
+    fnv1a(&bytes)
+}
+
+impl Node {
+    /// Returns the provenance recorded on the root of a tree generated by
+    /// [`crate::RegexTreeBuilder`], or `None` for other nodes and trees.
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{GenerateOptions, RegexTreeBuilder};
+
+    #[test]
+    fn test_provenance() {
+        let build = |builder: RegexTreeBuilder, inputs: &[&str]| {
+            builder.build(inputs).unwrap().provenance().unwrap().clone()
+        };
+        let inputs = ["disk sda full", "disk sdb full", "disk sda full"];
+        let provenance = build(RegexTreeBuilder::new().dataset_id("prod-2024-06"), &inputs);
+        assert_eq!(provenance.dataset_id(), Some("prod-2024-06"));
+        assert_eq!(provenance.input_count(), 3);
+
+        let shuffled = build(RegexTreeBuilder::new(), &[inputs[2], inputs[0], inputs[1]]);
+        assert_eq!(shuffled.input_hash(), provenance.input_hash());
+        assert_eq!(shuffled.options_hash(), provenance.options_hash());
+        assert_eq!(shuffled.dataset_id(), None);
+
+        let fewer = build(RegexTreeBuilder::new(), &inputs[..2]);
+        assert_ne!(fewer.input_hash(), provenance.input_hash());
+        let options = GenerateOptions {
+            max_alternatives: Some(2),
+            ..GenerateOptions::default()
+        };
+        let tuned = build(RegexTreeBuilder::new().options(options), &inputs);
+        assert_ne!(tuned.options_hash(), provenance.options_hash());
+        let lazy = build(RegexTreeBuilder::new().lazy_wildcards(true), &inputs);
+        assert_ne!(lazy.options_hash(), provenance.options_hash());
+
+        let tree = RegexTreeBuilder::new().build(&inputs).unwrap();
+        assert!(tree.children().all(|child| child.provenance().is_none()));
+        #[cfg(feature = "serde")]
+        {
+            let loaded = crate::Node::from_json(&tree.to_json().unwrap()).unwrap();
+            assert_eq!(loaded.provenance(), tree.provenance());
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::subsumption::same_language;
+use crate::{DetectorDictionary, Node, TreeError};
+use regex::Regex;
+use regex_syntax::ast::{
+    self, Ast, ClassAsciiKind, ClassSet, ClassSetItem, RepetitionKind, RepetitionRange,
+};
+use std::sync::Arc;
+
+/// A static measure of how hard a pattern is for a person to read, since
+/// generated rules end up maintained by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Readability {
+    length: usize,
+    nesting_depth: usize,
+    class_complexity: usize,
+}
+
+impl Readability {
+    /// Returns the length of the pattern, in chars.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Returns how deeply groups and bracketed classes nest, `0` for a
+    /// pattern without any.
+    pub fn nesting_depth(&self) -> usize {
+        self.nesting_depth
+    }
+
+    /// Returns the number of items, such as literals, ranges and named
+    /// classes, listed in the pattern's bracketed classes, a negation
+    /// counting as one more.
+    pub fn class_complexity(&self) -> usize {
+        self.class_complexity
+    }
+
+    /// Returns a relative score combining the other measures, higher being
+    /// harder to read: the length, plus four per nesting level and two per
+    /// class item. Scores are only meaningful compared with each other.
+    pub fn score(&self) -> usize {
+        self.length + 4 * self.nesting_depth + 2 * self.class_complexity
+    }
+}
+
+impl Node {
+    /// Measures how hard the node's pattern is to read.
+    ///
+    /// # Returns
+    ///
+    /// The measure, or an error if the pattern does not parse.
+    pub fn readability(&self) -> Result<Readability, TreeError> {
+        let ast = ast::parse::Parser::new()
+            .parse(&self.pattern)
+            .map_err(|e| TreeError::InvalidPattern {
+                pattern: self.pattern.to_string(),
+                message: e.to_string(),
+            })?;
+        let mut readability = Readability {
+            length: self.pattern.chars().count(),
+            nesting_depth: 0,
+            class_complexity: 0,
+        };
+        measure(&ast, 0, &mut readability);
+        Ok(readability)
+    }
+
+    /// Returns a copy of the tree with its patterns rewritten to read more
+    /// easily without changing what they match.
+    ///
+    /// Bracketed digit classes are written as shorthands, `[0-9]` and
+    /// `[[:digit:]]` as `\d` and their negations as `\D`, counted
+    /// repetitions as the usual operators, `{0,1}` as `?`, `{0,}` as `*`,
+    /// `{1,}` as `+`, and `{1}` is dropped. Then, when a `dictionary` is
+    /// given, the one occurrence of an entry's sub-pattern in a pattern is
+    /// wrapped in a group named after the entry, as in `(?P<order_id>...)`,
+    /// unless that changes the pattern's language.
+    ///
+    /// # Arguments
+    ///
+    /// * `dictionary` - The named sub-patterns to refer to, if any.
+    ///
+    /// # Returns
+    ///
+    /// The rewritten tree.
+    pub fn humanize(&self, dictionary: Option<&DetectorDictionary>) -> Node {
+        let mut root = self.clone();
+        humanize_tree(&mut root, dictionary);
+        root
+    }
+}
+
+/// Rewrites every pattern of the tree, see [`Node::humanize`].
+pub(crate) fn humanize_tree(node: &mut Node, dictionary: Option<&DetectorDictionary>) {
+    node.pattern = humanize_pattern(&node.pattern).into();
+    if let Some(dictionary) = dictionary {
+        node.pattern = name_references(&node.pattern, dictionary).into();
+    }
+    for child in &mut node.children {
+        humanize_tree(Arc::make_mut(child), dictionary);
+    }
+}
+
+/// Adds the nesting depth and class items of `ast` to `readability`.
+fn measure(ast: &Ast, depth: usize, readability: &mut Readability) {
+    match ast {
+        Ast::Group(group) => {
+            readability.nesting_depth = readability.nesting_depth.max(depth + 1);
+            measure(&group.ast, depth + 1, readability);
+        }
+        Ast::ClassBracketed(class) => {
+            readability.nesting_depth = readability.nesting_depth.max(depth + 1);
+            readability.class_complexity += usize::from(class.negated);
+            measure_set(&class.kind, depth + 1, readability);
+        }
+        Ast::Repetition(repetition) => measure(&repetition.ast, depth, readability),
+        Ast::Alternation(alternation) => {
+            for branch in &alternation.asts {
+                measure(branch, depth, readability);
+            }
+        }
+        Ast::Concat(concat) => {
+            for item in &concat.asts {
+                measure(item, depth, readability);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Adds the items of a bracketed class to `readability`.
+fn measure_set(set: &ClassSet, depth: usize, readability: &mut Readability) {
+    match set {
+        ClassSet::Item(ClassSetItem::Empty(_)) => {}
+        ClassSet::Item(ClassSetItem::Union(union)) => {
+            for item in &union.items {
+                measure_set(&ClassSet::Item(item.clone()), depth, readability);
+            }
+        }
+        ClassSet::Item(ClassSetItem::Bracketed(class)) => {
+            measure(&Ast::class_bracketed((**class).clone()), depth, readability)
+        }
+        ClassSet::Item(_) => readability.class_complexity += 1,
+        ClassSet::BinaryOp(op) => {
+            measure_set(&op.lhs, depth, readability);
+            measure_set(&op.rhs, depth, readability);
+        }
+    }
+}
+
+/// Returns `pattern` with its digit classes and counted repetitions
+/// written as shorthands, or unchanged if it does not parse.
+fn humanize_pattern(pattern: &str) -> String {
+    let Ok(ast) = ast::parse::Parser::new().parse(pattern) else {
+        return pattern.to_string();
+    };
+    let mut edits = vec![];
+    collect_edits(&ast, &mut edits);
+    edits.sort_by_key(|&(start, _, _)| std::cmp::Reverse(start));
+    let mut out = pattern.to_string();
+    for (start, end, text) in edits {
+        out.replace_range(start..end, text);
+    }
+    match Regex::new(&out) {
+        Ok(_) => out,
+        Err(_) => pattern.to_string(),
+    }
+}
+
+/// Collects the byte ranges of `ast` to replace, and their replacements.
+fn collect_edits(ast: &Ast, edits: &mut Vec<(usize, usize, &'static str)>) {
+    match ast {
+        Ast::ClassBracketed(class) if is_digits(&class.kind) => {
+            let text = if class.negated { r"\D" } else { r"\d" };
+            edits.push((class.span.start.offset, class.span.end.offset, text));
+        }
+        Ast::Repetition(repetition) => {
+            collect_edits(&repetition.ast, edits);
+            // The operator's span covers the `?` of a lazy repetition, and
+            // `{1}?` is the same as `{1}`.
+            let operator = match (&repetition.op.kind, repetition.greedy) {
+                (RepetitionKind::Range(RepetitionRange::Exactly(1)), _) => "",
+                (RepetitionKind::Range(RepetitionRange::Bounded(0, 1)), true) => "?",
+                (RepetitionKind::Range(RepetitionRange::Bounded(0, 1)), false) => "??",
+                (RepetitionKind::Range(RepetitionRange::AtLeast(0)), true) => "*",
+                (RepetitionKind::Range(RepetitionRange::AtLeast(0)), false) => "*?",
+                (RepetitionKind::Range(RepetitionRange::AtLeast(1)), true) => "+",
+                (RepetitionKind::Range(RepetitionRange::AtLeast(1)), false) => "+?",
+                _ => return,
+            };
+            let span = &repetition.op.span;
+            edits.push((span.start.offset, span.end.offset, operator));
+        }
+        Ast::Group(group) => collect_edits(&group.ast, edits),
+        Ast::Alternation(alternation) => {
+            for branch in &alternation.asts {
+                collect_edits(branch, edits);
+            }
+        }
+        Ast::Concat(concat) => {
+            for item in &concat.asts {
+                collect_edits(item, edits);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns whether a bracketed class lists only the ASCII digits.
+fn is_digits(set: &ClassSet) -> bool {
+    match set {
+        ClassSet::Item(ClassSetItem::Range(range)) => range.start.c == '0' && range.end.c == '9',
+        ClassSet::Item(ClassSetItem::Ascii(class)) => {
+            class.kind == ClassAsciiKind::Digit && !class.negated
+        }
+        _ => false,
+    }
+}
+
+/// Returns `pattern` with the sub-pattern of each dictionary entry that
+/// occurs exactly once in it wrapped in a group named after the entry,
+/// when the group name is valid, unused and the language is unchanged.
+fn name_references(pattern: &str, dictionary: &DetectorDictionary) -> String {
+    let mut pattern = pattern.to_string();
+    for entry in dictionary.entries() {
+        let fragment = entry.pattern();
+        let group = format!("(?P<{}>", entry.name());
+        if fragment.is_empty() || pattern.matches(fragment).count() != 1 || pattern.contains(&group)
+        {
+            continue;
+        }
+        let named = pattern.replacen(fragment, &format!("{group}{fragment})"), 1);
+        if Regex::new(&named).is_ok() && same_language(&pattern, &named) {
+            pattern = named;
+        }
+    }
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readability_and_humanize() {
+        let plain = Node::new(r"^id-\d+$".to_string()).readability().unwrap();
+        let nested = Node::new(r"^id-(?:[0-9]{1,}|(?:[^a-fA-F_]))$".to_string())
+            .readability()
+            .unwrap();
+        assert_eq!(plain.nesting_depth(), 0);
+        assert_eq!(nested.nesting_depth(), 3);
+        assert_eq!(nested.class_complexity(), 1 + 4);
+        assert!(nested.score() > plain.score());
+        assert!(Node::new("(".to_string()).readability().is_err());
+
+        assert_eq!(
+            humanize_pattern(r"^[0-9]{1,}-[^0-9]{0,1}?[[:digit:]]{1}x{0,}[0-9a-f]$"),
+            r"^\d+-\D??\dx*[0-9a-f]$"
+        );
+        assert_eq!(humanize_pattern("(["), "([");
+
+        let mut dictionary = DetectorDictionary::new();
+        dictionary.insert("order_id", r"ORD-\d{4}");
+        let mut tree = Node::new(".*".to_string());
+        tree.children
+            .push(Node::new(r"^order ORD-[0-9]{4} shipped$".to_string()).into());
+        let humanized = tree.humanize(Some(&dictionary));
+        assert_eq!(
+            humanized.children[0].pattern,
+            r"^order (?P<order_id>ORD-\d{4}) shipped$"
+        );
+        assert_eq!(
+            tree.humanize(None).children[0].pattern,
+            r"^order ORD-\d{4} shipped$"
+        );
+    }
+}
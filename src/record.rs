@@ -0,0 +1,110 @@
+use crate::{Detector, TemplateId};
+use regex::Regex;
+use std::ops::Range;
+
+/// A field extracted from an input by a named capture group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordField {
+    name: String,
+    value: String,
+    span: Range<usize>,
+    detector: Option<Detector>,
+}
+
+impl RecordField {
+    /// Returns the name of the capture group.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the captured text.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the byte range of the value within the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the most specific detector accepting the value, or `None`
+    /// if the value contains whitespace or is empty.
+    pub fn detector(&self) -> Option<Detector> {
+        self.detector
+    }
+}
+
+/// The fields extracted from an input by the template it matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    template: TemplateId,
+    fields: Vec<RecordField>,
+}
+
+impl Record {
+    /// Extracts the named groups of `regex` that participate in its match
+    /// of `input`, or `None` if it does not match.
+    pub(crate) fn capture(template: TemplateId, regex: &Regex, input: &str) -> Option<Record> {
+        let groups = regex.captures(input)?;
+        let fields = regex
+            .capture_names()
+            .enumerate()
+            .filter_map(|(index, name)| {
+                let found = groups.get(index)?;
+                Some(RecordField {
+                    name: name?.to_string(),
+                    value: found.as_str().to_string(),
+                    span: found.range(),
+                    detector: Detector::infer(&[found.as_str()]),
+                })
+            })
+            .collect();
+        Some(Record { template, fields })
+    }
+
+    /// Returns the stable identifier of the template that matched.
+    pub fn template(&self) -> TemplateId {
+        self.template
+    }
+
+    /// Returns the fields in the order of their groups in the pattern.
+    pub fn fields(&self) -> &[RecordField] {
+        &self.fields
+    }
+
+    /// Returns the field captured by the group called `name`, if it
+    /// participated in the match.
+    pub fn field(&self, name: &str) -> Option<&RecordField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Returns the text captured by the group called `name`, if it
+    /// participated in the match.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.field(name).map(RecordField::value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let pattern = r"^(?P<user>\w+) from (?P<ip>\S+)(?: port (?P<port>\d+))?$";
+        let regex = Regex::new(pattern).unwrap();
+        let record =
+            Record::capture(TemplateId::of(pattern), &regex, "alice from 10.0.0.1").unwrap();
+
+        assert_eq!(record.template(), TemplateId::of(pattern));
+        let names: Vec<&str> = record.fields().iter().map(RecordField::name).collect();
+        assert_eq!(names, ["user", "ip"]);
+        let ip = record.field("ip").unwrap();
+        assert_eq!((ip.value(), ip.span()), ("10.0.0.1", 11..19));
+        assert_eq!(ip.detector(), Some(Detector::Ip));
+        assert_eq!(record.get("port"), None);
+        assert!(Record::capture(TemplateId::of(pattern), &regex, "").is_none());
+    }
+}
@@ -0,0 +1,102 @@
+/// Where a common substring occurs in the inputs containing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+    /// Every input starts with the substring.
+    Prefix,
+    /// Every input ends with the substring.
+    Suffix,
+    /// The substring occurs elsewhere in at least one input.
+    Infix,
+    /// Every input is exactly the substring.
+    Full,
+}
+
+impl Role {
+    /// Determines the role of `literal` from the inputs containing it.
+    ///
+    /// A substring that is both a prefix and a suffix of every input is
+    /// reported as a prefix.
+    pub(crate) fn of(literal: &str, inputs: &[&str]) -> Role {
+        let containing: Vec<&str> = inputs
+            .iter()
+            .copied()
+            .filter(|i| i.contains(literal))
+            .collect();
+        if containing.is_empty() {
+            Role::Infix
+        } else if containing.iter().all(|&i| i == literal) {
+            Role::Full
+        } else if containing.iter().all(|i| i.starts_with(literal)) {
+            Role::Prefix
+        } else if containing.iter().all(|i| i.ends_with(literal)) {
+            Role::Suffix
+        } else {
+            Role::Infix
+        }
+    }
+
+    /// Returns the pattern matching `literal` anchored according to the role.
+    pub(crate) fn pattern(&self, literal: &str) -> String {
+        let mut pattern = String::with_capacity(literal.len() + 4);
+        self.write_pattern(literal, &mut pattern);
+        pattern
+    }
+
+    /// Appends the pattern of `literal` to `out`, escaping it in place
+    /// rather than through an intermediate string.
+    pub(crate) fn write_pattern(&self, literal: &str, out: &mut String) {
+        let (open, close) = self.anchors();
+        out.push_str(open);
+        regex_syntax::escape_into(literal, out);
+        out.push_str(close);
+    }
+
+    /// Returns the fragments surrounding the escaped literal.
+    pub(crate) fn anchors(&self) -> (&'static str, &'static str) {
+        match self {
+            Role::Prefix => ("^", ".*"),
+            Role::Suffix => (".*", "$"),
+            Role::Infix => (".*", ".*"),
+            Role::Full => ("^", "$"),
+        }
+    }
+
+    /// Returns whether the pattern of `literal` in this role matches every
+    /// string the pattern of `specific` in role `role` matches.
+    pub(crate) fn covers(&self, literal: &str, role: Role, specific: &str) -> bool {
+        match self {
+            Role::Infix => specific.contains(literal),
+            Role::Prefix => {
+                matches!(role, Role::Prefix | Role::Full) && specific.starts_with(literal)
+            }
+            Role::Suffix => {
+                matches!(role, Role::Suffix | Role::Full) && specific.ends_with(literal)
+            }
+            Role::Full => role == Role::Full && specific == literal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role() {
+        let inputs = ["GET /index", "GET /about"];
+        assert_eq!(Role::of("GET /", &inputs), Role::Prefix);
+        assert_eq!(Role::of("out", &inputs), Role::Suffix);
+        assert_eq!(Role::of("/", &inputs), Role::Infix);
+        assert_eq!(Role::of("ping", &["ping", "ping"]), Role::Full);
+        assert_eq!(Role::Prefix.pattern("GET /"), "^GET /.*");
+        let mut buffer = String::from("stale");
+        buffer.clear();
+        Role::Full.write_pattern("a.b", &mut buffer);
+        assert_eq!(buffer, r"^a\.b$");
+
+        assert!(Role::Prefix.covers("GET", Role::Prefix, "GET /"));
+        assert!(Role::Infix.covers("ET", Role::Prefix, "GET /"));
+        assert!(!Role::Prefix.covers("GET", Role::Infix, "GET /"));
+    }
+}
@@ -0,0 +1,50 @@
+/// Splits `s` into chunks of about `length` bytes, each starting `length -
+/// overlap` bytes after the previous one, so that any window of at most
+/// `overlap` bytes lies entirely within some chunk. Chunk bounds are widened
+/// to char boundaries, unless a char is wider than the step, in which case
+/// the next chunk starts after it.
+pub(crate) fn overlapping_chunks(s: &str, length: usize, overlap: usize) -> Vec<&str> {
+    let step = length.saturating_sub(overlap).max(1);
+    let mut chunks = vec![];
+    let mut start = 0;
+    loop {
+        let end = ceil_char_boundary(s, start + length);
+        chunks.push(&s[start..end]);
+        if end == s.len() {
+            return chunks;
+        }
+        let mut next = start + step;
+        while !s.is_char_boundary(next) {
+            next -= 1;
+        }
+        start = match next > start {
+            true => next,
+            false => ceil_char_boundary(s, start + 1),
+        };
+    }
+}
+
+fn ceil_char_boundary(s: &str, mut index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    while !s.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping_chunks() {
+        assert_eq!(
+            overlapping_chunks("abcdefgh", 4, 2),
+            ["abcd", "cdef", "efgh"]
+        );
+        assert_eq!(overlapping_chunks("aé", 1, 0), ["a", "é"]);
+        assert_eq!(overlapping_chunks("🎉ab", 3, 1), ["🎉", "ab"]);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{Node, TreeError};
+use regex::{Regex, RegexSet};
+use std::collections::VecDeque;
+
+/// A rule of a rule list: a pattern and the pre-order identifier of the
+/// node it comes from.
+pub type Rule = (String, usize);
+
+impl Node {
+    /// Linearizes the tree into an ordered rule list, from the most
+    /// specific rule to the most general one.
+    ///
+    /// Rules are meant to be tried in order with first-match-wins
+    /// semantics, as most routing and filtering systems do: deeper levels
+    /// come before shallower ones, so an input is attributed to the deepest
+    /// node matching it, and the root, which matches everything, comes
+    /// last as the fallback.
+    ///
+    /// # Returns
+    ///
+    /// The `(pattern, id)` pairs, identifiers being pre-order positions
+    /// with the root at `0`.
+    pub fn to_rule_list(&self) -> Vec<Rule> {
+        let mut levels = vec![];
+        let mut queue = VecDeque::from([(self, 0)]);
+        while let Some((node, id)) = queue.pop_front() {
+            levels.push((node.pattern.to_string(), id));
+            let mut child_id = id + 1;
+            for child in &node.children {
+                queue.push_back((&**child, child_id));
+                child_id += child.size();
+            }
+        }
+        levels.reverse();
+        levels
+    }
+}
+
+/// A compiled rule list evaluating every rule in one pass.
+#[derive(Debug, Clone)]
+pub struct RuleList {
+    rules: Vec<Rule>,
+    set: RegexSet,
+}
+
+impl RuleList {
+    /// Compiles the rule list of a tree.
+    ///
+    /// # Returns
+    ///
+    /// The compiled rule list, or an error if a pattern does not compile.
+    pub fn new(root: &Node) -> Result<RuleList, TreeError> {
+        let rules = root.to_rule_list();
+        let set = compile_set(&rules)?;
+        Ok(RuleList { rules, set })
+    }
+
+    /// Returns the rules in evaluation order.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Returns the identifier of the first rule matching `input`, or `None`
+    /// if no rule does.
+    pub fn first_match(&self, input: &str) -> Option<usize> {
+        let first = self.set.matches(input).into_iter().next()?;
+        Some(self.rules[first].1)
+    }
+}
+
+/// A partition of inputs into leaf templates: every input is assigned
+/// exactly one template identifier, as needed when identifiers are used as
+/// metric labels.
+///
+/// Leaf patterns may overlap, so they are made mutually exclusive by
+/// ordered semantics: an input belongs to the first leaf matching it,
+/// deeper leaves coming before shallower ones and siblings in tree order.
+/// An input matched by no leaf belongs to the root, `0`.
+#[derive(Debug, Clone)]
+pub struct Partition {
+    leaves: Vec<Rule>,
+    set: RegexSet,
+}
+
+impl Partition {
+    /// Compiles the leaves of a tree into a partition.
+    ///
+    /// # Returns
+    ///
+    /// The partition, or an error if a leaf pattern does not compile.
+    pub fn new(root: &Node) -> Result<Partition, TreeError> {
+        let mut leaves = vec![];
+        collect_leaves(root, 0, 0, &mut leaves);
+        // Stable, so siblings keep their tree order within a depth.
+        leaves.sort_by_key(|&(_, _, depth)| std::cmp::Reverse(depth));
+        let leaves: Vec<Rule> = leaves
+            .into_iter()
+            .map(|(pattern, id, _)| (pattern, id))
+            .collect();
+        let set = compile_set(&leaves)?;
+        Ok(Partition { leaves, set })
+    }
+
+    /// Returns the leaf rules in precedence order.
+    pub fn leaves(&self) -> &[Rule] {
+        &self.leaves
+    }
+
+    /// Returns the template identifier of `input`: the pre-order
+    /// identifier of the first leaf matching it, or `0` if none does.
+    pub fn assign(&self, input: &str) -> usize {
+        self.set
+            .matches(input)
+            .into_iter()
+            .next()
+            .map_or(0, |first| self.leaves[first].1)
+    }
+
+    /// Returns the indices of the inputs matched by more than one leaf,
+    /// whose template is decided by precedence rather than by the patterns
+    /// alone.
+    pub fn ambiguous(&self, inputs: &[&str]) -> Vec<usize> {
+        (0..inputs.len())
+            .filter(|&index| self.set.matches(inputs[index]).iter().nth(1).is_some())
+            .collect()
+    }
+}
+
+/// Appends the leaves below `node`, whose pre-order identifier is `id`,
+/// with their identifier and depth.
+fn collect_leaves(node: &Node, id: usize, depth: usize, leaves: &mut Vec<(String, usize, usize)>) {
+    if node.children.is_empty() {
+        if id > 0 {
+            leaves.push((node.pattern.to_string(), id, depth));
+        }
+        return;
+    }
+    let mut child_id = id + 1;
+    for child in &node.children {
+        collect_leaves(child, child_id, depth + 1, leaves);
+        child_id += child.size();
+    }
+}
+
+fn compile_set(rules: &[Rule]) -> Result<RegexSet, TreeError> {
+    RegexSet::new(rules.iter().map(|(pattern, _)| pattern)).map_err(|e| {
+        // The set does not say which pattern failed, so find it.
+        let pattern = rules
+            .iter()
+            .map(|(pattern, _)| pattern)
+            .find(|pattern| Regex::new(pattern).is_err())
+            .cloned()
+            .unwrap_or_default();
+        TreeError::InvalidPattern {
+            pattern,
+            message: e.to_string(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_rule_list() {
+        let tree = generate_regex_tree(&["disk full on sda", "disk full on sdb"]);
+        let rules = tree.to_rule_list();
+        assert_eq!(rules.len(), tree.size());
+        assert_eq!(rules.last(), Some(&(".*".to_string(), 0)));
+
+        let list = RuleList::new(&tree).unwrap();
+        let id = list.first_match("disk full on sdc").unwrap();
+        let matched = &tree.preorder()[id];
+        assert!(matched.children.is_empty());
+        assert!(matched.pattern.contains("disk full on sd"));
+    }
+
+    #[test]
+    fn test_partition() {
+        let mut root = Node::new(".*".to_string());
+        let mut disk = Node::new(".*disk.*".to_string());
+        disk.children
+            .push(std::sync::Arc::new(Node::new(".*disk full.*".to_string())));
+        root.children.push(std::sync::Arc::new(disk));
+        root.children
+            .push(std::sync::Arc::new(Node::new(".*full.*".to_string())));
+
+        let partition = Partition::new(&root).unwrap();
+        assert_eq!(
+            partition.leaves(),
+            [
+                (".*disk full.*".to_string(), 2),
+                (".*full.*".to_string(), 3)
+            ]
+        );
+        let inputs = ["disk full", "cpu full", "disk slow"];
+        let ids: Vec<usize> = inputs.iter().map(|s| partition.assign(s)).collect();
+        assert_eq!(ids, vec![2, 3, 0]);
+        assert_eq!(partition.ambiguous(&inputs), vec![0]);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::subsumption::anchored_literal;
+use crate::{Node, Role};
+use regex::Regex;
+use regex_syntax::hir::{Hir, HirKind};
+use std::sync::Arc;
+
+/// How specific a pattern is and how many inputs it covers, recorded on
+/// every node of a generated tree to tell `.*highlighted .*` from `.*e.*`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatternScore {
+    length: usize,
+    pinned_chars: usize,
+    specificity: f64,
+    coverage: f64,
+}
+
+impl PatternScore {
+    /// Returns the length of the pattern, in characters.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Returns how many literal characters every match contains.
+    pub fn pinned_chars(&self) -> usize {
+        self.pinned_chars
+    }
+
+    /// Returns the mean fraction of each input the pinned characters
+    /// account for, between `0` for `.*` and `1` for an exact match.
+    pub fn specificity(&self) -> f64 {
+        self.specificity
+    }
+
+    /// Returns the fraction of the inputs the pattern matched.
+    pub fn coverage(&self) -> f64 {
+        self.coverage
+    }
+}
+
+impl Node {
+    /// Returns the score recorded when the tree was generated, or `None`
+    /// for nodes built or edited otherwise.
+    pub fn score(&self) -> Option<PatternScore> {
+        self.score
+    }
+}
+
+/// Records the score of every node of a tree against the inputs it was
+/// mined from.
+///
+/// A pattern still in the form emitted for a mined substring is checked
+/// with plain string comparisons; others are compiled, and left unscored
+/// if they do not compile.
+pub(crate) fn score_tree(node: &mut Node, inputs: &[&str]) {
+    for child in &mut node.children {
+        score_tree(Arc::make_mut(child), inputs);
+    }
+    let Ok(hir) = regex_syntax::parse(&node.pattern) else {
+        return;
+    };
+    let matched = match anchored_literal(&node.pattern) {
+        Some((role, literal)) => inputs
+            .iter()
+            .filter(|s| match role {
+                Role::Prefix => s.starts_with(literal.as_str()),
+                Role::Suffix => s.ends_with(literal.as_str()),
+                Role::Infix => s.contains(literal.as_str()),
+                Role::Full => **s == literal,
+            })
+            .count(),
+        None => match Regex::new(&node.pattern) {
+            Ok(regex) => inputs.iter().filter(|s| regex.is_match(s)).count(),
+            Err(_) => return,
+        },
+    };
+    let pinned_chars = pinned_chars(&hir);
+    let specificity = if inputs.is_empty() {
+        0.0
+    } else {
+        inputs
+            .iter()
+            .map(|s| match s.chars().count() {
+                0 => 1.0,
+                length => (pinned_chars as f64 / length as f64).min(1.0),
+            })
+            .sum::<f64>()
+            / inputs.len() as f64
+    };
+    node.score = Some(PatternScore {
+        length: node.pattern.chars().count(),
+        pinned_chars,
+        specificity,
+        coverage: match inputs.len() {
+            0 => 0.0,
+            total => matched as f64 / total as f64,
+        },
+    });
+}
+
+/// Returns how many literal characters every match of `hir` contains.
+pub(crate) fn pinned_chars(hir: &Hir) -> usize {
+    match hir.kind() {
+        HirKind::Literal(literal) => String::from_utf8_lossy(&literal.0).chars().count(),
+        HirKind::Capture(capture) => pinned_chars(&capture.sub),
+        HirKind::Repetition(repetition) => repetition.min as usize * pinned_chars(&repetition.sub),
+        HirKind::Concat(parts) => parts.iter().map(pinned_chars).sum(),
+        HirKind::Alternation(branches) => branches.iter().map(pinned_chars).min().unwrap_or(0),
+        HirKind::Empty | HirKind::Class(_) | HirKind::Look(_) => 0,
+    }
+}
+
+/// Returns the `k` most specific nodes of a tree that matched every input
+/// it was generated from, by decreasing specificity, then by number of
+/// pinned characters, then in pre-order. Unscored nodes are skipped.
+///
+/// # Arguments
+///
+/// * `root` - The root node of a generated tree.
+/// * `k` - The largest number of nodes returned.
+///
+/// # Returns
+///
+/// The selected nodes.
+pub fn best_patterns(root: &Node, k: usize) -> Vec<&Node> {
+    let mut scored: Vec<(PatternScore, &Node)> = root
+        .preorder()
+        .into_iter()
+        .filter_map(|node| Some((node.score?, node)))
+        .filter(|(score, _)| score.coverage >= 1.0)
+        .collect();
+    scored.sort_by(|(a, _), (b, _)| {
+        b.specificity
+            .total_cmp(&a.specificity)
+            .then(b.pinned_chars.cmp(&a.pinned_chars))
+    });
+    scored.into_iter().take(k).map(|(_, node)| node).collect()
+}
+
+/// Returns the nodes of a tree offering the best trade-offs between
+/// coverage, specificity, which estimates precision, and length, so users
+/// can pick their own rather than rely on a single weighted score.
+///
+/// A node is kept unless another one is at least as good on all three
+/// measures and better on one: it covers as many inputs, is as specific,
+/// and is no longer. Unscored nodes are skipped.
+///
+/// # Arguments
+///
+/// * `root` - The root node of a generated tree.
+///
+/// # Returns
+///
+/// The Pareto front, by decreasing coverage, then by decreasing
+/// specificity, then by increasing length, then in pre-order.
+pub fn pareto_patterns(root: &Node) -> Vec<&Node> {
+    let scored: Vec<(PatternScore, &Node)> = root
+        .preorder()
+        .into_iter()
+        .filter_map(|node| Some((node.score?, node)))
+        .collect();
+    let dominates = |a: &PatternScore, b: &PatternScore| {
+        a.coverage >= b.coverage
+            && a.specificity >= b.specificity
+            && a.length <= b.length
+            && (a.coverage > b.coverage || a.specificity > b.specificity || a.length < b.length)
+    };
+    let mut front: Vec<(PatternScore, &Node)> = scored
+        .iter()
+        .filter(|(score, _)| !scored.iter().any(|(other, _)| dominates(other, score)))
+        .copied()
+        .collect();
+    front.sort_by(|(a, _), (b, _)| {
+        b.coverage
+            .total_cmp(&a.coverage)
+            .then(b.specificity.total_cmp(&a.specificity))
+            .then(a.length.cmp(&b.length))
+    });
+    front.into_iter().map(|(_, node)| node).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_best_patterns() {
+        let inputs = [
+            "highlighted text",
+            "highlighted part",
+            "highlighted section",
+        ];
+        let tree = generate_regex_tree(&inputs);
+
+        let root = tree.score().unwrap();
+        assert_eq!((root.pinned_chars(), root.specificity()), (0, 0.0));
+        let e = tree
+            .find(|n| n.pattern() == ".*e.*")
+            .unwrap()
+            .score()
+            .unwrap();
+        let prefix = tree.find(|n| n.pattern() == "^highlighted .*").unwrap();
+        let prefix_score = prefix.score().unwrap();
+        assert_eq!(prefix_score.pinned_chars(), 12);
+        assert_eq!(prefix_score.coverage(), 1.0);
+        assert!(prefix_score.specificity() > e.specificity());
+
+        let best = best_patterns(&tree, 2);
+        assert_eq!(best.len(), 2);
+        assert_eq!(best[0].pattern(), "^highlighted .*");
+        assert!(best_patterns(&tree, 0).is_empty());
+    }
+
+    #[test]
+    fn test_pareto_patterns() {
+        let inputs = ["GET /a 200", "GET /b 200", "POST /a 500"];
+        let tree = generate_regex_tree(&inputs);
+        let front: Vec<&str> = pareto_patterns(&tree)
+            .iter()
+            .map(|n| n.pattern().as_str())
+            .collect();
+        // Every input matches them all, so each is shorter or more specific
+        // than the others, and the rest are dominated by one of them.
+        assert_eq!(front, [".*T /.*", ".*00$", ".*0$", ".*"]);
+        assert!(pareto_patterns(&Node::new(".*".to_string())).is_empty());
+    }
+}
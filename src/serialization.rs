@@ -0,0 +1,237 @@
+use crate::{LoadError, Node};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The version of the serialized tree format written by this crate.
+///
+/// Trees serialized before the format was versioned are a bare node and are
+/// treated as version `0`.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    root: Node,
+}
+
+/// A hook run when loading a tree serialized by an older format version.
+type Hook = Box<dyn Fn(&mut Node) -> Result<(), String>>;
+
+/// Loads serialized trees, running user-registered hooks to bring trees
+/// written by older versions up to date.
+///
+/// A hook registered for version `v` runs when a tree of version `v` or
+/// older is loaded; hooks run from the oldest version to the newest, in
+/// registration order.
+#[derive(Default)]
+pub struct Upgrader {
+    hooks: BTreeMap<u32, Vec<Hook>>,
+}
+
+impl Upgrader {
+    /// Creates an upgrader without hooks.
+    pub fn new() -> Self {
+        Upgrader::default()
+    }
+
+    /// Registers a hook upgrading trees of format version `version`.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The format version the hook upgrades from.
+    /// * `hook` - Adjusts the loaded tree, or explains why it is unusable.
+    pub fn on_upgrade_from<F>(mut self, version: u32, hook: F) -> Self
+    where
+        F: Fn(&mut Node) -> Result<(), String> + 'static,
+    {
+        self.hooks.entry(version).or_default().push(Box::new(hook));
+        self
+    }
+
+    /// Registers a hook checking that every pattern of a tree of format
+    /// version `version` still compiles and matches all of `sample`.
+    pub fn reverify_with(self, version: u32, sample: &[&str]) -> Self {
+        let sample: Vec<String> = sample.iter().map(|s| s.to_string()).collect();
+        self.on_upgrade_from(version, move |root| verify_patterns(root, &sample))
+    }
+
+    /// Loads a tree from JSON, upgrading it if it was written by an older
+    /// format version.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The serialized tree.
+    ///
+    /// # Returns
+    ///
+    /// The tree, or an error if it cannot be parsed, was written by a newer
+    /// version, or is rejected by a hook.
+    pub fn load(&self, json: &str) -> Result<Node, LoadError> {
+        self.load_value(serde_json::from_str(json).map_err(LoadError::Json)?)
+    }
+
+    /// Loads a tree from YAML, upgrading it like [`Upgrader::load`].
+    ///
+    /// # Arguments
+    ///
+    /// * `yaml` - The serialized tree.
+    ///
+    /// # Returns
+    ///
+    /// The tree, or an error if it cannot be parsed, was written by a newer
+    /// version, or is rejected by a hook.
+    pub fn load_yaml(&self, yaml: &str) -> Result<Node, LoadError> {
+        self.load_value(serde_yaml::from_str(yaml).map_err(LoadError::Yaml)?)
+    }
+
+    fn load_value(&self, value: Value) -> Result<Node, LoadError> {
+        let (version, mut root) = if value.get("version").is_some() {
+            let envelope: Envelope = serde_json::from_value(value).map_err(LoadError::Json)?;
+            (envelope.version, envelope.root)
+        } else {
+            (0, serde_json::from_value(value).map_err(LoadError::Json)?)
+        };
+
+        if version > FORMAT_VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+        for (&from, hooks) in self.hooks.range(version..FORMAT_VERSION) {
+            for hook in hooks {
+                hook(&mut root).map_err(|message| LoadError::Hook {
+                    version: from,
+                    message,
+                })?;
+            }
+        }
+        Ok(root)
+    }
+}
+
+impl Node {
+    /// Serializes the tree to JSON, tagged with the current format version.
+    ///
+    /// The document is an object with the `version` and the `root` node.
+    /// A node is an object with its `pattern` and its `children`, then,
+    /// only when present, the metadata: `collapsed` patterns, `match_count`,
+    /// `role`, `observed_values` and `examples`. New metadata is only ever
+    /// added as optional fields, so older documents keep loading.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(&Envelope {
+            version: FORMAT_VERSION,
+            root: self.clone(),
+        })
+    }
+
+    /// Loads a tree from JSON without any upgrade hooks.
+    pub fn from_json(json: &str) -> Result<Node, LoadError> {
+        Upgrader::new().load(json)
+    }
+
+    /// Serializes the tree to YAML, with the same layout as
+    /// [`Node::to_json`].
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(&Envelope {
+            version: FORMAT_VERSION,
+            root: self.clone(),
+        })
+    }
+
+    /// Loads a tree from YAML without any upgrade hooks.
+    pub fn from_yaml(yaml: &str) -> Result<Node, LoadError> {
+        Upgrader::new().load_yaml(yaml)
+    }
+}
+
+fn verify_patterns(node: &Node, sample: &[String]) -> Result<(), String> {
+    let regex = Regex::new(&node.pattern).map_err(|e| e.to_string())?;
+    if let Some(missed) = sample.iter().find(|s| !regex.is_match(s)) {
+        return Err(format!(
+            "pattern {:?} no longer matches {missed:?}",
+            node.pattern
+        ));
+    }
+    node.children
+        .iter()
+        .try_for_each(|child| verify_patterns(child, sample))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_upgrade_hooks_on_load() {
+        let tree = generate_regex_tree(&["disk full", "disk slow"]);
+        let json = tree.to_json().unwrap();
+        let reloaded = Node::from_json(&json).unwrap();
+        assert_eq!(reloaded.pattern, tree.pattern);
+        assert_eq!(reloaded.children.len(), tree.children.len());
+
+        let legacy = r#"{"pattern":".*","children":[{"pattern":".*disk .*","children":[]}]}"#;
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let upgrader = Upgrader::new()
+            .on_upgrade_from(0, move |_| {
+                counter.set(counter.get() + 1);
+                Ok(())
+            })
+            .reverify_with(0, &["disk full"]);
+        assert!(upgrader.load(legacy).is_ok());
+        assert!(upgrader.load(&json).is_ok());
+        assert_eq!(calls.get(), 1);
+
+        let strict = Upgrader::new().reverify_with(0, &["network down"]);
+        assert!(matches!(
+            strict.load(legacy),
+            Err(LoadError::Hook { version: 0, .. })
+        ));
+        assert!(matches!(
+            Node::from_json(r#"{"version":99,"root":{"pattern":"","children":[]}}"#),
+            Err(LoadError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let inputs = ["GET /index", "POST /index"];
+        let options = crate::GenerateOptions {
+            max_alternatives: Some(2),
+            examples_per_node: 1,
+            ..crate::GenerateOptions::default()
+        };
+        let mut tree = crate::generate_regex_tree_with_options(&inputs, &options).unwrap();
+        crate::prune_by_coverage(&mut tree, &inputs, 0.0).unwrap();
+        let fields = |tree: &Node| -> Vec<String> {
+            tree.preorder()
+                .iter()
+                .map(|n| {
+                    format!(
+                        "{} {:?} {:?} {:?} {:?} {}",
+                        n.pattern,
+                        n.match_count,
+                        n.role,
+                        n.observed_values,
+                        n.examples,
+                        n.children.len()
+                    )
+                })
+                .collect()
+        };
+
+        let json = tree.to_json().unwrap();
+        assert!(json.starts_with(r#"{"version":1,"root":{"pattern":".*","children":["#));
+        assert_eq!(fields(&Node::from_json(&json).unwrap()), fields(&tree));
+        let yaml = tree.to_yaml().unwrap();
+        assert!(yaml.starts_with("version: 1\nroot:\n  pattern: .*\n"));
+        assert_eq!(fields(&Node::from_yaml(&yaml).unwrap()), fields(&tree));
+        assert!(matches!(
+            Node::from_yaml("version: ["),
+            Err(LoadError::Yaml(_))
+        ));
+    }
+}
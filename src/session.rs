@@ -0,0 +1,196 @@
+use crate::{contains_pattern, select_candidates, GenerateOptions};
+use std::collections::HashMap;
+
+/// The number of candidates returned by [`Session::propose`] by default.
+const DEFAULT_PROPOSAL_LIMIT: usize = 10;
+
+/// A candidate pattern proposed by a [`Session`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    id: usize,
+    literal: String,
+    pattern: String,
+}
+
+impl Candidate {
+    /// Returns the identifier used to accept or reject the candidate.
+    ///
+    /// A literal keeps the same identifier for the whole session.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the common substring the candidate is built on.
+    pub fn literal(&self) -> &str {
+        &self.literal
+    }
+
+    /// Returns the regex pattern of the candidate.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+/// A human-in-the-loop refinement session.
+///
+/// Each call to [`Session::propose`] mines the current examples for
+/// candidates. Accepted candidates are pinned as required literals, and
+/// rejected ones are never proposed again, nor is anything extending them,
+/// so every decision constrains the following proposals.
+#[derive(Debug, Clone)]
+pub struct Session {
+    examples: Vec<String>,
+    accepted: Vec<String>,
+    rejected: Vec<String>,
+    ids: HashMap<String, usize>,
+    limit: usize,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+impl Session {
+    /// Creates an empty session.
+    pub fn new() -> Self {
+        Session {
+            examples: vec![],
+            accepted: vec![],
+            rejected: vec![],
+            ids: HashMap::new(),
+            limit: DEFAULT_PROPOSAL_LIMIT,
+        }
+    }
+
+    /// Sets the maximum number of candidates returned by each proposal.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Adds an example the proposed patterns must match.
+    ///
+    /// Accepted candidates that the new example does not contain are
+    /// dropped, since no common pattern can include them anymore.
+    pub fn add_example(&mut self, example: &str) {
+        self.accepted
+            .retain(|literal| example.contains(literal.as_str()));
+        self.examples.push(example.to_string());
+    }
+
+    /// Proposes candidates for the current examples, best first.
+    ///
+    /// # Returns
+    ///
+    /// Up to the session limit of candidates, starting with the accepted
+    /// ones and excluding anything extending a rejected candidate.
+    pub fn propose(&mut self) -> Vec<Candidate> {
+        if self.examples.is_empty() {
+            return vec![];
+        }
+
+        let strings: Vec<&str> = self.examples.iter().map(String::as_str).collect();
+        let options = GenerateOptions {
+            required_literals: self.accepted.clone(),
+            ..GenerateOptions::default()
+        };
+
+        let literals: Vec<String> = select_candidates(&strings, &options)
+            .into_iter()
+            .filter(|l| !self.rejected.iter().any(|r| l.contains(r.as_str())))
+            .take(self.limit)
+            .map(str::to_string)
+            .collect();
+        literals
+            .into_iter()
+            .map(|literal| {
+                let next = self.ids.len();
+                let id = *self.ids.entry(literal.clone()).or_insert(next);
+                Candidate {
+                    id,
+                    pattern: contains_pattern(&literal),
+                    literal,
+                }
+            })
+            .collect()
+    }
+
+    /// Accepts a previously proposed candidate.
+    ///
+    /// # Returns
+    ///
+    /// `false` if no candidate with this identifier was ever proposed.
+    pub fn accept(&mut self, id: usize) -> bool {
+        let Some(literal) = self.literal(id) else {
+            return false;
+        };
+        self.rejected.retain(|r| *r != literal);
+        if !self.accepted.contains(&literal) {
+            self.accepted.push(literal);
+        }
+        true
+    }
+
+    /// Rejects a previously proposed candidate.
+    ///
+    /// # Returns
+    ///
+    /// `false` if no candidate with this identifier was ever proposed.
+    pub fn reject(&mut self, id: usize) -> bool {
+        let Some(literal) = self.literal(id) else {
+            return false;
+        };
+        self.accepted.retain(|a| *a != literal);
+        if !self.rejected.contains(&literal) {
+            self.rejected.push(literal);
+        }
+        true
+    }
+
+    /// Returns the literals accepted so far.
+    pub fn accepted(&self) -> &[String] {
+        &self.accepted
+    }
+
+    fn literal(&self, id: usize) -> Option<String> {
+        self.ids
+            .iter()
+            .find(|(_, &known)| known == id)
+            .map(|(literal, _)| literal.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_refinement_loop() {
+        let mut session = Session::new().with_limit(3);
+        session.add_example("Acme: disk full on sda");
+        session.add_example("Acme: disk full on sdb");
+
+        let first = session.propose();
+        assert_eq!(first[0].literal(), "Acme: disk full on sd");
+
+        assert!(session.reject(first[0].id()));
+        let second = session.propose();
+        assert!(second
+            .iter()
+            .all(|c| !c.literal().contains("Acme: disk full on sd")));
+
+        let disk = second
+            .iter()
+            .find(|c| c.literal().contains("disk"))
+            .unwrap();
+        assert!(session.accept(disk.id()));
+        let third = session.propose();
+        assert_eq!(third[0], *disk);
+
+        session.add_example("Acme: disk full on nvme0");
+        assert!(session.accepted().is_empty());
+        assert!(!session.accept(999));
+    }
+}
@@ -0,0 +1,310 @@
+use crate::subsumption::{same_language, subsumes};
+use crate::{Children, Node};
+use regex::Regex;
+use regex_syntax::ast::{self, Ast, GroupKind, RepetitionKind, RepetitionRange};
+use std::sync::Arc;
+
+impl Node {
+    /// Returns a minimal copy of the tree matching what this one matches.
+    ///
+    /// Patterns are tidied first: runs of adjacent wildcards collapse into
+    /// one, so `.*.*foo.*` becomes `.*foo.*` and `.+.*` becomes `.+`, and
+    /// non-capturing groups holding only literals are unwrapped. Then nodes
+    /// implied by a descendant, one matching everything they match, are
+    /// removed and their children re-linked to their parent, leaves
+    /// matching the same strings as an earlier sibling are dropped, as are
+    /// leaves matching everything a sibling matches and more, such as
+    /// `.*li.*` beside `^user alice.*`, and chains of single-child nodes
+    /// are merged as by [`Node::compress_chains`]. Every leaf left keeps
+    /// its language, and since every node of a generated tree matches all
+    /// the inputs, so does every remaining leaf. The root stays the root.
+    ///
+    /// # Returns
+    ///
+    /// The simplified tree.
+    pub fn simplify(&self) -> Node {
+        let mut root = Node {
+            pattern: collapse(&self.pattern).into(),
+            children: simplified_children(self),
+            ..self.clone()
+        };
+        root.compress_chains();
+        drop_repeated_leaves(&mut root);
+        drop_general_leaves(&mut root);
+        root
+    }
+}
+
+/// Returns the children of `node` simplified, with the nodes implied by a
+/// descendant replaced by their own children.
+fn simplified_children(node: &Node) -> Children {
+    let mut children = Children::new();
+    for child in &node.children {
+        let grandchildren = simplified_children(child);
+        let pattern = collapse(&child.pattern);
+        let implied = grandchildren.iter().any(|grandchild| {
+            grandchild
+                .preorder()
+                .iter()
+                .any(|descendant| subsumes(&descendant.pattern, &pattern))
+        });
+        if implied {
+            children.extend(grandchildren);
+        } else {
+            children.push(Arc::new(Node {
+                pattern: pattern.into(),
+                children: grandchildren,
+                ..(**child).clone()
+            }));
+        }
+    }
+    children
+}
+
+/// Drops, below `node`, the leaves matching the same strings as an earlier
+/// sibling leaf.
+fn drop_repeated_leaves(node: &mut Node) {
+    let children = std::mem::take(&mut node.children);
+    for mut child in children {
+        let repeated = child.children.is_empty()
+            && node.children.iter().any(|sibling| {
+                sibling.children.is_empty() && same_language(&sibling.pattern, &child.pattern)
+            });
+        if !repeated {
+            drop_repeated_leaves(Arc::make_mut(&mut child));
+            node.children.push(child);
+        }
+    }
+}
+
+/// Drops, below `node`, the leaves whose pattern strictly subsumes that of
+/// a sibling.
+fn drop_general_leaves(node: &mut Node) {
+    for child in &mut node.children {
+        drop_general_leaves(Arc::make_mut(child));
+    }
+    let general: Vec<bool> = node
+        .children
+        .iter()
+        .enumerate()
+        .map(|(index, child)| {
+            child.children.is_empty()
+                && node.children.iter().enumerate().any(|(other, sibling)| {
+                    other != index
+                        && subsumes(&child.pattern, &sibling.pattern)
+                        && !subsumes(&sibling.pattern, &child.pattern)
+                })
+        })
+        .collect();
+    let mut general = general.into_iter();
+    node.children.retain(|_| !general.next().unwrap_or(false));
+}
+
+/// Returns `pattern` with adjacent wildcards merged and literal-only
+/// non-capturing groups unwrapped, or unchanged if it does not parse.
+fn collapse(pattern: &str) -> String {
+    let Ok(ast) = ast::parse::Parser::new().parse(pattern) else {
+        return pattern.to_string();
+    };
+    let mut out = String::with_capacity(pattern.len());
+    write_item(&ast, pattern, &mut out);
+    match Regex::new(&out) {
+        Ok(_) => out,
+        Err(_) => pattern.to_string(),
+    }
+}
+
+/// Returns the source text of a span of `pattern`.
+fn text<'p>(pattern: &'p str, span: &ast::Span) -> &'p str {
+    &pattern[span.start.offset..span.end.offset]
+}
+
+/// Returns the minimum and greediness of a repetition of `.` without a
+/// maximum, such as `.*` or `.+?`.
+fn wildcard(ast: &Ast) -> Option<(u32, bool)> {
+    let Ast::Repetition(repetition) = ast else {
+        return None;
+    };
+    if !matches!(*repetition.ast, Ast::Dot(_)) {
+        return None;
+    }
+    let min = match &repetition.op.kind {
+        RepetitionKind::ZeroOrMore => 0,
+        RepetitionKind::OneOrMore => 1,
+        RepetitionKind::Range(RepetitionRange::AtLeast(n)) => *n,
+        _ => return None,
+    };
+    Some((min, repetition.greedy))
+}
+
+/// Returns the contents of `ast` if it is a non-capturing group without
+/// flags holding only literals.
+fn literal_group(ast: &Ast) -> Option<&Ast> {
+    let Ast::Group(group) = ast else {
+        return None;
+    };
+    let GroupKind::NonCapturing(flags) = &group.kind else {
+        return None;
+    };
+    let literal = match &*group.ast {
+        Ast::Literal(_) => true,
+        Ast::Concat(concat) => concat.asts.iter().all(|a| matches!(a, Ast::Literal(_))),
+        _ => false,
+    };
+    (flags.items.is_empty() && literal).then_some(&group.ast)
+}
+
+/// Writes an item that is not repeated, unwrapping it if it is a literal
+/// group.
+fn write_item(ast: &Ast, pattern: &str, out: &mut String) {
+    match literal_group(ast) {
+        Some(inner) => out.push_str(text(pattern, inner.span())),
+        None => write_collapsed(ast, pattern, out),
+    }
+}
+
+/// Writes `ast` with its adjacent wildcards merged.
+fn write_collapsed(ast: &Ast, pattern: &str, out: &mut String) {
+    match ast {
+        // Inline flags may change what `.` matches from one item to the
+        // next, so sequences setting any are left alone.
+        Ast::Concat(concat) if concat.asts.iter().any(|a| matches!(a, Ast::Flags(_))) => {
+            out.push_str(text(pattern, &concat.span))
+        }
+        Ast::Concat(concat) => {
+            let mut items = concat.asts.iter().peekable();
+            while let Some(item) = items.next() {
+                if let Some((mut min, mut greedy)) = wildcard(item) {
+                    while let Some((next_min, next_greedy)) = items.peek().and_then(|a| wildcard(a))
+                    {
+                        min += next_min;
+                        greedy |= next_greedy;
+                        items.next();
+                    }
+                    out.push_str(&match min {
+                        0 => ".*".to_string(),
+                        1 => ".+".to_string(),
+                        min => format!(".{{{min},}}"),
+                    });
+                    if !greedy {
+                        out.push('?');
+                    }
+                } else {
+                    write_item(item, pattern, out);
+                }
+            }
+        }
+        Ast::Group(group) => {
+            out.push_str(&pattern[group.span.start.offset..group.ast.span().start.offset]);
+            write_item(&group.ast, pattern, out);
+            out.push(')');
+        }
+        Ast::Alternation(alternation) => {
+            for (n, branch) in alternation.asts.iter().enumerate() {
+                if n > 0 {
+                    out.push('|');
+                }
+                write_item(branch, pattern, out);
+            }
+        }
+        Ast::Repetition(repetition) => {
+            write_collapsed(&repetition.ast, pattern, out);
+            out.push_str(&pattern[repetition.ast.span().end.offset..repetition.span.end.offset]);
+        }
+        ast => out.push_str(text(pattern, ast.span())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{equivalent, generate_regex_tree};
+
+    #[test]
+    fn test_simplify() {
+        assert_eq!(collapse(".*.*foo.*"), ".*foo.*");
+        assert_eq!(
+            collapse("^a.+.*?b.*?.*?(?:cd)(x|.*.*)+$"),
+            "^a.+b.*?cd(x|.*)+$"
+        );
+        assert_eq!(collapse("(?s).*(?-s).*"), "(?s).*(?-s).*");
+        assert_eq!(collapse("(?:ab)+|(?:c)"), "(?:ab)+|c");
+
+        let mut tree = Node::new(".*".to_string());
+        let mut chain = Node::new(".*e.*".to_string());
+        let mut ed = Node::new(".*.*ed.*".to_string());
+        ed.children.push(Node::new(".*ted.*".to_string()).into());
+        chain.children.push(ed.into());
+        chain.children.push(Node::new("(?:e)".to_string()).into());
+        tree.children.push(chain.into());
+        tree.children.push(Node::new(".*ted.*".to_string()).into());
+        let simplified = tree.simplify();
+        let patterns: Vec<&str> = simplified.iter().map(|n| n.pattern().as_str()).collect();
+        // `e` matches everything `.*ted.*` matches.
+        assert_eq!(patterns, [".*", ".*ted.*"]);
+        assert_eq!(simplified.children[0].collapsed(), [".*ed.*"]);
+
+        // On generated trees, the leaves left are leaves of the tree, and
+        // every input is still matched by all of them.
+        let words = [
+            "disk", "net", "cpu", "fan", "sda", "eth0", "ok", "full", "down",
+        ];
+        let mut state = 7u64;
+        let mut pick = |n: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as usize % n
+        };
+        for _ in 0..12 {
+            let inputs: Vec<String> = (0..2 + pick(6))
+                .map(|_| {
+                    (0..1 + pick(4))
+                        .map(|_| words[pick(words.len())])
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect();
+            let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+            let tree = generate_regex_tree(&inputs);
+            let simplified = tree.simplify();
+            assert!(simplified.size() <= tree.size(), "{inputs:?}");
+            let leaves = |root: &Node| -> Vec<String> {
+                root.iter()
+                    .filter(|n| n.children.is_empty())
+                    .map(|n| n.pattern.to_string())
+                    .collect()
+            };
+            let original = leaves(&tree);
+            for leaf in leaves(&simplified) {
+                assert!(original.iter().any(|o| same_language(o, &leaf)), "{leaf}");
+                let regex = Regex::new(&leaf).unwrap();
+                assert!(inputs.iter().all(|s| regex.is_match(s)), "{leaf}");
+            }
+            let before = Regex::new(&tree.to_regex()).unwrap();
+            let after = Regex::new(&simplified.to_regex()).unwrap();
+            for input in &inputs {
+                assert_eq!(before.is_match(input), after.is_match(input), "{input:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplify_drops_general_leaves() {
+        let inputs = ["user alice logged in", "user bob logged in"];
+        let tree = generate_regex_tree(&inputs);
+        assert!(tree.iter().any(|n| n.pattern() == ".*d.*"));
+        let simplified = tree.simplify();
+        assert!(simplified.size() < tree.size());
+        assert!(!simplified.iter().any(|n| n.pattern() == ".*d.*"));
+        for node in simplified.iter() {
+            for leaf in node.children.iter().filter(|c| c.children.is_empty()) {
+                assert!(!node.children.iter().any(|sibling| {
+                    subsumes(&leaf.pattern, &sibling.pattern)
+                        && !subsumes(&sibling.pattern, &leaf.pattern)
+                }));
+            }
+        }
+        assert!(!equivalent(&tree, &simplified));
+    }
+}
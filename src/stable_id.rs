@@ -0,0 +1,98 @@
+use crate::{Model, Node};
+use std::fmt;
+
+/// A content-derived identifier of a node, stable across regenerations.
+///
+/// The identifier is a 64-bit FNV-1a hash of the node's canonicalized
+/// pattern, so a template keeps its identifier for as long as its pattern
+/// is unchanged, whatever else changed in the tree, and dashboards keyed by
+/// it survive a retraining. Patterns spelled differently but parsing to the
+/// same expression, such as `^(?:a).*` and `^a.*`, share an identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemplateId(u64);
+
+impl TemplateId {
+    /// Returns the identifier of `pattern`.
+    ///
+    /// A pattern that does not parse is hashed as written.
+    pub fn of(pattern: &str) -> TemplateId {
+        let canonical = regex_syntax::Parser::new()
+            .parse(pattern)
+            .map(|hir| hir.to_string());
+        let bytes = match &canonical {
+            Ok(canonical) => canonical.as_bytes(),
+            Err(_) => pattern.as_bytes(),
+        };
+        TemplateId(fnv1a(bytes))
+    }
+
+    /// Returns the identifier as an integer.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for TemplateId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Hashes `bytes` with 64-bit FNV-1a, which unlike the standard hasher
+/// is the same in every process and on every platform.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+    })
+}
+
+impl Node {
+    /// Returns the stable identifier of the node's pattern.
+    pub fn template_id(&self) -> TemplateId {
+        TemplateId::of(&self.pattern)
+    }
+}
+
+impl Model {
+    /// Routes `input` like [`Model::classify`] and returns the stable
+    /// identifier of the node it reaches, which, unlike its pre-order
+    /// position, survives retraining as long as the node's pattern does.
+    pub fn classify_template(&self, input: &str) -> Option<TemplateId> {
+        let id = self.classify(input)?;
+        Some(self.root().preorder()[id].template_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_template_id() {
+        let before = generate_regex_tree(&["disk full on sda", "disk full on sdb"]);
+        let after =
+            generate_regex_tree(&["disk full on sdc", "disk full on sda", "disk full on sdb"]);
+        let ids = |tree: &Node| -> Vec<(String, TemplateId)> {
+            tree.preorder()
+                .iter()
+                .map(|n| (n.pattern.to_string(), n.template_id()))
+                .collect()
+        };
+        let after = ids(&after);
+        for (pattern, id) in ids(&before) {
+            if let Some((_, same)) = after.iter().find(|(p, _)| *p == pattern) {
+                assert_eq!(id, *same, "{pattern}");
+            }
+        }
+
+        assert_eq!(TemplateId::of("^(?:a).*"), TemplateId::of("^a.*"));
+        assert_ne!(TemplateId::of("^a.*"), TemplateId::of(".*a.*"));
+        assert_eq!(TemplateId::of(".*").to_string().len(), 16);
+
+        let model = Model::train(&["disk full on sda", "disk full on sdb"]);
+        let id = model.classify_template("disk full on sdz").unwrap();
+        assert_eq!(id, TemplateId::of(r"^disk full on sd.*"));
+    }
+}
@@ -0,0 +1,94 @@
+/// How inputs of very different sizes are kept apart during generation,
+/// see [`crate::GenerateOptions::stratification`].
+///
+/// Inputs fall in power-of-two bands of their size (1, 2 to 3, 4 to 7, and
+/// so on), so a 20-character line and a 2000-character one are never
+/// generalized together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stratification {
+    /// Bands of the length in characters.
+    Length,
+    /// Bands of the number of whitespace-separated tokens.
+    TokenCount,
+}
+
+impl Stratification {
+    /// Returns the size of `input` this stratification bands on.
+    fn size(&self, input: &str) -> usize {
+        match self {
+            Stratification::Length => input.chars().count(),
+            Stratification::TokenCount => input.split_whitespace().count(),
+        }
+    }
+
+    /// Splits `inputs` into strata, smallest first, keeping their order
+    /// within each stratum.
+    pub(crate) fn strata<'a>(&self, inputs: &[&'a str]) -> Vec<Vec<&'a str>> {
+        let mut strata: Vec<(u32, Vec<&str>)> = vec![];
+        for &input in inputs {
+            let band = self.size(input).checked_ilog2().map_or(0, |b| b + 1);
+            match strata.iter_mut().find(|(b, _)| *b == band) {
+                Some((_, stratum)) => stratum.push(input),
+                None => strata.push((band, vec![input])),
+            }
+        }
+        strata.sort_by_key(|(band, _)| *band);
+        strata.into_iter().map(|(_, stratum)| stratum).collect()
+    }
+
+    /// Returns the pattern matching exactly the inputs whose size is
+    /// within that of the `stratum`, such as `^(?s:.{16,31})$`.
+    pub(crate) fn pattern(&self, stratum: &[&str]) -> String {
+        let min = stratum.iter().map(|s| self.size(s)).min().unwrap_or(0);
+        let max = stratum.iter().map(|s| self.size(s)).max().unwrap_or(0);
+        match self {
+            Stratification::Length => format!("^(?s:.{{{min},{max}}})$"),
+            Stratification::TokenCount if max == 0 => r"^\s*$".to_string(),
+            Stratification::TokenCount => format!(
+                r"^\s*\S+(?:\s+\S+){{{},{}}}\s*$",
+                min.saturating_sub(1),
+                max - 1
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_regex_tree_with_options, GenerateOptions, RegexTreeBuilder};
+    use regex::Regex;
+
+    #[test]
+    fn test_stratification() {
+        let long = format!("GET /search?q={} HTTP/1.1", "x".repeat(60));
+        let longer = format!("GET /search?q={} HTTP/1.1", "y".repeat(70));
+        let inputs = ["id=17", "id=42", "id=7", long.as_str(), longer.as_str()];
+        let options = GenerateOptions {
+            stratification: Some(Stratification::Length),
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let strata: Vec<&str> = tree.children().map(|n| n.pattern().as_str()).collect();
+        assert_eq!(strata, ["^(?s:.{4,5})$", "^(?s:.{83,93})$"]);
+        assert!(tree.iter().any(|n| n.pattern() == "^id=.*"));
+        assert!(tree.iter().any(|n| n.pattern() == r"^GET /search\?q=.*"));
+        let regex = Regex::new(&tree.to_regex()).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+
+        let built = RegexTreeBuilder::new()
+            .options(options)
+            .build(&inputs)
+            .unwrap();
+        assert!(built.iter().any(|n| n.pattern() == "^id=.*"));
+
+        let unstratified =
+            generate_regex_tree_with_options(&inputs, &GenerateOptions::default()).unwrap();
+        assert!(!unstratified.iter().any(|n| n.pattern() == "^id=.*"));
+
+        let by_tokens = Stratification::TokenCount;
+        assert_eq!(by_tokens.strata(&["a b", "c", "d e f"]).len(), 2);
+        let pattern = Regex::new(&by_tokens.pattern(&["a b", "d e f"])).unwrap();
+        assert!(pattern.is_match("g h") && !pattern.is_match("g h i j"));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::{Deduplicator, Model, Record};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+/// A classified line: the line itself, the identifier of the node it was
+/// routed to, if any, and the record of the named groups that node
+/// captured.
+pub type Classified = (String, Option<usize>, Option<Record>);
+
+fn classify(model: &Model, line: String) -> Classified {
+    match model.extract(&line) {
+        Some((id, record)) => (line, Some(id), Some(record)),
+        None => (line, None, None),
+    }
+}
+
+/// A stream classifying each line of an inner stream with a model.
+///
+/// Lines are classified as they are polled, so the adapter adds no task
+/// or buffering of its own to the pipeline.
+pub struct ClassifiedLines<S> {
+    lines: S,
+    model: Arc<Model>,
+}
+
+impl<S> ClassifiedLines<S> {
+    /// Wraps a stream of lines.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines` - The lines to classify.
+    /// * `model` - The model routing them, shared with other consumers.
+    pub fn new(lines: S, model: Arc<Model>) -> Self {
+        ClassifiedLines { lines, model }
+    }
+}
+
+impl<S> Stream for ClassifiedLines<S>
+where
+    S: Stream<Item = String> + Unpin,
+{
+    type Item = Classified;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Classified>> {
+        let this = &mut *self;
+        Pin::new(&mut this.lines)
+            .poll_next(cx)
+            .map(|line| line.map(|line| classify(&this.model, line)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.lines.size_hint()
+    }
+}
+
+/// A stream passing on only the lines of an inner stream it has not seen
+/// before, counting the repeats in a [`Deduplicator`], so huge streams of
+/// mostly identical lines are classified or mined once per distinct line.
+///
+/// Once the stream is drained, [`DeduplicatedLines::into_deduplicator`]
+/// gives the counts, as weights for
+/// [`crate::generate_regex_tree_weighted`]. A poll skips at most
+/// [`DeduplicatedLines::BUDGET`] repeats before yielding to the executor.
+pub struct DeduplicatedLines<S> {
+    lines: S,
+    deduplicator: Deduplicator,
+}
+
+impl<S> DeduplicatedLines<S> {
+    /// The number of repeats a poll skips before yielding.
+    pub const BUDGET: usize = 128;
+
+    /// Wraps a stream of lines.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines` - The lines to deduplicate.
+    /// * `deduplicator` - Records the lines, exactly or within bounds.
+    pub fn new(lines: S, deduplicator: Deduplicator) -> Self {
+        DeduplicatedLines {
+            lines,
+            deduplicator,
+        }
+    }
+
+    /// Returns the counts of the lines polled so far.
+    pub fn deduplicator(&self) -> &Deduplicator {
+        &self.deduplicator
+    }
+
+    /// Returns the counts of the lines polled, once done with the stream.
+    pub fn into_deduplicator(self) -> Deduplicator {
+        self.deduplicator
+    }
+}
+
+impl<S> Stream for DeduplicatedLines<S>
+where
+    S: Stream<Item = String> + Unpin,
+{
+    type Item = String;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<String>> {
+        let this = &mut *self;
+        for _ in 0..Self::BUDGET {
+            match Pin::new(&mut this.lines).poll_next(cx) {
+                Poll::Ready(Some(line)) if !this.deduplicator.push(&line) => continue,
+                poll => return poll,
+            }
+        }
+        // A long run of ready repeats must not starve the other tasks.
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// A classifier lines are pushed into as a [`Sink`] and results read back
+/// from as a [`Stream`].
+///
+/// Use `StreamExt::split` to hand the two halves to different tasks. The
+/// stream ends once the sink is closed and every pushed line was read.
+/// At most a bounded number of results wait to be read; past that, the
+/// sink is not ready until the stream is polled.
+pub struct Classifier {
+    model: Arc<Model>,
+    ready: VecDeque<Classified>,
+    capacity: usize,
+    reader: Option<Waker>,
+    writer: Option<Waker>,
+    closed: bool,
+}
+
+impl Classifier {
+    /// The number of unread results [`Classifier::new`] allows.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Creates a classifier routing lines with `model`, holding up to
+    /// [`Classifier::DEFAULT_CAPACITY`] unread results.
+    pub fn new(model: Arc<Model>) -> Self {
+        Classifier::with_capacity(model, Classifier::DEFAULT_CAPACITY)
+    }
+
+    /// Creates a classifier routing lines with `model`.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model routing the lines, shared with other
+    ///   consumers.
+    /// * `capacity` - The number of unread results held before the sink
+    ///   waits for the stream, at least 1.
+    pub fn with_capacity(model: Arc<Model>, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Classifier {
+            model,
+            ready: VecDeque::with_capacity(capacity),
+            capacity,
+            reader: None,
+            writer: None,
+            closed: false,
+        }
+    }
+
+    fn wake_reader(&mut self) {
+        if let Some(waker) = self.reader.take() {
+            waker.wake();
+        }
+    }
+
+    fn wake_writer(&mut self) {
+        if let Some(waker) = self.writer.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Sink<String> for Classifier {
+    type Error = Infallible;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        if self.ready.len() < self.capacity {
+            return Poll::Ready(Ok(()));
+        }
+        self.writer = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, line: String) -> Result<(), Infallible> {
+        let classified = classify(&self.model, line);
+        self.ready.push_back(classified);
+        self.wake_reader();
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        self.closed = true;
+        self.wake_reader();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Stream for Classifier {
+    type Item = Classified;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Classified>> {
+        if let Some(classified) = self.ready.pop_front() {
+            self.wake_writer();
+            return Poll::Ready(Some(classified));
+        }
+        if self.closed {
+            return Poll::Ready(None);
+        }
+        self.reader = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{stream, SinkExt, StreamExt};
+
+    #[tokio::test]
+    async fn test_classify_streams() {
+        let model = Arc::new(Model::train(&["disk full on sda", "disk full on sdb"]));
+        let lines = stream::iter(["disk full on sdc".to_string(), "cpu hot".to_string()]);
+        let classified: Vec<Classified> = ClassifiedLines::new(lines, Arc::clone(&model))
+            .collect()
+            .await;
+        assert_eq!(classified.len(), 2);
+        assert_eq!(classified[0].1, model.classify("disk full on sdc"));
+
+        let (mut sink, results) = Classifier::new(Arc::clone(&model)).split();
+        let reader = tokio::spawn(results.collect::<Vec<Classified>>());
+        sink.send("disk full on sda".to_string()).await.unwrap();
+        sink.close().await.unwrap();
+        let results = reader.await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "disk full on sda");
+        assert_eq!(results[0].1, model.classify("disk full on sda"));
+    }
+
+    #[tokio::test]
+    async fn test_classifier_backpressure() {
+        let model = Arc::new(Model::train(&["disk full on sda", "disk full on sdb"]));
+        let mut classifier = Classifier::with_capacity(Arc::clone(&model), 1);
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut pinned = Pin::new(&mut classifier);
+        assert!(pinned.as_mut().poll_ready(&mut cx).is_ready());
+        pinned
+            .as_mut()
+            .start_send("disk full on sda".to_string())
+            .unwrap();
+        assert!(pinned.as_mut().poll_ready(&mut cx).is_pending());
+        assert!(pinned.as_mut().poll_next(&mut cx).is_ready());
+        assert!(pinned.as_mut().poll_ready(&mut cx).is_ready());
+
+        let (mut sink, results) = Classifier::with_capacity(model, 1).split();
+        let reader = tokio::spawn(results.collect::<Vec<Classified>>());
+        for line in ["disk full on sda", "disk full on sdb", "cpu hot"] {
+            sink.send(line.to_string()).await.unwrap();
+        }
+        sink.close().await.unwrap();
+        assert_eq!(reader.await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_deduplicated_lines() {
+        let lines = ["a 1", "a 1", "b 2", "a 1"].map(String::from);
+        let mut deduplicated = DeduplicatedLines::new(stream::iter(lines), Deduplicator::new());
+        let fresh: Vec<String> = deduplicated.by_ref().collect().await;
+        assert_eq!(fresh, ["a 1", "b 2"]);
+        let deduplicator = deduplicated.into_deduplicator();
+        assert_eq!(deduplicator.weighted(), [("a 1", 3.0), ("b 2", 1.0)]);
+
+        let repeats = DeduplicatedLines::<stream::Empty<String>>::BUDGET * 2;
+        let lines = std::iter::repeat_n("a".to_string(), repeats + 1).chain(["b".to_string()]);
+        let mut deduplicated = DeduplicatedLines::new(stream::iter(lines), Deduplicator::new());
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let polls: Vec<Poll<Option<String>>> =
+            std::iter::from_fn(|| match deduplicated.poll_next_unpin(&mut cx) {
+                Poll::Ready(None) => None,
+                poll => Some(poll),
+            })
+            .collect();
+        let a = Poll::Ready(Some("a".to_string()));
+        let b = Poll::Ready(Some("b".to_string()));
+        assert_eq!(polls, [a, Poll::Pending, Poll::Pending, b]);
+    }
+}
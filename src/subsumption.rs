@@ -0,0 +1,313 @@
+use crate::{Children, LanguageError, Node, Role};
+use petgraph::algo::dominators::simple_fast;
+use petgraph::graph::{DiGraph, NodeIndex};
+use regex_automata::dfa::{dense, Automaton};
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchKind};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+/// The largest automaton, in bytes, built for a containment check.
+pub(crate) const DFA_SIZE_LIMIT: usize = 16 << 20;
+
+/// Returns the role and unescaped literal of a pattern emitted for a mined
+/// substring, such as `.*literal.*` or `^literal.*`.
+pub(crate) fn anchored_literal(pattern: &str) -> Option<(Role, String)> {
+    let (start, rest) = match pattern.strip_prefix('^') {
+        Some(rest) => (true, rest),
+        None => (false, pattern.strip_prefix(".*")?),
+    };
+    let (end, inner) = match rest.strip_suffix(".*") {
+        Some(inner) => (false, inner),
+        None => (true, rest.strip_suffix('$')?),
+    };
+    let mut literal = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => literal.push(chars.next()?),
+            c if regex_syntax::is_meta_character(c) => return None,
+            c => literal.push(c),
+        }
+    }
+    let role = match (start, end) {
+        (true, true) => Role::Full,
+        (true, false) => Role::Prefix,
+        (false, true) => Role::Suffix,
+        (false, false) => Role::Infix,
+    };
+    Some((role, literal))
+}
+
+/// Returns whether every string matched by `specific` is matched by `general`.
+///
+/// The generator's match-all and anchored literal patterns are compared
+/// directly; other patterns fall back to [`contains_language`], and are
+/// treated as not subsumed if they cannot be compiled.
+pub(crate) fn subsumes(general: &str, specific: &str) -> bool {
+    if general.is_empty() || general == ".*" || general == specific {
+        return true;
+    }
+    match (anchored_literal(general), anchored_literal(specific)) {
+        (Some((role, general)), Some((specific_role, specific))) => {
+            role.covers(&general, specific_role, &specific)
+        }
+        _ => contains_language(general, specific).unwrap_or(false),
+    }
+}
+
+/// Compiles `pattern` to a DFA recognising the strings it finds a match in.
+pub(crate) fn searcher(pattern: &str) -> Result<dense::DFA<Vec<u32>>, LanguageError> {
+    dense::Builder::new()
+        .configure(
+            dense::Config::new()
+                .match_kind(MatchKind::All)
+                .dfa_size_limit(Some(DFA_SIZE_LIMIT))
+                .determinize_size_limit(Some(DFA_SIZE_LIMIT)),
+        )
+        .build(&format!("(?s:.)*(?:{pattern})(?s:.)*"))
+        .map_err(|error| LanguageError::InvalidPattern {
+            pattern: pattern.to_string(),
+            message: error.to_string(),
+        })
+}
+
+/// Returns whether the language of `a` contains the language of `b`, that
+/// is whether every string `b` finds a match in is also matched by `a`.
+///
+/// Both patterns are compiled to deterministic automata and their product
+/// is explored for a string accepted by `b` alone.
+///
+/// # Arguments
+///
+/// * `a` - The candidate general pattern.
+/// * `b` - The candidate specific pattern.
+///
+/// # Returns
+///
+/// Whether `a` matches everything `b` matches, or an error if either
+/// pattern does not compile or its automaton grows too large.
+pub fn contains_language(a: &str, b: &str) -> Result<bool, LanguageError> {
+    let general = searcher(a)?;
+    let specific = searcher(b)?;
+    let config = start::Config::new().anchored(Anchored::Yes);
+    let start = |dfa: &dense::DFA<Vec<u32>>, pattern: &str| {
+        dfa.start_state(&config)
+            .map_err(|error| LanguageError::InvalidPattern {
+                pattern: pattern.to_string(),
+                message: error.to_string(),
+            })
+    };
+
+    let initial = (start(&general, a)?, start(&specific, b)?);
+    let mut seen: HashSet<(StateID, StateID)> = HashSet::from([initial]);
+    let mut queue = VecDeque::from([initial]);
+    while let Some((g, s)) = queue.pop_front() {
+        let accepted = |dfa: &dense::DFA<Vec<u32>>, id| dfa.is_match_state(dfa.next_eoi_state(id));
+        if accepted(&specific, s) && !accepted(&general, g) {
+            return Ok(false);
+        }
+        for byte in 0..=u8::MAX {
+            let next = (general.next_state(g, byte), specific.next_state(s, byte));
+            if !specific.is_dead_state(next.1) && seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Returns whether two patterns match exactly the same strings.
+pub(crate) fn same_language(a: &str, b: &str) -> bool {
+    a == b || (contains_language(a, b).unwrap_or(false) && contains_language(b, a).unwrap_or(false))
+}
+
+/// Returns whether two trees define the same set of leaf languages.
+///
+/// Leaves are compared by language rather than by pattern text, and their
+/// order and position in the trees is ignored, so a tree can be checked
+/// against a stored reference after the generation pipeline changed.
+///
+/// # Arguments
+///
+/// * `a` - The root node of the first tree.
+/// * `b` - The root node of the second tree.
+///
+/// # Returns
+///
+/// Whether every leaf of each tree matches the same strings as some leaf
+/// of the other. Patterns that do not compile only equal themselves.
+pub fn equivalent(a: &Node, b: &Node) -> bool {
+    let leaves = |root: &Node| -> Vec<String> {
+        let mut patterns: Vec<String> = root
+            .preorder()
+            .into_iter()
+            .filter(|n| n.children.is_empty())
+            .map(|n| n.pattern.to_string())
+            .collect();
+        patterns.sort();
+        patterns.dedup();
+        patterns
+    };
+    let (a, b) = (leaves(a), leaves(b));
+    if a == b {
+        return true;
+    }
+    let covered = |from: &[String], to: &[String]| {
+        from.iter().all(|x| to.iter().any(|y| same_language(x, y)))
+    };
+    covered(&a, &b) && covered(&b, &a)
+}
+
+/// Builds the subsumption graph of the patterns in a tree.
+///
+/// Each distinct pattern becomes one graph node, in pre-order of first
+/// appearance, and there is an edge from `a` to `b` when `a` matches
+/// everything `b` matches and no other pattern lies between them. Unlike
+/// the tree, a pattern may have several direct generalizations.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// A directed graph whose first node is the root's pattern.
+pub fn subsumption_graph(root: &Node) -> DiGraph<String, ()> {
+    let mut graph = DiGraph::new();
+    let mut indices: HashMap<&str, NodeIndex> = HashMap::new();
+    for node in root.preorder() {
+        indices
+            .entry(node.pattern.as_str())
+            .or_insert_with(|| graph.add_node(node.pattern.to_string()));
+    }
+
+    let nodes: Vec<NodeIndex> = graph.node_indices().collect();
+    let strictly = |a: NodeIndex, b: NodeIndex| a != b && subsumes(&graph[a], &graph[b]);
+    let mut edges = vec![];
+    for &general in &nodes {
+        for &specific in &nodes {
+            let covers = strictly(general, specific)
+                && !nodes
+                    .iter()
+                    .any(|&between| strictly(general, between) && strictly(between, specific));
+            if covers {
+                edges.push((general, specific));
+            }
+        }
+    }
+    for (general, specific) in edges {
+        graph.add_edge(general, specific, ());
+    }
+    graph
+}
+
+/// Computes the dominator tree of the subsumption graph.
+///
+/// A pattern dominates another when every chain of specializations from the
+/// root to the latter passes through it. Patterns with many dominated
+/// descendants are choke points, useful as a small set of representative
+/// rules.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+///
+/// # Returns
+///
+/// A tree of the distinct patterns where each pattern's parent is its
+/// immediate dominator. Patterns the root does not subsume are left out.
+pub fn dominator_tree(root: &Node) -> Node {
+    let graph = subsumption_graph(root);
+    let entry = NodeIndex::new(0);
+    let dominators = simple_fast(&graph, entry);
+
+    let mut dominated: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+    for index in graph.node_indices() {
+        if let Some(parent) = dominators.immediate_dominator(index) {
+            dominated.entry(parent).or_default().push(index);
+        }
+    }
+    build_dominated(&graph, &dominated, entry)
+}
+
+fn build_dominated(
+    graph: &DiGraph<String, ()>,
+    dominated: &HashMap<NodeIndex, Vec<NodeIndex>>,
+    index: NodeIndex,
+) -> Node {
+    let mut node = Node::new(graph[index].clone());
+    node.children = dominated
+        .get(&index)
+        .map(|children| {
+            children
+                .iter()
+                .map(|&child| Arc::new(build_dominated(graph, dominated, child)))
+                .collect()
+        })
+        .unwrap_or_else(Children::new);
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsumes() {
+        assert!(subsumes(".*", r".*a\.b.*"));
+        assert!(subsumes(r".*a\..*", r".*xa\.b.*"));
+        assert!(!subsumes(r".*a\.b.*", r".*a\..*"));
+        assert!(!subsumes(r".*a.*", r"^b+$"));
+        assert!(subsumes(r"^a\..*", r"^a\.b$"));
+        assert!(!subsumes(r"^a.*", r".*ab.*"));
+        assert!(subsumes(r"\d+", r"^[0-9]{3}$"));
+    }
+
+    #[test]
+    fn test_contains_language() {
+        assert_eq!(contains_language(r"\d", r"^\d{4}-\d{2}$"), Ok(true));
+        assert_eq!(contains_language(r"^\d{4}-\d{2}$", r"\d"), Ok(false));
+        assert_eq!(contains_language("ab|cd", "^(ab|abc)x"), Ok(true));
+        assert_eq!(contains_language("ab|cd", "^(ab|ad)x"), Ok(false));
+        assert!(matches!(
+            contains_language("(", "a"),
+            Err(LanguageError::InvalidPattern { .. })
+        ));
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let tree = |patterns: &[&str]| {
+            let mut root = Node::new(".*".to_string());
+            for pattern in patterns {
+                root.children.push(Arc::new(Node::new(pattern.to_string())));
+            }
+            root
+        };
+        let reference = tree(&[".*[xy].*", ".*ab.*"]);
+        assert!(equivalent(&reference, &tree(&[".*ab.*", "x|y"])));
+        assert!(!equivalent(&reference, &tree(&[".*ab.*"])));
+        assert!(!equivalent(&reference, &tree(&["[xy]", ".*abc.*"])));
+    }
+
+    #[test]
+    fn test_dominator_tree() {
+        let mut root = Node::new(".*".to_string());
+        for pattern in [".*a.*", ".*ab.*", ".*abc.*", ".*bc.*", ".*x.*"] {
+            root.children.push(Arc::new(Node::new(pattern.to_string())));
+        }
+
+        let graph = subsumption_graph(&root);
+        assert_eq!(graph.node_count(), 6);
+        assert!(graph.contains_edge(NodeIndex::new(2), NodeIndex::new(3)));
+        assert!(!graph.contains_edge(NodeIndex::new(1), NodeIndex::new(3)));
+
+        let tree = dominator_tree(&root);
+        let top: Vec<&str> = tree.children.iter().map(|n| n.pattern.as_str()).collect();
+        assert_eq!(top, vec![".*a.*", ".*abc.*", ".*bc.*", ".*x.*"]);
+        assert_eq!(tree.children[0].children[0].pattern, ".*ab.*");
+        assert!(tree.children[0].children[0].children.is_empty());
+    }
+}
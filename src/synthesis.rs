@@ -0,0 +1,191 @@
+use crate::{best_patterns, find_common_substrings, scoring, Node};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+/// The largest number of distinct values spelled out as an alternation
+/// rather than generalized further.
+const MAX_ALTERNATIVES: usize = 8;
+
+impl Node {
+    /// Returns the most specific single regex the tree holds for its
+    /// inputs.
+    ///
+    /// For a scored tree, this is the pattern [`best_patterns`] ranks first
+    /// among those matching every input. Otherwise, every leaf of a
+    /// generated tree matches every input, and the leaf pinning the most
+    /// literal characters is returned, the first in pre-order on ties.
+    ///
+    /// Use [`generate_regex`] when the inputs are at hand, which is
+    /// tighter still.
+    pub fn to_regex(&self) -> String {
+        if let Some(best) = best_patterns(self, 1).first() {
+            return best.pattern.to_string();
+        }
+        let pinned = |node: &Node| {
+            regex_syntax::parse(&node.pattern).map_or(0, |hir| scoring::pinned_chars(&hir))
+        };
+        self.preorder()
+            .into_iter()
+            .filter(|n| n.children.is_empty())
+            .enumerate()
+            .max_by_key(|&(index, node)| (pinned(node), Reverse(index)))
+            .map_or_else(String::new, |(_, node)| node.pattern.to_string())
+    }
+}
+
+/// Generates a single anchored regex that matches all the given strings.
+///
+/// The strings are split around their common prefix, common suffix, and
+/// recursively around the longest substring the middles share. A region
+/// taking a handful of distinct values becomes an alternation of them, and
+/// one sharing nothing becomes a wildcard, so every input is matched by
+/// construction.
+///
+/// # Arguments
+///
+/// * `strings` - A slice of strings the regex must match.
+///
+/// # Returns
+///
+/// The regex, or an empty one, matching everything, if there are no
+/// strings.
+pub fn generate_regex(strings: &[&str]) -> String {
+    if strings.is_empty() {
+        return String::new();
+    }
+    let mut pattern = String::from("^");
+    write_fragment(strings, &mut pattern);
+    pattern.push('$');
+    pattern
+}
+
+/// Appends an unanchored fragment matching each of `parts` exactly.
+fn write_fragment(parts: &[&str], out: &mut String) {
+    let prefix = common_prefix(parts);
+    let rest: Vec<&str> = parts.iter().map(|p| &p[prefix.len()..]).collect();
+    let suffix = common_suffix(&rest);
+    let middles: Vec<&str> = rest.iter().map(|p| &p[..p.len() - suffix.len()]).collect();
+
+    regex_syntax::escape_into(prefix, out);
+    let mut seen = HashSet::new();
+    let distinct: Vec<&str> = middles
+        .iter()
+        .copied()
+        .filter(|m| seen.insert(*m))
+        .collect();
+    if distinct.len() > 1 && distinct.len() <= MAX_ALTERNATIVES {
+        out.push_str("(?:");
+        for (index, middle) in distinct.iter().enumerate() {
+            if index > 0 {
+                out.push('|');
+            }
+            regex_syntax::escape_into(middle, out);
+        }
+        out.push(')');
+    } else if distinct.len() > 1 {
+        match find_common_substrings(&middles).first() {
+            Some(&shared) => {
+                let splits: Vec<(&str, &str)> = middles
+                    .iter()
+                    .map(|m| {
+                        let at = m.find(shared).unwrap();
+                        (&m[..at], &m[at + shared.len()..])
+                    })
+                    .collect();
+                let left: Vec<&str> = splits.iter().map(|s| s.0).collect();
+                let right: Vec<&str> = splits.iter().map(|s| s.1).collect();
+                write_fragment(&left, out);
+                regex_syntax::escape_into(shared, out);
+                write_fragment(&right, out);
+            }
+            None if middles.iter().any(|m| m.contains('\n')) => out.push_str("(?s:.*)"),
+            None => out.push_str(".*"),
+        }
+    } else {
+        regex_syntax::escape_into(distinct[0], out);
+    }
+    regex_syntax::escape_into(suffix, out);
+}
+
+/// Returns the longest prefix shared by all `parts`, on a char boundary.
+fn common_prefix<'a>(parts: &[&'a str]) -> &'a str {
+    let first = parts[0];
+    let mut len = parts[1..].iter().fold(first.len(), |len, part| {
+        first
+            .bytes()
+            .zip(part.bytes())
+            .take(len)
+            .take_while(|(a, b)| a == b)
+            .count()
+    });
+    while !first.is_char_boundary(len) {
+        len -= 1;
+    }
+    &first[..len]
+}
+
+/// Returns the longest suffix shared by all `parts`, on a char boundary.
+fn common_suffix<'a>(parts: &[&'a str]) -> &'a str {
+    let first = parts[0];
+    let mut len = parts[1..].iter().fold(first.len(), |len, part| {
+        first
+            .bytes()
+            .rev()
+            .zip(part.bytes().rev())
+            .take(len)
+            .take_while(|(a, b)| a == b)
+            .count()
+    });
+    while !first.is_char_boundary(first.len() - len) {
+        len -= 1;
+    }
+    &first[first.len() - len..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+    use regex::Regex;
+
+    #[test]
+    fn test_generate_regex() {
+        assert_eq!(
+            generate_regex(&["GET /index", "POST /index", "PUT /index"]),
+            r"^(?:GE|POS|PU)T /index$"
+        );
+
+        let inputs: Vec<String> = (0..20)
+            .map(|i| format!("job {i} finished in {}ms on node-{}", i * 7, i % 3))
+            .collect();
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let pattern = generate_regex(&inputs);
+        let regex = Regex::new(&pattern).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+        assert!(pattern.starts_with("^job "));
+        assert!(pattern.contains(" finished in "));
+
+        let tree = generate_regex_tree(&inputs[..2]);
+        let regex = Regex::new(&tree.to_regex()).unwrap();
+        assert!(inputs[..2].iter().all(|s| regex.is_match(s)));
+    }
+
+    #[test]
+    fn test_to_regex_is_specific() {
+        let inputs = ["user alice logged in", "user bob logged in"];
+        assert_eq!(generate_regex(&inputs), "^user (?:alice|bob) logged in$");
+
+        let tree = generate_regex_tree(&inputs);
+        let regex = Regex::new(&tree.to_regex()).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+        assert!(!regex.is_match("disk sda dead"));
+
+        let mut unscored = Node::new(".*".to_string());
+        for pattern in ["^user .*", ".* logged in$", ".*d.*"] {
+            unscored
+                .children
+                .push(Node::new(pattern.to_string()).into());
+        }
+        assert_eq!(unscored.to_regex(), ".* logged in$");
+    }
+}
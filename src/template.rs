@@ -0,0 +1,964 @@
+use crate::{Detector, DetectorDictionary, FieldType, GenerateError, NamedPattern};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+/// A partial schema that pins what some fields are.
+///
+/// Entries are keyed either by token position ("token 0 is a timestamp") or
+/// by detector ("whatever looks like an IP is called `client`"); fields not
+/// covered by the schema are inferred automatically.
+#[derive(Debug, Clone, Default)]
+pub struct FieldSchema {
+    positions: HashMap<usize, Detector>,
+    names: HashMap<Detector, String>,
+    min_presence: Option<f64>,
+    key_values: bool,
+}
+
+impl FieldSchema {
+    /// Creates an empty schema.
+    pub fn new() -> Self {
+        FieldSchema::default()
+    }
+
+    /// Pins the token at `position` to be a field of the given kind.
+    pub fn position(mut self, position: usize, detector: Detector) -> Self {
+        self.positions.insert(position, detector);
+        self
+    }
+
+    /// Names every field detected as `detector`.
+    pub fn detector_name(mut self, detector: Detector, name: &str) -> Self {
+        self.names.insert(detector, name.to_string());
+        self
+    }
+
+    /// Lets inputs omit a literal token present in at least `min_presence`
+    /// of them, such as an occasional `[debug]` tag, which then becomes an
+    /// optional segment instead of breaking the alignment.
+    ///
+    /// Positions pinned by the schema are counted without the optional
+    /// tokens.
+    pub fn optional_segments(mut self, min_presence: f64) -> Self {
+        self.min_presence = Some(min_presence);
+        self
+    }
+
+    /// Detects `key=value` and `key: value` structures: the key is kept as
+    /// a literal, the value becomes a typed field named after the key, and
+    /// the regex captures it in a named group.
+    pub fn key_values(mut self) -> Self {
+        self.key_values = true;
+        self
+    }
+}
+
+/// A variable field of a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    name: String,
+    detector: Detector,
+    field_type: FieldType,
+    pinned: bool,
+    shape: Option<NamedPattern>,
+    captured: bool,
+    enclosure: Option<Enclosure>,
+}
+
+/// The delimiters around a bracketed or quoted field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Enclosure {
+    open: char,
+    close: char,
+    /// Whether no detector accepted the interiors, which are then matched
+    /// as any text up to the closing delimiter.
+    free_text: bool,
+}
+
+impl Field {
+    /// Returns the name of the field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the detector describing the field's values.
+    pub fn detector(&self) -> Detector {
+        self.detector
+    }
+
+    /// Returns the type inferred from the field's observed values.
+    pub fn field_type(&self) -> &FieldType {
+        &self.field_type
+    }
+
+    /// Returns whether the detector was pinned by a schema rather than inferred.
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    /// Returns the dictionary sub-pattern describing the field's values more
+    /// precisely than its detector, if one was found.
+    pub fn shape(&self) -> Option<&NamedPattern> {
+        self.shape.as_ref()
+    }
+
+    /// Returns the delimiters around the field when its values are
+    /// bracketed or quoted, such as `('[', ']')`; the detector then
+    /// describes their interior, or is [`Detector::Any`] when the interior
+    /// is free text matched up to the closing delimiter.
+    pub fn enclosing(&self) -> Option<(char, char)> {
+        self.enclosure.map(|e| (e.open, e.close))
+    }
+
+    /// Returns whether the regex captures the field in a group named after
+    /// it, as it does for the values of key-value pairs.
+    pub fn is_captured(&self) -> bool {
+        self.captured
+    }
+
+    fn pattern(&self) -> &str {
+        match &self.shape {
+            Some(shape) => shape.pattern(),
+            None => self.detector.pattern(),
+        }
+    }
+
+    /// Appends the field's pattern to `out`, in a named group if captured.
+    fn write_pattern(&self, out: &mut String) {
+        let mut buffer = [0; 4];
+        if let Some(enclosure) = self.enclosure {
+            regex_syntax::escape_into(enclosure.open.encode_utf8(&mut buffer), out);
+        }
+        if self.captured {
+            out.push_str("(?P<");
+            out.push_str(&self.name);
+            out.push('>');
+        } else {
+            out.push_str("(?:");
+        }
+        match self.enclosure {
+            Some(enclosure) if enclosure.free_text => {
+                out.push_str("[^");
+                regex_syntax::escape_into(enclosure.close.encode_utf8(&mut buffer), out);
+                out.push_str("]*");
+            }
+            _ => out.push_str(self.pattern()),
+        }
+        out.push(')');
+        if let Some(enclosure) = self.enclosure {
+            regex_syntax::escape_into(enclosure.close.encode_utf8(&mut buffer), out);
+        }
+    }
+}
+
+/// One token position of a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// A token shared verbatim by every input.
+    Literal(String),
+    /// A token that varies across inputs.
+    Field(Field),
+    /// A token some inputs omit, always at the same place.
+    Optional(String),
+    /// A token made of a fixed prefix and a bracketed group whose
+    /// contents are generalized by a template of their own, such as the
+    /// arguments of `open(file.txt, r)`.
+    Group {
+        /// The text before the opening delimiter, such as a function name.
+        prefix: String,
+        /// The opening delimiter.
+        open: char,
+        /// The closing delimiter.
+        close: char,
+        /// The template of the group's contents.
+        template: Template,
+    },
+    /// A `key=value` or `key:value` token with a fixed key and a varying
+    /// value.
+    Pair {
+        /// The key.
+        key: String,
+        /// The separator between the key and the value.
+        separator: char,
+        /// The value.
+        field: Field,
+    },
+}
+
+/// A token-level template aligned across all inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+    // The patterns of the whitespace before the first token and after the
+    // last one, which tokenizing drops.
+    leading: &'static str,
+    trailing: &'static str,
+}
+
+impl Template {
+    /// Returns the segments of the template, one per token position.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns an anchored regex matching the template, with tokens
+    /// separated by runs of whitespace.
+    ///
+    /// An optional segment is wrapped in an optional group together with
+    /// the whitespace separating it from the required tokens, if any. Whitespace
+    /// before the first token or after the last one in some inputs is
+    /// matched by `\s*`, and by `\s+` when all of them have it.
+    pub fn to_regex(&self) -> String {
+        let mut pattern = String::from("^");
+        pattern.push_str(self.leading);
+        self.write_segments(&mut pattern);
+        if !self.segments.is_empty() {
+            pattern.push_str(self.trailing);
+        }
+        pattern.push('$');
+        pattern
+    }
+
+    /// Appends the unanchored pattern of the segments to `pattern`.
+    fn write_segments(&self, pattern: &mut String) {
+        let last_required = self
+            .segments
+            .iter()
+            .rposition(|s| !matches!(s, Segment::Optional(_)));
+        let mut required = false;
+        let mut optional = false;
+        for (index, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Optional(literal) if required => {
+                    pattern.push_str(r"(?:\s+");
+                    regex_syntax::escape_into(literal, pattern);
+                    pattern.push_str(")?");
+                }
+                Segment::Optional(literal) if last_required.is_some_and(|last| index < last) => {
+                    pattern.push_str("(?:");
+                    regex_syntax::escape_into(literal, pattern);
+                    pattern.push_str(r"\s+)?");
+                }
+                // With no required token around, whether an earlier
+                // optional one is present is unknown, so the separator may
+                // be empty.
+                Segment::Optional(literal) => {
+                    pattern.push_str(if optional { r"(?:\s*" } else { "(?:" });
+                    regex_syntax::escape_into(literal, pattern);
+                    pattern.push_str(")?");
+                    optional = true;
+                }
+                Segment::Literal(literal) => {
+                    if required {
+                        pattern.push_str(r"\s+");
+                    }
+                    regex_syntax::escape_into(literal, pattern);
+                    required = true;
+                }
+                Segment::Field(field) => {
+                    if required {
+                        pattern.push_str(r"\s+");
+                    }
+                    field.write_pattern(pattern);
+                    required = true;
+                }
+                Segment::Group {
+                    prefix,
+                    open,
+                    close,
+                    template,
+                } => {
+                    if required {
+                        pattern.push_str(r"\s+");
+                    }
+                    regex_syntax::escape_into(prefix, pattern);
+                    regex_syntax::escape_into(open.encode_utf8(&mut [0; 4]), pattern);
+                    pattern.push_str(r"\s*");
+                    template.write_segments(pattern);
+                    pattern.push_str(r"\s*");
+                    regex_syntax::escape_into(close.encode_utf8(&mut [0; 4]), pattern);
+                    required = true;
+                }
+                Segment::Pair {
+                    key,
+                    separator,
+                    field,
+                } => {
+                    if required {
+                        pattern.push_str(r"\s+");
+                    }
+                    regex_syntax::escape_into(key, pattern);
+                    regex_syntax::escape_into(separator.encode_utf8(&mut [0; 4]), pattern);
+                    field.write_pattern(pattern);
+                    required = true;
+                }
+            }
+        }
+    }
+}
+
+/// Generates a token template from whitespace-separated inputs.
+///
+/// Tokens identical across all inputs become literals; the others become
+/// fields whose detector is taken from `schema` when pinned there, and
+/// otherwise inferred from the observed values. A bracketed or quoted
+/// segment, such as `[2024-01-02 10:00:00]` or `"disk full"`, is a single
+/// token even if it contains whitespace, and when it varies, the detector
+/// describes its interior. When the contents of a varying group, such as
+/// the arguments of a function call, span several tokens that align across
+/// inputs, they are generalized recursively by a nested template instead.
+///
+/// # Arguments
+///
+/// * `strings` - The inputs, which must all have the same number of tokens.
+/// * `schema` - The fields pinned by the caller.
+///
+/// # Returns
+///
+/// The template, or an error if the inputs cannot be aligned or contradict
+/// the schema.
+pub fn generate_template(
+    strings: &[&str],
+    schema: &FieldSchema,
+) -> Result<Template, GenerateError> {
+    build_template(strings, schema, None)
+}
+
+/// Generates a token template, describing fields with the named
+/// sub-patterns of a dictionary.
+///
+/// Each field not pinned by `schema` reuses the first dictionary entry that
+/// accepts all its values; when none does and the values share a
+/// distinctive shape (such as `ORD-1234`), that shape is learned into the
+/// dictionary so later runs reuse it.
+///
+/// # Arguments
+///
+/// * `strings` - The inputs, which must all have the same number of tokens.
+/// * `schema` - The fields pinned by the caller.
+/// * `dictionary` - The shared vocabulary of named sub-patterns.
+///
+/// # Returns
+///
+/// The template, or an error if the inputs cannot be aligned or contradict
+/// the schema.
+pub fn generate_template_with_dictionary(
+    strings: &[&str],
+    schema: &FieldSchema,
+    dictionary: &mut DetectorDictionary,
+) -> Result<Template, GenerateError> {
+    build_template(strings, schema, Some(dictionary))
+}
+
+/// The tokens of each input.
+type Rows<'a> = Vec<Vec<&'a str>>;
+
+fn build_template(
+    strings: &[&str],
+    schema: &FieldSchema,
+    dictionary: Option<&mut DetectorDictionary>,
+) -> Result<Template, GenerateError> {
+    build_nested(strings, schema, dictionary, &mut HashSet::new())
+}
+
+/// Builds a template, recursing into the contents of varying bracketed
+/// groups. Capture group names are unique across all levels.
+fn build_nested(
+    strings: &[&str],
+    schema: &FieldSchema,
+    mut dictionary: Option<&mut DetectorDictionary>,
+    group_names: &mut HashSet<String>,
+) -> Result<Template, GenerateError> {
+    if strings.is_empty() {
+        return Err(GenerateError::EmptyInput);
+    }
+
+    let mut rows: Rows = strings.iter().map(|s| tokenize(s)).collect();
+    let mut optional = vec![];
+    if let Some(min_presence) = schema.min_presence {
+        if rows.iter().any(|r| r.len() != rows[0].len()) {
+            if let Some((stripped, slots)) = strip_optional(&rows, min_presence) {
+                rows = stripped;
+                optional = slots;
+            }
+        }
+    }
+    let width = rows[0].len();
+    if let Some(input) = rows.iter().position(|r| r.len() != width) {
+        return Err(GenerateError::TokenCountMismatch {
+            expected: width,
+            found: rows[input].len(),
+            input,
+        });
+    }
+    if let Some(&position) = schema.positions.keys().find(|&&p| p >= width) {
+        return Err(GenerateError::SchemaPositionOutOfRange { position, width });
+    }
+
+    let mut segments: Vec<Segment> = Vec::with_capacity(width + optional.len());
+    let mut optional = optional.into_iter().peekable();
+    for position in 0..=width {
+        while let Some((_, literal)) = optional.next_if(|(slot, _)| *slot == position) {
+            segments.push(Segment::Optional(literal.to_string()));
+        }
+        if position == width {
+            break;
+        }
+        let mut values: Vec<&str> = rows.iter().map(|r| r[position]).collect();
+        let pinned = schema.positions.get(&position).copied();
+
+        // A varying group whose contents span several tokens gets a
+        // template of its own, unless they do not align.
+        if pinned.is_none() && values.iter().any(|v| *v != values[0]) {
+            if let Some((prefix, open, close)) = common_group(&values) {
+                let contents: Vec<&str> = values
+                    .iter()
+                    .map(|v| &v[prefix.len() + open.len_utf8()..v.len() - close.len_utf8()])
+                    .collect();
+                if contents.iter().any(|c| tokenize(c).len() > 1) {
+                    let nested_schema = FieldSchema {
+                        positions: HashMap::new(),
+                        ..schema.clone()
+                    };
+                    let nested = build_nested(
+                        &contents,
+                        &nested_schema,
+                        dictionary.as_deref_mut(),
+                        group_names,
+                    );
+                    if let Ok(template) = nested {
+                        segments.push(Segment::Group {
+                            prefix: prefix.to_string(),
+                            open,
+                            close,
+                            template,
+                        });
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // A varying bracketed or quoted token is generalized by its interior.
+        let mut enclosure = None;
+        if values.iter().any(|v| *v != values[0]) {
+            if let Some((open, close)) = common_enclosure(&values) {
+                values = values
+                    .iter()
+                    .map(|v| &v[open.len_utf8()..v.len() - close.len_utf8()])
+                    .collect();
+                enclosure = Some(Enclosure {
+                    open,
+                    close,
+                    free_text: false,
+                });
+            }
+        }
+
+        // With key-value detection, a varying `key=value` token keeps its
+        // key, and a value following a `key:` token is named after it.
+        let mut separator = None;
+        let mut key = None;
+        if schema.key_values && values.iter().any(|v| *v != values[0]) {
+            if let Some((k, sep)) = common_key(&values) {
+                values = values.iter().map(|v| &v[k.len() + 1..]).collect();
+                separator = Some(sep);
+                key = Some(k.to_string());
+            } else if let Some(Segment::Literal(previous)) = segments.last() {
+                key = previous
+                    .strip_suffix(':')
+                    .filter(|k| is_key(k))
+                    .map(str::to_string);
+            }
+        }
+
+        let detector = match pinned {
+            Some(detector) => {
+                if let Some(input) = values.iter().position(|v| !detector.matches(v)) {
+                    return Err(GenerateError::SchemaViolation {
+                        position,
+                        detector,
+                        input,
+                    });
+                }
+                detector
+            }
+            None if values.iter().all(|v| *v == values[0]) => {
+                segments.push(Segment::Literal(values[0].to_string()));
+                continue;
+            }
+            None => match Detector::infer(&values) {
+                Some(detector) => detector,
+                None => {
+                    if let Some(enclosure) = &mut enclosure {
+                        enclosure.free_text = true;
+                    }
+                    Detector::Any
+                }
+            },
+        };
+
+        let name = match &key {
+            Some(key) => unique_group_name(key, group_names),
+            None => schema
+                .names
+                .get(&detector)
+                .cloned()
+                .unwrap_or_else(|| format!("field{position}")),
+        };
+        let shape = match (&mut dictionary, pinned) {
+            (Some(dictionary), None) => dictionary.resolve(&values),
+            _ => None,
+        };
+        let field = Field {
+            name,
+            detector,
+            field_type: FieldType::infer(&values).unwrap_or(FieldType::String),
+            pinned: pinned.is_some(),
+            shape,
+            captured: key.is_some(),
+            enclosure,
+        };
+        segments.push(match (key, separator) {
+            (Some(key), Some(separator)) => Segment::Pair {
+                key,
+                separator,
+                field,
+            },
+            _ => Segment::Field(field),
+        });
+    }
+
+    Ok(Template {
+        segments,
+        leading: edge(strings, |s| s.starts_with(char::is_whitespace)),
+        trailing: edge(strings, |s| s.ends_with(char::is_whitespace)),
+    })
+}
+
+/// Returns the pattern of the whitespace at an edge of the inputs, where
+/// `has_space` tells whether an input has some there.
+fn edge(strings: &[&str], has_space: impl Fn(&str) -> bool) -> &'static str {
+    let spaced = strings.iter().filter(|s| has_space(s)).count();
+    match spaced {
+        0 => "",
+        n if n == strings.len() => r"\s+",
+        _ => r"\s*",
+    }
+}
+
+/// The delimiters of the segments kept whole by [`tokenize`].
+const ENCLOSURES: [(char, char); 5] =
+    [('[', ']'), ('(', ')'), ('{', '}'), ('"', '"'), ('\'', '\'')];
+
+/// Splits `s` on whitespace, keeping each bracketed or quoted segment
+/// together with the text attached to it in a single token.
+///
+/// Brackets nest, and a backslash escapes a quote. An unclosed delimiter
+/// is treated as ordinary text.
+pub(crate) fn tokenize(s: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(start, _)) = chars.peek() {
+        let mut end = start;
+        let mut depth: Vec<char> = vec![];
+        let mut escaped = false;
+        for (index, c) in chars.by_ref() {
+            end = index + c.len_utf8();
+            match depth.last() {
+                Some(_) if escaped => escaped = false,
+                Some(&close) if c == '\\' && (close == '"' || close == '\'') => escaped = true,
+                Some(&close) if c == close => {
+                    depth.pop();
+                }
+                Some('"' | '\'') => {}
+                _ => match ENCLOSURES.iter().find(|(open, _)| *open == c) {
+                    Some(&(_, close)) if s[end..].contains(close) => depth.push(close),
+                    _ if depth.is_empty() && c.is_whitespace() => {
+                        end = index;
+                        break;
+                    }
+                    _ => {}
+                },
+            }
+        }
+        if end > start {
+            tokens.push(&s[start..end]);
+        }
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+    tokens
+}
+
+/// Returns the delimiters enclosing every value, if they share some.
+fn common_enclosure(values: &[&str]) -> Option<(char, char)> {
+    ENCLOSURES.into_iter().find(|&(open, close)| {
+        values.iter().all(|v| {
+            v.len() >= open.len_utf8() + close.len_utf8()
+                && v.starts_with(open)
+                && v.ends_with(close)
+        })
+    })
+}
+
+/// Returns the prefix and delimiters shared by every value made of a
+/// prefix followed by a bracketed group, such as `open(...)`.
+fn common_group<'a>(values: &[&'a str]) -> Option<(&'a str, char, char)> {
+    ENCLOSURES.into_iter().find_map(|(open, close)| {
+        let prefix = &values[0][..values[0].find(open)?];
+        values
+            .iter()
+            .all(|v| {
+                v.len() >= prefix.len() + open.len_utf8() + close.len_utf8()
+                    && v.strip_prefix(prefix).is_some_and(|v| v.starts_with(open))
+                    && v.ends_with(close)
+            })
+            .then_some((prefix, open, close))
+    })
+}
+
+/// Returns the key and separator shared by every `key=value` or
+/// `key:value` token of `values`, if they all have one.
+fn common_key<'a>(values: &[&'a str]) -> Option<(&'a str, char)> {
+    let split = |value: &'a str| {
+        let at = value.find(['=', ':'])?;
+        let key = &value[..at];
+        is_key(key).then(|| (key, value[at..].chars().next().unwrap()))
+    };
+    let first = split(values[0])?;
+    values[1..]
+        .iter()
+        .all(|v| split(v) == Some(first))
+        .then_some(first)
+}
+
+/// Returns whether `key` looks like the key of a key-value pair: a letter
+/// or underscore followed by word characters, dots or dashes.
+fn is_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// Turns `key` into a capture group name not already in `used`.
+fn unique_group_name(key: &str, used: &mut HashSet<String>) -> String {
+    let base: String = key
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while !used.insert(name.clone()) {
+        name = format!("{base}_{suffix}");
+        suffix += 1;
+    }
+    name
+}
+
+/// Removes the tokens present in at least `min_presence` of the rows, but
+/// not all, from every row.
+///
+/// # Returns
+///
+/// The remaining rows with the place of each removed token, that is the
+/// number of remaining tokens before it, ordered by place; or `None` if the
+/// remaining rows still differ in length or a removed token does not always
+/// occur at the same place.
+fn strip_optional<'a>(
+    rows: &[Vec<&'a str>],
+    min_presence: f64,
+) -> Option<(Rows<'a>, Vec<(usize, &'a str)>)> {
+    let mut presence: HashMap<&str, usize> = HashMap::new();
+    let mut first_seen: Vec<&str> = vec![];
+    for row in rows {
+        let mut distinct = HashSet::new();
+        for &token in row.iter().filter(|&&token| distinct.insert(token)) {
+            let count = presence.entry(token).or_default();
+            if *count == 0 {
+                first_seen.push(token);
+            }
+            *count += 1;
+        }
+    }
+    let threshold = min_presence * rows.len() as f64;
+    let candidates: HashSet<&str> = presence
+        .into_iter()
+        .filter(|&(_, count)| count < rows.len() && count as f64 >= threshold)
+        .map(|(token, _)| token)
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let mut slots: HashMap<&str, usize> = HashMap::new();
+    let mut stripped = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut kept = vec![];
+        for &token in row {
+            if !candidates.contains(token) {
+                kept.push(token);
+                continue;
+            }
+            match slots.entry(token) {
+                Entry::Occupied(slot) if *slot.get() != kept.len() => return None,
+                Entry::Occupied(_) => {}
+                Entry::Vacant(slot) => {
+                    slot.insert(kept.len());
+                }
+            }
+        }
+        stripped.push(kept);
+    }
+    if stripped.iter().any(|r| r.len() != stripped[0].len()) {
+        return None;
+    }
+
+    let mut slots: Vec<(usize, &str)> = first_seen
+        .into_iter()
+        .filter_map(|token| slots.get(token).map(|&slot| (slot, token)))
+        .collect();
+    slots.sort_by_key(|&(slot, _)| slot);
+    Some((stripped, slots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_generate_template_with_schema() {
+        let inputs = vec!["2024-01-02 10.0.0.1 GET 200", "2024-01-03 10.0.0.2 GET 404"];
+        let schema = FieldSchema::new()
+            .position(0, Detector::Timestamp)
+            .position(2, Detector::Word)
+            .detector_name(Detector::Ip, "client");
+        let template = generate_template(&inputs, &schema).unwrap();
+
+        let fields: Vec<(&str, Detector, bool)> = template
+            .segments()
+            .iter()
+            .filter_map(|s| match s {
+                Segment::Field(f) => Some((f.name(), f.detector(), f.is_pinned())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("field0", Detector::Timestamp, true),
+                ("client", Detector::Ip, false),
+                ("field2", Detector::Word, true),
+                ("field3", Detector::Integer, false),
+            ]
+        );
+
+        let types: Vec<&FieldType> = template
+            .segments()
+            .iter()
+            .filter_map(|s| match s {
+                Segment::Field(f) => Some(f.field_type()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            types,
+            [
+                &FieldType::Timestamp,
+                &FieldType::Ip,
+                &FieldType::Enum(vec!["GET".to_string()]),
+                &FieldType::Integer,
+            ]
+        );
+
+        let regex = Regex::new(&template.to_regex()).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+
+        let violating = FieldSchema::new().position(1, Detector::Integer);
+        assert_eq!(
+            generate_template(&inputs, &violating).unwrap_err(),
+            GenerateError::SchemaViolation {
+                position: 1,
+                detector: Detector::Integer,
+                input: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_generate_template_with_dictionary() {
+        let mut dictionary = DetectorDictionary::new();
+        dictionary.insert("order_id", r"ORD-\d+");
+        let inputs = vec!["order ORD-12 by u-ab1", "order ORD-345 by u-cd2"];
+        let template =
+            generate_template_with_dictionary(&inputs, &FieldSchema::new(), &mut dictionary)
+                .unwrap();
+
+        let shapes: Vec<&str> = template
+            .segments()
+            .iter()
+            .filter_map(|s| match s {
+                Segment::Field(f) => f.shape().map(|shape| shape.name()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(shapes, vec!["order_id", "learned2"]);
+        assert_eq!(dictionary.entries()[1].pattern(), r"[a-z]\-[a-z]{2}\d");
+
+        let regex = Regex::new(&template.to_regex()).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+    }
+
+    #[test]
+    fn test_enclosed_tokens() {
+        assert_eq!(
+            tokenize(r#"[10:00:01 +0200] "GET /a b" (x (y)) 'it\'s' [open"#),
+            vec![
+                "[10:00:01 +0200]",
+                r#""GET /a b""#,
+                "(x (y))",
+                r"'it\'s'",
+                "[open"
+            ]
+        );
+
+        let inputs = vec![
+            r#"[2024-01-02T03:04:05Z] "disk full on sda" 12"#,
+            r#"[2024-01-03T08:00:00Z] "cpu hot" 12"#,
+        ];
+        let template = generate_template(&inputs, &FieldSchema::new()).unwrap();
+        let fields: Vec<(Option<(char, char)>, Detector)> = template
+            .segments()
+            .iter()
+            .filter_map(|s| match s {
+                Segment::Field(f) => Some((f.enclosing(), f.detector())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (Some(('[', ']')), Detector::Timestamp),
+                (Some(('"', '"')), Detector::Any),
+            ]
+        );
+        let regex = Regex::new(&template.to_regex()).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+        assert!(!regex.is_match(r#"[later] "cpu hot" 12"#));
+    }
+
+    #[test]
+    fn test_nested_groups() {
+        let inputs = vec!["call open(file.txt, r) -> 3", "call open(data.csv, w) -> 4"];
+        let template = generate_template(&inputs, &FieldSchema::new()).unwrap();
+        let Segment::Group {
+            prefix,
+            open,
+            template: nested,
+            ..
+        } = &template.segments()[1]
+        else {
+            panic!("expected a group, got {:?}", template.segments()[1]);
+        };
+        assert_eq!((prefix.as_str(), *open), ("open", '('));
+        assert_eq!(nested.segments().len(), 2);
+        let regex = Regex::new(&template.to_regex()).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+    }
+
+    #[test]
+    fn test_key_values() {
+        let inputs = vec![
+            "level=info user: alice took 12ms",
+            "level=warn user: bob took 340ms",
+        ];
+        let template = generate_template(&inputs, &FieldSchema::new().key_values()).unwrap();
+        let keyed: Vec<(&str, Detector)> = template
+            .segments()
+            .iter()
+            .filter_map(|s| match s {
+                Segment::Pair { field, .. } => Some((field.name(), field.detector())),
+                Segment::Field(field) if field.is_captured() => {
+                    Some((field.name(), field.detector()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            keyed,
+            vec![("level", Detector::Word), ("user", Detector::Word)]
+        );
+
+        let regex = Regex::new(&template.to_regex()).unwrap();
+        let captures = regex.captures(inputs[1]).unwrap();
+        assert_eq!(&captures["level"], "warn");
+        assert_eq!(&captures["user"], "bob");
+    }
+
+    #[test]
+    fn test_optional_segments() {
+        let inputs = vec!["[debug] GET 200", "[debug] GET 404", "GET 500"];
+        assert!(generate_template(&inputs, &FieldSchema::new()).is_err());
+
+        let schema = FieldSchema::new().optional_segments(0.5);
+        let template = generate_template(&inputs, &schema).unwrap();
+        assert_eq!(
+            template.segments()[..2],
+            [
+                Segment::Optional("[debug]".to_string()),
+                Segment::Literal("GET".to_string()),
+            ]
+        );
+        assert_eq!(
+            template.to_regex(),
+            r"^(?:\[debug\]\s+)?GET\s+(?:[+-]?\d+)$"
+        );
+        let regex = Regex::new(&template.to_regex()).unwrap();
+        assert!(inputs.iter().all(|s| regex.is_match(s)));
+
+        let rare = FieldSchema::new().optional_segments(0.9);
+        assert!(generate_template(&inputs, &rare).is_err());
+    }
+
+    #[test]
+    fn test_trailing_optional_segments() {
+        let schema = FieldSchema::new().optional_segments(0.5);
+        let cases: [&[&str]; 3] = [
+            &["", "a"],
+            &["GET", "GET [debug]", "GET [debug]"],
+            &["", "a b", "a", "b"],
+        ];
+        for inputs in cases {
+            let template = generate_template(inputs, &schema).unwrap();
+            let regex = Regex::new(&template.to_regex()).unwrap();
+            assert!(
+                inputs.iter().all(|s| regex.is_match(s)),
+                "{inputs:?} {}",
+                template.to_regex()
+            );
+        }
+    }
+
+    #[test]
+    fn test_edge_whitespace() {
+        let cases: [&[&str]; 4] = [
+            &[" GET 200", " GET 404"],
+            &["GET 200 ", "GET 404 "],
+            &["GET 200", "\tGET 404  "],
+            &[" ", "  "],
+        ];
+        for inputs in cases {
+            let template = generate_template(inputs, &FieldSchema::new()).unwrap();
+            let regex = Regex::new(&template.to_regex()).unwrap();
+            assert!(inputs.iter().all(|s| regex.is_match(s)), "{inputs:?}");
+        }
+        let template = generate_template(&[" GET 200", " GET 404"], &FieldSchema::new()).unwrap();
+        assert_eq!(template.to_regex(), r"^\s+GET\s+(?:[+-]?\d+)$");
+    }
+}
@@ -0,0 +1,154 @@
+//! Generators of input sets and invariant checks, for property tests and
+//! fuzzers exercising the generator, built with the `testing` feature.
+//!
+//! ```text
+//! fuzz_target!(|set: InputSet| {
+//!     let inputs = set.as_strs();
+//!     assert_tree_matches_all(&generate_regex_tree(&inputs), &inputs);
+//! });
+//! ```
+
+use crate::Node;
+use arbitrary::{Arbitrary, Unstructured};
+use regex::Regex;
+
+/// The pieces generated strings are made of, chosen for the edge cases
+/// they exercise: empty and white space strings, regex metacharacters,
+/// digits, multi-byte characters and line breaks.
+const PIECES: [&str; 20] = [
+    "", " ", "  ", "\t", "\n", "a", "b", "ab", "x y", "0", "42", ".", "*", "(", "\\", "-", "=",
+    "é", "日本", "ERROR",
+];
+
+/// The most strings an input set holds.
+const MAX_INPUTS: usize = 8;
+
+/// The most pieces a generated string is made of.
+const MAX_PIECES: usize = 6;
+
+/// A set of inputs to generate a tree from, drawn to favour the cases the
+/// generator finds hardest: empty strings, repeated strings, white space
+/// only strings and metacharacters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputSet(pub Vec<String>);
+
+impl InputSet {
+    /// Draws an input set from a seed, for property tests run without a
+    /// fuzzing engine.
+    pub fn from_seed(seed: u64) -> InputSet {
+        let mut state = seed;
+        let bytes: Vec<u8> = (0..256)
+            .map(|_| {
+                state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                let mut z = state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                (z ^ (z >> 31)) as u8
+            })
+            .collect();
+        InputSet::arbitrary(&mut Unstructured::new(&bytes)).unwrap_or(InputSet(vec![]))
+    }
+
+    /// Returns the inputs as string slices, as the generator takes them.
+    pub fn as_strs(&self) -> Vec<&str> {
+        self.0.iter().map(String::as_str).collect()
+    }
+}
+
+impl<'a> Arbitrary<'a> for InputSet {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let len = u.int_in_range(1..=MAX_INPUTS)?;
+        let mut inputs: Vec<String> = Vec::with_capacity(len);
+        for _ in 0..len {
+            // Some inputs repeat an earlier one, as log lines often do.
+            if !inputs.is_empty() && u.ratio(1, 4)? {
+                let earlier = u.choose(&inputs)?.clone();
+                inputs.push(earlier);
+                continue;
+            }
+            let pieces = u.int_in_range(0..=MAX_PIECES)?;
+            let mut input = String::new();
+            for _ in 0..pieces {
+                input.push_str(u.choose(&PIECES)?);
+            }
+            inputs.push(input);
+        }
+        Ok(InputSet(inputs))
+    }
+}
+
+/// Checks the invariants every generated tree upholds for the inputs it
+/// was generated from, panicking with the offending pattern if one fails:
+/// every pattern compiles, the root and the flattened [`Node::to_regex`]
+/// match every input, and every node matches at least one input.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `inputs` - The inputs the tree was generated from.
+pub fn assert_tree_matches_all(root: &Node, inputs: &[&str]) {
+    let compile = |pattern: &str| {
+        Regex::new(pattern).unwrap_or_else(|e| panic!("pattern {pattern:?} does not compile: {e}"))
+    };
+    for pattern in [root.pattern.to_string(), root.to_regex()] {
+        let regex = compile(&pattern);
+        for input in inputs {
+            assert!(
+                regex.is_match(input),
+                "pattern {pattern:?} does not match input {input:?}"
+            );
+        }
+    }
+    for node in root.preorder() {
+        let regex = compile(&node.pattern);
+        assert!(
+            inputs.is_empty() || inputs.iter().any(|input| regex.is_match(input)),
+            "pattern {:?} matches no input",
+            node.pattern
+        );
+    }
+}
+
+/// Finds the common substrings of `strings` on the suffix automaton tree
+/// generation reads them off, for the benchmarks comparing it with other
+/// searches.
+#[doc(hidden)]
+pub fn automaton_common_substrings<'a>(strings: &[&'a str]) -> Vec<&'a str> {
+    crate::automaton::common_substrings(strings, usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generate_regex_tree, generate_regex_tree_weighted, generate_regex_tree_with_options,
+        GenerateOptions, Stratification,
+    };
+
+    #[test]
+    fn test_generated_trees_match_all() {
+        let stratified = GenerateOptions {
+            stratification: Some(Stratification::Length),
+            ..GenerateOptions::default()
+        };
+        let edge_cases = [vec![""], vec!["", ""], vec!["a", "a"], vec![" ", "\t", ""]];
+        let sets = (0..50).map(|seed| InputSet::from_seed(seed).0);
+        for set in edge_cases
+            .iter()
+            .map(|set| set.iter().map(|s| s.to_string()).collect())
+            .chain(sets)
+        {
+            let inputs = InputSet(set);
+            let inputs = inputs.as_strs();
+            assert_tree_matches_all(&generate_regex_tree(&inputs), &inputs);
+            let tree = generate_regex_tree_with_options(&inputs, &stratified).unwrap();
+            assert_tree_matches_all(&tree, &inputs);
+            let weighted: Vec<(&str, f64)> = inputs
+                .iter()
+                .enumerate()
+                .map(|(i, &input)| (input, i as f64))
+                .collect();
+            assert_tree_matches_all(&generate_regex_tree_weighted(&weighted), &inputs);
+        }
+    }
+}
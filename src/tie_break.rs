@@ -0,0 +1,100 @@
+use crate::{DocumentFrequencies, Role};
+
+/// How candidates of equal score are ordered, which decides the ones the
+/// tree is built around; see [`crate::GenerateOptions::tie_break`].
+///
+/// A candidate's score is its length, weighted with its inverse document
+/// frequency when [`crate::GenerateOptions::background`] is set. Candidates
+/// still tied after the tie-breaker keep the order they were mined in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TieBreak {
+    /// The longer candidate first, then the one occurring earlier in the
+    /// first input.
+    #[default]
+    Longest,
+    /// The candidate occurring earlier in the first input first, which
+    /// favors the leading fields of log lines.
+    EarliestPosition,
+    /// The candidate making up every input first, then the ones every
+    /// input starts or ends with, which can be anchored with `^` or `$`.
+    MostAnchored,
+    /// The candidate first in byte order, which does not depend on how
+    /// the inputs are laid out.
+    Lexicographic,
+}
+
+impl TieBreak {
+    /// Reorders the candidates of equal score according to the
+    /// tie-breaker, leaving the order of the scores alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `substrings` - The ranked candidate substrings, reordered in place.
+    /// * `inputs` - The strings the candidates were mined from.
+    /// * `background` - The frequencies the candidates were weighted with,
+    ///   if any.
+    pub(crate) fn break_ties(
+        self,
+        substrings: &mut [&str],
+        inputs: &[&str],
+        background: Option<&DocumentFrequencies>,
+    ) {
+        // Candidates are mined longer first, then earlier first, and
+        // weighting with a stable sort keeps that order among ties.
+        if self == TieBreak::Longest {
+            return;
+        }
+        let first = inputs.first().copied().unwrap_or_default();
+        let mut keyed: Vec<(f64, usize, &str)> = substrings
+            .iter()
+            .map(|&s| {
+                let score = s.len() as f64 * background.map_or(1.0, |b| b.idf(s));
+                let key = match self {
+                    TieBreak::EarliestPosition => first.find(s).unwrap_or(usize::MAX),
+                    TieBreak::MostAnchored => match Role::of(s, inputs) {
+                        Role::Full => 0,
+                        Role::Prefix | Role::Suffix => 1,
+                        Role::Infix => 2,
+                    },
+                    TieBreak::Longest | TieBreak::Lexicographic => 0,
+                };
+                (score, key, s)
+            })
+            .collect();
+        keyed.sort_by(|a, b| {
+            b.0.total_cmp(&a.0).then_with(|| match self {
+                TieBreak::Lexicographic => a.2.cmp(b.2),
+                _ => a.1.cmp(&b.1),
+            })
+        });
+        for (slot, (_, _, s)) in substrings.iter_mut().zip(keyed) {
+            *slot = s;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{select_candidates, GenerateOptions, TieBreak};
+
+    #[test]
+    fn test_tie_break() {
+        let inputs = ["zz a: yy", "a: zz yy"];
+        let best = |tie_break| {
+            let options = GenerateOptions {
+                tie_break,
+                ..GenerateOptions::default()
+            };
+            let candidates = select_candidates(&inputs, &options);
+            candidates[..3].join("|")
+        };
+        assert_eq!(best(TieBreak::Longest), "zz |a: | yy");
+        assert_eq!(best(TieBreak::EarliestPosition), "zz |a: | yy");
+        assert_eq!(best(TieBreak::MostAnchored), " yy|zz |a: ");
+        assert_eq!(best(TieBreak::Lexicographic), " yy|a: |zz ");
+
+        let mut substrings = ["b", "ab", "a", "ba"];
+        TieBreak::Lexicographic.break_ties(&mut substrings, &["ab"], None);
+        assert_eq!(substrings, ["ab", "ba", "a", "b"]);
+    }
+}
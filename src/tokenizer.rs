@@ -0,0 +1,97 @@
+use std::ops::Range;
+
+/// How inputs are split into tokens when candidates must align on token
+/// boundaries; see [`crate::GenerateOptions::tokenizer`].
+#[derive(Debug, Clone)]
+pub enum Tokenizer {
+    /// Tokens are the runs of non-whitespace characters.
+    Whitespace,
+    /// Tokens are the runs of characters other than these delimiters, such
+    /// as `,` for CSV rows. Whitespace is part of the tokens unless listed.
+    Delimiters(Vec<char>),
+    /// Tokens are the byte ranges returned by the function, in ascending
+    /// order. Ranges outside the input or not on char boundaries are
+    /// ignored.
+    Custom(fn(&str) -> Vec<Range<usize>>),
+}
+
+impl Tokenizer {
+    /// Returns the byte ranges of the tokens of `s`.
+    pub fn tokens(&self, s: &str) -> Vec<Range<usize>> {
+        match self {
+            Tokenizer::Whitespace => runs(s, char::is_whitespace),
+            Tokenizer::Delimiters(delimiters) => runs(s, |c| delimiters.contains(&c)),
+            Tokenizer::Custom(split) => split(s)
+                .into_iter()
+                .filter(|r| {
+                    r.start < r.end && s.is_char_boundary(r.start) && s.is_char_boundary(r.end)
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns whether `candidate` occurs in `s` starting at the start of a
+    /// token and ending at the end of one, possibly spanning several tokens.
+    pub(crate) fn is_aligned(&self, s: &str, candidate: &str) -> bool {
+        let tokens = self.tokens(s);
+        s.match_indices(candidate).any(|(start, _)| {
+            let end = start + candidate.len();
+            tokens.iter().any(|t| t.start == start) && tokens.iter().any(|t| t.end == end)
+        })
+    }
+}
+
+/// Returns the maximal runs of characters of `s` not matching `separator`.
+fn runs(s: &str, separator: impl Fn(char) -> bool) -> Vec<Range<usize>> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        match (separator(c), start) {
+            (true, Some(begin)) => {
+                tokens.push(begin..i);
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push(begin..s.len());
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_regex_tree_with_options, GenerateOptions, Tokenizer};
+
+    #[test]
+    fn test_tokenizer() {
+        assert_eq!(Tokenizer::Whitespace.tokens(" a bc  d"), [1..2, 3..5, 7..8]);
+        let csv = Tokenizer::Delimiters(vec![',']);
+        assert_eq!(csv.tokens("id,,a b"), [0..2, 4..7]);
+        assert!(csv.is_aligned("7,disk full,8", "disk full"));
+        assert!(!csv.is_aligned("7,disk full,8", "isk"));
+
+        let inputs = [
+            r#"10.0.0.1 - - [10/Oct/2000:13:55:36] "GET /index.html""#,
+            r#"10.0.0.2 - - [11/Oct/2000:09:01:02] "GET /about.html""#,
+        ];
+        let options = GenerateOptions {
+            tokenizer: Some(Tokenizer::Whitespace),
+            ..GenerateOptions::default()
+        };
+        let tree = generate_regex_tree_with_options(&inputs, &options).unwrap();
+        let patterns: Vec<&str> = tree.preorder().iter().map(|n| n.pattern.as_str()).collect();
+        assert!(patterns.contains(&r".*\- \-.*"), "{patterns:?}");
+        assert!(patterns.contains(&r#".*"GET.*"#), "{patterns:?}");
+        assert!(
+            !patterns.iter().any(|p| p.contains(".*0.*")),
+            "{patterns:?}"
+        );
+        assert!(
+            !patterns.iter().any(|p| p.contains(".*Oct.*")),
+            "{patterns:?}"
+        );
+    }
+}
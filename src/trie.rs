@@ -0,0 +1,154 @@
+use crate::Node;
+use std::sync::Arc;
+
+/// How a regex pattern tree is mined from its inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// Patterns are built around the substrings common to the inputs, as
+    /// in `.*ERROR.*`, which suits log lines and other long inputs.
+    #[default]
+    CommonSubstring,
+    /// The inputs are spelled out in a prefix trie collapsed into a regex,
+    /// as in `^get(?:All|ById)?$`, which suits small sets of short strings
+    /// such as enum values or command names. Each branch of the trie is a
+    /// node matching exactly the inputs beneath it.
+    ///
+    /// Since every input is spelled out, generation falls back to common
+    /// substrings when literals are guarded, see
+    /// [`crate::GenerateOptions::min_literal_support`] and
+    /// [`crate::GenerateOptions::secret_policy`].
+    Trie,
+}
+
+/// A node of a prefix trie over chars.
+#[derive(Debug, Default)]
+struct TrieNode {
+    /// Whether an input ends here.
+    terminal: bool,
+    /// The children, in order of first insertion.
+    children: Vec<(char, TrieNode)>,
+}
+
+impl TrieNode {
+    fn insert(&mut self, input: &str) {
+        let mut node = self;
+        for c in input.chars() {
+            let index = match node.children.iter().position(|(d, _)| *d == c) {
+                Some(index) => index,
+                None => {
+                    node.children.push((c, TrieNode::default()));
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[index].1;
+        }
+        node.terminal = true;
+    }
+
+    /// Returns the branches leaving this node, each as the chars along its
+    /// chain of single-child nodes and the node the chain ends at.
+    fn branches(&self) -> Vec<(String, &TrieNode)> {
+        self.children
+            .iter()
+            .map(|(c, node)| {
+                let (mut label, mut node) = (c.to_string(), node);
+                while let ([(c, child)], false) = (&node.children[..], node.terminal) {
+                    label.push(*c);
+                    node = child;
+                }
+                (label, node)
+            })
+            .collect()
+    }
+
+    /// Returns the regex matching exactly the suffixes of the inputs
+    /// beneath this node, unanchored.
+    fn body(&self) -> String {
+        let alternatives: Vec<String> = self
+            .branches()
+            .into_iter()
+            .map(|(label, node)| {
+                let mut alternative = regex_syntax::escape(&label);
+                alternative.push_str(&node.body());
+                alternative
+            })
+            .collect();
+        let optional = if self.terminal { "?" } else { "" };
+        match &alternatives[..] {
+            [] => String::new(),
+            [single] if !self.terminal => single.clone(),
+            [single] if single.chars().count() == 1 => format!("{single}{optional}"),
+            alternatives => format!("(?:{}){optional}", alternatives.join("|")),
+        }
+    }
+
+    /// Returns the node of the tree matching exactly the inputs beneath
+    /// this node, all starting with `prefix`.
+    fn to_node(&self, prefix: &str) -> Node {
+        let mut node = Node::new(format!("^{}{}$", regex_syntax::escape(prefix), self.body()));
+        for (label, child) in self.branches() {
+            let child = child.to_node(&format!("{prefix}{label}"));
+            node.children.push(Arc::new(child));
+        }
+        node
+    }
+}
+
+/// Builds the tree of the prefix trie of `strings`, beneath a `.*` root.
+///
+/// The root's child matches exactly the inputs, and every branching of
+/// the trie, or input that is a prefix of others, adds a level beneath.
+///
+/// # Arguments
+///
+/// * `strings` - A non-empty slice of strings.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree.
+pub(crate) fn build_trie(strings: &[&str]) -> Node {
+    let mut trie = TrieNode::default();
+    for input in strings {
+        trie.insert(input);
+    }
+    let top = match &trie.branches()[..] {
+        [(label, node)] if !trie.terminal => node.to_node(label),
+        _ => trie.to_node(""),
+    };
+    let mut root = Node::new(".*".to_string());
+    root.children.push(Arc::new(top));
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Algorithm, RegexTreeBuilder};
+    use regex::Regex;
+
+    #[test]
+    fn test_trie_algorithm() {
+        let inputs = ["get", "getAll", "getById", "set", "setAll", "reset"];
+        let tree = RegexTreeBuilder::new()
+            .algorithm(Algorithm::Trie)
+            .build(&inputs)
+            .unwrap();
+        let top = &tree.children[0];
+        assert_eq!(top.pattern, "^(?:get(?:All|ById)?|set(?:All)?|reset)$");
+        let branches: Vec<&str> = top.children.iter().map(|n| n.pattern.as_str()).collect();
+        assert_eq!(branches, ["^get(?:All|ById)?$", "^set(?:All)?$", "^reset$"]);
+        assert_eq!(top.children[0].children[1].pattern, "^getById$");
+        for node in tree.preorder() {
+            let regex = Regex::new(&node.pattern).unwrap();
+            assert!(inputs.iter().any(|s| regex.is_match(s)), "{}", node.pattern);
+        }
+        assert!(inputs
+            .iter()
+            .all(|s| Regex::new(&top.pattern).unwrap().is_match(s)));
+
+        let tree = RegexTreeBuilder::new()
+            .algorithm(Algorithm::Trie)
+            .build(&["ls", "lsof"])
+            .unwrap();
+        assert_eq!(tree.children[0].pattern, "^ls(?:of)?$");
+    }
+}
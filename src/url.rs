@@ -0,0 +1,241 @@
+use crate::{generate_regex_tree, Node};
+use std::fmt::Write;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Generates a regex pattern tree from URLs, mining them in a normalized
+/// form so that encodings of the same address generalize together.
+///
+/// Before mining, hosts are mapped to Unicode as IDNA does, and the rest of each URL is percent-decoded (see [`normalize_url`]).
+/// The emitted patterns accept both forms again: a complete
+/// internationalized label matches its Unicode or `xn--` spelling, and a
+/// decoded character outside the host matches itself or its
+/// percent-encoding.
+///
+/// # Arguments
+///
+/// * `urls` - The URLs to generate the regex pattern tree from.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree.
+pub fn generate_url_tree(urls: &[&str]) -> Node {
+    let normalized: Vec<(String, Range<usize>)> = urls.iter().map(|u| normalize(u)).collect();
+    let inputs: Vec<&str> = normalized.iter().map(|(u, _)| u.as_str()).collect();
+    let mut root = generate_regex_tree(&inputs);
+    reencode(&mut root, &normalized);
+    root
+}
+
+/// Normalizes a URL for mining: the scheme is lowercased, the host is
+/// mapped and its punycode labels decoded with the IDNA processing URL
+/// parsers use, falling back to lowercasing when it is not a valid domain
+/// name, and percent-encoded sequences after
+/// the host are decoded, except those for `/`, `?`, `#` and `%`, which
+/// would change the structure of the URL.
+///
+/// A string without a `://` separator is treated as a path.
+pub fn normalize_url(url: &str) -> String {
+    normalize(url).0
+}
+
+/// Normalizes a URL, also returning the byte range of its host.
+fn normalize(url: &str) -> (String, Range<usize>) {
+    let mut normalized = String::with_capacity(url.len());
+    let mut host = 0..0;
+    let mut rest = url;
+    if let Some(separator) = url.find("://") {
+        normalized.push_str(&url[..separator].to_lowercase());
+        normalized.push_str("://");
+        let authority = &url[separator + 3..];
+        let end = authority.find(['/', '?', '#']).unwrap_or(authority.len());
+        host.start = normalized.len();
+        let host_port = &authority[..end];
+        let name = match host_port.rsplit_once(':') {
+            Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+            _ => host_port,
+        };
+        match idna::domain_to_unicode(name) {
+            (domain, Ok(())) => normalized.push_str(&domain),
+            (_, Err(_)) => normalized.push_str(&name.to_lowercase()),
+        }
+        normalized.push_str(&host_port[name.len()..]);
+        host.end = normalized.len();
+        rest = &authority[end..];
+    }
+    percent_decode_into(rest, &mut normalized);
+    (normalized, host)
+}
+
+/// Appends `text` to `out`, decoding each run of percent-encoded bytes
+/// that forms valid UTF-8.
+fn percent_decode_into(text: &str, out: &mut String) {
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        let mut decoded = vec![];
+        let mut end = index;
+        while let Some(byte) = percent_byte(bytes, end) {
+            decoded.push(byte);
+            end += 3;
+        }
+        match String::from_utf8(decoded) {
+            Ok(run) if end > index => {
+                // Keep structural characters encoded, one sequence each.
+                for (offset, c) in run.char_indices() {
+                    if matches!(c, '/' | '?' | '#' | '%') {
+                        let start = index + 3 * offset;
+                        out.push_str(&text[start..start + 3]);
+                    } else {
+                        out.push(c);
+                    }
+                }
+                index = end;
+            }
+            _ => {
+                let len = text[index..].chars().next().map_or(1, char::len_utf8);
+                let step = if end > index { 3 } else { len };
+                out.push_str(&text[index..index + step]);
+                index += step;
+            }
+        }
+    }
+}
+
+/// Returns the byte encoded by the `%XX` sequence at `index`, if any.
+fn percent_byte(bytes: &[u8], index: usize) -> Option<u8> {
+    if bytes.get(index) != Some(&b'%') {
+        return None;
+    }
+    let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+    u8::from_str_radix(hex, 16).ok()
+}
+
+/// Rewrites the literal of every anchored node so that it also matches the
+/// encoded spelling of the URLs.
+fn reencode(node: &mut Node, normalized: &[(String, Range<usize>)]) {
+    if let Some(role) = node.role {
+        let (open, close) = role.anchors();
+        let escaped = &node.pattern[open.len()..node.pattern.len() - close.len()];
+        let literal = unescape(escaped);
+        let context = normalized
+            .iter()
+            .find_map(|(url, host)| url.find(&literal).map(|at| (url.as_str(), host, at)));
+        if let Some((url, host, at)) = context {
+            let mut pattern = String::from(open);
+            write_encoded(url, host, at..at + literal.len(), &mut pattern);
+            pattern.push_str(close);
+            node.pattern = pattern.into();
+        }
+    }
+    for child in &mut node.children {
+        reencode(Arc::make_mut(child), normalized);
+    }
+}
+
+/// Removes the backslashes `regex::escape` put before metacharacters.
+fn unescape(escaped: &str) -> String {
+    let mut literal = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        literal.push(if c == '\\' {
+            chars.next().unwrap_or(c)
+        } else {
+            c
+        });
+    }
+    literal
+}
+
+/// Appends a pattern for `url[span]` to `out`, where `host` is the byte
+/// range of the host in `url`. The scheme and host, which were lowercased,
+/// are matched case-insensitively.
+fn write_encoded(url: &str, host: &Range<usize>, span: Range<usize>, out: &mut String) {
+    let mut index = span.start;
+    if index < host.end {
+        let end = span.end.min(host.end);
+        out.push_str("(?i:");
+        while index < end {
+            let label_end = url[index..host.end]
+                .find('.')
+                .map_or(host.end, |d| index + d);
+            let label = &url[index..label_end];
+            let at_label_start =
+                index == host.start || (index > host.start && url[..index].ends_with('.'));
+            match idna::domain_to_ascii(label) {
+                Ok(encoded) if at_label_start && label_end <= end && !label.is_ascii() => {
+                    out.push_str("(?:");
+                    regex_syntax::escape_into(label, out);
+                    out.push('|');
+                    regex_syntax::escape_into(&encoded, out);
+                    out.push(')');
+                    index = label_end;
+                }
+                _ => {
+                    let c = url[index..].chars().next().unwrap();
+                    regex_syntax::escape_into(c.encode_utf8(&mut [0; 4]), out);
+                    index += c.len_utf8();
+                }
+            }
+        }
+        out.push(')');
+    }
+
+    for c in url[index..span.end].chars() {
+        let mut buffer = [0; 4];
+        let text = c.encode_utf8(&mut buffer);
+        if needs_encoding(c) {
+            out.push_str("(?:");
+            regex_syntax::escape_into(text, out);
+            out.push_str("|(?i:");
+            for byte in text.bytes() {
+                write!(out, "%{byte:02X}").unwrap();
+            }
+            out.push_str("))");
+        } else {
+            regex_syntax::escape_into(text, out);
+        }
+    }
+}
+
+/// Returns whether `c` must be percent-encoded in a URL.
+fn needs_encoding(c: char) -> bool {
+    !(c.is_ascii_alphanumeric() || "-._~!$&'()*+,;=:@/?#%".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_generate_url_tree() {
+        assert_eq!(
+            normalize_url("HTTPS://XN--MNCHEN-3YA.de/stra%C3%9Fe%2F1"),
+            "https://münchen.de/straße%2F1"
+        );
+        assert_eq!(normalize_url("https://Faß.DE/a"), "https://faß.de/a");
+        assert_eq!(
+            normalize_url("https://XN--MNCHEN-3YA.de:8080/a"),
+            "https://münchen.de:8080/a"
+        );
+
+        let urls = [
+            "https://xn--mnchen-3ya.de/stra%C3%9Fe/1",
+            "https://München.de/straße/2",
+        ];
+        let tree = generate_url_tree(&urls);
+        let node = tree
+            .preorder()
+            .into_iter()
+            .filter(|n| n.pattern.starts_with("^(?i:https"))
+            .max_by_key(|n| n.pattern.len())
+            .unwrap();
+        assert_eq!(
+            node.pattern,
+            r"^(?i:https://(?:münchen|xn\-\-mnchen\-3ya)\.de)/stra(?:ß|(?i:%C3%9F))e/.*"
+        );
+        let regex = Regex::new(&node.pattern).unwrap();
+        assert!(urls.iter().all(|u| regex.is_match(u)));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{Node, TreeError};
+use regex::Regex;
+
+/// The verification of one node's pattern against the inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeVerification {
+    id: usize,
+    parent: Option<usize>,
+    pattern: String,
+    matched: Result<Vec<usize>, TreeError>,
+}
+
+impl NodeVerification {
+    /// Returns the pre-order identifier of the node, the root being `0`.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the node's pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the indices of the inputs the pattern matches, in ascending
+    /// order, or the error raised while compiling it.
+    pub fn matched(&self) -> Result<&[usize], &TreeError> {
+        self.matched.as_deref()
+    }
+}
+
+/// The result of compiling every pattern of a tree and running it over
+/// the inputs the tree was generated from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    nodes: Vec<NodeVerification>,
+    input_count: usize,
+}
+
+impl VerificationReport {
+    /// Returns the verification of every node, in pre-order.
+    pub fn nodes(&self) -> &[NodeVerification] {
+        &self.nodes
+    }
+
+    /// Returns the errors of the patterns that do not compile.
+    pub fn errors(&self) -> impl Iterator<Item = &TreeError> {
+        self.nodes.iter().filter_map(|n| n.matched.as_ref().err())
+    }
+
+    /// Returns the indices of the inputs the root does not match.
+    pub fn unmatched_inputs(&self) -> Vec<usize> {
+        let matched = match self.nodes.first().map(|n| &n.matched) {
+            Some(Ok(matched)) => matched.as_slice(),
+            _ => &[],
+        };
+        (0..self.input_count)
+            .filter(|i| matched.binary_search(i).is_err())
+            .collect()
+    }
+
+    /// Returns the identifiers of the nodes matching an input their parent
+    /// does not, which breaks routing from the root.
+    pub fn escaping_nodes(&self) -> Vec<usize> {
+        self.nodes
+            .iter()
+            .filter(|node| {
+                let Some(parent) = node.parent else {
+                    return false;
+                };
+                match (&node.matched, &self.nodes[parent].matched) {
+                    (Ok(matched), Ok(allowed)) => {
+                        matched.iter().any(|i| allowed.binary_search(i).is_err())
+                    }
+                    _ => false,
+                }
+            })
+            .map(|node| node.id)
+            .collect()
+    }
+
+    /// Returns whether every pattern compiles, the root matches every input
+    /// and no node matches an input its parent does not.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+            && self.unmatched_inputs().is_empty()
+            && self.escaping_nodes().is_empty()
+    }
+}
+
+/// Compiles every pattern of a tree with the `regex` crate and records
+/// which inputs each one matches, so callers can check the output before
+/// relying on it.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the regex pattern tree.
+/// * `inputs` - The inputs the tree should cover.
+///
+/// # Returns
+///
+/// The report, holding an error for each pattern that does not compile.
+pub fn verify_tree(root: &Node, inputs: &[&str]) -> VerificationReport {
+    let mut nodes = vec![];
+    verify_node(root, None, inputs, &mut nodes);
+    VerificationReport {
+        nodes,
+        input_count: inputs.len(),
+    }
+}
+
+fn verify_node(
+    node: &Node,
+    parent: Option<usize>,
+    inputs: &[&str],
+    nodes: &mut Vec<NodeVerification>,
+) {
+    let id = nodes.len();
+    let matched = Regex::new(&node.pattern)
+        .map(|regex| {
+            (0..inputs.len())
+                .filter(|&i| regex.is_match(inputs[i]))
+                .collect()
+        })
+        .map_err(|e| TreeError::InvalidPattern {
+            pattern: node.pattern.to_string(),
+            message: e.to_string(),
+        });
+    nodes.push(NodeVerification {
+        id,
+        parent,
+        pattern: node.pattern.to_string(),
+        matched,
+    });
+    for child in &node.children {
+        verify_node(child, Some(id), inputs, nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_regex_tree;
+
+    #[test]
+    fn test_verify_tree() {
+        let inputs = ["disk sda full", "disk sdb full", "net down"];
+        let report = verify_tree(&generate_regex_tree(&inputs), &inputs);
+        assert!(report.is_valid(), "{report:?}");
+        assert_eq!(report.nodes()[0].matched(), Ok(&[0, 1, 2][..]));
+
+        let mut root = Node::new(".*".to_string());
+        root.children.push(Node::new("^disk (".to_string()).into());
+        root.children.push(Node::new("^net.*".to_string()).into());
+        let mut escaping = Node::new("^disk.*".to_string());
+        escaping
+            .children
+            .push(Node::new(".*full$".to_string()).into());
+        root.children.push(escaping.into());
+        root.pattern = "^disk.*".into();
+
+        let report = verify_tree(&root, &inputs);
+        assert_eq!(report.errors().count(), 1);
+        assert_eq!(report.unmatched_inputs(), [2]);
+        assert_eq!(report.escaping_nodes(), [2]);
+        assert!(!report.is_valid());
+    }
+}
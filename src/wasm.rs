@@ -0,0 +1,55 @@
+//! Bindings for generating patterns in the browser, built with
+//! `wasm-pack build --target web -- --features wasm`.
+
+use crate::generate_regex_tree;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Generates a single anchored pattern matching every line, as
+/// [`crate::generate_regex`] does.
+///
+/// # Arguments
+///
+/// * `lines` - The sample lines, a JavaScript array of strings.
+///
+/// # Returns
+///
+/// The pattern, in the `regex` crate's syntax, or an empty one, matching
+/// everything, if there are no lines.
+#[wasm_bindgen]
+pub fn generate_regex(lines: Vec<String>) -> String {
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    crate::generate_regex(&lines)
+}
+
+/// Generates the regex pattern tree of the lines, serialized as with
+/// [`crate::Node::to_json`].
+///
+/// # Arguments
+///
+/// * `lines` - The sample lines, a JavaScript array of strings.
+///
+/// # Returns
+///
+/// The JSON document of the tree.
+#[wasm_bindgen]
+pub fn generate_tree_json(lines: Vec<String>) -> String {
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    generate_regex_tree(&lines)
+        .to_json()
+        .expect("a tree serializes to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Node;
+
+    #[test]
+    fn test_wasm_bindings() {
+        let lines = vec!["disk sda full".to_string(), "disk sdb full".to_string()];
+        assert_eq!(generate_regex(lines.clone()), "^disk sd(?:a|b) full$");
+        let tree = Node::from_json(&generate_tree_json(lines)).unwrap();
+        assert_eq!(tree.pattern, ".*");
+        assert_eq!(generate_regex(vec![]), "");
+    }
+}
@@ -0,0 +1,145 @@
+use crate::builder::TreeShape;
+use crate::{best_patterns, build_with_options, scoring, GenerateOptions, Node, Role};
+use regex::Regex;
+use std::collections::hash_map::{Entry, HashMap};
+use std::sync::Arc;
+
+/// Generates a regex pattern tree from weighted inputs, such as log lines
+/// with how often each occurs, preferring patterns that tightly fit the
+/// heaviest ones.
+///
+/// The tree is generated from every input, as with
+/// [`crate::generate_regex_tree`], then, unless the inputs share nothing,
+/// the inputs weighing at least the mean weight get a branch of their own
+/// beneath the root, ahead of the others, built from them alone: the most
+/// specific pattern matching them all, with its subtree. Every node
+/// records the total weight of the
+/// inputs it matches in [`Node::weight`], and the other children of every
+/// node are ordered by decreasing weight, so the heaviest branches come
+/// first and survive truncation.
+///
+/// # Arguments
+///
+/// * `examples` - The inputs, with their non-negative weights. The weights
+///   of repeated inputs add up. A negative, infinite or NaN weight counts
+///   as zero.
+///
+/// # Returns
+///
+/// The root node of the regex pattern tree.
+pub fn generate_regex_tree_weighted(examples: &[(&str, f64)]) -> Node {
+    let strings: Vec<&str> = examples.iter().map(|&(s, _)| s).collect();
+    let options = GenerateOptions::default();
+    let shape = TreeShape::default();
+    let mut root = build_with_options(&strings, &options, &shape);
+
+    let mut totals: Vec<(&str, f64)> = vec![];
+    let mut indices: HashMap<&str, usize> = HashMap::with_capacity(examples.len());
+    for &(input, weight) in examples {
+        let weight = if weight.is_finite() {
+            weight.max(0.0)
+        } else {
+            0.0
+        };
+        match indices.entry(input) {
+            Entry::Occupied(index) => totals[*index.get()].1 += weight,
+            Entry::Vacant(slot) => {
+                slot.insert(totals.len());
+                totals.push((input, weight));
+            }
+        }
+    }
+    let mean = totals.iter().map(|(_, w)| w).sum::<f64>() / totals.len().max(1) as f64;
+    let core: Vec<&str> = totals
+        .iter()
+        .filter(|&&(_, weight)| weight >= mean)
+        .map(|&(input, _)| input)
+        .collect();
+    weigh(&mut root, &totals);
+    // A branch beside none would leave the other inputs unmatched by the
+    // leaves.
+    if !root.children.is_empty() && !core.is_empty() && core.len() < totals.len() {
+        if let Some(mut branch) = core_branch(&core, &options, &shape) {
+            scoring::score_tree(&mut branch, &strings);
+            weigh(&mut branch, &totals);
+            root.children.insert(0, Arc::new(branch));
+        }
+    }
+    root
+}
+
+/// Returns the node of the most specific pattern matching every one of
+/// `core`, with its subtree, or `None` if they only share `.*`.
+fn core_branch(core: &[&str], options: &GenerateOptions, shape: &TreeShape) -> Option<Node> {
+    if let [input] = core {
+        let mut exact = Node::new(format!("^{}$", regex_syntax::escape(input)));
+        exact.role = Some(Role::Full);
+        return Some(exact);
+    }
+    let tree = build_with_options(core, options, shape);
+    let best = best_patterns(&tree, 1).into_iter().next()?;
+    (best.pattern != ".*").then(|| best.clone())
+}
+
+/// Records the weight of the inputs each node matches and orders the
+/// children of every node by decreasing weight.
+fn weigh(node: &mut Node, totals: &[(&str, f64)]) {
+    for child in &mut node.children {
+        weigh(Arc::make_mut(child), totals);
+    }
+    node.children
+        .sort_by(|a, b| b.weight.unwrap_or(0.0).total_cmp(&a.weight.unwrap_or(0.0)));
+    if let Ok(regex) = Regex::new(&node.pattern) {
+        let weight = totals
+            .iter()
+            .filter(|(input, _)| regex.is_match(input))
+            .map(|(_, weight)| weight)
+            .sum();
+        node.weight = Some(weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_regex_tree_weighted() {
+        let examples = [
+            ("GET /api/users", 50.0),
+            ("POST /login", 1.0),
+            ("GET /api/orders", 30.0),
+            ("DELETE /x", 1.0),
+            ("GET /api/users", 10.0),
+        ];
+        let tree = generate_regex_tree_weighted(&examples);
+        assert_eq!(tree.weight(), Some(92.0));
+        let heaviest = &tree.children[0];
+        assert!(
+            heaviest.pattern.starts_with("^GET /api/"),
+            "{}",
+            heaviest.pattern
+        );
+        assert_eq!(heaviest.weight(), Some(90.0));
+        for node in tree.iter().skip(1) {
+            let weights: Vec<f64> = node.children.iter().filter_map(|c| c.weight()).collect();
+            assert!(weights.windows(2).all(|pair| pair[0] >= pair[1]));
+        }
+
+        let apart = generate_regex_tree_weighted(&[("abc", 5.0), ("xyz", 1.0)]);
+        let flattened = Regex::new(&apart.to_regex()).unwrap();
+        assert!(flattened.is_match("abc") && flattened.is_match("xyz"));
+
+        let unweighted = crate::generate_regex_tree(&["a-1", "a-2"]);
+        let even = generate_regex_tree_weighted(&[("a-1", 1.0), ("a-2", 1.0)]);
+        assert_eq!(even.size(), unweighted.size());
+
+        let invalid = generate_regex_tree_weighted(&[
+            ("a-1", f64::NAN),
+            ("a-2", -3.0),
+            ("a-3", f64::INFINITY),
+            ("a-1", 2.0),
+        ]);
+        assert_eq!(invalid.weight(), Some(2.0));
+    }
+}
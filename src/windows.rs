@@ -0,0 +1,209 @@
+use crate::{generate_regex_tree, Node, TemplateId};
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The tree generated from the inputs of one time window.
+#[derive(Debug, Clone)]
+pub struct Window {
+    start: SystemTime,
+    end: SystemTime,
+    inputs: usize,
+    tree: Node,
+    drift: Drift,
+}
+
+impl Window {
+    /// Returns the start of the window, inclusive.
+    pub fn start(&self) -> SystemTime {
+        self.start
+    }
+
+    /// Returns the end of the window, exclusive.
+    pub fn end(&self) -> SystemTime {
+        self.end
+    }
+
+    /// Returns how many inputs fell in the window.
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns the tree generated from the inputs of the window.
+    pub fn tree(&self) -> &Node {
+        &self.tree
+    }
+
+    /// Returns how the patterns changed since the previous window holding
+    /// inputs. Every pattern of the first window counts as added.
+    pub fn drift(&self) -> &Drift {
+        &self.drift
+    }
+}
+
+/// How the patterns of a window's tree differ from those of the previous
+/// window, patterns being compared by [`TemplateId`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Drift {
+    added: Vec<String>,
+    removed: Vec<String>,
+    kept: usize,
+}
+
+impl Drift {
+    /// Returns the patterns new in this window, in pre-order.
+    pub fn added(&self) -> &[String] {
+        &self.added
+    }
+
+    /// Returns the patterns of the previous window gone from this one, in
+    /// their pre-order there.
+    pub fn removed(&self) -> &[String] {
+        &self.removed
+    }
+
+    /// Returns how many patterns both windows share.
+    pub fn kept(&self) -> usize {
+        self.kept
+    }
+
+    /// Returns whether the patterns are unchanged.
+    pub fn is_stable(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Returns the share of the patterns of both windows that only one of
+    /// them has, from `0.0` when unchanged to `1.0` when nothing is shared.
+    pub fn score(&self) -> f64 {
+        let changed = self.added.len() + self.removed.len();
+        match changed + self.kept {
+            0 => 0.0,
+            total => changed as f64 / total as f64,
+        }
+    }
+}
+
+/// Generates a regex pattern tree per time window of timestamped inputs,
+/// with the drift of each from the previous one, to follow how log
+/// formats evolve from release to release.
+///
+/// Windows are consecutive spans of `window`, aligned on the Unix epoch,
+/// and those without inputs are skipped. A zero `window` is taken as one
+/// nanosecond, and timestamps before the epoch fall in its first window.
+/// The root `.*`, shared by every tree, is left out of the drift.
+///
+/// # Arguments
+///
+/// * `inputs` - The inputs with their timestamps, in any order.
+/// * `window` - The span of each window.
+///
+/// # Returns
+///
+/// The windows holding inputs, earliest first, each keeping its inputs in
+/// their given order.
+pub fn generate_windows(inputs: &[(SystemTime, &str)], window: Duration) -> Vec<Window> {
+    let span = window.as_nanos().max(1);
+    let mut buckets: Vec<(u128, Vec<&str>)> = vec![];
+    for &(timestamp, input) in inputs {
+        let since_epoch = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let bucket = since_epoch.as_nanos() / span;
+        match buckets.iter_mut().find(|(b, _)| *b == bucket) {
+            Some((_, bucket)) => bucket.push(input),
+            None => buckets.push((bucket, vec![input])),
+        }
+    }
+    buckets.sort_by_key(|(bucket, _)| *bucket);
+
+    let mut windows: Vec<Window> = vec![];
+    for (bucket, strings) in buckets {
+        let tree = generate_regex_tree(&strings);
+        let drift = drift(windows.last().map(|w| &w.tree), &tree);
+        let start = UNIX_EPOCH + nanos(bucket * span);
+        windows.push(Window {
+            start,
+            end: start + nanos(span),
+            inputs: strings.len(),
+            tree,
+            drift,
+        });
+    }
+    windows
+}
+
+/// Compares the patterns of the trees of consecutive windows, if there is
+/// a previous one.
+fn drift(previous: Option<&Node>, current: &Node) -> Drift {
+    let patterns = |tree: Option<&Node>| -> Vec<(TemplateId, String)> {
+        let nodes = tree.map(Node::preorder).unwrap_or_default();
+        let mut seen = HashSet::new();
+        nodes
+            .into_iter()
+            .skip(1)
+            .map(|n| (n.template_id(), n.pattern.to_string()))
+            .filter(|(id, _)| seen.insert(*id))
+            .collect()
+    };
+    let (before, after) = (patterns(previous), patterns(Some(current)));
+    let only = |nodes: &[(TemplateId, String)], other: &[(TemplateId, String)]| -> Vec<String> {
+        nodes
+            .iter()
+            .filter(|(id, _)| !other.iter().any(|(o, _)| o == id))
+            .map(|(_, pattern)| pattern.clone())
+            .collect()
+    };
+    let added = only(&after, &before);
+    Drift {
+        kept: after.len() - added.len(),
+        removed: only(&before, &after),
+        added,
+    }
+}
+
+/// Converts nanoseconds to a duration, saturating past its range.
+fn nanos(nanos: u128) -> Duration {
+    let secs = u64::try_from(nanos / 1_000_000_000).unwrap_or(u64::MAX);
+    Duration::new(secs, (nanos % 1_000_000_000) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_windows() {
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+        let inputs = [
+            (at(3700), "v2 user=carol status=ok"),
+            (at(10), "v1 login alice ok"),
+            (at(20), "v1 login bob ok"),
+            (at(3650), "v2 user=dave status=ok"),
+            (at(7300), "v2 user=dave status=ok"),
+            (at(7400), "v2 user=carol status=ok"),
+        ];
+        let windows = generate_windows(&inputs, Duration::from_secs(3600));
+
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0].start(), at(0));
+        assert_eq!(windows[1].end(), at(7200));
+        assert_eq!(windows[1].inputs(), 2);
+        let patterns = |w: &Window| -> Vec<String> {
+            w.tree()
+                .preorder()
+                .iter()
+                .map(|n| n.pattern.to_string())
+                .collect()
+        };
+        assert!(patterns(&windows[0]).contains(&"^v1 login .*".to_string()));
+        assert!(windows[0].drift().removed().is_empty());
+        assert_eq!(
+            windows[0].drift().added().len(),
+            windows[0].tree().preorder().len() - 1
+        );
+
+        let drift = windows[1].drift();
+        assert!(drift.added().contains(&".* status=ok$".to_string()));
+        assert!(drift.removed().contains(&"^v1 login .*".to_string()));
+        assert!(drift.score() > 0.5);
+        assert!(windows[2].drift().is_stable());
+        assert_eq!(windows[2].drift().score(), 0.0);
+    }
+}
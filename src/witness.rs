@@ -0,0 +1,86 @@
+use crate::subsumption::DFA_SIZE_LIMIT;
+use crate::{LanguageError, Node};
+use regex_automata::dfa::{dense, Automaton};
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchKind};
+use std::collections::{HashMap, VecDeque};
+
+impl Node {
+    /// Returns the shortest string the node's pattern matches, a minimal
+    /// concrete witness of what the pattern admits, such as `"id-00"` for
+    /// `^id-\d{2}$`.
+    ///
+    /// The pattern is compiled to a deterministic automaton explored
+    /// breadth-first from its start, so the string is the shortest in
+    /// bytes and, among those, the smallest byte-wise.
+    ///
+    /// # Returns
+    ///
+    /// The string, `None` if the pattern matches nothing, or an error if
+    /// it does not compile or its automaton grows too large.
+    pub fn shortest_match_example(&self) -> Result<Option<String>, LanguageError> {
+        let invalid = |message: String| LanguageError::InvalidPattern {
+            pattern: self.pattern.to_string(),
+            message,
+        };
+        let dfa = dense::Builder::new()
+            .configure(
+                dense::Config::new()
+                    .match_kind(MatchKind::All)
+                    .dfa_size_limit(Some(DFA_SIZE_LIMIT))
+                    .determinize_size_limit(Some(DFA_SIZE_LIMIT)),
+            )
+            .build(&self.pattern)
+            .map_err(|error| invalid(error.to_string()))?;
+        let initial = dfa
+            .start_state(&start::Config::new().anchored(Anchored::Yes))
+            .map_err(|error| invalid(error.to_string()))?;
+
+        // The state each one was first reached from, and by which byte.
+        let mut parents: HashMap<StateID, Option<(StateID, u8)>> = HashMap::from([(initial, None)]);
+        let mut queue = VecDeque::from([initial]);
+        while let Some(state) = queue.pop_front() {
+            if dfa.is_match_state(dfa.next_eoi_state(state)) {
+                let mut bytes = vec![];
+                let mut at = state;
+                while let Some(&Some((parent, byte))) = parents.get(&at) {
+                    bytes.push(byte);
+                    at = parent;
+                }
+                bytes.reverse();
+                return Ok(Some(String::from_utf8_lossy(&bytes).into_owned()));
+            }
+            for byte in 0..=u8::MAX {
+                let next = dfa.next_state(state, byte);
+                if !dfa.is_dead_state(next) && !parents.contains_key(&next) {
+                    parents.insert(next, Some((state, byte)));
+                    queue.push_back(next);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_regex_tree, Node};
+
+    #[test]
+    fn test_shortest_match_example() {
+        let example = |pattern: &str| Node::new(pattern.to_string()).shortest_match_example();
+        assert_eq!(example(r"^id-\d{2}$").unwrap().as_deref(), Some("id-00"));
+        assert_eq!(example("^(?:abc|de)+!$").unwrap().as_deref(), Some("de!"));
+        assert_eq!(example("^[é-ë]{2}$").unwrap().as_deref(), Some("éé"));
+        assert_eq!(example("x*").unwrap().as_deref(), Some(""));
+        assert_eq!(example(r"[^\s\S]").unwrap(), None);
+        assert!(example("(").is_err());
+
+        let tree = generate_regex_tree(&["disk sda full", "disk sdb full"]);
+        for node in tree.iter() {
+            let witness = node.shortest_match_example().unwrap().unwrap();
+            assert!(regex::Regex::new(&node.pattern).unwrap().is_match(&witness));
+        }
+    }
+}